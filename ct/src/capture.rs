@@ -6,11 +6,14 @@ This module generates calls to `rt::capture`.
 
 use std::fmt::Write;
 
-use proc_macro2::{Ident, TokenStream};
+use proc_macro2::{Delimiter, Ident, TokenStream, TokenTree};
 use quote::ToTokens;
 use syn::{
+    parse::Parser,
+    punctuated::Punctuated,
     visit_mut::{self, VisitMut},
-    Expr, ExprLit, ExprMacro, FieldValue, Lit, LitStr, Member,
+    Expr, ExprArray, ExprLit, ExprMacro, ExprTuple, FieldValue, Lit, LitStr, Member, MetaNameValue, Path,
+    Token,
 };
 
 pub(super) struct ExpandTokens<F: Fn(&str) -> TokenStream> {
@@ -23,20 +26,42 @@ pub(super) fn expand_tokens(opts: ExpandTokens<impl Fn(&str) -> TokenStream>) ->
 
     let key_name = key_value.key_name().expect("expected a string literal");
 
-    let fn_name = syn::parse2::<Ident>((opts.fn_name)(&key_name)).expect("failed to parse ident");
+    let fn_name = (opts.fn_name)(&key_name);
 
-    expand(key_value, fn_name)
+    expand(key_value, fn_name, false)
 }
 
-fn expand(key_value: FieldValue, fn_name: Ident) -> TokenStream {
+/**
+Like `expand_tokens`, but captures a reference to the value instead of the value itself.
+
+This is needed for values that are unsized, like `std::path::Path`, that can only be captured
+through a sized reference to them. The reference is a temporary that's bound in the same `match`
+as the other field values, so it lives until the record is emitted.
+*/
+pub(super) fn expand_ref_tokens(opts: ExpandTokens<impl Fn(&str) -> TokenStream>) -> TokenStream {
+    let key_value = syn::parse2::<FieldValue>(opts.expr).expect("failed to parse expr");
+
+    let key_name = key_value.key_name().expect("expected a string literal");
+
+    let fn_name = (opts.fn_name)(&key_name);
+
+    expand(key_value, fn_name, true)
+}
+
+fn expand(key_value: FieldValue, fn_name: TokenStream, by_ref: bool) -> TokenStream {
     let key_expr = key_value.key_expr();
     let expr = key_value.expr;
 
+    let value = if by_ref {
+        quote!((&(#expr).__private_reborrow()))
+    } else {
+        quote!((#expr).__private_reborrow())
+    };
+
     quote!(
         {
-            extern crate emit;
-            use emit::rt::__private::__PrivateCapture;
-            (#key_expr, (#expr).#fn_name())
+            use ::emit::rt::__private::{__PrivateCapture, __PrivateCaptureAtomic, __PrivateCaptureInferred, __PrivateReborrow, __PrivateReborrowMut};
+            (#key_expr, #value.#fn_name())
         }
     )
 }
@@ -45,6 +70,10 @@ pub(super) struct RenameCaptureTokens<F: Fn(&str) -> bool> {
     pub(super) expr: TokenStream,
     pub(super) predicate: F,
     pub(super) to: TokenStream,
+    /**
+    Arguments to pass to the renamed macro before its field-value.
+    */
+    pub(super) args: Option<TokenStream>,
 }
 
 pub(super) fn rename_capture_tokens(
@@ -53,14 +82,20 @@ pub(super) fn rename_capture_tokens(
     let expr = syn::parse2::<Expr>(opts.expr).expect("failed to parse expr");
     let to = syn::parse2::<Ident>(opts.to).expect("failed to parse ident");
 
-    rename_capture(expr, opts.predicate, to)
+    rename_capture(expr, opts.predicate, to, opts.args)
 }
 
-fn rename_capture(mut expr: Expr, predicate: impl Fn(&str) -> bool, to: Ident) -> TokenStream {
+fn rename_capture(
+    mut expr: Expr,
+    predicate: impl Fn(&str) -> bool,
+    to: Ident,
+    args: Option<TokenStream>,
+) -> TokenStream {
     struct ReplaceLogDefaultMethod<F> {
         scratch: String,
         predicate: F,
         to: Ident,
+        args: Option<TokenStream>,
     }
 
     impl<F> VisitMut for ReplaceLogDefaultMethod<F>
@@ -80,6 +115,11 @@ fn rename_capture(mut expr: Expr, predicate: impl Fn(&str) -> bool, to: Ident) -
                         // Set the name of the identifier, retaining its original span
                         last.ident = self.to.clone();
                         last.ident.set_span(span);
+
+                        if let Some(ref args) = self.args {
+                            let tokens = &mac.tokens;
+                            mac.tokens = quote!([#args] #tokens);
+                        }
                     }
                 }
             }
@@ -93,12 +133,346 @@ fn rename_capture(mut expr: Expr, predicate: impl Fn(&str) -> bool, to: Ident) -
         scratch: String::new(),
         predicate,
         to,
+        args,
     }
     .visit_expr_mut(&mut expr);
 
     expr.to_token_stream()
 }
 
+/**
+Parse `max_depth = N, max_entries = N` arguments for limiting captured values.
+*/
+pub(super) fn parse_limits(args: TokenStream) -> (TokenStream, TokenStream) {
+    let args = Punctuated::<MetaNameValue, Token![,]>::parse_terminated
+        .parse2(args)
+        .expect("expected `max_depth = N, max_entries = N`");
+
+    let mut max_depth = quote!(16usize);
+    let mut max_entries = quote!({ usize::MAX });
+
+    for arg in args {
+        let value = match arg.lit {
+            Lit::Int(ref value) => value.base10_parse::<usize>().expect("expected a number"),
+            _ => panic!("expected a number"),
+        };
+
+        match arg
+            .path
+            .get_ident()
+            .map(|ident| ident.to_string())
+            .as_deref()
+        {
+            Some("max_depth") => {
+                assert!(value > 0, "`max_depth` must be greater than zero");
+                max_depth = quote!(#value);
+            }
+            Some("max_entries") => max_entries = quote!(#value),
+            _ => panic!("unsupported argument, expected `max_depth` or `max_entries`"),
+        }
+    }
+
+    (max_depth, max_entries)
+}
+
+/**
+Parse a `take = N` argument for the number of elements to capture from an iterator.
+*/
+pub(super) fn parse_take(args: TokenStream) -> usize {
+    let arg = syn::parse2::<MetaNameValue>(args).expect("expected `take = N`");
+
+    assert!(arg.path.is_ident("take"), "unsupported argument, expected `take`");
+
+    match arg.lit {
+        Lit::Int(ref value) => value.base10_parse::<usize>().expect("expected a number"),
+        _ => panic!("expected a number"),
+    }
+}
+
+/**
+Parse the `max_len = N` argument to `#[as_debug]` and `#[as_hexdump]`.
+*/
+pub(super) fn parse_max_len(args: TokenStream) -> usize {
+    let arg = syn::parse2::<MetaNameValue>(args).expect("expected `max_len = N`");
+
+    assert!(arg.path.is_ident("max_len"), "unsupported argument, expected `max_len`");
+
+    match arg.lit {
+        Lit::Int(ref value) => value.base10_parse::<usize>().expect("expected a number"),
+        _ => panic!("expected a number"),
+    }
+}
+
+/**
+Parse the `with = path::to::serialize` argument to `#[as_serde]`.
+
+Returns `None` if the arguments aren't a `with` argument.
+*/
+pub(super) fn parse_serialize_with(args: TokenStream) -> Option<Path> {
+    let mut tokens = args.into_iter();
+
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Ident(ident)), Some(TokenTree::Punct(punct)))
+            if ident == "with" && punct.as_char() == '=' =>
+        {
+            Some(
+                syn::parse2::<Path>(tokens.collect())
+                    .expect("expected a function like `with = path::to::serialize`"),
+            )
+        }
+        _ => None,
+    }
+}
+
+/**
+Parse the `with_display` argument to `#[as_serde]`.
+
+Returns `true` if the value should be rendered in templates with its `Display` string.
+*/
+pub(super) fn parse_with_display(args: TokenStream) -> bool {
+    if args.is_empty() {
+        return false;
+    }
+
+    let arg = syn::parse2::<Path>(args).expect("expected `with_display`");
+
+    assert!(arg.is_ident("with_display"), "unsupported argument, expected `with_display`");
+
+    true
+}
+
+/**
+Expand a field-value into a sequence of the first `take` elements of an iterator.
+
+Each element is captured using its default capturing method.
+*/
+pub(super) fn expand_iter_tokens(take: TokenStream, expr: TokenStream) -> TokenStream {
+    let key_value = syn::parse2::<FieldValue>(expr).expect("failed to parse expr");
+
+    let key_expr = key_value.key_expr();
+    let expr = key_value.expr;
+
+    // The element is dereferenced so iterators of references capture the values they point to
+    quote!(
+        {
+            use ::emit::rt::__private::{__PrivateCapture, __PrivateCaptureAtomic, __PrivateCaptureInferred, __PrivateReborrow, __PrivateReborrowMut};
+            (
+                #key_expr,
+                ::emit::rt::__private::IterPrefix::new(
+                    #expr,
+                    #take,
+                    |__emit_item| (*__emit_item).__private_reborrow().__private_capture_as_inferred(),
+                )
+                .capture(),
+            )
+        }
+    )
+}
+
+pub(super) fn expand_serialize_with_tokens(with: TokenStream, expr: TokenStream) -> TokenStream {
+    let key_value = syn::parse2::<FieldValue>(expr).expect("failed to parse expr");
+
+    let key_expr = key_value.key_expr();
+    let expr = key_value.expr;
+
+    // The function is called through a closure so it's given the erased serializer
+    quote!(
+        {
+            (
+                #key_expr,
+                ::emit::rt::__private::SerializeWith::new(&(#expr), |__emit_value, __emit_serializer| {
+                    #with(__emit_value, __emit_serializer)
+                })
+                .capture(),
+            )
+        }
+    )
+}
+
+/**
+Expand a field-value into a value that's captured with `serde` and rendered with `Display`.
+*/
+pub(super) fn expand_serde_with_display_tokens(expr: TokenStream) -> TokenStream {
+    let key_value = syn::parse2::<FieldValue>(expr).expect("failed to parse expr");
+
+    let key_expr = key_value.key_expr();
+    let expr = key_value.expr;
+
+    quote!(
+        {
+            (
+                #key_expr,
+                ::emit::rt::__private::WithDisplay::new(&(#expr)).capture(),
+            )
+        }
+    )
+}
+
+pub(super) fn expand_hexdump_tokens(max_len: TokenStream, expr: TokenStream) -> TokenStream {
+    let key_value = syn::parse2::<FieldValue>(expr).expect("failed to parse expr");
+
+    let key_expr = key_value.key_expr();
+    let expr = key_value.expr;
+
+    // The value is borrowed so slices, which aren't sized, are captured through a reference
+    quote!(
+        {
+            use ::emit::rt::__private::__PrivateCapture;
+            (#key_expr, (&(#expr)).__private_capture_as_hexdump::<#max_len>())
+        }
+    )
+}
+
+/**
+Capture a field-value whose expression is an array or tuple as a sequence of its elements.
+
+The elements of an `#[as_array]` field can have different types, so they're each captured
+on their own instead of as a Rust array.
+*/
+pub(super) fn expand_array_tokens(expr: TokenStream) -> TokenStream {
+    let key_value = syn::parse2::<FieldValue>(expr).expect("failed to parse expr");
+
+    let key_expr = key_value.key_expr();
+    let elems = match key_value.expr {
+        Expr::Tuple(ExprTuple { elems, .. }) | Expr::Array(ExprArray { elems, .. }) => {
+            elems.into_iter()
+        }
+        _ => panic!("expected the elements of an array to be an array or tuple"),
+    };
+
+    quote!(
+        {
+            use ::emit::rt::__private::{__PrivateCapture, __PrivateCaptureAtomic, __PrivateCaptureInferred, __PrivateReborrow, __PrivateReborrowMut};
+            (
+                #key_expr,
+                ::emit::rt::__private::Array::new(&[#((#elems).__private_reborrow().__private_capture_as_inferred()),*])
+                    .capture(),
+            )
+        }
+    )
+}
+
+/**
+Parse a format string like `"{:>10}"` for formatting a captured value.
+
+The format string must contain a single `{}` argument, optionally with a format spec.
+*/
+pub(super) fn parse_format(args: TokenStream) -> LitStr {
+    let format = syn::parse2::<LitStr>(args).expect("expected a format string like `\"{:>10}\"`");
+
+    let value = format.value();
+    let mut chars = value.chars().peekable();
+    let mut holes = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+            }
+            '{' => {
+                let hole: String = chars.by_ref().take_while(|c| *c != '}').collect();
+
+                assert!(
+                    hole.is_empty() || hole.starts_with(':'),
+                    "the format argument can't be named or positional"
+                );
+
+                holes += 1;
+            }
+            _ => (),
+        }
+    }
+
+    assert_eq!(1, holes, "expected a format string with a single argument");
+
+    format
+}
+
+/**
+Replace the expression in a field-value with the result of formatting it.
+*/
+pub(super) fn format_field_value(format: TokenStream, fv: TokenStream) -> TokenStream {
+    map_field_value(fv, |expr| quote!(::std::format!(#format, #expr)))
+}
+
+/**
+Replace the expression in a field-value with a status code of it.
+*/
+pub(super) fn status_field_value(fv: TokenStream) -> TokenStream {
+    map_field_value(fv, |expr| quote!(::emit::rt::__private::Status::new(#expr)))
+}
+
+/**
+Replace the expression in a field-value with its serialized JSON.
+*/
+pub(super) fn json_string_field_value(fv: TokenStream) -> TokenStream {
+    map_field_value(fv, |expr| quote!(::emit::rt::__private::JsonString::new(&#expr)))
+}
+
+/**
+Replace the expression in a field-value, which is a tuple of an old and new value, with a diff of them.
+*/
+pub(super) fn diff_field_value(fv: TokenStream) -> TokenStream {
+    map_field_value(fv, |expr| match expr {
+        Expr::Tuple(ExprTuple { elems, .. }) if elems.len() == 2 => {
+            let (old, new) = (&elems[0], &elems[1]);
+
+            quote!(::emit::rt::__private::Diff::new(&(#old), &(#new)))
+        }
+        _ => panic!("expected a pair of values like `#[as_diff] key: (old, new)`"),
+    })
+}
+
+/**
+Replace the expression in a field-value with its `Display` or `Debug` output without any ANSI escape sequences.
+*/
+pub(super) fn strip_ansi_field_value(debug: bool, fv: TokenStream) -> TokenStream {
+    map_field_value(fv, |expr| {
+        if debug {
+            quote!(::emit::rt::__private::strip_ansi_debug(&::std::format!("{:?}", #expr)))
+        } else {
+            quote!(::emit::rt::__private::strip_ansi(&::std::format!("{}", #expr)))
+        }
+    })
+}
+
+/**
+Replace the expression in a field-value with a fingerprint of its `Debug` output.
+*/
+pub(super) fn redact_field_value(fv: TokenStream) -> TokenStream {
+    map_field_value(fv, |expr| quote!(::emit::rt::__private::redact(&(#expr))))
+}
+
+fn map_field_value(fv: TokenStream, f: impl FnOnce(&Expr) -> TokenStream) -> TokenStream {
+    let mut fv = syn::parse2::<FieldValue>(fv).expect("failed to parse expr");
+
+    // Make sure the key is set explicitly before replacing the expression
+    if fv.colon_token.is_none() {
+        fv.colon_token = Some(Default::default());
+    }
+
+    fv.expr = syn::parse2(f(&fv.expr)).expect("failed to parse expr");
+
+    fv.into_token_stream()
+}
+
+/**
+Split the bracketed arguments passed by `rename_capture_tokens` from the field-value.
+*/
+pub(super) fn split_args(item: TokenStream) -> (TokenStream, TokenStream) {
+    let mut item = item.into_iter();
+
+    match item.next() {
+        Some(TokenTree::Group(args)) if args.delimiter() == Delimiter::Bracket => {
+            (args.stream(), item.collect())
+        }
+        _ => panic!("expected bracketed arguments"),
+    }
+}
+
 pub(super) trait FieldValueExt {
     fn key_expr(&self) -> ExprLit;
     fn key_name(&self) -> Option<String> {
@@ -130,20 +504,18 @@ mod tests {
         let cases = vec![
             (
                 quote!(a),
-                quote!(__private_capture_as_default),
+                quote!(__private_capture_as_inferred),
                 quote!({
-                    extern crate emit;
-                    use emit::rt::__private::__PrivateCapture;
-                    ("a", (a).__private_capture_as_default())
+                    use ::emit::rt::__private::{__PrivateCapture, __PrivateCaptureAtomic, __PrivateCaptureInferred, __PrivateReborrow, __PrivateReborrowMut};
+                    ("a", (a).__private_reborrow().__private_capture_as_inferred())
                 }),
             ),
             (
                 quote!(a: 42),
-                quote!(__private_capture_as_default),
+                quote!(__private_capture_as_inferred),
                 quote!({
-                    extern crate emit;
-                    use emit::rt::__private::__PrivateCapture;
-                    ("a", (42).__private_capture_as_default())
+                    use ::emit::rt::__private::{__PrivateCapture, __PrivateCaptureAtomic, __PrivateCaptureInferred, __PrivateReborrow, __PrivateReborrowMut};
+                    ("a", (42).__private_reborrow().__private_capture_as_inferred())
                 }),
             ),
         ];
@@ -182,9 +554,210 @@ mod tests {
                 expr,
                 predicate: |ident| ident.starts_with("__private"),
                 to,
+                args: None,
             });
 
             assert_eq!(expected.to_string(), actual.to_string());
         }
     }
+
+    #[test]
+    fn expand_rename_args() {
+        let (max_depth, max_entries) = parse_limits(quote!(max_entries = 64));
+        let args = quote!(#max_depth, #max_entries);
+
+        let actual = rename_capture_tokens(RenameCaptureTokens {
+            expr: quote!(__private_capture!(a)),
+            predicate: |ident| ident.starts_with("__private"),
+            to: quote!(__private_capture_as_sval_limited),
+            args: Some(args),
+        });
+
+        assert_eq!(
+            quote!(__private_capture_as_sval_limited!([16usize, 64usize] a)).to_string(),
+            actual.to_string()
+        );
+
+        let (args, expr) = split_args(quote!([16usize, 64usize] a: 42));
+        assert_eq!(quote!(16usize, 64usize).to_string(), args.to_string());
+        assert_eq!(quote!(a: 42).to_string(), expr.to_string());
+    }
+
+    #[test]
+    fn expand_capture_ref() {
+        let actual = expand_ref_tokens(ExpandTokens {
+            expr: quote!(a),
+            fn_name: |_| quote!(__private_capture_as_path),
+        });
+
+        assert_eq!(
+            quote!({
+                use ::emit::rt::__private::{__PrivateCapture, __PrivateCaptureAtomic, __PrivateCaptureInferred, __PrivateReborrow, __PrivateReborrowMut};
+                ("a", (&(a).__private_reborrow()).__private_capture_as_path())
+            })
+            .to_string(),
+            actual.to_string()
+        );
+    }
+
+    #[test]
+    fn expand_format() {
+        let format = parse_format(quote!("{:>10}"));
+
+        let actual = expand_tokens(ExpandTokens {
+            expr: format_field_value(quote!(#format), quote!(a)),
+            fn_name: |_| quote!(__private_capture_as_display),
+        });
+
+        assert_eq!(
+            quote!({
+                use ::emit::rt::__private::{__PrivateCapture, __PrivateCaptureAtomic, __PrivateCaptureInferred, __PrivateReborrow, __PrivateReborrowMut};
+                ("a", (::std::format!("{:>10}", a)).__private_reborrow().__private_capture_as_display())
+            })
+            .to_string(),
+            actual.to_string()
+        );
+    }
+
+    #[test]
+    fn expand_status() {
+        let actual = expand_tokens(ExpandTokens {
+            expr: status_field_value(quote!(code)),
+            fn_name: |_| quote!(__private_capture_as_sval),
+        });
+
+        assert_eq!(
+            quote!({
+                use ::emit::rt::__private::{__PrivateCapture, __PrivateCaptureAtomic, __PrivateCaptureInferred, __PrivateReborrow, __PrivateReborrowMut};
+                ("code", (::emit::rt::__private::Status::new(code)).__private_reborrow().__private_capture_as_sval())
+            })
+            .to_string(),
+            actual.to_string()
+        );
+    }
+
+    #[test]
+    fn expand_json_string() {
+        let actual = expand_tokens(ExpandTokens {
+            expr: json_string_field_value(quote!(a)),
+            fn_name: |_| quote!(__private_capture_as_display),
+        });
+
+        assert_eq!(
+            quote!({
+                use ::emit::rt::__private::{__PrivateCapture, __PrivateCaptureAtomic, __PrivateCaptureInferred, __PrivateReborrow, __PrivateReborrowMut};
+                ("a", (::emit::rt::__private::JsonString::new(&a)).__private_reborrow().__private_capture_as_display())
+            })
+            .to_string(),
+            actual.to_string()
+        );
+    }
+
+    #[test]
+    fn max_len() {
+        assert_eq!(1024, parse_max_len(quote!(max_len = 1024)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn max_len_unsupported() {
+        parse_max_len(quote!(max_depth = 1024));
+    }
+
+    #[test]
+    fn serialize_with() {
+        assert_eq!(
+            "path :: to :: serialize",
+            parse_serialize_with(quote!(with = path::to::serialize))
+                .unwrap()
+                .to_token_stream()
+                .to_string()
+        );
+
+        assert!(parse_serialize_with(quote!()).is_none());
+        assert!(parse_serialize_with(quote!(with_display)).is_none());
+    }
+
+    #[test]
+    fn with_display() {
+        assert!(!parse_with_display(quote!()));
+        assert!(parse_with_display(quote!(with_display)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_display_unsupported() {
+        parse_with_display(quote!(with_debug));
+    }
+
+    #[test]
+    fn expand_iter() {
+        let take = parse_take(quote!(take = 10));
+        assert_eq!(10, take);
+
+        let actual = expand_iter_tokens(quote!(#take), quote!(items: items.iter()));
+
+        assert_eq!(
+            quote!({
+                use ::emit::rt::__private::{__PrivateCapture, __PrivateCaptureAtomic, __PrivateCaptureInferred, __PrivateReborrow, __PrivateReborrowMut};
+                (
+                    "items",
+                    ::emit::rt::__private::IterPrefix::new(
+                        items.iter(),
+                        10usize,
+                        |__emit_item| (*__emit_item).__private_reborrow().__private_capture_as_inferred(),
+                    )
+                    .capture(),
+                )
+            })
+            .to_string(),
+            actual.to_string()
+        );
+    }
+
+    #[test]
+    fn expand_redact() {
+        let actual = expand_tokens(ExpandTokens {
+            expr: redact_field_value(quote!(a)),
+            fn_name: |_| quote!(__private_capture_as_display),
+        });
+
+        assert_eq!(
+            quote!({
+                use ::emit::rt::__private::{__PrivateCapture, __PrivateCaptureAtomic, __PrivateCaptureInferred, __PrivateReborrow, __PrivateReborrowMut};
+                ("a", (::emit::rt::__private::redact(&(a))).__private_reborrow().__private_capture_as_display())
+            })
+            .to_string(),
+            actual.to_string()
+        );
+    }
+
+    #[test]
+    fn expand_strip_ansi() {
+        let actual = expand_tokens(ExpandTokens {
+            expr: strip_ansi_field_value(true, quote!(a)),
+            fn_name: |_| quote!(__private_capture_as_display),
+        });
+
+        assert_eq!(
+            quote!({
+                use ::emit::rt::__private::{__PrivateCapture, __PrivateCaptureAtomic, __PrivateCaptureInferred, __PrivateReborrow, __PrivateReborrowMut};
+                ("a", (::emit::rt::__private::strip_ansi_debug(&::std::format!("{:?}", a))).__private_reborrow().__private_capture_as_display())
+            })
+            .to_string(),
+            actual.to_string()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_format_named_argument() {
+        parse_format(quote!("{a:>10}"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_format_multiple_arguments() {
+        parse_format(quote!("{} {}"));
+    }
 }
@@ -4,17 +4,290 @@ Compile-time implementation of event emission.
 This module generates calls to `rt::emit`.
 */
 
-use std::{collections::BTreeMap, mem};
+use std::{collections::BTreeMap, mem, ops::Range};
 
-use proc_macro2::{Span, TokenStream};
-use syn::{spanned::Spanned, Attribute, Expr, ExprPath, FieldValue, Ident, Meta};
+use proc_macro2::{Delimiter, Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{
+    parse::{ParseStream, Parser},
+    spanned::Spanned, Attribute, Expr, ExprLit, ExprPath, FieldValue, Ident, Lit, LitStr, Member, Meta, MetaNameValue, Token,
+};
+
+#[cfg(test)]
+use syn::{ExprArray, ExprCall, ExprReference};
 
 use fv_template::ct::Template;
 
 use crate::capture::FieldValueExt;
 
-pub(super) fn expand_tokens(input: TokenStream) -> TokenStream {
-    let record_ident = Ident::new(&"record", input.span());
+pub(super) struct ExpandTokens {
+    pub(super) level: TokenStream,
+    pub(super) input: TokenStream,
+    /**
+    A function to pass the record to instead of forwarding it to an emitter.
+    */
+    pub(super) receiver: Option<TokenStream>,
+    /**
+    Whether to flush the emitter after the record is emitted.
+    */
+    pub(super) blocking: bool,
+    /**
+    A slice of key-values built at runtime to merge into the record's key-values.
+    */
+    pub(super) runtime_kvs: Option<TokenStream>,
+}
+
+pub(super) fn expand_tokens(opts: ExpandTokens) -> TokenStream {
+    let record_ident = Ident::new("record", opts.input.span());
+
+    // Standard levels are identifiers, like `Info`, anything else is a path to a custom level
+    let level = opts.level;
+    let level_tokens = match syn::parse2::<Ident>(level.clone()) {
+        Ok(level) => quote!(::emit::rt::__private::Level::#level),
+        Err(_) => quote!((#level).to_level()),
+    };
+
+    let input = match split_serde_event(opts.input) {
+        Ok(event) if opts.runtime_kvs.is_some() => {
+            return syn::Error::new(
+                event.span(),
+                "runtime key-values can't be emitted alongside a `serde` event",
+            )
+            .to_compile_error()
+        }
+        Ok(event) => {
+            return expand_serde_event(event, record_ident, level_tokens, opts.receiver, opts.blocking)
+        }
+        Err(input) => input,
+    };
+
+    let Prepared {
+        template,
+        source,
+        fields,
+        hole_specs,
+    } = match prepare(level.to_string(), input) {
+        Ok(prepared) => prepared,
+        Err(err) => return err,
+    };
+
+    // The log target expression
+    // A string literal is the target of the record itself. Anything else is resolved when the
+    // record is emitted, as either the target of the record or the emitter to use
+    let target = target_field_value(&template);
+
+    let (record_target_tokens, target_tokens) = match target.map(|fv| &fv.expr) {
+        Some(Expr::Lit(ExprLit {
+            lit: Lit::Str(ref target),
+            ..
+        })) => (quote!(#target), quote!(None)),
+        // Closures need a `Fn` bound to infer their argument types
+        Some(Expr::Closure(ref target)) => (
+            quote!(module_path!()),
+            quote!(Some(::emit::__private::fn_target(#target))),
+        ),
+        Some(_) => (
+            quote!(__emit_target.0),
+            quote!(Some(__emit_target.1)),
+        ),
+        None => (quote!(module_path!()), quote!(None)),
+    };
+
+    let template_tokens = template_tokens(&template, &fields, &hole_specs);
+
+    // The original literal is kept so the template can be written back out exactly
+    let template_tokens = match source {
+        Some(source) => quote!(#template_tokens.with_source(#source)),
+        None => template_tokens,
+    };
+
+    let field_record_tokens = fields.sorted_field_record_tokens();
+    let field_cfg_tokens = fields.sorted_field_cfg_tokens();
+    let field_key_tokens = fields.sorted_field_key_tokens();
+    let field_value_tokens = fields.sorted_field_value_tokens();
+
+    let forward_tokens = match opts.receiver {
+        Some(receiver) => quote!(#receiver(&record);),
+        // Blocking records are only emitted to an emitter, so they can be flushed
+        None if opts.blocking => match target.map(|fv| &fv.expr) {
+            Some(Expr::Lit(ExprLit {
+                lit: Lit::Str(_), ..
+            }))
+            | None => quote!(::emit::__private::emit_blocking(&record);),
+            Some(Expr::Closure(ref target)) => quote!(
+                ::emit::__private::emit_to_blocking(&::emit::__private::fn_target(#target), &record);
+            ),
+            Some(_) => quote!(::emit::__private::emit_to_blocking(&__emit_target.1, &record);),
+        },
+        None => quote!(::emit::rt::__private_forward!({
+            level: #level_tokens,
+            target: #target_tokens,
+            key_value_cfgs: [#(#field_cfg_tokens),*],
+            keys: [#(#field_key_tokens),*],
+            values: [#(#field_value_tokens),*],
+            record: &record,
+        });),
+    };
+
+    // Optional fields that are `None` are removed from the key-values after they're sorted
+    let kvs_tokens = if fields.has_optionals() {
+        let field_present_tokens = fields.sorted_field_present_tokens();
+
+        quote!(
+            let mut kvs = [#(#field_record_tokens),*];
+            let kvs_len = ::emit::rt::__private::retain_present(&mut kvs, &[#(#field_present_tokens),*]);
+
+            let kvs = ::emit::rt::__private::KeyValues {
+                sorted_key_values: &kvs[..kvs_len]
+            };
+        )
+    } else {
+        quote!(
+            let kvs = ::emit::rt::__private::KeyValues {
+                sorted_key_values: &[#(#field_record_tokens),*]
+            };
+        )
+    };
+
+    // Runtime key-values are merged with the captured ones, which are then replaced
+    let kvs_tokens = match opts.runtime_kvs {
+        Some(_) => quote!(
+            #kvs_tokens
+
+            let kvs = ::emit::rt::__private::merge_key_values(kvs.sorted_key_values, &__emit_runtime_kvs[..]);
+            let kvs = ::emit::rt::__private::KeyValues {
+                sorted_key_values: &kvs
+            };
+        ),
+        None => kvs_tokens,
+    };
+
+    // Targets that aren't string literals or closures are bound in a `match` so any
+    // temporaries they borrow live until the record is emitted
+    let (target_binding_tokens, target_match_tokens) = match target.map(|fv| &fv.expr) {
+        Some(Expr::Lit(ExprLit {
+            lit: Lit::Str(_), ..
+        }))
+        | Some(Expr::Closure(_))
+        | None => (quote!(), None),
+        Some(target) => (
+            quote!(
+                let __emit_target = {
+                    use ::emit::__private::ResolveTarget;
+
+                    (&::emit::__private::TargetArg(__emit_target)).resolve(module_path!())
+                };
+            ),
+            Some(quote!(&(#target))),
+        ),
+    };
+
+    let body = quote!({
+        #target_binding_tokens
+
+        #kvs_tokens
+
+        let template = #template_tokens;
+
+        let #record_ident = ::emit::rt::__private::Record {
+            level: #level_tokens,
+            target: #record_target_tokens,
+            location: ::emit::rt::__private::SourceLocation {
+                file: file!(),
+                line: line!(),
+                module_path: module_path!(),
+            },
+            kvs,
+            template,
+        };
+
+        #forward_tokens
+    });
+
+    let body = match target_match_tokens {
+        Some(target_match_tokens) => quote!({
+            match #target_match_tokens {
+                __emit_target => #body
+            }
+        }),
+        None => body,
+    };
+
+    // Runtime key-values are bound the same way as targets
+    let body = match opts.runtime_kvs {
+        Some(runtime_kvs) => quote!({
+            match &(#runtime_kvs) {
+                __emit_runtime_kvs => #body
+            }
+        }),
+        None => body,
+    };
+
+    // The field values are bound in a `match` so any temporaries they borrow live
+    // until the record is emitted. Simpler cases don't need the tuple around them
+    match (
+        &*fields.match_value_tokens,
+        &*fields.match_binding_tokens,
+        fields.has_cfgs(),
+    ) {
+        ([], [], _) => quote!({
+            #body
+        }),
+        ([field_match_value_tokens], [field_match_binding_tokens], false) => quote!({
+            match #field_match_value_tokens {
+                #field_match_binding_tokens => #body
+            }
+        }),
+        (field_match_value_tokens, field_match_binding_tokens, _) => quote!({
+            match (#(#field_match_value_tokens),*) {
+                (#(#field_match_binding_tokens),*) => #body
+            }
+        }),
+    }
+}
+
+/**
+The template and fields of a record, worked out before any tokens are generated for it.
+*/
+struct Prepared {
+    template: Template,
+    source: Option<LitStr>,
+    fields: Fields,
+    hole_specs: BTreeMap<String, TokenStream>,
+}
+
+/**
+Parse the template and capture its fields.
+
+If the input is invalid then the error to emit instead of the record is returned.
+*/
+fn prepare(level: String, input: TokenStream) -> Result<Prepared, TokenStream> {
+    let input = split_receiver(input);
+
+    let (default_capture, input) = split_default_capture(input);
+
+    // Holes are checked against the template as it's written so errors can point at them
+    let written = find_template_lit(&input);
+
+    let input = match strip_comments(input) {
+        Ok(input) => input,
+        Err(err) => return Err(err.to_compile_error()),
+    };
+
+    // The source of the template is kept as it's written, except for comments
+    let source = find_template_lit(&input);
+
+    let (input, hole_specs) = split_hole_specs(input);
+
+    if let Some(Err(err)) = written.as_ref().map(check_holes) {
+        return Err(err.to_compile_error());
+    }
+
+    // Holes in the template don't have spans of their own, so errors point at the whole template
+    let template_span = source.as_ref().map_or_else(Span::call_site, |lit| lit.span());
+
+    let input = mark_escapes(input);
+
     let template = Template::parse2(input).expect("failed to expand template");
 
     // Any field-values that aren't part of the template
@@ -23,17 +296,26 @@ pub(super) fn expand_tokens(input: TokenStream) -> TokenStream {
         .map(|fv| (fv.key_name().expect("expected a string key"), fv))
         .collect();
 
-    let mut fields = Fields::default();
+    let mut fields = Fields {
+        default_capture,
+        level,
+        ..Default::default()
+    };
 
     // Push the field-values that appear in the template
     for fv in template.template_field_values() {
         let k = fv.key_name().expect("expected a string key");
 
+        // Escaped braces are marked as holes, but they aren't fields
+        if escaped_brace(&k).is_some() {
+            continue;
+        }
+
         // If the hole has a corresponding field-value outside the template
         // then it will be used as the source for the value and attributes
         // In this case, it's expected that the field-value in the template is
         // just a single identifier
-        let fv = match extra_field_values.remove(&k) {
+        let (fv, span) = match extra_field_values.remove(&k) {
             Some(extra_fv) => {
                 if let Expr::Path(ExprPath { ref path, .. }) = fv.expr {
                     // Make sure the field-value in the template is just a plain identifier
@@ -47,258 +329,1378 @@ pub(super) fn expand_tokens(input: TokenStream) -> TokenStream {
                     panic!("keys that exist in the template and extra pairs should only use identifiers");
                 }
 
-                extra_fv
+                (extra_fv, extra_fv.span())
             }
-            None => fv,
+            None => (fv, template_span),
         };
 
+        if let Err(err) = check_capture(&k, fv, span) {
+            return Err(err.to_compile_error());
+        }
+
         fields.push(k, fv.clone());
     }
 
     // Push any remaining extra field-values
     // This won't include any field values that also appear in the template
     for (k, fv) in extra_field_values {
+        if let Err(err) = check_capture(&k, fv, fv.span()) {
+            return Err(err.to_compile_error());
+        }
+
         fields.push(k, fv.clone());
     }
 
-    // The log target expression
-    let target_tokens = template
-        .before_template_field_values()
-        .find(|fv| {
-            fv.key_name()
-                .map(|k| k.as_str() == "target")
-                .unwrap_or(false)
-        })
-        .map(|fv| {
-            let target = &fv.expr;
-            quote!(Some(#target))
-        })
-        .unwrap_or_else(|| quote!(None));
-
-    // A runtime representation of the template
-    let template_tokens = template.to_rt_tokens_with_visitor(
-        quote!(emit::rt::__private),
-        CfgVisitor(|label: &str| {
-            fields.sorted_fields
-                .get(label)
-                .and_then(|field| field.cfg_attr.as_ref())
-        }),
-    );
+    Ok(Prepared {
+        template,
+        source,
+        fields,
+        hole_specs,
+    })
+}
 
-    let field_match_value_tokens = fields.match_value_tokens();
-    let field_match_binding_tokens = fields.match_binding_tokens();
+/**
+Find the `target` field-value that appears before the template, if there is one.
+*/
+fn target_field_value(template: &Template) -> Option<&FieldValue> {
+    template.before_template_field_values().find(|fv| {
+        fv.key_name()
+            .map(|k| k.as_str() == "target")
+            .unwrap_or(false)
+    })
+}
 
-    let field_record_tokens = fields.sorted_field_record_tokens();
-    let field_cfg_tokens = fields.sorted_field_cfg_tokens();
-    let field_key_tokens = fields.sorted_field_key_tokens();
-    let field_value_tokens = fields.sorted_field_value_tokens();
+/**
+A runtime representation of the template.
+*/
+fn template_tokens(
+    template: &Template,
+    fields: &Fields,
+    hole_specs: &BTreeMap<String, TokenStream>,
+) -> TokenStream {
+    template.to_rt_tokens_with_visitor(
+        quote!(::emit::rt::__private),
+        HoleVisitor(|label: &str| fields.get_by_label(label), hole_specs),
+    )
+}
 
-    quote!({
-        extern crate emit;
+/**
+Split a `serde` value to emit as the whole event, like `#[emit::serde] event`, off the input.
 
-        match (#(#field_match_value_tokens),*) {
-            (#(#field_match_binding_tokens),*) => {
-                let kvs = emit::rt::__private::KeyValues {
-                    sorted_key_values: &[#(#field_record_tokens),*]
-                };
+If the input isn't a single `serde` value then it's returned as-is.
+*/
+fn split_serde_event(input: TokenStream) -> Result<Expr, TokenStream> {
+    let mut tokens = input.clone().into_iter();
 
-                let template = #template_tokens;
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Punct(pound)), Some(TokenTree::Group(group)))
+            if pound.as_char() == '#' && group.delimiter() == Delimiter::Bracket =>
+        {
+            let is_serde = syn::parse2::<syn::Path>(group.stream())
+                .map(|path| {
+                    let path = path
+                        .segments
+                        .iter()
+                        .map(|segment| segment.ident.to_string())
+                        .collect::<Vec<_>>();
 
-                let #record_ident = emit::rt::__private::Record {
-                    kvs,
-                    template,
-                };
+                    path == ["serde"] || path == ["emit", "serde"]
+                })
+                .unwrap_or(false);
 
-                emit::rt::__private_forward!({
-                    target: #target_tokens,
-                    key_value_cfgs: [#(#field_cfg_tokens),*],
-                    keys: [#(#field_key_tokens),*],
-                    values: [#(#field_value_tokens),*],
-                    record: &record,
-                });
+            if !is_serde {
+                return Err(input);
             }
+
+            Ok(syn::parse2(tokens.collect())
+                .expect("expected a single `serde::Serialize` value after `#[emit::serde]`"))
         }
-    })
+        _ => Err(input),
+    }
 }
 
-#[derive(Default)]
-struct Fields {
-    match_value_tokens: Vec<TokenStream>,
-    match_binding_tokens: Vec<TokenStream>,
-    sorted_fields: BTreeMap<String, SortedField>,
-    field_index: usize,
+/**
+Emit a `serde` value as the whole event.
+
+The fields of the value become the key-values of the record, and its name becomes the message.
+There's no template, so the keys aren't known until the record is emitted, which means the record
+can't be forwarded to `tracing`.
+*/
+fn expand_serde_event(
+    event: Expr,
+    record_ident: Ident,
+    level_tokens: TokenStream,
+    receiver: Option<TokenStream>,
+    blocking: bool,
+) -> TokenStream {
+    let forward_tokens = match receiver {
+        Some(receiver) => quote!(#receiver(&#record_ident);),
+        None if blocking => quote!(::emit::__private::emit_blocking(&#record_ident);),
+        None => quote!(::emit::__private::emit(&#record_ident);),
+    };
+
+    quote!({
+        let event = ::emit::rt::__private::SerdeEvent::new(&(#event));
+        let sorted_key_values = event.key_values();
+        let parts = [::emit::rt::__private::Part::Text(event.name())];
+
+        let #record_ident = ::emit::rt::__private::Record {
+            level: #level_tokens,
+            target: module_path!(),
+            location: ::emit::rt::__private::SourceLocation {
+                file: file!(),
+                line: line!(),
+                module_path: module_path!(),
+            },
+            kvs: ::emit::rt::__private::KeyValues {
+                sorted_key_values: &sorted_key_values,
+            },
+            template: ::emit::rt::__private::template(&parts),
+        };
+
+        #forward_tokens
+    })
 }
 
-struct SortedField {
-    field_key_tokens: TokenStream,
-    field_record_tokens: TokenStream,
-    field_value_tokens: TokenStream,
-    cfg_attr: Option<Attribute>,
+/**
+Split a leading attribute like `#[all_debug]` off the input.
+
+The attribute is converted into the capture attribute to use for any field
+that doesn't have one of its own.
+*/
+fn split_default_capture(input: TokenStream) -> (Option<Attribute>, TokenStream) {
+    let mut tokens = input.clone().into_iter();
+
+    let attr = match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Punct(pound)), Some(TokenTree::Group(group)))
+            if pound.as_char() == '#' && group.delimiter() == Delimiter::Bracket =>
+        {
+            Attribute::parse_outer
+                .parse2(quote!(#pound #group))
+                .expect("failed to parse attribute")
+                .remove(0)
+        }
+        _ => return (None, input),
+    };
+
+    let capture = match attr.path.get_ident().map(|ident| ident.to_string()).as_deref() {
+        Some("all_debug") => quote!(as_debug),
+        Some("all_display") => quote!(as_display),
+        Some("all_string") => quote!(as_string),
+        Some("all_sval") => quote!(as_sval),
+        Some("all_serde") => quote!(as_serde),
+        _ => panic!("unsupported attribute, expected one of `#[all_debug]`, `#[all_display]`, `#[all_string]`, `#[all_sval]`, or `#[all_serde]`"),
+    };
+
+    let default_capture = Attribute::parse_outer
+        .parse2(quote_spanned!(attr.span()=> #[::emit::#capture]))
+        .expect("failed to parse attribute")
+        .remove(0);
+
+    (Some(default_capture), tokens.collect())
 }
 
-impl Fields {
-    fn match_value_tokens(&self) -> impl Iterator<Item = &TokenStream> {
-        self.match_value_tokens.iter()
-    }
+/**
+Split a leading level, like `Level::Warn` or `MyLevel::Critical`, from the input.
 
-    fn match_binding_tokens(&self) -> impl Iterator<Item = &TokenStream> {
-        self.match_binding_tokens.iter()
-    }
+A standard level is returned as just its identifier, like `Warn`.
+Any other path is returned as-is as a custom level.
+*/
+pub(super) fn split_level(input: TokenStream) -> (Option<TokenStream>, TokenStream) {
+    let mut tokens = input.clone().into_iter();
 
-    fn sorted_field_key_tokens(&self) -> impl Iterator<Item = &TokenStream> {
-        self.sorted_fields.values().map(|field| &field.field_key_tokens)
-    }
+    // Commas inside groups are part of a single token tree, so this stops at the first field
+    let level = tokens
+        .by_ref()
+        .take_while(|tt| !matches!(tt, TokenTree::Punct(punct) if punct.as_char() == ','))
+        .collect::<TokenStream>();
 
-    fn sorted_field_record_tokens(&self) -> impl Iterator<Item = &TokenStream> {
-        self.sorted_fields.values().map(|field| &field.field_record_tokens)
-    }
+    // Paths with a single segment could be field-values, so a level needs at least two
+    let level = match syn::parse2::<syn::Path>(level) {
+        Ok(level) if level.segments.len() > 1 => level,
+        _ => return (None, input),
+    };
 
-    fn sorted_field_value_tokens(&self) -> impl Iterator<Item = &TokenStream> {
-        self.sorted_fields.values().map(|field| &field.field_value_tokens)
-    }
+    let mut segments = level.segments.iter().rev();
 
-    fn sorted_field_cfg_tokens(&'_ self) -> impl Iterator<Item = TokenStream> + '_ {
-        self.sorted_fields.values().map(|field| field
-            .cfg_attr
-            .as_ref()
-            .map(|cfg_attr| quote!(#cfg_attr))
-            .unwrap_or_else(|| quote!(#[cfg(not(emit_rt__private_false))])))
+    match (segments.next(), segments.next()) {
+        (Some(variant), Some(ty))
+            if ty.ident == "Level" && LEVELS.iter().any(|level| variant.ident == level) =>
+        {
+            let variant = &variant.ident;
+
+            (Some(quote!(#variant)), tokens.collect())
+        }
+        _ => (Some(quote!(#level)), tokens.collect()),
     }
+}
 
-    fn next_ident(&mut self, span: Span) -> Ident {
-        let i = Ident::new(&format!("__tmp{}", self.field_index), span);
-        self.field_index += 1;
+/**
+Turn a leading emitter expression, like `logger` in `logger, "Text {a}", a`, into a `target` field-value.
 
-        i
+This lets an emitter like a `Logger` be passed as the first argument of the macros.
+The template and field-values like `target: logger` aren't emitters, so they're left alone.
+*/
+pub(super) fn split_receiver(input: TokenStream) -> TokenStream {
+    let parser = |input: ParseStream| {
+        let is_field_value =
+            input.peek(Ident) && input.peek2(Token![:]) && !input.peek2(Token![::]);
+
+        if input.peek(LitStr) || input.peek(Token![#]) || is_field_value {
+            return Ok(None);
+        }
+
+        let receiver = input.parse::<Expr>()?;
+        input.parse::<Token![,]>()?;
+
+        Ok(Some((receiver, input.parse::<TokenStream>()?)))
+    };
+
+    match parser.parse2(input.clone()) {
+        Ok(Some((receiver, rest))) => quote!(target: #receiver, #rest),
+        _ => input,
     }
+}
 
-    fn push(&mut self, label: String, mut fv: FieldValue) {
-        let mut attrs = vec![];
-        let mut cfg_attr = None;
+/**
+Split a trailing expression for key-values built at runtime, like `kvs` in `"Text {a}", a, kvs`, from the input.
 
-        for attr in mem::take(&mut fv.attrs) {
-            if attr.is_cfg() {
-                assert!(
-                    cfg_attr.is_none(),
-                    "only a single #[cfg] is supported on fields"
-                );
-                cfg_attr = Some(attr);
+The input is parsed into its arguments, so commas inside an expression, like the ones in
+`f::<A, B>()`, don't split it. If there's no expression after the template then `None` is returned.
+*/
+pub(super) fn split_runtime_kvs(
+    input: TokenStream,
+) -> Result<(TokenStream, Option<TokenStream>), syn::Error> {
+    let parser = |input: ParseStream| {
+        let mut args = Vec::new();
+
+        while !input.is_empty() {
+            let is_field_value =
+                input.peek(Ident) && input.peek2(Token![:]) && !input.peek2(Token![::]);
+
+            // Field-values with attributes or keys aren't expressions, so they can't be the runtime key-values
+            let arg = if input.peek(Token![#]) || is_field_value {
+                (input.parse::<FieldValue>()?.into_token_stream(), false)
             } else {
-                attrs.push(attr);
+                (input.parse::<Expr>()?.into_token_stream(), true)
+            };
+
+            args.push(arg);
+
+            if input.is_empty() {
+                break;
             }
+
+            input.parse::<Token![,]>()?;
         }
 
-        let v = self.next_ident(fv.span());
+        Ok(args)
+    };
 
-        // NOTE: We intentionally wrap the expression in layers of blocks
-        self.match_value_tokens.push(quote_spanned!(fv.span()=> #cfg_attr { #(#attrs)* emit::ct::__private_capture!(#fv) }));
+    let mut args = parser.parse2(input)?;
 
-        // If there's a #[cfg] then also push its reverse
-        // This is to give a dummy value to the pattern binding since they don't support attributes
-        if let Some(cfg_attr) = &cfg_attr {
-            let cfg_attr = cfg_attr.invert_cfg().expect("attribute is not a #[cfg]");
+    let runtime_kvs = match args.last() {
+        Some((_, true)) if args.len() > 1 => args.pop().map(|(kvs, _)| kvs),
+        _ => None,
+    };
 
-            self.match_value_tokens.push(quote_spanned!(fv.span()=> #cfg_attr ()));
+    let mut input = TokenStream::new();
+    for (i, (arg, _)) in args.into_iter().enumerate() {
+        if i > 0 {
+            input.extend(quote!(,));
         }
 
-        self.match_binding_tokens.push(quote!(#v));
+        input.extend(arg);
+    }
 
-        // Make sure keys aren't duplicated
-        let previous = self.sorted_fields.insert(
-            label.clone(),
-            SortedField {
-                field_key_tokens: quote_spanned!(fv.span()=> #cfg_attr #label),
-                field_record_tokens: quote_spanned!(fv.span()=> #cfg_attr #v.clone()),
-                field_value_tokens: quote_spanned!(fv.span()=> #cfg_attr &#v),
-                cfg_attr,
-            }
-        );
+    Ok((input, runtime_kvs))
+}
 
-        if previous.is_some() {
-            panic!("keys cannot be duplicated");
-        }
-    }
+/**
+The labels of the holes that escaped braces are marked with, like `{{` and `}}`.
+
+They start with `__emit`, like the other identifiers the macros generate, so they won't clash
+with the label of a real hole.
+*/
+const ESCAPED_BRACES: [(&str, char); 2] = [
+    ("__emit_escaped_open", '{'),
+    ("__emit_escaped_close", '}'),
+];
+
+/**
+The brace that a hole marks, if its label is one of `ESCAPED_BRACES`.
+*/
+fn escaped_brace(label: &str) -> Option<char> {
+    ESCAPED_BRACES
+        .iter()
+        .find(|(escape, _)| *escape == label)
+        .map(|(_, c)| *c)
 }
 
-struct CfgVisitor<F>(F);
+/**
+Mark the escaped braces in the template literal as holes.
 
-impl<'a, F> fv_template::ct::Visitor for CfgVisitor<F>
-where
-    F: Fn(&str) -> Option<&'a Attribute> + 'a,
-{
-    fn visit_hole(&mut self, label: &str, hole: TokenStream) -> TokenStream {
-        match (self.0)(label) {
-            Some(cfg_attr) => {
-                quote!(#cfg_attr #hole)
-            }
-            _ => hole,
-        }
-    }
+The template parser unescapes braces into the text around them. Marking them as holes keeps
+them as parts of their own, which are turned into `Part::Escape`s instead of being captured.
+*/
+fn mark_escapes(input: TokenStream) -> TokenStream {
+    map_template_lit(input, |lit| Ok(mark_template_escapes(&lit.value())))
+        .expect("marking escapes is infallible")
 }
 
-pub(super) trait AttributeExt {
-    fn is_cfg(&self) -> bool;
-    fn invert_cfg(&self) -> Option<Attribute>;
+/**
+Remove any comments, like `{! a comment !}`, from the template literal.
+
+The template literal is the string literal that isn't part of a field-value.
+*/
+fn strip_comments(input: TokenStream) -> Result<TokenStream, syn::Error> {
+    map_template_lit(input, |lit| {
+        strip_template_comments(&lit.value()).ok_or_else(|| {
+            syn::Error::new(lit.span(), "unterminated template comment, expected `!}`")
+        })
+    })
 }
 
-impl AttributeExt for Attribute {
-    fn is_cfg(&self) -> bool {
-        if let Some(ident) = self.path.get_ident() {
-            ident == "cfg"
-        } else {
-            false
+/**
+Split format specs, like `{a:*^8}`, off the holes in the template literal.
+
+The specs are returned by the label of the hole they belong to, as tokens for a runtime `HoleSpec`.
+*/
+pub(super) fn split_hole_specs(input: TokenStream) -> (TokenStream, BTreeMap<String, TokenStream>) {
+    let mut specs = BTreeMap::new();
+
+    let input = map_template_lit(input, |lit| Ok(strip_template_specs(&lit.value(), &mut specs)))
+        .expect("stripping specs is infallible");
+
+    (input, specs)
+}
+
+/**
+Replace the template literal in the input.
+
+The template literal is the string literal that isn't part of a field-value.
+*/
+fn map_template_lit(
+    input: TokenStream,
+    mut f: impl FnMut(&LitStr) -> Result<String, syn::Error>,
+) -> Result<TokenStream, syn::Error> {
+    let mut output = TokenStream::new();
+    let mut stripped = false;
+
+    let mut tokens = input.into_iter().peekable();
+    while tokens.peek().is_some() {
+        let mut segment = tokens
+            .by_ref()
+            .take_while(|tt| !matches!(tt, TokenTree::Punct(punct) if punct.as_char() == ','))
+            .collect::<Vec<_>>();
+
+        if let (false, [TokenTree::Literal(lit)]) = (stripped, &*segment) {
+            if let Ok(lit) = syn::parse2::<LitStr>(TokenTree::Literal(lit.clone()).into()) {
+                let template = f(&lit)?;
+
+                segment = LitStr::new(&template, lit.span())
+                    .into_token_stream()
+                    .into_iter()
+                    .collect();
+                stripped = true;
+            }
+        }
+
+        output.extend(segment);
+
+        // The comma was consumed by `take_while`, so it needs to be added back
+        if tokens.peek().is_some() {
+            output.extend(quote!(,));
         }
     }
 
-    fn invert_cfg(&self) -> Option<Attribute> {
-        match self.path.get_ident() {
-            Some(ident) if ident == "cfg" => match self.parse_meta() {
-                Ok(Meta::List(list)) => {
-                    let inner = list.nested;
+    Ok(output)
+}
 
-                    Some(Attribute {
-                        pound_token: self.pound_token.clone(),
-                        style: self.style.clone(),
-                        bracket_token: self.bracket_token.clone(),
-                        path: self.path.clone(),
-                        tokens: quote!((not(#inner))),
-                    })
+/**
+Remove comments from a template, returning `None` if a comment isn't terminated.
+
+Comments only start outside of holes, so `{a: {!b}}` is a hole with an expression in it.
+*/
+fn strip_template_comments(template: &str) -> Option<String> {
+    let mut stripped = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    let mut depth = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' if depth == 0 && chars.peek() == Some(&c) => {
+                chars.next();
+                stripped.push(c);
+                stripped.push(c);
+            }
+            '{' if depth == 0 && chars.peek() == Some(&'!') => {
+                chars.next();
+
+                loop {
+                    match chars.next()? {
+                        '!' if chars.peek() == Some(&'}') => {
+                            chars.next();
+                            break;
+                        }
+                        _ => (),
+                    }
                 }
-                _ => None,
-            },
-            _ => None,
+            }
+            '{' => {
+                depth += 1;
+                stripped.push(c);
+            }
+            '}' => {
+                depth -= 1;
+                stripped.push(c);
+            }
+            c => stripped.push(c),
         }
     }
+
+    Some(stripped)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/**
+Replace escaped braces in a template with holes labelled by `ESCAPED_BRACES`.
 
-    #[test]
+Escapes only appear outside of holes, so `{a: {b}}` is a hole with an expression in it.
+*/
+fn mark_template_escapes(template: &str) -> String {
+    let mut marked = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    let mut depth = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' if depth == 0 && chars.peek() == Some(&c) => {
+                chars.next();
+
+                let (label, _) = ESCAPED_BRACES
+                    .iter()
+                    .find(|(_, escape)| *escape == c)
+                    .expect("missing escape label");
+
+                marked.push('{');
+                marked.push_str(label);
+                marked.push('}');
+            }
+            '{' => {
+                depth += 1;
+                marked.push(c);
+            }
+            '}' => {
+                depth -= 1;
+                marked.push(c);
+            }
+            c => marked.push(c),
+        }
+    }
+
+    marked
+}
+
+/**
+Remove format specs from the holes in a template, collecting them into `specs`.
+
+A hole only has a spec if it isn't a valid field-value by itself, so `{a:b<10}`
+is still a hole with an expression in it.
+*/
+fn strip_template_specs(template: &str, specs: &mut BTreeMap<String, TokenStream>) -> String {
+    let mut stripped = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek() == Some(&c) => {
+                chars.next();
+                stripped.push(c);
+                stripped.push(c);
+            }
+            '{' => {
+                let mut depth = 1;
+                let mut hole = String::new();
+
+                for c in &mut chars {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => (),
+                    }
+
+                    if depth == 0 {
+                        break;
+                    }
+
+                    hole.push(c);
+                }
+
+                stripped.push('{');
+
+                match split_hole_spec(&hole) {
+                    Some((label, fv, spec)) => {
+                        stripped.push_str(fv);
+                        specs.insert(label, spec);
+                    }
+                    None => stripped.push_str(&hole),
+                }
+
+                // An unterminated hole is left for the template parser to report
+                if depth == 0 {
+                    stripped.push('}');
+                }
+            }
+            c => stripped.push(c),
+        }
+    }
+
+    stripped
+}
+
+/**
+Split a hole like `a:*^8` into its label, its field-value, and tokens for its spec.
+
+A spec is an optional fill character, an alignment of `<`, `^`, or `>`, and an optional width.
+*/
+fn split_hole_spec(hole: &str) -> Option<(String, &str, TokenStream)> {
+    if syn::parse_str::<FieldValue>(hole).is_ok() {
+        return None;
+    }
+
+    let (fv, spec) = hole.rsplit_once(':')?;
+
+    let label = match syn::parse_str::<FieldValue>(fv).ok()? {
+        FieldValue {
+            member: Member::Named(ident),
+            colon_token: None,
+            ..
+        } => ident.to_string(),
+        _ => return None,
+    };
+
+    let align = |c: char| match c {
+        '<' => Some(quote!(Left)),
+        '^' => Some(quote!(Center)),
+        '>' => Some(quote!(Right)),
+        _ => None,
+    };
+
+    let mut chars = spec.chars();
+    let first = chars.next()?;
+
+    let (fill, align, width) = match (chars.next().and_then(align), align(first)) {
+        (Some(align), _) => (first, align, &spec[first.len_utf8() + 1..]),
+        (None, Some(align)) => (' ', align, &spec[1..]),
+        (None, None) => return None,
+    };
+
+    if !width.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let width = width.parse::<usize>().unwrap_or(0);
+
+    Some((
+        label,
+        fv,
+        quote!(::emit::rt::__private::HoleSpec {
+            fill: #fill,
+            align: ::emit::rt::__private::Align::#align,
+            width: #width,
+        }),
+    ))
+}
+
+/**
+Make sure each hole in the template literal is a valid field-value.
+
+This gives a more targeted error than failing to parse the whole template
+when a single hole is malformed. The literal is the template as it's written,
+so the error can point at just the malformed hole. Anything unusual about the
+template itself is left for the template parser to report.
+*/
+fn check_holes(lit: &LitStr) -> Result<(), syn::Error> {
+    let template = lit.value();
+    let mut chars = template.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek().map(|(_, next)| *next) == Some(c) => {
+                chars.next();
+            }
+            '{' if chars.peek().map(|(_, c)| *c) == Some('!') => {
+                // Comments aren't holes, so they're skipped up to their closing `!}`
+                while let Some((_, c)) = chars.next() {
+                    if c == '!' && chars.peek().map(|(_, c)| *c) == Some('}') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            '{' => {
+                let mut depth = 1;
+                let mut end = template.len();
+
+                for (i, c) in &mut chars {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => (),
+                    }
+
+                    if depth == 0 {
+                        end = i + 1;
+                        break;
+                    }
+                }
+
+                if depth != 0 {
+                    return Ok(());
+                }
+
+                let hole = &template[start + 1..end - 1];
+                let fv = split_hole_spec(hole).map_or(hole, |(_, fv, _)| fv);
+
+                if let Err(err) = syn::parse_str::<FieldValue>(fv) {
+                    return Err(syn::Error::new(
+                        hole_span(lit, start..end),
+                        format!("failed to parse hole `{{{}}}`: {}", hole, err),
+                    ));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/**
+Get the span of a hole in the template literal, from its byte range in the template.
+
+The range can only be mapped onto the literal as it's written when the literal doesn't
+contain any escapes. Otherwise the span of the whole literal is used.
+*/
+fn hole_span(lit: &LitStr, range: Range<usize>) -> Span {
+    let token = match lit.to_token_stream().into_iter().next() {
+        Some(TokenTree::Literal(token)) => token,
+        _ => return lit.span(),
+    };
+
+    let written = token.to_string();
+
+    let offset = if written.starts_with('r') {
+        written.find('"').map(|quote| quote + 1)
+    } else if written.starts_with('"') && !written.contains('\\') {
+        Some(1)
+    } else {
+        None
+    };
+
+    offset
+        .and_then(|offset| token.subspan(range.start + offset..range.end + offset))
+        .unwrap_or_else(|| lit.span())
+}
+
+/**
+Find the template literal in the input, as it was written.
+
+The template literal is the string literal that isn't part of a field-value.
+*/
+fn find_template_lit(input: &TokenStream) -> Option<LitStr> {
+    let mut found = None;
+
+    map_template_lit(input.clone(), |lit| {
+        found = Some(lit.clone());
+        Ok(lit.value())
+    })
+    .expect("finding the template is infallible");
+
+    found
+}
+
+/**
+Attributes that determine how a field is captured.
+
+Each of these replaces how the value is captured, so a field can only use one of them.
+*/
+const CAPTURE_ATTRS: [&str; 26] = [
+    "as_array",
+    "as_debug",
+    "as_diff",
+    "as_display",
+    "as_exit_status",
+    "as_flags",
+    "as_hexdump",
+    "as_io_error",
+    "as_iso_duration",
+    "as_iter",
+    "as_json_string",
+    "as_locked_debug",
+    "as_path",
+    "as_records",
+    "as_serde",
+    "as_status",
+    "as_string",
+    "as_sval",
+    "as_timestamp",
+    "as_uptime",
+    "as_weak_debug",
+    "precision",
+    "redact",
+    "source",
+    "strip_ansi",
+    "ts",
+];
+
+/**
+Make sure a field has a single way to be captured.
+
+A field without a capture attribute uses the default for the whole statement, if there is one,
+so it's only ambiguous when a field has more than one capture attribute of its own.
+*/
+fn check_capture(label: &str, fv: &FieldValue, span: Span) -> Result<(), syn::Error> {
+    let captures = fv
+        .attrs
+        .iter()
+        .filter(|attr| attr.is_capture())
+        .map(|attr| format!("`#[{}]`", attr.path.to_token_stream().to_string().replace(' ', "")))
+        .collect::<Vec<_>>();
+
+    if captures.len() > 1 {
+        return Err(syn::Error::new(
+            span,
+            format!(
+                "`{}` has more than one capture attribute, {}; use only one of them",
+                label,
+                captures.join(" and ")
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct Fields {
+    match_value_tokens: Vec<TokenStream>,
+    match_binding_tokens: Vec<TokenStream>,
+    sorted_fields: BTreeMap<String, SortedField>,
+    /**
+    Keys in the order they were declared in.
+
+    These are only used when the `no_sort` feature is enabled.
+    */
+    keys_in_order: Vec<String>,
+    keys_by_label: BTreeMap<String, String>,
+    field_index: usize,
+    default_capture: Option<Attribute>,
+    /**
+    The level of the record the fields are captured for, like `Info`.
+
+    Custom levels are the path to the level, like `MyLevel::Critical`.
+    */
+    level: String,
+}
+
+struct SortedField {
+    field_key_tokens: TokenStream,
+    field_record_tokens: TokenStream,
+    field_value_tokens: TokenStream,
+    field_present_tokens: TokenStream,
+    cfg_attr: Option<Attribute>,
+    optional: bool,
+    no_render: bool,
+    /**
+    The attributes the value is captured with, like `#[as_debug]`, if it has any.
+    */
+    #[cfg(test)]
+    capture: Option<String>,
+}
+
+impl Fields {
+    fn get_by_label(&self, label: &str) -> Option<(&str, &SortedField)> {
+        let key = self.keys_by_label.get(label)?;
+
+        self.sorted_fields
+            .get(key)
+            .map(|field| (key.as_str(), field))
+    }
+
+    fn has_cfgs(&self) -> bool {
+        self.sorted_fields
+            .values()
+            .any(|field| field.cfg_attr.is_some())
+    }
+
+    fn has_optionals(&self) -> bool {
+        self.sorted_fields.values().any(|field| field.optional)
+    }
+
+    /**
+    The fields in the order they're stored on the record.
+
+    Fields are sorted by key, unless the `no_sort` feature is enabled.
+    In that case they're kept in the order they were declared in.
+    */
+    #[cfg(not(feature = "no_sort"))]
+    fn sorted_field_values(&self) -> impl Iterator<Item = &SortedField> {
+        self.sorted_fields.values()
+    }
+
+    #[cfg(feature = "no_sort")]
+    fn sorted_field_values(&self) -> impl Iterator<Item = &SortedField> {
+        self.keys_in_order.iter().map(move |key| &self.sorted_fields[key])
+    }
+
+    #[cfg(test)]
+    fn sorted_keys(&self) -> Vec<String> {
+        if cfg!(feature = "no_sort") {
+            self.keys_in_order.clone()
+        } else {
+            self.sorted_fields.keys().cloned().collect()
+        }
+    }
+
+    fn sorted_field_key_tokens(&self) -> impl Iterator<Item = &TokenStream> {
+        self.sorted_field_values().map(|field| &field.field_key_tokens)
+    }
+
+    fn sorted_field_record_tokens(&self) -> impl Iterator<Item = &TokenStream> {
+        self.sorted_field_values().map(|field| &field.field_record_tokens)
+    }
+
+    fn sorted_field_value_tokens(&self) -> impl Iterator<Item = &TokenStream> {
+        self.sorted_field_values().map(|field| &field.field_value_tokens)
+    }
+
+    fn sorted_field_present_tokens(&self) -> impl Iterator<Item = &TokenStream> {
+        self.sorted_field_values().map(|field| &field.field_present_tokens)
+    }
+
+    fn sorted_field_cfg_tokens(&'_ self) -> impl Iterator<Item = TokenStream> + '_ {
+        self.sorted_field_values().map(|field| field
+            .cfg_attr
+            .as_ref()
+            .map(|cfg_attr| quote!(#cfg_attr))
+            .unwrap_or_else(|| quote!(#[cfg(not(emit_rt__private_false))])))
+    }
+
+    fn next_ident(&mut self, span: Span) -> Ident {
+        let i = Ident::new(&format!("__tmp{}", self.field_index), span);
+        self.field_index += 1;
+
+        i
+    }
+
+    fn push(&mut self, label: String, mut fv: FieldValue) {
+        let mut attrs = vec![];
+        let mut cfg_attr = None;
+        let mut key = None;
+        let mut group = None;
+        let mut aliases = vec![];
+        let mut detailed_when = None;
+        let mut optional = false;
+        let mut no_render = false;
+
+        for attr in mem::take(&mut fv.attrs) {
+            if attr.is_cfg() {
+                assert!(
+                    cfg_attr.is_none(),
+                    "only a single #[cfg] is supported on fields"
+                );
+                cfg_attr = Some(attr);
+            } else if let Some(value) = attr.str_value("key") {
+                assert!(key.is_none(), "only a single #[key] or #[ts] is supported on fields");
+                key = Some(value);
+            } else if let Some(value) = attr.str_value("group") {
+                assert!(group.is_none(), "only a single #[group] is supported on fields");
+                group = Some(value);
+            } else if let Some(value) = attr.str_value("alias") {
+                aliases.push(value);
+            } else if attr.path.is_ident("detailed_when") {
+                assert!(detailed_when.is_none(), "only a single #[detailed_when] is supported on fields");
+                detailed_when = Some(attr.level_arg());
+            } else if attr.path.is_ident("ts") {
+                // A timestamp is captured with a well-known key so emitters can find it
+                assert!(key.is_none(), "only a single #[key] or #[ts] is supported on fields");
+                key = Some("ts".to_owned());
+                attrs.push(syn::parse_quote!(#[::emit::as_timestamp]));
+            } else if attr.path.is_ident("optional") {
+                assert!(!optional, "only a single #[optional] is supported on fields");
+                optional = true;
+            } else if attr.path.is_ident("precision") {
+                // Floats are rounded when they're captured, so the stored value is rounded too
+                let digits = attr.precision_arg();
+                attrs.push(syn::parse_quote!(#[::emit::ct::__private_as_precision(#digits)]));
+            } else if attr.path.is_ident("as_array") {
+                // The elements are written explicitly, so nothing after the field is pulled into it
+                assert!(
+                    attr.tokens.is_empty() && matches!(fv.expr, Expr::Array(_) | Expr::Tuple(_)),
+                    "expected an array like `#[as_array] tags: [a, b, c]`"
+                );
+                attrs.push(syn::parse_quote!(#[::emit::ct::__private_as_array]));
+            } else if attr.path.is_ident("no_render") {
+                assert!(!no_render, "only a single #[no_render] is supported on fields");
+                no_render = true;
+            } else {
+                attrs.push(attr);
+            }
+        }
+
+        // Fields with a #[detailed_when] use their capture attribute at or below the given level,
+        // or structured capture if they don't have one. At any other level they use `Display`
+        if let Some(detailed_when) = detailed_when {
+            assert!(
+                LEVELS.contains(&&*self.level),
+                "`#[detailed_when]` can't be used with custom levels"
+            );
+
+            if level_rank(&self.level) <= level_rank(&detailed_when) {
+                if attrs.is_empty() {
+                    attrs.push(syn::parse_quote!(#[::emit::as_sval]));
+                }
+            } else {
+                attrs = vec![syn::parse_quote!(#[::emit::as_display])];
+            }
+        }
+
+        // Fields without a capture attribute of their own use the default, if there is one
+        // The `source` is left alone so it's still captured as an error
+        if attrs.is_empty() && label != "source" {
+            attrs.extend(self.default_capture.clone());
+        }
+
+        // An explicit key always wins over a group
+        let key = match (key, group) {
+            (Some(key), _) => key,
+            (None, Some(group)) => format!("{}.{}", group, label),
+            (None, None) => label.clone(),
+        };
+
+        #[cfg(test)]
+        let capture = if attrs.is_empty() {
+            None
+        } else {
+            Some(quote!(#(#attrs)*).to_string())
+        };
+
+        let v = self.next_ident(fv.span());
+
+        // NOTE: We intentionally wrap the expression in layers of blocks
+        if optional {
+            // Optional fields are only captured if they're `Some`
+            // Both `Option<T>` and `Option<&T>` are matched as a reference to the inner `T`,
+            // so dereferencing the binding gives a `T` to capture without cloning it
+            let expr = &fv.expr;
+
+            let mut some_fv = fv.clone();
+            some_fv.colon_token = Some(Default::default());
+            some_fv.expr = syn::parse_quote!(*__emit_optional);
+
+            // The block passed to `Some` uses the call site's span so it isn't linted as unnecessary
+            let some_tokens = quote!(Some({ #(#attrs)* ::emit::ct::__private_capture!(#some_fv) }));
+
+            self.match_value_tokens.push(quote_spanned!(fv.span()=> #cfg_attr {
+                match &(#expr) {
+                    Some(__emit_optional) => #some_tokens,
+                    None => None,
+                }
+            }));
+        } else {
+            self.match_value_tokens.push(quote_spanned!(fv.span()=> #cfg_attr { #(#attrs)* ::emit::ct::__private_capture!(#fv) }));
+        }
+
+        // If there's a #[cfg] then also push its reverse
+        // This is to give a dummy value to the pattern binding since they don't support attributes
+        if let Some(cfg_attr) = &cfg_attr {
+            let cfg_attr = cfg_attr.invert_cfg().expect("attribute is not a #[cfg]");
+
+            self.match_value_tokens.push(quote_spanned!(fv.span()=> #cfg_attr ()));
+        }
+
+        self.match_binding_tokens.push(quote!(#v));
+
+        // If the key has been changed then it needs to replace the one that was captured
+        let field_record_tokens = if optional {
+            quote_spanned!(fv.span()=> #cfg_attr ::emit::rt::__private::optional_key_value(#key, &#v))
+        } else if key == label {
+            quote_spanned!(fv.span()=> #cfg_attr #v.clone())
+        } else {
+            quote_spanned!(fv.span()=> #cfg_attr (#key, #v.1.clone()))
+        };
+
+        self.keys_by_label.insert(label, key.clone());
+
+        // Make sure keys aren't duplicated
+        let previous = self.sorted_fields.insert(
+            key.clone(),
+            SortedField {
+                field_key_tokens: quote_spanned!(fv.span()=> #cfg_attr #key),
+                field_record_tokens,
+                field_value_tokens: if optional {
+                    quote_spanned!(fv.span()=> #cfg_attr #v.as_ref())
+                } else {
+                    quote_spanned!(fv.span()=> #cfg_attr Some(&#v))
+                },
+                field_present_tokens: if optional {
+                    quote_spanned!(fv.span()=> #cfg_attr #v.is_some())
+                } else {
+                    quote_spanned!(fv.span()=> #cfg_attr true)
+                },
+                cfg_attr,
+                optional,
+                no_render,
+                #[cfg(test)]
+                capture: capture.clone(),
+            }
+        );
+
+        assert!(previous.is_none(), "keys cannot be duplicated");
+
+        // Aliases reference the same captured value under their own keys
+        let field = &self.sorted_fields[&key];
+        let cfg_attr = field.cfg_attr.clone();
+        let field_value_tokens = field.field_value_tokens.clone();
+        let field_present_tokens = field.field_present_tokens.clone();
+
+        self.keys_in_order.push(key.clone());
+
+        for alias in aliases {
+            assert_ne!(alias, key, "an alias can't be the same as the key of its field");
+
+            let field_record_tokens = if optional {
+                quote_spanned!(fv.span()=> #cfg_attr ::emit::rt::__private::optional_key_value(#alias, &#v))
+            } else {
+                quote_spanned!(fv.span()=> #cfg_attr (#alias, #v.1.clone()))
+            };
+
+            let previous = self.sorted_fields.insert(
+                alias.clone(),
+                SortedField {
+                    field_key_tokens: quote_spanned!(fv.span()=> #cfg_attr #alias),
+                    field_record_tokens,
+                    field_value_tokens: field_value_tokens.clone(),
+                    field_present_tokens: field_present_tokens.clone(),
+                    cfg_attr: cfg_attr.clone(),
+                    optional,
+                    no_render,
+                    #[cfg(test)]
+                    capture: capture.clone(),
+                }
+            );
+
+            assert!(previous.is_none(), "keys cannot be duplicated");
+
+            self.keys_in_order.push(alias);
+        }
+    }
+}
+
+struct HoleVisitor<'b, F>(F, &'b BTreeMap<String, TokenStream>);
+
+impl<'a, 'b, F> fv_template::ct::Visitor for HoleVisitor<'b, F>
+where
+    F: Fn(&str) -> Option<(&'a str, &'a SortedField)> + 'a,
+{
+    fn visit_hole(&mut self, label: &str, hole: TokenStream) -> TokenStream {
+        if let Some(escape) = escaped_brace(label) {
+            return quote!(::emit::rt::__private::Part::Escape(#escape));
+        }
+
+        let (key, cfg_attr, no_render) = match (self.0)(label) {
+            Some((key, field)) => (key, field.cfg_attr.as_ref(), field.no_render),
+            None => (label, None, false),
+        };
+
+        // Formatted holes, and holes whose key has been changed, need to be rebuilt
+        // Holes for fields that aren't rendered are always missing, so they aren't padded
+        let hole = match self.1.get(label) {
+            _ if no_render => quote!(::emit::rt::__private::Part::UnrenderedHole(#key)),
+            Some(spec) => quote!(::emit::rt::__private::Part::FormattedHole(#key, #spec)),
+            None if key != label => quote!(::emit::rt::__private::Part::Hole(#key)),
+            None => hole,
+        };
+
+        quote!(#cfg_attr #hole)
+    }
+}
+
+/**
+The standard levels, from the most to least verbose.
+*/
+const LEVELS: [&str; 5] = ["Trace", "Debug", "Info", "Warn", "Error"];
+
+/**
+The position of a level, like `Info`, from the most to least verbose.
+*/
+fn level_rank(level: &str) -> usize {
+    LEVELS
+        .iter()
+        .position(|candidate| *candidate == level)
+        .unwrap_or_else(|| panic!("unexpected level `{}`", level))
+}
+
+pub(super) trait AttributeExt {
+    fn is_cfg(&self) -> bool;
+    fn is_capture(&self) -> bool;
+    fn invert_cfg(&self) -> Option<Attribute>;
+    fn str_value(&self, name: &str) -> Option<String>;
+    fn level_arg(&self) -> String;
+    fn precision_arg(&self) -> u32;
+}
+
+impl AttributeExt for Attribute {
+    fn is_cfg(&self) -> bool {
+        if let Some(ident) = self.path.get_ident() {
+            ident == "cfg"
+        } else {
+            false
+        }
+    }
+
+    fn is_capture(&self) -> bool {
+        // Capture attributes may be used through a path, like `#[emit::as_debug]`
+        self.path
+            .segments
+            .last()
+            .map(|segment| CAPTURE_ATTRS.iter().any(|attr| segment.ident == attr))
+            .unwrap_or(false)
+    }
+
+    fn invert_cfg(&self) -> Option<Attribute> {
+        match self.path.get_ident() {
+            Some(ident) if ident == "cfg" => match self.parse_meta() {
+                Ok(Meta::List(list)) => {
+                    let inner = list.nested;
+
+                    Some(Attribute {
+                        pound_token: self.pound_token,
+                        style: self.style,
+                        bracket_token: self.bracket_token,
+                        path: self.path.clone(),
+                        tokens: quote!((not(#inner))),
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn str_value(&self, name: &str) -> Option<String> {
+        match self.path.get_ident() {
+            Some(ident) if ident == name => match self.parse_meta() {
+                Ok(Meta::NameValue(MetaNameValue {
+                    lit: Lit::Str(lit), ..
+                })) => Some(lit.value()),
+                _ => panic!("expected a string value like `#[{} = \"value\"]`", name),
+            },
+            _ => None,
+        }
+    }
+
+    fn precision_arg(&self) -> u32 {
+        self.parse_args::<syn::LitInt>()
+            .and_then(|digits| digits.base10_parse::<u32>())
+            .expect("expected a number of decimal places like `#[precision(2)]`")
+    }
+
+    fn level_arg(&self) -> String {
+        let level = self
+            .parse_args::<syn::Path>()
+            .expect("expected a level like `#[detailed_when(Level::Debug)]`");
+
+        let level = level
+            .segments
+            .last()
+            .expect("expected a level like `#[detailed_when(Level::Debug)]`")
+            .ident
+            .to_string();
+
+        // Make sure the level is a valid one
+        level_rank(&level);
+
+        level
+    }
+}
+
+/**
+A description of what `expand_tokens` generates for some input.
+
+Tests can check the keys, target, and template of an expansion with this instead of
+matching every generated token.
+*/
+#[cfg(test)]
+#[derive(Debug, PartialEq)]
+pub(super) struct Expansion {
+    /**
+    The keys of the record, in the order they're stored on it.
+    */
+    pub(super) keys: Vec<String>,
+    /**
+    The attributes each key's value is captured with, or `None` if it uses the default.
+    */
+    pub(super) captures: BTreeMap<String, Option<String>>,
+    pub(super) target: ExpansionTarget,
+    pub(super) template: Vec<ExpansionPart>,
+}
+
+#[cfg(test)]
+#[derive(Debug, PartialEq)]
+pub(super) enum ExpansionTarget {
+    /**
+    The target is the module the record is emitted from.
+    */
+    ModulePath,
+    /**
+    A string literal is the target of the record.
+    */
+    Str(String),
+    /**
+    A closure is the emitter to send the record to.
+    */
+    Closure(String),
+    /**
+    An expression is resolved as a `Target` or emitter when the record is emitted.
+    */
+    Resolved(String),
+}
+
+#[cfg(test)]
+#[derive(Debug, PartialEq)]
+pub(super) enum ExpansionPart {
+    Text(String),
+    Escape(char),
+    Hole(String),
+    /**
+    A hole with its key and format spec, like `("a", ">10")`.
+    */
+    FormattedHole(String, String),
+    UnrenderedHole(String),
+}
+
+/**
+Describe the expansion of the input.
+
+The keys and capture attributes are described from the fields as they're parsed,
+and the template from the tokens that are generated for it.
+Any tokens in the description, like target expressions, are formatted as `TokenStream`s.
+*/
+#[cfg(test)]
+pub(super) fn describe_tokens(level: &str, input: TokenStream) -> Expansion {
+    let Prepared {
+        template,
+        fields,
+        hole_specs,
+        ..
+    } = prepare(level.to_owned(), input)
+        .unwrap_or_else(|err| panic!("failed to expand the input: {}", err));
+
+    let target = match target_field_value(&template).map(|fv| &fv.expr) {
+        Some(Expr::Lit(ExprLit {
+            lit: Lit::Str(target),
+            ..
+        })) => ExpansionTarget::Str(target.value()),
+        Some(Expr::Closure(target)) => ExpansionTarget::Closure(target.to_token_stream().to_string()),
+        Some(target) => ExpansionTarget::Resolved(target.to_token_stream().to_string()),
+        None => ExpansionTarget::ModulePath,
+    };
+
+    let template = syn::parse2::<ExprCall>(template_tokens(&template, &fields, &hole_specs))
+        .expect("failed to parse template");
+
+    let parts = match template.args.first() {
+        Some(Expr::Reference(ExprReference { expr, .. })) => match &**expr {
+            Expr::Array(ExprArray { elems, .. }) => elems.iter().map(describe_part).collect(),
+            _ => panic!("expected an array of template parts"),
+        },
+        _ => panic!("expected an array of template parts"),
+    };
+
+    Expansion {
+        keys: fields.sorted_keys(),
+        captures: fields
+            .sorted_fields
+            .iter()
+            .map(|(key, field)| (key.clone(), field.capture.clone()))
+            .collect(),
+        target,
+        template: parts,
+    }
+}
+
+#[cfg(test)]
+fn describe_part(part: &Expr) -> ExpansionPart {
+    let (func, args) = match part {
+        Expr::Call(ExprCall { func, args, .. }) => (func, args),
+        _ => panic!("expected a template part"),
+    };
+
+    let kind = match &**func {
+        Expr::Path(ExprPath { path, .. }) => path
+            .segments
+            .last()
+            .expect("expected a template part")
+            .ident
+            .to_string(),
+        _ => panic!("expected a template part"),
+    };
+
+    let str_arg = || match args.first() {
+        Some(Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        })) => lit.value(),
+        _ => panic!("expected a string argument to `{}`", kind),
+    };
+
+    match &*kind {
+        "Text" => ExpansionPart::Text(str_arg()),
+        "Hole" => ExpansionPart::Hole(str_arg()),
+        "UnrenderedHole" => ExpansionPart::UnrenderedHole(str_arg()),
+        "FormattedHole" => {
+            let spec = args.iter().nth(1).expect("expected a hole spec");
+
+            ExpansionPart::FormattedHole(str_arg(), spec.to_token_stream().to_string())
+        }
+        "Escape" => match args.first() {
+            Some(Expr::Lit(ExprLit {
+                lit: Lit::Char(lit), ..
+            })) => ExpansionPart::Escape(lit.value()),
+            _ => panic!("expected a char argument to `Escape`"),
+        },
+        kind => panic!("unexpected template part `{}`", kind),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
     #[rustfmt::skip]
+    #[cfg(not(feature = "no_sort"))]
     fn expand_emit() {
         let cases = vec![
             (
                 quote!("Text and {b: 17} and {a} and {#[as_debug] c} and {d: String::from(\"short lived\")} and {#[cfg(disabled)] e}"),
                 quote!({
-                    extern crate emit;
-
                     match (
-                        {emit::ct::__private_capture!(b: 17) },
-                        {emit::ct::__private_capture!(a) },
+                        {::emit::ct::__private_capture!(b: 17) },
+                        {::emit::ct::__private_capture!(a) },
                         {
                             #[as_debug]
-                            emit::ct::__private_capture!(c)
+                            ::emit::ct::__private_capture!(c)
                         },
-                        {emit::ct::__private_capture!(d: String::from("short lived")) },
+                        {::emit::ct::__private_capture!(d: String::from("short lived")) },
                         #[cfg(disabled)]
-                        {emit::ct::__private_capture!(e) },
+                        {::emit::ct::__private_capture!(e) },
                         #[cfg(not(disabled))]
                         ()
                     ) {
                         (__tmp0, __tmp1, __tmp2, __tmp3, __tmp4) => {
-                            let kvs = emit::rt::__private::KeyValues {
+                            let kvs = ::emit::rt::__private::KeyValues {
                                 sorted_key_values: &[
                                     __tmp1.clone(),
                                     __tmp0.clone(),
@@ -309,26 +1711,34 @@ mod tests {
                                 ]
                             };
 
-                            let template = emit::rt::__private::template(&[
-                                emit::rt::__private::Part::Text("Text and "),
-                                emit::rt::__private::Part::Hole ( "b"),
-                                emit::rt::__private::Part::Text(" and "),
-                                emit::rt::__private::Part::Hole ( "a"),
-                                emit::rt::__private::Part::Text(" and "),
-                                emit::rt::__private::Part::Hole ( "c" ),
-                                emit::rt::__private::Part::Text(" and "),
-                                emit::rt::__private::Part::Hole ( "d" ),
-                                emit::rt::__private::Part::Text(" and "),
+                            let template = ::emit::rt::__private::template(&[
+                                ::emit::rt::__private::Part::Text("Text and "),
+                                ::emit::rt::__private::Part::Hole ( "b"),
+                                ::emit::rt::__private::Part::Text(" and "),
+                                ::emit::rt::__private::Part::Hole ( "a"),
+                                ::emit::rt::__private::Part::Text(" and "),
+                                ::emit::rt::__private::Part::Hole ( "c" ),
+                                ::emit::rt::__private::Part::Text(" and "),
+                                ::emit::rt::__private::Part::Hole ( "d" ),
+                                ::emit::rt::__private::Part::Text(" and "),
                                 #[cfg(disabled)]
-                                emit::rt::__private::Part::Hole ( "e" )
-                            ]);
+                                ::emit::rt::__private::Part::Hole ( "e" )
+                            ]).with_source("Text and {b: 17} and {a} and {#[as_debug] c} and {d: String::from(\"short lived\")} and {#[cfg(disabled)] e}");
 
-                            let record = emit::rt::__private::Record {
+                            let record = ::emit::rt::__private::Record {
+                                level: ::emit::rt::__private::Level::Info,
+                                target: module_path!(),
+                                location: ::emit::rt::__private::SourceLocation {
+                                    file: file!(),
+                                    line: line!(),
+                                    module_path: module_path!(),
+                                },
                                 kvs,
                                 template,
                             };
 
-                            emit::rt::__private_forward!({
+                            ::emit::rt::__private_forward!({
+                                level: ::emit::rt::__private::Level::Info,
                                 target: None,
                                 key_value_cfgs: [
                                     #[cfg(not(emit_rt__private_false))],
@@ -338,7 +1748,7 @@ mod tests {
                                     #[cfg(disabled)]
                                 ],
                                 keys: ["a", "b", "c", "d", #[cfg(disabled)] "e"],
-                                values: [&__tmp1, &__tmp0, &__tmp2, &__tmp3, #[cfg(disabled)] &__tmp4],
+                                values: [Some(&__tmp1), Some(&__tmp0), Some(&__tmp2), Some(&__tmp3), #[cfg(disabled)] Some(&__tmp4)],
                                 record: &record,
                             });
                         }
@@ -346,35 +1756,136 @@ mod tests {
                 }),
             ),
             (
-                quote!(target: log, "Text and {a}", a: 42),
+                quote!(target: log, "Text and {a}", a: 42),
+                quote!({
+                    match { ::emit::ct::__private_capture!(a: 42) } {
+                        __tmp0 => {
+                            match &(log) {
+                                __emit_target => {
+                                    let __emit_target = {
+                                        use ::emit::__private::ResolveTarget;
+
+                                        (&::emit::__private::TargetArg(__emit_target))
+                                            .resolve(module_path!())
+                                    };
+
+                                    let kvs = ::emit::rt::__private::KeyValues {
+                                        sorted_key_values: &[__tmp0.clone()]
+                                    };
+
+                                    let template = ::emit::rt::__private::template(&[
+                                        ::emit::rt::__private::Part::Text("Text and "),
+                                        ::emit::rt::__private::Part::Hole ( "a")
+                                    ]).with_source("Text and {a}");
+
+                                    let record = ::emit::rt::__private::Record {
+                                        level: ::emit::rt::__private::Level::Info,
+                                        target: __emit_target.0,
+                                        location: ::emit::rt::__private::SourceLocation {
+                                            file: file!(),
+                                            line: line!(),
+                                            module_path: module_path!(),
+                                        },
+                                        kvs,
+                                        template,
+                                    };
+
+                                    ::emit::rt::__private_forward!({
+                                        level: ::emit::rt::__private::Level::Info,
+                                        target: Some(__emit_target.1),
+                                        key_value_cfgs: [
+                                            #[cfg(not(emit_rt__private_false))]
+                                        ],
+                                        keys: ["a"],
+                                        values: [Some(&__tmp0)],
+                                        record: &record,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                })
+            ),
+            (
+                quote!("Text"),
                 quote!({
-                    extern crate emit;
+                    {
+                        let kvs = ::emit::rt::__private::KeyValues {
+                            sorted_key_values: &[]
+                        };
+
+                        let template = ::emit::rt::__private::template(&[
+                            ::emit::rt::__private::Part::Text("Text")
+                        ]).with_source("Text");
+
+                        let record = ::emit::rt::__private::Record {
+                            level: ::emit::rt::__private::Level::Info,
+                            target: module_path!(),
+                            location: ::emit::rt::__private::SourceLocation {
+                                file: file!(),
+                                line: line!(),
+                                module_path: module_path!(),
+                            },
+                            kvs,
+                            template,
+                        };
 
+                        ::emit::rt::__private_forward!({
+                            level: ::emit::rt::__private::Level::Info,
+                            target: None,
+                            key_value_cfgs: [],
+                            keys: [],
+                            values: [],
+                            record: &record,
+                        });
+                    }
+                })
+            ),
+            (
+                quote!("Request {#[group = \"http\"] method} finished", #[group = "http"] status: 200, #[group = "http"] #[key = "url"] path),
+                quote!({
                     match (
-                        { emit::ct::__private_capture!(a: 42) }
+                        { ::emit::ct::__private_capture!(method) },
+                        { ::emit::ct::__private_capture!(path) },
+                        { ::emit::ct::__private_capture!(status: 200) }
                     ) {
-                        (__tmp0) => {
-                            let kvs = emit::rt::__private::KeyValues {
-                                sorted_key_values: &[__tmp0.clone()]
+                        (__tmp0, __tmp1, __tmp2) => {
+                            let kvs = ::emit::rt::__private::KeyValues {
+                                sorted_key_values: &[
+                                    ("http.method", __tmp0.1.clone()),
+                                    ("http.status", __tmp2.1.clone()),
+                                    ("url", __tmp1.1.clone())
+                                ]
                             };
 
-                            let template = emit::rt::__private::template(&[
-                                emit::rt::__private::Part::Text("Text and "),
-                                emit::rt::__private::Part::Hole ( "a")
-                            ]);
+                            let template = ::emit::rt::__private::template(&[
+                                ::emit::rt::__private::Part::Text("Request "),
+                                ::emit::rt::__private::Part::Hole("http.method"),
+                                ::emit::rt::__private::Part::Text(" finished")
+                            ]).with_source("Request {#[group = \"http\"] method} finished");
 
-                            let record = emit::rt::__private::Record {
+                            let record = ::emit::rt::__private::Record {
+                                level: ::emit::rt::__private::Level::Info,
+                                target: module_path!(),
+                                location: ::emit::rt::__private::SourceLocation {
+                                    file: file!(),
+                                    line: line!(),
+                                    module_path: module_path!(),
+                                },
                                 kvs,
                                 template,
                             };
 
-                            emit::rt::__private_forward!({
-                                target: Some(log),
+                            ::emit::rt::__private_forward!({
+                                level: ::emit::rt::__private::Level::Info,
+                                target: None,
                                 key_value_cfgs: [
+                                    #[cfg(not(emit_rt__private_false))],
+                                    #[cfg(not(emit_rt__private_false))],
                                     #[cfg(not(emit_rt__private_false))]
                                 ],
-                                keys: ["a"],
-                                values: [&__tmp0],
+                                keys: ["http.method", "http.status", "url"],
+                                values: [Some(&__tmp0), Some(&__tmp2), Some(&__tmp1)],
                                 record: &record,
                             });
                         }
@@ -384,9 +1895,711 @@ mod tests {
         ];
 
         for (expr, expected) in cases {
-            let actual = expand_tokens(expr);
+            let actual = expand_tokens(ExpandTokens {
+                level: quote!(Info),
+                input: expr,
+                receiver: None,
+                blocking: false,
+                runtime_kvs: None,
+            });
 
             assert_eq!(expected.to_string(), actual.to_string());
         }
     }
+
+    #[test]
+    fn check_malformed_holes() {
+        let cases = vec![
+            (quote!("Text {a} and {b: 42}", c), None),
+            (quote!(target: log, "Text {{a}} and {b: Some { x: 1 }.x}"), None),
+            (quote!("Text {a:*^8} and {! {b: } !}"), None),
+            (
+                quote!(target: "log", "Text {a} and {b: }"),
+                Some("failed to parse hole `{b: }`: unexpected end of input, expected expression"),
+            ),
+            (
+                quote!("Text {a} and {b: }"),
+                Some("failed to parse hole `{b: }`: unexpected end of input, expected expression"),
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let lit = find_template_lit(&input).expect("missing template");
+            let actual = check_holes(&lit).err().map(|err| err.to_string());
+
+            assert_eq!(expected.map(String::from), actual);
+        }
+    }
+
+    #[test]
+    fn mark_template_escapes_cases() {
+        for (template, expected) in [
+            ("Text {a} and {b}", "Text {a} and {b}"),
+            (
+                "Text {{a}} and {b}",
+                "Text {__emit_escaped_open}a{__emit_escaped_close} and {b}",
+            ),
+            (
+                "{{{a}}}",
+                "{__emit_escaped_open}{a}{__emit_escaped_close}",
+            ),
+            ("Text {a: {b}}", "Text {a: {b}}"),
+        ] {
+            assert_eq!(expected, mark_template_escapes(template), "{}", template);
+        }
+    }
+
+    #[test]
+    fn split_receiver_cases() {
+        for (input, expected) in [
+            (
+                quote!(logger, "Text {a}", a),
+                quote!(target: logger, "Text {a}", a),
+            ),
+            (
+                quote!(&self.logger, "Text {a}"),
+                quote!(target: &self.logger, "Text {a}"),
+            ),
+            (
+                quote!(loggers.get::<A, B>(), "Text"),
+                quote!(target: loggers.get::<A, B>(), "Text"),
+            ),
+            (
+                quote!(target: logger, "Text {a}", a),
+                quote!(target: logger, "Text {a}", a),
+            ),
+            (quote!("Text {a}", a), quote!("Text {a}", a)),
+            (
+                quote!(#[all_debug] "Text {a}", a),
+                quote!(#[all_debug] "Text {a}", a),
+            ),
+        ] {
+            assert_eq!(expected.to_string(), split_receiver(input).to_string());
+        }
+    }
+
+    #[test]
+    fn split_default_capture_attr() {
+        let (default_capture, input) =
+            split_default_capture(quote!(#[all_debug] "Text {a} and {b}", c));
+
+        assert_eq!(
+            quote!(#[::emit::as_debug]).to_string(),
+            quote!(#default_capture).to_string()
+        );
+        assert_eq!(quote!("Text {a} and {b}", c).to_string(), input.to_string());
+
+        let (default_capture, input) = split_default_capture(quote!("Text {a}"));
+
+        assert!(default_capture.is_none());
+        assert_eq!(quote!("Text {a}").to_string(), input.to_string());
+    }
+
+    #[test]
+    fn strip_template_comments_cases() {
+        for (template, expected) in [
+            ("Text {a} and {b}", Some("Text {a} and {b}")),
+            ("Text {a} {! the b hole !}and {b}", Some("Text {a} and {b}")),
+            ("{!a comment!}{a}", Some("{a}")),
+            ("Text {! with ! and } inside !}", Some("Text ")),
+            ("Text {{! not a comment !}}", Some("Text {{! not a comment !}}")),
+            ("Text {a: {!b}}", Some("Text {a: {!b}}")),
+            ("Text {! unterminated", None),
+        ] {
+            assert_eq!(
+                expected.map(String::from),
+                strip_template_comments(template),
+                "{}",
+                template
+            );
+        }
+    }
+
+    #[test]
+    fn strip_comments_template_literal() {
+        let actual = strip_comments(quote!(
+            target: "app {! not the template !}",
+            "Text {! a comment !}{a}",
+            a
+        ))
+        .unwrap();
+
+        assert_eq!(
+            quote!(target: "app {! not the template !}", "Text {a}", a).to_string(),
+            actual.to_string()
+        );
+
+        assert!(strip_comments(quote!("Text {! unterminated")).is_err());
+    }
+
+    #[test]
+    fn check_capture_cases() {
+        for (fv, ok) in [
+            (quote!(a), true),
+            (quote!(#[emit::as_debug] a), true),
+            (quote!(#[cfg(test)] #[key = "b"] #[as_display] a), true),
+            (quote!(#[as_debug] #[emit::as_display] a), false),
+            (quote!(#[ts] #[as_debug] a), false),
+        ] {
+            let fv = syn::parse2::<FieldValue>(fv).unwrap();
+
+            assert_eq!(
+                ok,
+                check_capture("a", &fv, Span::call_site()).is_ok(),
+                "{}",
+                fv.to_token_stream()
+            );
+        }
+    }
+
+    #[test]
+    fn strip_template_specs_cases() {
+        for (template, expected, labels) in [
+            ("Text {a} and {b}", "Text {a} and {b}", vec![]),
+            ("Text {a:<10} and {b:*^8}", "Text {a} and {b}", vec!["a", "b"]),
+            ("{#[emit::as_debug] a:->4}", "{#[emit::as_debug] a}", vec!["a"]),
+            ("Text {a: b < 10}", "Text {a: b < 10}", vec![]),
+            ("Text {a:10}", "Text {a:10}", vec![]),
+            ("Text {{a:<10}}", "Text {{a:<10}}", vec![]),
+            ("Text {a: {b}}", "Text {a: {b}}", vec![]),
+        ] {
+            let mut specs = BTreeMap::new();
+
+            assert_eq!(expected, strip_template_specs(template, &mut specs), "{}", template);
+            assert_eq!(labels, specs.keys().collect::<Vec<_>>(), "{}", template);
+        }
+    }
+
+    #[test]
+    fn split_hole_spec_components() {
+        for (hole, expected) in [
+            ("a:<10", Some((' ', quote!(Left), 10usize))),
+            ("a:*^8", Some(('*', quote!(Center), 8))),
+            ("a:>", Some((' ', quote!(Right), 0))),
+            ("a:<<3", Some(('<', quote!(Left), 3))),
+            ("a:<x", None),
+            ("a:*8", None),
+        ] {
+            let expected = expected.map(|(fill, align, width)| {
+                (
+                    "a".to_owned(),
+                    "a",
+                    quote!(::emit::rt::__private::HoleSpec {
+                        fill: #fill,
+                        align: ::emit::rt::__private::Align::#align,
+                        width: #width,
+                    })
+                    .to_string(),
+                )
+            });
+
+            let actual =
+                split_hole_spec(hole).map(|(label, fv, spec)| (label, fv, spec.to_string()));
+
+            assert_eq!(expected, actual, "{}", hole);
+        }
+    }
+
+    #[test]
+    fn split_level_path() {
+        let cases = vec![
+            (
+                quote!(Level::Warn, "Text {a}", b),
+                Some(quote!(Warn)),
+                quote!("Text {a}", b),
+            ),
+            (
+                quote!(emit::Level::Error, "Text"),
+                Some(quote!(Error)),
+                quote!("Text"),
+            ),
+            (
+                quote!(MyLevel::Critical, target: "app", "Text {a}"),
+                Some(quote!(MyLevel::Critical)),
+                quote!(target: "app", "Text {a}"),
+            ),
+            (quote!(target: "app", "Text"), None, quote!(target: "app", "Text")),
+            (quote!(a, "Text"), None, quote!(a, "Text")),
+            (quote!("Text {a}", b), None, quote!("Text {a}", b)),
+        ];
+
+        for (input, expected_level, expected_input) in cases {
+            let (level, input) = split_level(input);
+
+            assert_eq!(
+                expected_level.map(|level| level.to_string()),
+                level.map(|level| level.to_string())
+            );
+            assert_eq!(expected_input.to_string(), input.to_string());
+        }
+    }
+
+    #[test]
+    fn split_runtime_kvs_expr() {
+        let cases = vec![
+            (quote!("Text", kvs), quote!("Text"), Some(quote!(kvs))),
+            (quote!("Text {a}", a, kvs,), quote!("Text {a}", a), Some(quote!(kvs))),
+            (
+                quote!(target: "app", "Text", &[("a", v), ("b", w)]),
+                quote!(target: "app", "Text"),
+                Some(quote!(&[("a", v), ("b", w)])),
+            ),
+            (
+                quote!("Text {a}", a, f::<A, B>(x, y)),
+                quote!("Text {a}", a),
+                Some(quote!(f::<A, B>(x, y))),
+            ),
+            (
+                quote!("Text {a}", #[as_debug] a, kvs),
+                quote!("Text {a}", #[as_debug] a),
+                Some(quote!(kvs)),
+            ),
+            (quote!("Text {a}", a: 1), quote!("Text {a}", a: 1), None),
+            (quote!("Text"), quote!("Text"), None),
+        ];
+
+        for (input, expected_input, expected_kvs) in cases {
+            let (input, kvs) = split_runtime_kvs(input).unwrap();
+
+            assert_eq!(expected_input.to_string(), input.to_string());
+            assert_eq!(
+                expected_kvs.map(|kvs| kvs.to_string()),
+                kvs.map(|kvs| kvs.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn expand_runtime_kvs() {
+        let actual = expand_tokens(ExpandTokens {
+            level: quote!(Info),
+            input: quote!("Text {a}", a),
+            receiver: Some(quote!(receive)),
+            blocking: false,
+            runtime_kvs: Some(quote!(kvs)),
+        })
+        .to_string();
+
+        assert!(actual.contains(&quote!(match &(kvs)).to_string()), "{}", actual);
+        assert!(actual.contains("merge_key_values"), "{}", actual);
+    }
+
+    #[test]
+    fn expand_custom_level() {
+        let actual = expand_tokens(ExpandTokens {
+            level: quote!(MyLevel::Critical),
+            input: quote!("Text"),
+            receiver: Some(quote!(receive)),
+            blocking: false,
+            runtime_kvs: None,
+        });
+
+        assert!(actual
+            .to_string()
+            .contains(&quote!(level: (MyLevel::Critical).to_level(),).to_string()));
+    }
+
+    #[test]
+    fn describe_keys() {
+        for (input, expected) in [
+            (quote!("Text"), vec![]),
+            (quote!("Text {b} and {a}", a: 1, b: 2), vec!["a", "b"]),
+            (quote!("Text {a}", #[key = "z"] a: 1, c: 3), vec!["c", "z"]),
+            (quote!("Text {a}", #[group = "http"] a: 1), vec!["http.a"]),
+            (quote!("Text {a}", #[ts] a: now), vec!["ts"]),
+            (quote!("Text {a}", #[alias = "old_a"] a: 1), vec!["a", "old_a"]),
+        ] {
+            let actual = describe_tokens("Info", input);
+
+            if cfg!(feature = "no_sort") {
+                let mut keys = actual.keys.clone();
+                keys.sort();
+
+                assert_eq!(expected, keys);
+            } else {
+                assert_eq!(expected, actual.keys);
+            }
+        }
+    }
+
+    #[test]
+    fn describe_captures() {
+        for (input, expected) in [
+            (quote!("Text {a}", a: 1), vec![("a", None)]),
+            (
+                quote!("Text {#[as_debug] a}", #[emit::as_display] b: 2),
+                vec![
+                    ("a", Some(quote!(#[as_debug]))),
+                    ("b", Some(quote!(#[emit::as_display]))),
+                ],
+            ),
+            (
+                quote!("Text {a}", #[ts] a: now, #[alias = "b"] #[as_debug] c: 3),
+                vec![
+                    ("b", Some(quote!(#[as_debug]))),
+                    ("c", Some(quote!(#[as_debug]))),
+                    ("ts", Some(quote!(#[::emit::as_timestamp]))),
+                ],
+            ),
+        ] {
+            let expected = expected
+                .into_iter()
+                .map(|(key, capture)| (key.to_owned(), capture.map(|capture| capture.to_string())))
+                .collect::<BTreeMap<_, _>>();
+
+            assert_eq!(expected, describe_tokens("Info", input).captures);
+        }
+    }
+
+    #[test]
+    fn describe_target() {
+        for (input, expected) in [
+            (quote!("Text"), ExpansionTarget::ModulePath),
+            (quote!(target: "app", "Text"), ExpansionTarget::Str("app".into())),
+            (
+                quote!(target: |r| println!("{}", r.msg()), "Text"),
+                ExpansionTarget::Closure(quote!(|r| println!("{}", r.msg())).to_string()),
+            ),
+            (
+                quote!(target: Route::Billing, "Text"),
+                ExpansionTarget::Resolved(quote!(Route::Billing).to_string()),
+            ),
+        ] {
+            assert_eq!(expected, describe_tokens("Info", input).target);
+        }
+    }
+
+    #[test]
+    fn describe_template() {
+        for (input, expected) in [
+            (
+                quote!("Text {a} and {{b}}", a: 1),
+                vec![
+                    ExpansionPart::Text("Text ".into()),
+                    ExpansionPart::Hole("a".into()),
+                    ExpansionPart::Text(" and ".into()),
+                    ExpansionPart::Escape('{'),
+                    ExpansionPart::Text("b".into()),
+                    ExpansionPart::Escape('}'),
+                ],
+            ),
+            (
+                quote!("{{{a: {1}}}}"),
+                vec![
+                    ExpansionPart::Escape('{'),
+                    ExpansionPart::Hole("a".into()),
+                    ExpansionPart::Escape('}'),
+                ],
+            ),
+            (
+                quote!("Text {a}", #[key = "z"] a: 1),
+                vec![ExpansionPart::Text("Text ".into()), ExpansionPart::Hole("z".into())],
+            ),
+            (
+                quote!("Text {a:>10}", a: 1),
+                vec![
+                    ExpansionPart::Text("Text ".into()),
+                    ExpansionPart::FormattedHole(
+                        "a".into(),
+                        quote!(::emit::rt::__private::HoleSpec {
+                            fill: ' ',
+                            align: ::emit::rt::__private::Align::Right,
+                            width: 10usize,
+                        })
+                        .to_string(),
+                    ),
+                ],
+            ),
+            (
+                quote!("Text {a:>10}", #[no_render] #[key = "z"] a: 1),
+                vec![ExpansionPart::Text("Text ".into()), ExpansionPart::UnrenderedHole("z".into())],
+            ),
+        ] {
+            assert_eq!(expected, describe_tokens("Info", input).template);
+        }
+    }
+
+    #[test]
+    fn expand_blocking() {
+        for (input, expected) in [
+            (
+                quote!("Text"),
+                quote!(::emit::__private::emit_blocking(&record);),
+            ),
+            (
+                quote!(target: "app", "Text"),
+                quote!(::emit::__private::emit_blocking(&record);),
+            ),
+            (
+                quote!(target: logger, "Text"),
+                quote!(::emit::__private::emit_to_blocking(&__emit_target.1, &record);),
+            ),
+        ] {
+            let actual = expand_tokens(ExpandTokens {
+                level: quote!(Error),
+                input,
+                receiver: None,
+                blocking: true,
+                runtime_kvs: None,
+            })
+            .to_string();
+
+            assert!(actual.contains(&expected.to_string()), "{}", actual);
+            assert!(!actual.contains("__private_forward"), "{}", actual);
+        }
+    }
+
+    #[test]
+    fn expand_serde_event_input() {
+        for input in [quote!(#[emit::serde] event), quote!(#[serde] event)] {
+            let actual = expand_tokens(ExpandTokens {
+                level: quote!(Info),
+                input,
+                receiver: None,
+                blocking: false,
+                runtime_kvs: None,
+            })
+            .to_string();
+
+            let expected = quote!(::emit::rt::__private::SerdeEvent::new(&(event)));
+
+            let forward = quote!(::emit::__private::emit(&record););
+
+            assert!(actual.contains(&expected.to_string()), "{}", actual);
+            assert!(actual.contains(&forward.to_string()), "{}", actual);
+            assert!(!actual.contains("__private_forward"), "{}", actual);
+        }
+
+        // Other leading attributes are still default captures
+        assert!(split_serde_event(quote!(#[all_debug] "template")).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_detailed_when_custom_level() {
+        let mut fields = Fields {
+            level: "MyLevel :: Critical".into(),
+            ..Default::default()
+        };
+
+        fields.push("a".into(), syn::parse_quote!(#[detailed_when(Level::Debug)] a));
+    }
+
+    #[test]
+    fn push_default_capture() {
+        let mut fields = Fields {
+            default_capture: Some(syn::parse_quote!(#[emit::as_debug])),
+            ..Default::default()
+        };
+
+        fields.push("a".into(), syn::parse_quote!(a));
+        fields.push("b".into(), syn::parse_quote!(#[emit::as_display] b));
+        fields.push("source".into(), syn::parse_quote!(source));
+
+        assert_eq!(
+            vec![
+                quote!({ #[emit::as_debug] ::emit::ct::__private_capture!(a) }).to_string(),
+                quote!({ #[emit::as_display] ::emit::ct::__private_capture!(b) }).to_string(),
+                quote!({ ::emit::ct::__private_capture!(source) }).to_string(),
+            ],
+            fields
+                .match_value_tokens
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn push_timestamp() {
+        let mut fields = Fields::default();
+
+        fields.push("at".into(), syn::parse_quote!(#[ts] at: now));
+
+        assert_eq!(
+            vec![quote!({ #[::emit::as_timestamp] ::emit::ct::__private_capture!(at: now) }).to_string()],
+            fields
+                .match_value_tokens
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some("ts"), fields.get_by_label("at").map(|(key, _)| key));
+    }
+
+    #[test]
+    fn push_array() {
+        let mut fields = Fields::default();
+
+        fields.push("tags".into(), syn::parse_quote!(#[as_array] tags: [a, b]));
+        fields.push("c".into(), syn::parse_quote!(c));
+
+        assert_eq!(
+            vec![
+                quote!({ #[::emit::ct::__private_as_array] ::emit::ct::__private_capture!(tags: [a, b]) }).to_string(),
+                quote!({ ::emit::ct::__private_capture!(c) }).to_string(),
+            ],
+            fields
+                .match_value_tokens
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "expected an array")]
+    fn push_array_not_array() {
+        let mut fields = Fields::default();
+
+        fields.push("tags".into(), syn::parse_quote!(#[as_array(tags)] a));
+    }
+
+    #[test]
+    fn push_optional() {
+        let mut fields = Fields::default();
+
+        fields.push("maybe".into(), syn::parse_quote!(#[optional] #[as_debug] maybe));
+        fields.push("a".into(), syn::parse_quote!(a));
+
+        assert_eq!(
+            vec![
+                quote!({
+                    match &(maybe) {
+                        Some(__emit_optional) => Some({ #[as_debug] ::emit::ct::__private_capture!(maybe: *__emit_optional) }),
+                        None => None,
+                    }
+                })
+                .to_string(),
+                quote!({ ::emit::ct::__private_capture!(a) }).to_string(),
+            ],
+            fields
+                .match_value_tokens
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+        );
+
+        assert!(fields.has_optionals());
+
+        // Fields are kept in the order they were declared in with `no_sort`
+        let expected = if cfg!(feature = "no_sort") {
+            vec![quote!(__tmp0.is_some()).to_string(), quote!(true).to_string()]
+        } else {
+            vec![quote!(true).to_string(), quote!(__tmp0.is_some()).to_string()]
+        };
+
+        assert_eq!(
+            expected,
+            fields
+                .sorted_field_present_tokens()
+                .map(|present| present.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn push_alias() {
+        let mut fields = Fields::default();
+
+        fields.push("a".into(), syn::parse_quote!(#[alias = "old_a"] #[alias = "older_a"] a));
+        fields.push("b".into(), syn::parse_quote!(b));
+
+        // The value is only captured once
+        assert_eq!(2, fields.match_value_tokens.len());
+
+        let a = quote!(__tmp0.clone()).to_string();
+        let b = quote!(__tmp1.clone()).to_string();
+        let old_a = quote!(("old_a", __tmp0.1.clone())).to_string();
+        let older_a = quote!(("older_a", __tmp0.1.clone())).to_string();
+
+        let expected = if cfg!(feature = "no_sort") {
+            vec![a, old_a, older_a, b]
+        } else {
+            vec![a, b, old_a, older_a]
+        };
+
+        assert_eq!(
+            expected,
+            fields
+                .sorted_field_record_tokens()
+                .map(|record| record.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "keys cannot be duplicated")]
+    fn push_alias_duplicate() {
+        let mut fields = Fields::default();
+
+        fields.push("a".into(), syn::parse_quote!(#[alias = "b"] a));
+        fields.push("b".into(), syn::parse_quote!(b));
+    }
+
+    #[test]
+    fn push_detailed_when() {
+        for (level, expected) in [
+            ("Trace", vec![quote!(#[emit::as_serde]), quote!(#[::emit::as_sval])]),
+            ("Debug", vec![quote!(#[emit::as_serde]), quote!(#[::emit::as_sval])]),
+            ("Info", vec![quote!(#[::emit::as_display]), quote!(#[::emit::as_display])]),
+        ] {
+            let mut fields = Fields {
+                level: level.into(),
+                ..Default::default()
+            };
+
+            fields.push("a".into(), syn::parse_quote!(#[detailed_when(Level::Debug)] #[emit::as_serde] a));
+            fields.push("b".into(), syn::parse_quote!(#[detailed_when(Debug)] b));
+
+            let a = &expected[0];
+            let b = &expected[1];
+
+            assert_eq!(
+                vec![
+                    quote!({ #a ::emit::ct::__private_capture!(a) }).to_string(),
+                    quote!({ #b ::emit::ct::__private_capture!(b) }).to_string(),
+                ],
+                fields
+                    .match_value_tokens
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_detailed_when_invalid_level() {
+        let mut fields = Fields {
+            level: "Info".into(),
+            ..Default::default()
+        };
+
+        fields.push("a".into(), syn::parse_quote!(#[detailed_when(Level::Verbose)] a));
+    }
+
+    #[test]
+    fn push_field_order() {
+        let mut fields = Fields::default();
+
+        fields.push("c".into(), syn::parse_quote!(c));
+        fields.push("a".into(), syn::parse_quote!(a));
+        fields.push("b".into(), syn::parse_quote!(#[key = "d"] b));
+
+        let expected = if cfg!(feature = "no_sort") {
+            vec!["\"c\"", "\"a\"", "\"d\""]
+        } else {
+            vec!["\"a\"", "\"c\"", "\"d\""]
+        };
+
+        assert_eq!(
+            expected,
+            fields
+                .sorted_field_key_tokens()
+                .map(|key| key.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
 }
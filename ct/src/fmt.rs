@@ -0,0 +1,278 @@
+/*!
+Compile-time conversion of `format!`-style templates.
+
+This module rewrites a `format!` string and its arguments into the template
+syntax understood by `emit`, so it can be expanded like any other record.
+*/
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use proc_macro2::TokenStream;
+use syn::{
+    parse::{Parse, ParseStream},
+    Expr, ExprPath, Ident, LitStr, Token,
+};
+
+pub(super) struct ExpandTokens {
+    pub(super) input: TokenStream,
+}
+
+/**
+Convert `format!` input into `emit!` input.
+*/
+pub(super) fn expand_tokens(opts: ExpandTokens) -> TokenStream {
+    let input = syn::parse2::<Input>(opts.input).expect("failed to parse format input");
+
+    let mut template = String::new();
+    let mut captures = BTreeMap::<String, (Expr, Spec)>::new();
+
+    let mut next_positional = 0;
+    let mut used_positional = vec![false; input.positional.len()];
+
+    for part in parse_format(&input.template.value()) {
+        match part {
+            FormatPart::Text(text) => {
+                for c in text.chars() {
+                    match c {
+                        '{' => template.push_str("{{"),
+                        '}' => template.push_str("}}"),
+                        c => template.push(c),
+                    }
+                }
+            }
+            FormatPart::Hole(arg, spec) => {
+                let (key, expr) = match arg {
+                    Arg::Next | Arg::Index(_) => {
+                        let index = match arg {
+                            Arg::Index(index) => index,
+                            _ => {
+                                next_positional += 1;
+                                next_positional - 1
+                            }
+                        };
+
+                        let expr = input
+                            .positional
+                            .get(index)
+                            .unwrap_or_else(|| {
+                                panic!("missing positional argument for `{{{}}}`", index)
+                            })
+                            .clone();
+
+                        used_positional[index] = true;
+
+                        // Plain identifiers keep their name, other expressions are named by position
+                        let key = match expr {
+                            Expr::Path(ExprPath { ref path, .. }) if path.get_ident().is_some() => {
+                                path.get_ident().expect("missing ident").to_string()
+                            }
+                            _ => format!("arg{}", index),
+                        };
+
+                        (key, expr)
+                    }
+                    Arg::Named(name) => {
+                        let expr = match input.named.get(&name) {
+                            Some(expr) => expr.clone(),
+                            None => syn::parse_str(&name).expect("failed to parse ident"),
+                        };
+
+                        (name, expr)
+                    }
+                };
+
+                if let Some((_, previous_spec)) = captures.get(&key) {
+                    assert!(
+                        *previous_spec == spec,
+                        "`{}` is formatted with different specs",
+                        key
+                    );
+                }
+
+                write!(&mut template, "{{{}}}", key).expect("infallible write to string");
+                captures.insert(key, (expr, spec));
+            }
+        }
+    }
+
+    assert!(
+        used_positional.iter().all(|used| *used),
+        "all positional arguments must be used"
+    );
+
+    let template = LitStr::new(&template, input.template.span());
+    let field_values = captures.into_iter().map(|(key, (expr, spec))| {
+        let key = Ident::new(&key, input.template.span());
+
+        match spec {
+            Spec::Display => quote!(#[::emit::as_display] #key: #expr),
+            Spec::Debug => quote!(#[::emit::as_debug] #key: #expr),
+        }
+    });
+
+    quote!(#template, #(#field_values),*)
+}
+
+struct Input {
+    template: LitStr,
+    positional: Vec<Expr>,
+    named: BTreeMap<String, Expr>,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let template = input.parse()?;
+
+        let mut positional = Vec::new();
+        let mut named = BTreeMap::new();
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+
+            if input.is_empty() {
+                break;
+            }
+
+            if input.peek(Ident) && input.peek2(Token![=]) && !input.peek2(Token![==]) {
+                let name: Ident = input.parse()?;
+                input.parse::<Token![=]>()?;
+
+                named.insert(name.to_string(), input.parse()?);
+            } else {
+                assert!(
+                    named.is_empty(),
+                    "positional arguments must come before named arguments"
+                );
+
+                positional.push(input.parse()?);
+            }
+        }
+
+        Ok(Input {
+            template,
+            positional,
+            named,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum FormatPart {
+    Text(String),
+    Hole(Arg, Spec),
+}
+
+#[derive(Debug, PartialEq)]
+enum Arg {
+    Next,
+    Index(usize),
+    Named(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Spec {
+    Display,
+    Debug,
+}
+
+fn parse_format(input: &str) -> Vec<FormatPart> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut hole = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => hole.push(c),
+                        None => panic!("unterminated hole in format string"),
+                    }
+                }
+
+                if !text.is_empty() {
+                    parts.push(FormatPart::Text(std::mem::take(&mut text)));
+                }
+
+                let (arg, spec) = match hole.find(':') {
+                    Some(i) => (&hole[..i], &hole[i + 1..]),
+                    None => (&hole[..], ""),
+                };
+
+                let arg = match arg.trim() {
+                    "" => Arg::Next,
+                    arg if arg.chars().all(|c| c.is_ascii_digit()) => {
+                        Arg::Index(arg.parse().expect("failed to parse index"))
+                    }
+                    arg => Arg::Named(arg.to_owned()),
+                };
+
+                let spec = match spec {
+                    "" => Spec::Display,
+                    "?" => Spec::Debug,
+                    spec => panic!("unsupported format spec `{}`", spec),
+                };
+
+                parts.push(FormatPart::Hole(arg, spec));
+            }
+            '}' => panic!("unmatched closing brace in format string"),
+            c => text.push(c),
+        }
+    }
+
+    if !text.is_empty() {
+        parts.push(FormatPart::Text(text));
+    }
+
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            vec![
+                FormatPart::Text("a {".to_owned()),
+                FormatPart::Hole(Arg::Next, Spec::Display),
+                FormatPart::Text(" ".to_owned()),
+                FormatPart::Hole(Arg::Index(1), Spec::Debug),
+                FormatPart::Hole(Arg::Named("x".to_owned()), Spec::Debug),
+                FormatPart::Text("}".to_owned()),
+            ],
+            parse_format("a {{{} {1:?}{x:?}}}")
+        );
+    }
+
+    #[test]
+    fn expand_fmt() {
+        let cases = vec![
+            (
+                quote!("{} and {:?} and {2}", a, b.c, 42),
+                quote!("{a} and {arg1} and {arg2}", #[::emit::as_display] a: a, #[::emit::as_debug] arg1: b.c, #[::emit::as_display] arg2: 42),
+            ),
+            (
+                quote!("{{{name}}} is {x:?}", x = 1 + 1),
+                quote!("{{{name}}} is {x}", #[::emit::as_display] name: name, #[::emit::as_debug] x: 1 + 1),
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let actual = expand_tokens(ExpandTokens { input });
+
+            assert_eq!(expected.to_string(), actual.to_string());
+        }
+    }
+}
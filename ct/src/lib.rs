@@ -14,13 +14,15 @@ use proc_macro2::TokenStream;
 mod capture;
 mod emit;
 mod filter;
+mod fmt;
+mod records;
 
 /**
 Emit a trace record.
 */
 #[proc_macro]
 pub fn trace(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    emit(item)
+    base_emit(quote!(Trace), TokenStream::from(item))
 }
 
 /**
@@ -28,7 +30,7 @@ Emit a debug record.
 */
 #[proc_macro]
 pub fn debug(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    emit(item)
+    base_emit(quote!(Debug), TokenStream::from(item))
 }
 
 /**
@@ -36,7 +38,7 @@ Emit a info record.
 */
 #[proc_macro]
 pub fn info(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    emit(item)
+    base_emit(quote!(Info), TokenStream::from(item))
 }
 
 /**
@@ -44,7 +46,7 @@ Emit a warn record.
 */
 #[proc_macro]
 pub fn warn(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    emit(item)
+    base_emit(quote!(Warn), TokenStream::from(item))
 }
 
 /**
@@ -52,60 +54,705 @@ Emit a error record.
 */
 #[proc_macro]
 pub fn error(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    emit(item)
+    base_emit(quote!(Error), TokenStream::from(item))
 }
 
 /**
 Logging statements.
+
+Comments in the template, like `"{user} {! the request's user !}logged in"`, are removed
+before it's parsed, so they don't appear in the message or the key-values.
+
+Holes in the template can pad their values like `format!`, with an optional fill character,
+an alignment of `<`, `^`, or `>`, and an optional width, like `"{user:<10}"` or `"{id:*^8}"`.
+A hole that's a valid field-value by itself is never treated as formatted, so `"{a:b<10}"`
+captures the expression `b<10`, and a width without an alignment isn't supported.
+
+Fields without a capture attribute are captured using their `Display` implementation. Primitive
+numbers, from `i8` and `u8` up to `i128` and `u128`, along with `f32` and `f64`, are captured as
+typed numbers that keep their signedness, instead of as text. That includes values in generic code,
+like `n: T` where `T: Display + 'static`, so the typed number is still available when `T` is
+a number. Their width isn't kept though: integers narrower than 128 bits are stored as `i64` or
+`u64`, and `f32`s as `f64`s, so emitters can't tell a `u8` from a `u64`. An `f32` is widened
+exactly, so it may render with more digits than it would through its own `Display`, like
+`0.1f32` as `0.10000000149011612`. Use `#[as_string]` to keep the value's own formatting.
+
+A leading `#[all_debug]`, `#[all_display]`, `#[all_string]`, `#[all_sval]`, or `#[all_serde]`
+sets how fields are captured unless they have a capture attribute of their own.
+
+A field with a `#[ts]` attribute, like `#[ts] at: some_systemtime`, is captured as the
+timestamp of the record instead of the time it's emitted at.
+
+A field with an `#[optional]` attribute, like `#[optional] user: maybe_user`, is an `Option<T>`
+or `Option<&T>`. If it's `Some`, the inner `T` is captured by reference, without cloning it.
+If it's `None`, the field is left off the record.
+
+A field with an `#[alias = "old_name"]` attribute, like `#[alias = "user"] user_id: id`, is also
+emitted under the alias, which is handy while migrating consumers from one key to another.
+The value is only captured once. A field can have more than one alias.
+
+A field with a `#[precision(2)]` attribute is an `f32` or `f64` that's rounded to the given
+number of decimal places when it's captured, so `3.14159` is stored and rendered as `3.14`.
+It replaces how the field is captured, so it can't be combined with other capture attributes.
+
+A field with an `#[as_array]` attribute, like `#[as_array] tags: [tag1, tag2, tag3]`, is captured
+as a sequence of the elements in its array or tuple. The elements can have different types,
+and each one is captured using its default capturing method. Their labels aren't keys on the record.
+
+A field with a `#[no_render]` attribute, like `"{user} logged in", #[no_render] user`, is captured
+on the record, but any hole for it in the template is rendered as if it was missing. That keeps a
+value out of the message while still storing it as a key-value.
+
+A field with a `#[detailed_when(Level::Debug)]` attribute is only captured using its capture
+attribute, or `sval::Value` if it doesn't have one, when the record is at or below the given level.
+At any other level it's captured using `Display` instead. The level of a record is known when
+it's expanded, so this doesn't add any runtime cost.
+
+A single `serde::Serialize` value with a `#[emit::serde]` attribute, like `emit!(#[emit::serde] event)`,
+is emitted as the whole event instead of a template. The fields of a struct become the key-values of
+the record, using the names they're serialized with, and the name of the struct becomes the message.
+Struct variants of enums use the name of the variant. Any other value is emitted as a single `value`
+key-value without a message. This requires the `serde_json` feature, and these records aren't
+forwarded to `tracing`.
+
+A leading level, like `emit!(Level::Warn, "template")`, sets the level of the record, which is
+`Info` by default. Custom levels defined with `define_levels!` can also be used, like
+`emit!(MyLevel::Critical, "template")`, but they don't support `#[detailed_when]`.
+
+A leading emitter, like `emit!(logger, "template")` or `info!(self.logger, "template")`, is
+the emitter the record is sent to instead of the global one, the same as `target: logger`.
+It comes after the level, if there is one.
+*/
+#[proc_macro]
+pub fn emit(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let (level, input) = emit::split_level(TokenStream::from(item));
+
+    base_emit(level.unwrap_or_else(|| quote!(Info)), input)
+}
+
+/**
+Emit a record and then block until it's been delivered.
+
+This accepts the same input as `emit!`, including a leading level. Once the record has been
+emitted, the emitter it was emitted to is flushed with `Emitter::flush`, so records sent to
+a buffered or async emitter have been delivered by the time the macro returns. This is useful
+for critical records, like errors logged just before a process exits.
+
+That includes panics, which can be emitted from a panic hook:
+
+```ignore
+std::panic::set_hook(Box::new(|info| {
+    let location = info.location().map(|location| location.to_string());
+
+    emit::emit_blocking!(Level::Error, "panicked at {#[optional] location}", location);
+}));
+```
+
+Emitting from a panic hook doesn't panic again itself. If the panic came from an emitter while
+it was emitting a record, then records emitted from the hook on the same thread are discarded
+instead of re-entering it.
+
+Records emitted with `emit_blocking!` aren't forwarded to `tracing`.
+*/
+#[proc_macro]
+pub fn emit_blocking(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let (level, input) = emit::split_level(TokenStream::from(item));
+
+    if filter::matches_build_filter() {
+        proc_macro::TokenStream::from(emit::expand_tokens(emit::ExpandTokens {
+            level: level.unwrap_or_else(|| quote!(Info)),
+            input,
+            receiver: None,
+            blocking: true,
+            runtime_kvs: None,
+        }))
+    } else {
+        proc_macro::TokenStream::new()
+    }
+}
+
+/**
+Emit a record with additional key-values built at runtime.
+
+This accepts the same input as `emit!`, including a leading level, followed by an expression
+for a slice of `(&str, emit::Value)` pairs, like a `Vec` or array:
+
+```ignore
+let kvs = config
+    .iter()
+    .map(|(k, v)| (k.as_str(), emit::Value::from(v.as_str())))
+    .collect::<Vec<_>>();
+
+emit::emit_kvs!("loaded config", kvs);
+```
+
+The runtime key-values are merged into the key-values captured on the record. If a key is
+both captured and in the runtime key-values then the captured value is used. Runtime
+key-values aren't forwarded to `tracing`.
+*/
+#[proc_macro]
+pub fn emit_kvs(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let (level, input) = emit::split_level(TokenStream::from(item));
+    let (input, runtime_kvs) = match emit::split_runtime_kvs(input) {
+        Ok(split) => split,
+        Err(err) => return proc_macro::TokenStream::from(err.to_compile_error()),
+    };
+
+    let runtime_kvs = match runtime_kvs {
+        Some(runtime_kvs) => runtime_kvs,
+        None => {
+            return proc_macro::TokenStream::from(
+                syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "expected a template followed by an expression for the runtime key-values",
+                )
+                .to_compile_error(),
+            )
+        }
+    };
+
+    if filter::matches_build_filter() {
+        proc_macro::TokenStream::from(emit::expand_tokens(emit::ExpandTokens {
+            level: level.unwrap_or_else(|| quote!(Info)),
+            input,
+            receiver: None,
+            blocking: false,
+            runtime_kvs: Some(runtime_kvs),
+        }))
+    } else {
+        proc_macro::TokenStream::new()
+    }
+}
+
+/**
+Emit a info record using `format!` syntax.
+
+Holes can be positional (`{}`, `{0}`) or named (`{name}`), and can use
+the `?` spec to capture using `Debug`. Other format specs aren't supported.
+*/
+#[proc_macro]
+pub fn fmt(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    base_emit(
+        quote!(Info),
+        fmt::expand_tokens(fmt::ExpandTokens {
+            input: TokenStream::from(item),
+        }),
+    )
+}
+
+/**
+Capture a key-value pair using its `Debug` implementation.
+
+Atomics like `AtomicU64` are captured as the number or boolean they hold, loaded with
+`Ordering::Relaxed`, the same as they are without `#[as_debug]`.
+
+Use `#[as_debug(redact)]` to capture a hex fingerprint of the `Debug` output instead of the value itself.
+Use `#[as_debug(strip_ansi)]` to capture the `Debug` output as a string with any ANSI escape sequences removed.
+
+Use `#[as_debug(max_len = 1024)]` to stop the `Debug` output after a number of bytes, ending it with
+`"<truncated>"`. Writes beyond the limit fail, so a `Debug` implementation that never finishes,
+like one that follows a cycle, can't hang the thread that formats it.
+*/
+#[proc_macro_attribute]
+pub fn as_debug(
+    args: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let (to, args) = match args.to_string().as_str() {
+        "" => (quote!(__private_capture_as_debug), None),
+        "redact" => (quote!(__private_capture_as_redacted), None),
+        "strip_ansi" => (quote!(__private_capture_as_debug_stripped_ansi), None),
+        arg if arg.starts_with("max_len") => {
+            let max_len = capture::parse_max_len(TokenStream::from(args));
+
+            (quote!(__private_capture_as_debug_bounded), Some(quote!(#max_len)))
+        }
+        args => panic!("unsupported `as_debug` argument `{}`", args),
+    };
+
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to,
+            args,
+        },
+    ))
+}
+
+/**
+Capture a byte buffer, like a `Vec<u8>` or `[u8]`, as a hex dump string.
+
+Each line of the dump has an offset, 16 bytes in hex, and the same bytes as ASCII,
+in the same layout as `hexdump -C`. At most 256 bytes are rendered by default; use
+`#[as_hexdump(max_len = 1024)]` to change the limit. If there are more bytes than that,
+the dump ends with a `"<truncated>"` line.
+*/
+#[proc_macro_attribute]
+pub fn as_hexdump(
+    args: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let max_len = if args.is_empty() {
+        256
+    } else {
+        capture::parse_max_len(TokenStream::from(args))
+    };
+
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_hexdump),
+            args: Some(quote!(#max_len)),
+        },
+    ))
+}
+
+/**
+Capture the value behind a `Mutex` or `RwLock` using its `Debug` implementation.
+
+The lock is only held while the value is formatted. A poisoned lock is captured as `"<poisoned>"`,
+and a lock that's already held is captured as `"<locked>"` instead of waiting for it.
+*/
+#[proc_macro_attribute]
+pub fn as_locked_debug(
+    _: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_locked_debug),
+            args: None,
+        },
+    ))
+}
+
+/**
+Capture the value behind an `Arc`, `Rc`, or a `Weak` reference to one, using its `Debug` implementation.
+
+A `Weak` reference is only upgraded while the value is formatted. If the value has been dropped
+then it's captured as `"<dropped>"`. An `Arc` or `Rc` captures the value it points to directly.
+*/
+#[proc_macro_attribute]
+pub fn as_weak_debug(
+    _: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_weak_debug),
+            args: None,
+        },
+    ))
+}
+
+/**
+Capture a key-value pair as a fingerprint of its `Debug` implementation.
+
+Two equal values will produce the same fingerprint, so they can be correlated
+without the original value appearing in logs. This is the same as `#[as_debug(redact)]`.
+
+The fingerprint is a truncated SHA-256 hash of the `Debug` output, written as hex.
+On its own, a hash doesn't hide values that come from a small set of possible inputs,
+like phone numbers or account ids, because they can be found by hashing every input.
+Call `emit::set_redaction_key` with a secret key at startup to compute fingerprints
+with HMAC-SHA-256 instead.
+*/
+#[proc_macro_attribute]
+pub fn redact(
+    _: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_redacted),
+            args: None,
+        },
+    ))
+}
+
+/**
+Capture a key-value pair using its `Display` implementation.
+
+Use `#[as_display("{:>10}")]` to capture the value formatted with the given format string instead.
+The format string must contain a single unnamed argument.
+*/
+#[proc_macro_attribute]
+pub fn as_display(
+    args: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let args = TokenStream::from(args);
+
+    let (to, args) = if args.is_empty() {
+        (quote!(__private_capture_as_display), None)
+    } else {
+        let format = capture::parse_format(args);
+
+        (
+            quote!(__private_capture_as_display_formatted),
+            Some(quote!(#format)),
+        )
+    };
+
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to,
+            args,
+        },
+    ))
+}
+
+/**
+Capture a key-value pair as a string using its `Display` implementation, with any ANSI escape sequences removed.
+
+This is useful for command output or colored strings that would otherwise corrupt structured output.
+Use `#[as_debug(strip_ansi)]` to strip ANSI escape sequences from the `Debug` output instead.
+*/
+#[proc_macro_attribute]
+pub fn strip_ansi(
+    _: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_display_stripped_ansi),
+            args: None,
+        },
+    ))
+}
+
+/**
+Capture a key-value pair as a string using its `Display` implementation.
+
+This is useful for values like large integers or identifiers that could
+lose precision if they were captured as numbers.
+*/
+#[proc_macro_attribute]
+pub fn as_string(
+    _: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_string),
+            args: None,
+        },
+    ))
+}
+
+/**
+Capture a `std::time::Duration` as an ISO 8601 period, like `PT1H30M`.
+*/
+#[proc_macro_attribute]
+pub fn as_iso_duration(
+    _: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_iso_duration),
+            args: None,
+        },
+    ))
+}
+
+/**
+Capture a `std::path::Path` or `std::path::PathBuf` as a string.
+
+Paths that aren't valid UTF-8 have any invalid characters replaced with `U+FFFD`.
+
+Paths need this attribute because they don't implement `Display`. Capturing them by default
+would need an impl that overlaps the one for `Display` types, which the compiler rejects since
+the standard library could implement `Display` for paths in the future.
+*/
+#[proc_macro_attribute]
+pub fn as_path(
+    _: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_path),
+            args: None,
+        },
+    ))
+}
+
+/**
+Capture a `std::time::Instant` as the time elapsed since the process started.
+
+The value is captured as a number of seconds, like `1.5`, and rendered in messages as an
+ISO 8601 period, like `PT1.5S`. Instants before the process started are captured as zero.
+The start of the process is taken to be the first time an emitter is set or an uptime is captured.
+*/
+#[proc_macro_attribute]
+pub fn as_uptime(
+    _: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_uptime),
+            args: None,
+        },
+    ))
+}
+
+/**
+Capture a `std::process::ExitStatus` with its exit code and whether it was successful.
+
+The value is captured as a map with a `success` key and a `code` key. On Unix, a process
+that was terminated by a signal has no code, so it also has a `signal` key.
+It's rendered in messages as just the code, or the signal if there's no code.
+*/
+#[proc_macro_attribute]
+pub fn as_exit_status(
+    _: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_exit_status),
+            args: None,
+        },
+    ))
+}
+
+/**
+Capture a `std::io::Error` with its kind, OS error code, and message.
+
+The value is captured as a map with a `kind` key, like `"NotFound"`, and a `msg` key. Errors
+that came from the OS also have a `code` key with the raw OS error code. It's rendered in
+messages as just the error's message.
+*/
+#[proc_macro_attribute]
+pub fn as_io_error(
+    _: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_io_error),
+            args: None,
+        },
+    ))
+}
+
+/**
+Capture a bit-flags value as a sequence of the names of its active flags, like `["READ", "WRITE"]`.
+
+The value must implement `emit::Flags`.
+*/
+#[proc_macro_attribute]
+pub fn as_flags(
+    _: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_flags),
+            args: None,
+        },
+    ))
+}
+
+/**
+Capture an HTTP-style status code, like `503`, along with its class, like `5xx`.
+
+The value is captured as a map with the code in a `code` key and its class in a `class` key,
+so records can be grouped by class. It's rendered in messages as just the code.
+*/
+#[proc_macro_attribute]
+pub fn as_status(
+    _: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_status),
+            args: None,
+        },
+    ))
+}
+
+/**
+Capture a `std::time::SystemTime` as an RFC 3339 date-time in UTC, like `2021-10-01T08:30:00Z`.
+
+Use `#[ts]` instead to capture the value as the timestamp of the record itself.
+*/
+#[proc_macro_attribute]
+pub fn as_timestamp(
+    _: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_timestamp),
+            args: None,
+        },
+    ))
+}
+
+/**
+Capture a key-value pair using its `sval::Value` implementation.
+
+Use `#[as_sval(max_depth = 3, max_entries = 64)]` to stop streaming nested
+values beyond a depth, or entries beyond a number in any single map or sequence.
+If only one limit is given, `max_depth` defaults to 16, and `max_entries` is unlimited.
+*/
+#[proc_macro_attribute]
+pub fn as_sval(
+    args: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let args = TokenStream::from(args);
+
+    let (to, args) = if args.is_empty() {
+        (quote!(__private_capture_as_sval), None)
+    } else {
+        let (max_depth, max_entries) = capture::parse_limits(args);
+
+        (
+            quote!(__private_capture_as_sval_limited),
+            Some(quote!(#max_depth, #max_entries)),
+        )
+    };
+
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to,
+            args,
+        },
+    ))
+}
+
+/**
+Capture a slice as a sequence of records, one for each element.
+
+The argument is a template for each record, like `#[as_records("{id}: {name}")]`.
+Holes that are just an identifier are filled from the field of the same name on each element.
+Other holes can refer to the element as `item`, like `{len: item.name.len()}`.
 */
-#[proc_macro]
-pub fn emit(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    if filter::matches_build_filter() {
-        proc_macro::TokenStream::from(emit::expand_tokens(TokenStream::from(item)))
-    } else {
-        proc_macro::TokenStream::new()
-    }
+#[proc_macro_attribute]
+pub fn as_records(
+    args: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_records),
+            args: Some(TokenStream::from(args)),
+        },
+    ))
 }
 
 /**
-Capture a key-value pair using its `Debug` implementation.
+Capture the first few elements of an iterator as a sequence.
+
+The argument is the number of elements to capture, like `#[as_iter(take = 10)]`.
+Each element is captured using its default capturing method. If the iterator has
+more elements, the sequence ends with a `"<truncated>"` element. At most one more
+element than `take` is pulled from the iterator.
 */
 #[proc_macro_attribute]
-pub fn as_debug(
-    _: proc_macro::TokenStream,
+pub fn as_iter(
+    args: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
+    let take = capture::parse_take(TokenStream::from(args));
+
     proc_macro::TokenStream::from(capture::rename_capture_tokens(
         capture::RenameCaptureTokens {
             expr: TokenStream::from(item),
             predicate: |ident| ident.starts_with("__private_capture"),
-            to: quote!(__private_capture_as_debug),
+            to: quote!(__private_capture_as_iter),
+            args: Some(quote!(#take)),
         },
     ))
 }
 
 /**
-Capture a key-value pair using its `Display` implementation.
+Capture a key-value pair using its `serde::Serialize` implementation.
+
+Use `#[as_serde(with_display)]` to render the value's hole in the message with its `Display`
+implementation, for values whose structure is worth indexing but whose text is easier to read.
+The value is still captured as its structure, so only the rendered message changes.
+
+Use `#[as_serde(with = path::to::serialize)]` to serialize the value with a function instead of
+its own `Serialize` implementation, like serde's `#[serde(serialize_with)]`. The function has the
+signature `fn<S: Serializer>(&T, S) -> Result<S::Ok, S::Error>`, and the value doesn't need to
+implement `Serialize` itself.
 */
 #[proc_macro_attribute]
-pub fn as_display(
-    _: proc_macro::TokenStream,
+pub fn as_serde(
+    args: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
+    let args = TokenStream::from(args);
+
+    let (to, args) = if let Some(with) = capture::parse_serialize_with(args.clone()) {
+        (quote!(__private_capture_as_serde_with), Some(quote!(#with)))
+    } else if capture::parse_with_display(args) {
+        (quote!(__private_capture_as_serde_with_display), None)
+    } else {
+        (quote!(__private_capture_as_serde), None)
+    };
+
     proc_macro::TokenStream::from(capture::rename_capture_tokens(
         capture::RenameCaptureTokens {
             expr: TokenStream::from(item),
             predicate: |ident| ident.starts_with("__private_capture"),
-            to: quote!(__private_capture_as_display),
+            to,
+            args,
         },
     ))
 }
 
 /**
-Capture a key-value pair using its `sval::Value` implementation.
+Capture the differences between two `serde::Serialize` values, like `#[as_diff] user: (old, new)`.
+
+The top-level keys of the values once they're serialized are compared, and the diff is captured as
+a map with `added`, `removed`, and `changed` keys. Keys that were added or removed contain
+their new or old values, and keys that were changed contain a map of their `old` and `new` values.
+Values that aren't serialized as maps are compared as a whole, under a `value` key. This requires
+the `serde_json` feature.
 */
 #[proc_macro_attribute]
-pub fn as_sval(
+pub fn as_diff(
     _: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
@@ -113,16 +760,20 @@ pub fn as_sval(
         capture::RenameCaptureTokens {
             expr: TokenStream::from(item),
             predicate: |ident| ident.starts_with("__private_capture"),
-            to: quote!(__private_capture_as_sval),
+            to: quote!(__private_capture_as_diff),
+            args: None,
         },
     ))
 }
 
 /**
-Capture a key-value pair using its `serde::Serialize` implementation.
+Capture a key-value pair as a string of compact JSON using its `serde::Serialize` implementation.
+
+Unlike `#[as_serde]`, the value is captured as text instead of a structured value, for backends
+that store records as text. This requires the `serde_json` feature.
 */
 #[proc_macro_attribute]
-pub fn as_serde(
+pub fn as_json_string(
     _: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
@@ -130,7 +781,8 @@ pub fn as_serde(
         capture::RenameCaptureTokens {
             expr: TokenStream::from(item),
             predicate: |ident| ident.starts_with("__private_capture"),
-            to: quote!(__private_capture_as_serde),
+            to: quote!(__private_capture_as_json_string),
+            args: None,
         },
     ))
 }
@@ -151,10 +803,25 @@ pub fn source(
             expr: TokenStream::from(item),
             predicate: |ident| ident.starts_with("__private_capture"),
             to: quote!(__private_capture_as_error),
+            args: None,
         },
     ))
 }
 
+fn base_emit(level: TokenStream, input: TokenStream) -> proc_macro::TokenStream {
+    if filter::matches_build_filter() {
+        proc_macro::TokenStream::from(emit::expand_tokens(emit::ExpandTokens {
+            level,
+            input,
+            receiver: None,
+            blocking: false,
+            runtime_kvs: None,
+        }))
+    } else {
+        proc_macro::TokenStream::new()
+    }
+}
+
 #[proc_macro]
 #[doc(hidden)]
 pub fn __private_capture(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -163,7 +830,7 @@ pub fn __private_capture(item: proc_macro::TokenStream) -> proc_macro::TokenStre
         fn_name: |key| match key {
             // A value with `source` as the key will be treated as the error by default
             "source" => quote!(__private_capture_as_error),
-            _ => quote!(__private_capture_as_default),
+            _ => quote!(__private_capture_as_inferred),
         },
     }))
 }
@@ -177,6 +844,33 @@ pub fn __private_capture_as_debug(item: proc_macro::TokenStream) -> proc_macro::
     }))
 }
 
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_redacted(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: capture::redact_field_value(TokenStream::from(item)),
+        fn_name: |_| quote!(__private_capture_as_display),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_locked_debug(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: TokenStream::from(item),
+        fn_name: |_| quote!(__private_capture_as_locked_debug),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_weak_debug(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: TokenStream::from(item),
+        fn_name: |_| quote!(__private_capture_as_weak_debug),
+    }))
+}
+
 #[proc_macro]
 #[doc(hidden)]
 pub fn __private_capture_as_display(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -186,6 +880,122 @@ pub fn __private_capture_as_display(item: proc_macro::TokenStream) -> proc_macro
     }))
 }
 
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_display_formatted(
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let (format, expr) = capture::split_args(TokenStream::from(item));
+
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: capture::format_field_value(format, expr),
+        fn_name: |_| quote!(__private_capture_as_display),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_display_stripped_ansi(
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: capture::strip_ansi_field_value(false, TokenStream::from(item)),
+        fn_name: |_| quote!(__private_capture_as_display),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_debug_stripped_ansi(
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: capture::strip_ansi_field_value(true, TokenStream::from(item)),
+        fn_name: |_| quote!(__private_capture_as_display),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_string(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: TokenStream::from(item),
+        fn_name: |_| quote!(__private_capture_as_string),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_iso_duration(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: TokenStream::from(item),
+        fn_name: |_| quote!(__private_capture_as_iso_duration),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_path(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_ref_tokens(capture::ExpandTokens {
+        expr: TokenStream::from(item),
+        fn_name: |_| quote!(__private_capture_as_path),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_uptime(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: TokenStream::from(item),
+        fn_name: |_| quote!(__private_capture_as_uptime),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_exit_status(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: TokenStream::from(item),
+        fn_name: |_| quote!(__private_capture_as_exit_status),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_io_error(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: TokenStream::from(item),
+        fn_name: |_| quote!(__private_capture_as_io_error),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_flags(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: TokenStream::from(item),
+        fn_name: |_| quote!(__private_capture_as_flags),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_status(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: capture::status_field_value(TokenStream::from(item)),
+        fn_name: |_| quote!(__private_capture_as_sval),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_timestamp(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: TokenStream::from(item),
+        fn_name: |_| quote!(__private_capture_as_timestamp),
+    }))
+}
+
 #[proc_macro]
 #[doc(hidden)]
 pub fn __private_capture_as_sval(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -195,6 +1005,108 @@ pub fn __private_capture_as_sval(item: proc_macro::TokenStream) -> proc_macro::T
     }))
 }
 
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_sval_limited(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let (args, expr) = capture::split_args(TokenStream::from(item));
+
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr,
+        fn_name: |_| quote!(__private_capture_as_sval_limited::<#args>),
+    }))
+}
+
+#[proc_macro_attribute]
+#[doc(hidden)]
+pub fn __private_as_precision(
+    args: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let digits = TokenStream::from(args);
+
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_precision),
+            args: Some(quote!(#digits)),
+        },
+    ))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_precision(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let (args, expr) = capture::split_args(TokenStream::from(item));
+
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr,
+        fn_name: |_| quote!(__private_capture_as_precision::<#args>),
+    }))
+}
+
+#[proc_macro_attribute]
+#[doc(hidden)]
+pub fn __private_as_array(
+    _: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::rename_capture_tokens(
+        capture::RenameCaptureTokens {
+            expr: TokenStream::from(item),
+            predicate: |ident| ident.starts_with("__private_capture"),
+            to: quote!(__private_capture_as_array),
+            args: None,
+        },
+    ))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_array(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_array_tokens(TokenStream::from(item)))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_hexdump(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let (max_len, expr) = capture::split_args(TokenStream::from(item));
+
+    proc_macro::TokenStream::from(capture::expand_hexdump_tokens(max_len, expr))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_debug_bounded(
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let (args, expr) = capture::split_args(TokenStream::from(item));
+
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr,
+        fn_name: |_| quote!(__private_capture_as_debug_bounded::<#args>),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_records(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let (template, expr) = capture::split_args(TokenStream::from(item));
+
+    proc_macro::TokenStream::from(records::expand_tokens(records::ExpandTokens {
+        template,
+        expr,
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_iter(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let (take, expr) = capture::split_args(TokenStream::from(item));
+
+    proc_macro::TokenStream::from(capture::expand_iter_tokens(take, expr))
+}
+
 #[proc_macro]
 #[doc(hidden)]
 pub fn __private_capture_as_serde(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -204,6 +1116,42 @@ pub fn __private_capture_as_serde(item: proc_macro::TokenStream) -> proc_macro::
     }))
 }
 
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_serde_with(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let (with, expr) = capture::split_args(TokenStream::from(item));
+
+    proc_macro::TokenStream::from(capture::expand_serialize_with_tokens(with, expr))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_serde_with_display(
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_serde_with_display_tokens(
+        TokenStream::from(item),
+    ))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_diff(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: capture::diff_field_value(TokenStream::from(item)),
+        fn_name: |_| quote!(__private_capture_as_serde),
+    }))
+}
+
+#[proc_macro]
+#[doc(hidden)]
+pub fn __private_capture_as_json_string(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::from(capture::expand_tokens(capture::ExpandTokens {
+        expr: capture::json_string_field_value(TokenStream::from(item)),
+        fn_name: |_| quote!(__private_capture_as_display),
+    }))
+}
+
 #[proc_macro]
 #[doc(hidden)]
 pub fn __private_capture_as_error(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -0,0 +1,102 @@
+/*!
+Compile-time expansion of sub-records captured from a slice.
+
+Each element of the slice is converted into a record using a template.
+Holes in the template are filled from the fields of each element.
+*/
+
+use proc_macro2::TokenStream;
+use syn::{Expr, ExprPath, FieldValue, LitStr};
+
+use fv_template::ct::Template;
+
+use crate::{capture::FieldValueExt, emit};
+
+pub(super) struct ExpandTokens {
+    pub(super) template: TokenStream,
+    pub(super) expr: TokenStream,
+}
+
+/**
+Expand a field-value into a sequence of records built from each of its elements.
+
+A hole that's just an identifier, like `{id}`, is filled with the field of the
+same name on the element. Any other hole can refer to the element as `item`,
+like `{len: item.name.len()}`.
+*/
+pub(super) fn expand_tokens(opts: ExpandTokens) -> TokenStream {
+    let template = syn::parse2::<LitStr>(opts.template)
+        .expect("expected a template string like `\"{id}: {name}\"`");
+    let key_value = syn::parse2::<FieldValue>(opts.expr).expect("failed to parse expr");
+
+    let key_expr = key_value.key_expr();
+    let expr = key_value.expr;
+
+    let (template_tokens, _) = emit::split_hole_specs(quote!(#template));
+
+    let item_field_values = Template::parse2(template_tokens)
+        .expect("failed to parse template")
+        .template_field_values()
+        .filter_map(|hole| match hole.expr {
+            Expr::Path(ExprPath { ref path, .. }) if hole.attrs.is_empty() => {
+                let ident = path.get_ident()?;
+
+                if Some(ident.to_string()) == hole.key_name() {
+                    Some(quote!(#ident: item.#ident))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let record_tokens = emit::expand_tokens(emit::ExpandTokens {
+        level: quote!(Info),
+        input: quote!(#template #(, #item_field_values)*),
+        receiver: Some(quote!(__emit_record)),
+        blocking: false,
+        runtime_kvs: None,
+    });
+
+    quote!(
+        {
+            (
+                #key_expr,
+                ::emit::rt::__private::Records::new(
+                    &(#expr)[..],
+                    |item, __emit_record: &mut dyn FnMut(&::emit::rt::__private::Record)| #record_tokens,
+                )
+                .capture(),
+            )
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn item_field_values() {
+        let actual = expand_tokens(ExpandTokens {
+            template: quote!("{id}: {len: item.name.len()}"),
+            expr: quote!(items),
+        })
+        .to_string();
+
+        // Plain holes are filled from the item, anything else is left as-is
+        assert!(actual.contains(&quote!(item.id).to_string()), "{}", actual);
+        assert!(
+            actual.contains(&quote!(item.name.len()).to_string()),
+            "{}",
+            actual
+        );
+        assert!(
+            actual.contains(&quote!(__emit_record(&record);).to_string()),
+            "{}",
+            actual
+        );
+        assert!(!actual.contains("__private_forward"), "{}", actual);
+    }
+}
@@ -0,0 +1,164 @@
+/*!
+Stripping ANSI escape sequences from captured strings.
+*/
+
+use crate::std::{iter::Peekable, str::Chars, string::String};
+
+const ESC: char = '\u{1b}';
+const BEL: char = '\u{7}';
+
+/**
+Remove any ANSI escape sequences, like colors or cursor movements, from a string.
+
+Control sequences (`ESC [`), operating system commands (`ESC ]`), and other
+escape sequences are removed. All other text is left as-is.
+*/
+pub fn strip_ansi(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == ESC {
+            skip_escape(&mut chars);
+        } else {
+            output.push(c);
+        }
+    }
+
+    output
+}
+
+/**
+Remove any ANSI escape sequences from the `Debug` output of a value.
+
+Strings escape control characters in their `Debug` output, so an escape is written as `\u{1b}`
+instead of the character itself. Those escaped characters are turned back into the real ones
+before stripping. Backslashes are escaped too, so text that was written as `\u{1b}` in the
+original string appears as `\\u{1b}` and is left alone.
+*/
+pub fn strip_ansi_debug(input: &str) -> String {
+    let mut unescaped = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(index) = rest.find('\\') {
+        unescaped.push_str(&rest[..index]);
+        rest = &rest[index..];
+
+        if let Some(escape) = rest.strip_prefix("\\u{1b}") {
+            unescaped.push(ESC);
+
+            // The string terminator `ESC \` has its backslash escaped too
+            rest = match escape.strip_prefix("\\\\") {
+                Some(escape) => {
+                    unescaped.push('\\');
+                    escape
+                }
+                None => escape,
+            };
+        } else if let Some(escape) = rest.strip_prefix("\\u{7}") {
+            unescaped.push(BEL);
+            rest = escape;
+        } else {
+            // Any other escape, including an escaped backslash, is kept as it is
+            let len = 1 + rest[1..].chars().next().map_or(0, char::len_utf8);
+
+            unescaped.push_str(&rest[..len]);
+            rest = &rest[len..];
+        }
+    }
+
+    unescaped.push_str(rest);
+
+    strip_ansi(&unescaped)
+}
+
+fn skip_escape(chars: &mut Peekable<Chars>) {
+    match chars.next() {
+        // A control sequence has parameters and intermediates followed by a final character
+        Some('[') => {
+            for c in chars.by_ref() {
+                if ('\u{40}'..='\u{7e}').contains(&c) {
+                    break;
+                }
+            }
+        }
+        // An operating system command is terminated by `BEL` or `ESC \`
+        Some(']') => {
+            while let Some(c) = chars.next() {
+                if c == BEL {
+                    break;
+                }
+
+                if c == ESC && chars.peek() == Some(&'\\') {
+                    chars.next();
+                    break;
+                }
+            }
+        }
+        // Other sequences can have intermediates followed by a final character
+        Some(c) if ('\u{20}'..='\u{2f}').contains(&c) => {
+            for c in chars.by_ref() {
+                if !('\u{20}'..='\u{2f}').contains(&c) {
+                    break;
+                }
+            }
+        }
+        // Anything else is a single character after the escape
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip() {
+        for (input, expected) in [
+            ("plain text", "plain text"),
+            ("\u{1b}[31merror\u{1b}[0m: failed", "error: failed"),
+            ("\u{1b}[1;38;5;208mbold orange\u{1b}[m", "bold orange"),
+            ("\u{1b}[2K\u{1b}[1Gprogress", "progress"),
+            ("\u{1b}]0;title\u{7}text", "text"),
+            (
+                "\u{1b}]8;;https://example.com\u{1b}\\link\u{1b}]8;;\u{1b}\\",
+                "link",
+            ),
+            ("\u{1b}(Btext", "text"),
+            ("\u{1b}7saved\u{1b}8", "saved"),
+            ("trailing \u{1b}", "trailing "),
+            ("ünïcödé \u{1b}[32m✓\u{1b}[0m", "ünïcödé ✓"),
+        ] {
+            assert_eq!(expected, strip_ansi(input), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn strip_debug() {
+        assert_eq!(
+            "\"error: failed\"",
+            strip_ansi_debug(&format!("{:?}", "\u{1b}[31merror\u{1b}[0m: failed"))
+        );
+        assert_eq!(
+            "Some(\"title\")",
+            strip_ansi_debug(&format!("{:?}", Some("\u{1b}]0;\u{7}title")))
+        );
+        assert_eq!(
+            "\"link\"",
+            strip_ansi_debug(&format!(
+                "{:?}",
+                "\u{1b}]8;;https://example.com\u{1b}\\link\u{1b}]8;;\u{1b}\\"
+            ))
+        );
+
+        // Text that only looks like an escape is kept
+        assert_eq!(
+            "\"\\\\u{1b}[31m is not red\"",
+            strip_ansi_debug(&format!("{:?}", "\\u{1b}[31m is not red"))
+        );
+        assert_eq!(
+            "\"C:\\\\ \\\\\"",
+            strip_ansi_debug(&format!("{:?}", "C:\\ \\"))
+        );
+    }
+}
@@ -0,0 +1,61 @@
+/*!
+Capturing several values as a single sequence.
+*/
+
+use crate::value::ValueBag;
+
+use sval::value::{self, Value};
+
+/**
+A sequence of values that were each captured separately.
+
+This is what `#[as_array(key)]` fields are captured as, with one element for each
+field-value in the group.
+*/
+pub struct Array<'a, 'v> {
+    items: &'a [ValueBag<'v>],
+}
+
+impl<'a, 'v> Array<'a, 'v> {
+    pub fn new(items: &'a [ValueBag<'v>]) -> Self {
+        Array { items }
+    }
+
+    pub fn capture(&self) -> ValueBag {
+        ValueBag::from_sval1(self)
+    }
+}
+
+impl<'a, 'v> Value for Array<'a, 'v> {
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        stream.seq_begin(Some(self.items.len()))?;
+
+        for item in self.items {
+            stream.seq_elem(item)?;
+        }
+
+        stream.seq_end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{capture::__PrivateCapture, std::string::ToString};
+
+    #[test]
+    fn capture_elements() {
+        let items = [
+            1u64.__private_capture_as_default(),
+            "a".__private_capture_as_default(),
+            true.__private_capture_as_default(),
+        ];
+
+        assert_eq!("[1, \"a\", true]", Array::new(&items).capture().to_string());
+    }
+
+    #[test]
+    fn capture_empty() {
+        assert_eq!("[]", Array::new(&[]).capture().to_string());
+    }
+}
@@ -0,0 +1,72 @@
+/*!
+Limiting the size of captured `Debug` output.
+*/
+
+use crate::{limit::TRUNCATED, std::fmt};
+
+/**
+A value whose `Debug` output stops after `LEN` bytes.
+
+Output beyond the limit is replaced by `"<truncated>"`. Once the limit is reached every
+further write fails, so a `Debug` implementation that keeps writing, like one for a cyclic
+graph, is cut short instead of running forever.
+*/
+#[repr(transparent)]
+pub struct BoundedDebug<T: ?Sized, const LEN: usize>(T);
+
+impl<T: ?Sized, const LEN: usize> BoundedDebug<T, LEN> {
+    pub fn new(value: &T) -> &Self {
+        // SAFETY: `BoundedDebug<T>` is `repr(transparent)` over `T`
+        unsafe { &*(value as *const T as *const Self) }
+    }
+}
+
+impl<T: fmt::Debug + ?Sized, const LEN: usize> fmt::Debug for BoundedDebug<T, LEN> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut writer = BoundedWriter {
+            inner: &mut *f,
+            remaining: LEN,
+            truncated: false,
+        };
+
+        let result = if writer.inner.alternate() {
+            fmt::write(&mut writer, format_args!("{:#?}", &self.0))
+        } else {
+            fmt::write(&mut writer, format_args!("{:?}", &self.0))
+        };
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(_) if writer.truncated => f.write_str(TRUNCATED),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+struct BoundedWriter<'a, 'b> {
+    inner: &'a mut fmt::Formatter<'b>,
+    remaining: usize,
+    truncated: bool,
+}
+
+impl<'a, 'b> fmt::Write for BoundedWriter<'a, 'b> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if s.len() <= self.remaining {
+            self.remaining -= s.len();
+            return self.inner.write_str(s);
+        }
+
+        // Write as much as fits without splitting a character
+        let mut end = self.remaining;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        self.inner.write_str(&s[..end])?;
+
+        self.remaining = 0;
+        self.truncated = true;
+
+        Err(fmt::Error)
+    }
+}
@@ -1,11 +1,55 @@
-use crate::{std::fmt, value::ValueBag};
+use crate::{
+    bounded::BoundedDebug,
+    hexdump::HexDump,
+    duration::IsoDuration,
+    flags::{FlagNames, Flags},
+    limit::Limited,
+    std::{
+        fmt,
+        sync::atomic::{
+            AtomicBool, AtomicI16, AtomicI32, AtomicI64, AtomicI8, AtomicIsize, AtomicU16,
+            AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering,
+        },
+        time::Duration,
+    },
+    value::{CaptureError as CaptureErrorValue, ValueBag},
+};
 
 #[cfg(feature = "std")]
-use crate::std::error::Error;
+use crate::{
+    exit_status::ExitStatusValue,
+    io_error::IoErrorValue,
+    lock::{LockDebug, Locked},
+    path::LossyPath,
+    precision::Round,
+    std::{
+        error::Error,
+        io,
+        path::{Path, PathBuf},
+        process::ExitStatus,
+        string::String,
+        time::{Instant, SystemTime},
+    },
+    timestamp::Timestamp,
+    uptime::Uptime,
+    weak::{Upgraded, WeakDebug},
+};
 
 #[cfg(feature = "serde")]
 use serde_lib::Serialize;
 
+#[cfg(feature = "serde")]
+use crate::newtype::Unwrapped;
+
+#[cfg(all(feature = "serde", feature = "serde_json"))]
+use crate::dynamic::JsonValue;
+
+#[cfg(all(feature = "std", feature = "serde", feature = "serde_json"))]
+use crate::{diff::Diff, json::JsonString};
+
+#[cfg(all(feature = "serde", feature = "toml"))]
+use crate::dynamic::TomlValue;
+
 use sval::value::Value;
 
 /**
@@ -26,6 +70,16 @@ pub type WithDefault = CaptureDisplay;
 extern "C" {
     pub type CaptureDisplay;
     pub type CaptureDebug;
+    pub type CaptureString;
+    pub type CaptureIsoDuration;
+    pub type CapturePath;
+    pub type CaptureTimestamp;
+    pub type CaptureUptime;
+    pub type CaptureExitStatus;
+    pub type CaptureIoError;
+    pub type CaptureFlags;
+    pub type CaptureLockedDebug;
+    pub type CaptureWeakDebug;
     pub type CaptureSval;
     pub type CaptureSerde;
     pub type CaptureError;
@@ -40,6 +94,56 @@ where
     }
 }
 
+// Strings are borrowed as a `str` instead of being captured as an opaque `Display`
+#[cfg(feature = "std")]
+impl Capture<CaptureDisplay> for String {
+    fn capture(&self) -> ValueBag {
+        ValueBag::from(self.as_str())
+    }
+}
+
+/**
+Primitive numbers are captured as typed numbers instead of as an opaque `Display`.
+
+This applies through generic code too, so a `T: Display` that's a number keeps its signedness.
+Integers smaller than 128 bits are stored as 64-bit ones, and `f32`s are stored as `f64`s.
+*/
+macro_rules! impl_capture_number {
+    ($($number:ty,)*) => {
+        $(
+            impl Capture<CaptureDisplay> for $number {
+                fn capture(&self) -> ValueBag {
+                    ValueBag::from(*self)
+                }
+            }
+        )*
+    };
+}
+
+impl_capture_number![
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+];
+
+// Capture errors are streamed as a structured value so emitters can find them
+impl Capture<CaptureDisplay> for CaptureErrorValue {
+    fn capture(&self) -> ValueBag {
+        ValueBag::capture_sval1(self)
+    }
+}
+
 impl Capture<CaptureDisplay> for dyn fmt::Display {
     fn capture(&self) -> ValueBag {
         ValueBag::from_dyn_display(self)
@@ -61,6 +165,158 @@ impl Capture<CaptureDebug> for dyn fmt::Debug {
     }
 }
 
+/**
+Atomics are captured as the value they hold when the record is emitted, instead of as an opaque `Debug`.
+
+The value is loaded with `Ordering::Relaxed`, so it's not synchronized with any other memory.
+They're captured this way by default and with `#[as_debug]`.
+*/
+macro_rules! impl_capture_atomic {
+    ($($atomic:ty,)*) => {
+        $(
+            impl Capture<CaptureDebug> for $atomic {
+                fn capture(&self) -> ValueBag {
+                    ValueBag::from(self.load(Ordering::Relaxed))
+                }
+            }
+
+            impl __PrivateCaptureAtomic for $atomic {
+                fn __private_capture_as_inferred(&self) -> ValueBag {
+                    ValueBag::from(self.load(Ordering::Relaxed))
+                }
+            }
+        )*
+    };
+}
+
+impl_capture_atomic![
+    AtomicBool,
+    AtomicU8,
+    AtomicU16,
+    AtomicU32,
+    AtomicU64,
+    AtomicUsize,
+    AtomicI8,
+    AtomicI16,
+    AtomicI32,
+    AtomicI64,
+    AtomicIsize,
+];
+
+#[cfg(feature = "std")]
+impl<T> Capture<CaptureLockedDebug> for T
+where
+    T: LockDebug,
+{
+    fn capture(&self) -> ValueBag {
+        ValueBag::from_debug(Locked::new(self))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Capture<CaptureWeakDebug> for T
+where
+    T: WeakDebug,
+{
+    fn capture(&self) -> ValueBag {
+        ValueBag::from_debug(Upgraded::new(self))
+    }
+}
+
+impl<T> Capture<CaptureString> for T
+where
+    T: fmt::Display,
+{
+    fn capture(&self) -> ValueBag {
+        ValueBag::from_dyn_display(self)
+    }
+}
+
+impl Capture<CaptureString> for dyn fmt::Display {
+    fn capture(&self) -> ValueBag {
+        ValueBag::from_dyn_display(self)
+    }
+}
+
+impl Capture<CaptureIsoDuration> for Duration {
+    fn capture(&self) -> ValueBag {
+        ValueBag::capture_display(IsoDuration::new(self))
+    }
+}
+
+/**
+Paths are captured as strings, replacing any invalid UTF-8 with `U+FFFD`.
+
+Paths are captured through a reference, because `Path` is unsized and formatting it lossily
+needs a sized value to borrow.
+*/
+macro_rules! impl_capture_path {
+    ($($path:ty,)*) => {
+        $(
+            #[cfg(feature = "std")]
+            impl<'a> Capture<CapturePath> for &'a $path {
+                fn capture(&self) -> ValueBag {
+                    match self.to_str() {
+                        Some(path) => ValueBag::from(path),
+                        None => ValueBag::from_display(LossyPath::new(self)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_capture_path![Path, PathBuf,];
+
+#[cfg(feature = "std")]
+impl Capture<CaptureTimestamp> for SystemTime {
+    fn capture(&self) -> ValueBag {
+        ValueBag::capture_display(Timestamp::new(self))
+    }
+}
+
+/**
+Instants are captured as the time elapsed since the process started.
+*/
+#[cfg(feature = "std")]
+impl Capture<CaptureUptime> for Instant {
+    fn capture(&self) -> ValueBag {
+        ValueBag::capture_sval1(Uptime::new(self))
+    }
+}
+
+/**
+Exit statuses are captured as a structured value with their code and whether they were successful.
+*/
+#[cfg(feature = "std")]
+impl Capture<CaptureExitStatus> for ExitStatus {
+    fn capture(&self) -> ValueBag {
+        ValueBag::capture_sval1(ExitStatusValue::new(self))
+    }
+}
+
+/**
+I/O errors are captured as a structured value with their kind, OS error code, and message.
+*/
+#[cfg(feature = "std")]
+impl Capture<CaptureIoError> for io::Error {
+    fn capture(&self) -> ValueBag {
+        ValueBag::capture_sval1(IoErrorValue::new(self))
+    }
+}
+
+/**
+Bit-flags are captured as a sequence of the names of their active flags.
+*/
+impl<T> Capture<CaptureFlags> for T
+where
+    T: Flags + 'static,
+{
+    fn capture(&self) -> ValueBag {
+        ValueBag::capture_sval1(FlagNames::new(self))
+    }
+}
+
 impl<T> Capture<CaptureSval> for T
 where
     T: Value + 'static,
@@ -76,13 +332,62 @@ impl Capture<CaptureSval> for dyn Value {
     }
 }
 
+// NOTE: This impl doesn't require `T: 'static` so borrowed values in generic code can be captured.
+// That means the value can't be downcast to a primitive like the other captures.
+// Newtype structs are captured as the values they wrap, the same as they're serialized by formats
 #[cfg(feature = "serde")]
 impl<T> Capture<CaptureSerde> for T
 where
-    T: Serialize + 'static,
+    T: Serialize,
 {
     default fn capture(&self) -> ValueBag {
-        ValueBag::capture_serde1(self)
+        ValueBag::from_serde1(Unwrapped::new(self))
+    }
+}
+
+/**
+A `serde_json::Value` is already structured, so it's streamed directly instead of being serialized.
+*/
+#[cfg(all(feature = "serde", feature = "serde_json"))]
+impl Capture<CaptureSerde> for serde_json::Value {
+    fn capture(&self) -> ValueBag {
+        ValueBag::capture_sval1(JsonValue::new(self))
+    }
+}
+
+/**
+Pre-serialized JSON is captured as a string.
+*/
+#[cfg(all(feature = "std", feature = "serde", feature = "serde_json"))]
+impl Capture<CaptureDisplay> for JsonString {
+    fn capture(&self) -> ValueBag {
+        match self.get() {
+            Ok(json) => ValueBag::from(json),
+            Err(err) => ValueBag::capture_sval1(err),
+        }
+    }
+}
+
+/**
+A diff is already structured, so it's streamed directly instead of being serialized.
+*/
+#[cfg(all(feature = "std", feature = "serde", feature = "serde_json"))]
+impl Capture<CaptureSerde> for Diff {
+    fn capture(&self) -> ValueBag {
+        match self.get() {
+            Ok(diff) => ValueBag::capture_sval1(JsonValue::new(diff)),
+            Err(err) => ValueBag::capture_sval1(err),
+        }
+    }
+}
+
+/**
+A `toml::Value` is already structured, so it's streamed directly instead of being serialized.
+*/
+#[cfg(all(feature = "serde", feature = "toml"))]
+impl Capture<CaptureSerde> for toml::Value {
+    fn capture(&self) -> ValueBag {
+        ValueBag::capture_sval1(TomlValue::new(self))
     }
 }
 
@@ -104,7 +409,7 @@ impl Capture<CaptureError> for dyn Error {
 }
 
 impl<T: ?Sized> Capture<T> for str {
-    fn capture(&self) -> ValueBag {
+    default fn capture(&self) -> ValueBag {
         ValueBag::from(self)
     }
 }
@@ -144,6 +449,98 @@ pub trait __PrivateCapture {
         Capture::capture(self)
     }
 
+    fn __private_capture_as_debug_bounded<const LEN: usize>(&self) -> ValueBag
+    where
+        Self: fmt::Debug + Sized + 'static,
+    {
+        ValueBag::capture_debug(BoundedDebug::<Self, LEN>::new(self))
+    }
+
+    fn __private_capture_as_hexdump<const LEN: usize>(&self) -> ValueBag
+    where
+        Self: AsRef<[u8]> + Sized,
+    {
+        ValueBag::from_display(HexDump::<Self, LEN>::new(self))
+    }
+
+    #[cfg(feature = "std")]
+    fn __private_capture_as_precision<const DIGITS: u32>(&self) -> ValueBag
+    where
+        Self: Round,
+    {
+        ValueBag::from(self.round_to(DIGITS))
+    }
+
+    fn __private_capture_as_locked_debug(&self) -> ValueBag
+    where
+        Self: Capture<CaptureLockedDebug>,
+    {
+        Capture::capture(self)
+    }
+
+    fn __private_capture_as_weak_debug(&self) -> ValueBag
+    where
+        Self: Capture<CaptureWeakDebug>,
+    {
+        Capture::capture(self)
+    }
+
+    fn __private_capture_as_string(&self) -> ValueBag
+    where
+        Self: Capture<CaptureString>,
+    {
+        Capture::capture(self)
+    }
+
+    fn __private_capture_as_iso_duration(&self) -> ValueBag
+    where
+        Self: Capture<CaptureIsoDuration>,
+    {
+        Capture::capture(self)
+    }
+
+    fn __private_capture_as_path(&self) -> ValueBag
+    where
+        Self: Capture<CapturePath>,
+    {
+        Capture::capture(self)
+    }
+
+    fn __private_capture_as_timestamp(&self) -> ValueBag
+    where
+        Self: Capture<CaptureTimestamp>,
+    {
+        Capture::capture(self)
+    }
+
+    fn __private_capture_as_uptime(&self) -> ValueBag
+    where
+        Self: Capture<CaptureUptime>,
+    {
+        Capture::capture(self)
+    }
+
+    fn __private_capture_as_exit_status(&self) -> ValueBag
+    where
+        Self: Capture<CaptureExitStatus>,
+    {
+        Capture::capture(self)
+    }
+
+    fn __private_capture_as_io_error(&self) -> ValueBag
+    where
+        Self: Capture<CaptureIoError>,
+    {
+        Capture::capture(self)
+    }
+
+    fn __private_capture_as_flags(&self) -> ValueBag
+    where
+        Self: Capture<CaptureFlags>,
+    {
+        Capture::capture(self)
+    }
+
     fn __private_capture_as_sval(&self) -> ValueBag
     where
         Self: Capture<CaptureSval>,
@@ -151,6 +548,15 @@ pub trait __PrivateCapture {
         Capture::capture(self)
     }
 
+    fn __private_capture_as_sval_limited<const DEPTH: usize, const ENTRIES: usize>(
+        &self,
+    ) -> ValueBag
+    where
+        Self: Value + Sized + 'static,
+    {
+        ValueBag::capture_sval1(Limited::<Self, DEPTH, ENTRIES>::new(self))
+    }
+
     fn __private_capture_as_serde(&self) -> ValueBag
     where
         Self: Capture<CaptureSerde>,
@@ -168,10 +574,107 @@ pub trait __PrivateCapture {
 
 impl<T: ?Sized> __PrivateCapture for T {}
 
+/**
+The capturing method the macros use when a value doesn't have an attribute for one.
+
+Atomics don't implement `Display`, so they can't be captured with `Capture<WithDefault>`.
+They can't be given a `Capture<CaptureDisplay>` impl either, because the standard library
+could implement `Display` for them in the future. Instead, the method is defined twice,
+on this trait and on `__PrivateCaptureAtomic`, so that method resolution picks:
+
+- `__PrivateCaptureAtomic` for an `&Atomic`, which it's implemented for without auto-ref.
+- This trait for any other `&T`, which it's implemented for through auto-ref to `&&T`.
+
+The value is expected to have already been reborrowed with `__private_reborrow`.
+*/
+pub trait __PrivateCaptureInferred<'a> {
+    type Target: ?Sized;
+
+    fn __private_capture_as_inferred(&self) -> ValueBag<'a>
+    where
+        Self::Target: Capture<WithDefault>;
+}
+
+impl<'a, T: ?Sized> __PrivateCaptureInferred<'a> for &'a T {
+    type Target = T;
+
+    fn __private_capture_as_inferred(&self) -> ValueBag<'a>
+    where
+        T: Capture<WithDefault>,
+    {
+        Capture::capture(*self)
+    }
+}
+
+/**
+The atomic half of `__PrivateCaptureInferred`.
+
+Atomics are captured as the value they hold, loaded with `Ordering::Relaxed`.
+*/
+pub trait __PrivateCaptureAtomic {
+    fn __private_capture_as_inferred(&self) -> ValueBag;
+}
+
+/**
+Reborrow a value as `&T` before it's captured.
+
+Values behind a `&mut T` would otherwise be captured as the `&mut T` itself,
+which only satisfies the `'static` bounds on capturing when the borrow is `'static` too.
+The method is defined twice, on this trait with a `&self` receiver and on `__PrivateReborrowMut`
+with a `&mut self` receiver, so that method resolution picks:
+
+- `&mut self` for a `&mut T` without auto-ref, reborrowing it as `&T`.
+- `&self` for anything else, auto-reffing it the same way as `__PrivateCapture`.
+
+The value isn't moved or cloned, so it can still be used mutably after it's captured.
+*/
+pub trait __PrivateReborrow {
+    fn __private_reborrow(&self) -> &Self {
+        self
+    }
+}
+
+impl<T: ?Sized> __PrivateReborrow for T {}
+
+/**
+The `&mut self` half of `__PrivateReborrow`.
+*/
+pub trait __PrivateReborrowMut {
+    fn __private_reborrow(&mut self) -> &Self {
+        self
+    }
+}
+
+impl<T: ?Sized> __PrivateReborrowMut for T {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::std::{fmt, string::String};
+    use crate::std::{fmt, string::String, string::ToString};
+
+    #[test]
+    fn capture_reborrowed() {
+        fn capture(buf: &mut String) -> String {
+            buf.__private_reborrow()
+                .__private_capture_as_default()
+                .to_string()
+        }
+
+        let mut buf = String::from("abc");
+
+        assert_eq!("abc", capture(&mut buf));
+
+        // The value can still be used after it's captured
+        buf.push_str("def");
+        assert_eq!("abcdef", capture(&mut buf));
+
+        assert_eq!(
+            "42",
+            42.__private_reborrow()
+                .__private_capture_as_default()
+                .to_string()
+        );
+    }
 
     #[test]
     fn capture_default() {
@@ -201,6 +704,7 @@ mod tests {
 
         // Capture and borrow a string as an expression
         let v = SomeType;
+        #[allow(clippy::match_single_binding)]
         match (
             (v).__private_capture_as_default(),
             (String::from("a string")).__private_capture_as_default(),
@@ -249,6 +753,308 @@ mod tests {
         let _ = v.__private_capture_as_debug();
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn capture_debug_bounded() {
+        use crate::std::string::ToString;
+
+        struct Cycle;
+
+        impl fmt::Debug for Cycle {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.debug_struct("Cycle").field("next", &Cycle).finish()
+            }
+        }
+
+        struct Forever;
+
+        impl fmt::Debug for Forever {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                loop {
+                    f.write_str("forever ")?;
+                }
+            }
+        }
+
+        // Values that would never finish formatting are cut short
+        assert_eq!(
+            "Cycle { next: Cycle { <truncated>",
+            Cycle.__private_capture_as_debug_bounded::<22>().to_string()
+        );
+
+        let forever = Forever.__private_capture_as_debug_bounded::<1024>().to_string();
+        assert_eq!(1024 + "<truncated>".len(), forever.len());
+        assert!(forever.starts_with("forever forever "));
+
+        // Truncation doesn't split a character
+        assert_eq!(
+            "'<truncated>",
+            'é'.__private_capture_as_debug_bounded::<2>().to_string()
+        );
+
+        // Values within the limit are formatted as-is
+        assert_eq!(
+            "[1, 2, 3]",
+            [1, 2, 3].__private_capture_as_debug_bounded::<9>().to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn capture_hexdump() {
+        use crate::std::{string::ToString, vec::Vec};
+
+        let bytes = b"Hello, world!\nmore bytes".to_vec();
+
+        assert_eq!(
+            concat!(
+                "00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a 6d 6f  |Hello, world!.mo|\n",
+                "00000010  72 65 20 62 79 74 65 73                           |re bytes|",
+            ),
+            bytes.__private_capture_as_hexdump::<256>().to_string()
+        );
+
+        // Bytes beyond the limit aren't rendered
+        let slice = &bytes[..];
+        assert_eq!(
+            "00000000  48 65 6c 6c                                       |Hell|\n<truncated>",
+            (&slice).__private_capture_as_hexdump::<4>().to_string()
+        );
+
+        assert_eq!("", Vec::<u8>::new().__private_capture_as_hexdump::<4>().to_string());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[allow(clippy::approx_constant)]
+    fn capture_precision() {
+        use crate::std::string::ToString;
+
+        assert_eq!(Some(3.14), 3.14159f64.__private_capture_as_precision::<2>().to_f64());
+        assert_eq!("3.14", 3.14159f64.__private_capture_as_precision::<2>().to_string());
+        assert_eq!("3.142", 3.14159f32.__private_capture_as_precision::<3>().to_string());
+        assert_eq!("3", 3.14159f64.__private_capture_as_precision::<0>().to_string());
+
+        // Values that can't be rounded are captured as-is
+        assert_eq!(Some(f64::MAX), f64::MAX.__private_capture_as_precision::<2>().to_f64());
+        assert!(f64::NAN
+            .__private_capture_as_precision::<2>()
+            .to_f64()
+            .unwrap()
+            .is_nan());
+    }
+
+    #[test]
+    fn capture_atomic() {
+        // Capture the current value of an atomic as a number
+        let counter = AtomicU64::new(1);
+        counter.fetch_add(41, Ordering::SeqCst);
+        assert_eq!(Some(42), counter.__private_capture_as_debug().to_u64());
+
+        let flag = AtomicBool::new(true);
+        assert_eq!(Some(true), flag.__private_capture_as_debug().to_bool());
+
+        let offset = AtomicI32::new(-1);
+        assert_eq!(Some(-1), offset.__private_capture_as_debug().to_i64());
+
+        // Atomics are captured the same way by default
+        assert_eq!(
+            Some(42),
+            counter
+                .__private_reborrow()
+                .__private_capture_as_inferred()
+                .to_u64()
+        );
+        assert_eq!(
+            Some(true),
+            flag.__private_reborrow()
+                .__private_capture_as_inferred()
+                .to_bool()
+        );
+
+        // Other values are captured with their default capturing method
+        assert_eq!(
+            Some(42),
+            42u64.__private_reborrow().__private_capture_as_inferred().to_u64()
+        );
+        assert_eq!(
+            Some("a"),
+            "a".__private_reborrow()
+                .__private_capture_as_inferred()
+                .to_borrowed_str()
+        );
+    }
+
+    #[test]
+    fn capture_string() {
+        // Capture a number as a string, without any loss of precision
+        let v = u64::MAX.__private_capture_as_string();
+        assert_eq!(None, v.to_u64());
+        assert_eq!("18446744073709551615", v.to_string());
+
+        let v = i128::MIN.__private_capture_as_string();
+        assert_eq!("-170141183460469231731687303715884105728", v.to_string());
+
+        // Capture a `&dyn Display`
+        let v: &dyn fmt::Display = &42;
+        assert_eq!("42", v.__private_capture_as_string().to_string());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn capture_str_borrowed() {
+        let owned = String::from("a string");
+        let borrowed: &str = &owned;
+
+        // Both `str` and `String` are captured without copying the string
+        let captured = borrowed.__private_capture_as_default();
+        assert_eq!(
+            borrowed.as_ptr(),
+            captured.to_borrowed_str().unwrap().as_ptr()
+        );
+
+        let captured = owned.__private_capture_as_default();
+        assert_eq!(owned.as_ptr(), captured.to_borrowed_str().unwrap().as_ptr());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn capture_path() {
+        use crate::std::path::{Path, PathBuf};
+
+        let path = PathBuf::from("/var/log/app.log");
+        assert_eq!(
+            Some("/var/log/app.log"),
+            (&path.__private_reborrow()).__private_capture_as_path().to_borrowed_str()
+        );
+
+        let path: &Path = Path::new("/var/log/app.log");
+        assert_eq!(
+            Some("/var/log/app.log"),
+            (&path.__private_reborrow()).__private_capture_as_path().to_borrowed_str()
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", unix))]
+    fn capture_path_non_utf8() {
+        use crate::std::{
+            ffi::OsStr,
+            os::unix::ffi::OsStrExt,
+            path::{Path, PathBuf},
+        };
+
+        let path = PathBuf::from(OsStr::from_bytes(b"/var/log/\xFF.log"));
+        assert_eq!(
+            "/var/log/\u{FFFD}.log",
+            (&path.__private_reborrow()).__private_capture_as_path().to_string()
+        );
+
+        let path: &Path = Path::new(OsStr::from_bytes(b"/var/log/\xFF.log"));
+        assert_eq!(
+            "/var/log/\u{FFFD}.log",
+            (&path.__private_reborrow()).__private_capture_as_path().to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn capture_timestamp() {
+        use crate::std::time::{Duration, UNIX_EPOCH};
+
+        let ts = UNIX_EPOCH + Duration::from_secs(1_633_077_000);
+        let captured = ts.__private_capture_as_timestamp();
+
+        assert_eq!("2021-10-01T08:30:00Z", captured.to_string());
+        assert_eq!(
+            Some(ts),
+            captured
+                .downcast_ref::<Timestamp>()
+                .map(|ts| ts.to_system_time())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn capture_locked_debug() {
+        use crate::std::{
+            sync::{Arc, Mutex, RwLock},
+            thread, vec,
+        };
+
+        let mutex = Mutex::new(vec![1, 2, 3]);
+        assert_eq!(
+            "[1, 2, 3]",
+            mutex.__private_capture_as_locked_debug().to_string()
+        );
+
+        let rwlock = RwLock::new(vec![1, 2, 3]);
+        assert_eq!(
+            "[1, 2, 3]",
+            rwlock.__private_capture_as_locked_debug().to_string()
+        );
+
+        // A lock that's already held isn't waited on
+        let guard = mutex.lock().unwrap();
+        assert_eq!(
+            "<locked>",
+            mutex.__private_capture_as_locked_debug().to_string()
+        );
+        drop(guard);
+
+        // A poisoned lock doesn't panic
+        let poisoned = Arc::new(Mutex::new(vec![1, 2, 3]));
+        let _ = thread::spawn({
+            let poisoned = poisoned.clone();
+            move || {
+                let _guard = poisoned.lock().unwrap();
+                panic!("poisoning the lock");
+            }
+        })
+        .join();
+
+        assert_eq!(
+            "<poisoned>",
+            poisoned.__private_capture_as_locked_debug().to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn capture_weak_debug() {
+        use crate::std::{
+            rc::Rc,
+            sync::Arc,
+            vec,
+        };
+
+        let arc = Arc::new(vec![1, 2, 3]);
+        assert_eq!("[1, 2, 3]", arc.__private_capture_as_weak_debug().to_string());
+
+        let weak = Arc::downgrade(&arc);
+        assert_eq!("[1, 2, 3]", weak.__private_capture_as_weak_debug().to_string());
+
+        // A weak reference to a dropped value doesn't keep it alive
+        drop(arc);
+        assert_eq!("<dropped>", weak.__private_capture_as_weak_debug().to_string());
+
+        let rc = Rc::new("a");
+        let weak = Rc::downgrade(&rc);
+        assert_eq!("\"a\"", weak.__private_capture_as_weak_debug().to_string());
+        drop(rc);
+        assert_eq!("<dropped>", weak.__private_capture_as_weak_debug().to_string());
+    }
+
+    #[test]
+    fn capture_iso_duration() {
+        let duration = Duration::from_secs(90 * 60);
+
+        assert_eq!(
+            "PT1H30M",
+            duration.__private_capture_as_iso_duration().to_string()
+        );
+    }
+
     #[test]
 
     fn capture_sval() {
@@ -280,12 +1086,280 @@ mod tests {
         let _ = v.__private_capture_as_sval();
     }
 
+    #[test]
+    fn capture_sval_limited() {
+        use sval::value::{self, Value};
+
+        struct Nested(u64);
+
+        impl Value for Nested {
+            fn stream(&self, stream: &mut value::Stream) -> value::Result {
+                if self.0 == 0 {
+                    return stream.u64(0);
+                }
+
+                stream.map_begin(Some(2))?;
+
+                stream.map_key("depth")?;
+                stream.map_value(self.0)?;
+
+                stream.map_key("child")?;
+                stream.map_value(Nested(self.0 - 1))?;
+
+                stream.map_end()
+            }
+        }
+
+        struct Seq(u64);
+
+        impl Value for Seq {
+            fn stream(&self, stream: &mut value::Stream) -> value::Result {
+                stream.seq_begin(Some(self.0 as usize))?;
+
+                for i in 0..self.0 {
+                    stream.seq_elem(Nested(i))?;
+                }
+
+                stream.seq_end()
+            }
+        }
+
+        // Values within the limits are unchanged
+        assert_eq!(
+            Nested(2).__private_capture_as_sval().to_string(),
+            Nested(2)
+                .__private_capture_as_sval_limited::<3, 64>()
+                .to_string()
+        );
+
+        // Values beyond the depth limit are truncated
+        assert_eq!(
+            "{\"depth\": 5, \"child\": {\"depth\": 4, \"child\": {\"depth\": 3, \"child\": \"<truncated>\"}}}",
+            Nested(5)
+                .__private_capture_as_sval_limited::<3, 64>()
+                .to_string()
+        );
+
+        // Entries beyond the size limit are truncated
+        assert_eq!(
+            "[0, {\"depth\": 1, \"child\": 0}, \"<truncated>\"]",
+            Seq(5)
+                .__private_capture_as_sval_limited::<2, 2>()
+                .to_string()
+        );
+    }
+
     #[test]
     #[cfg(feature = "serde")]
     fn capture_serde() {
         let tuple = (1, 2, 3, 4, 5);
 
         let _ = tuple.__private_capture_as_serde();
+
+        // Capture a borrowed (non-static) value
+        let v = String::from("a string");
+        let borrowed = (&*v, 1);
+
+        let _ = borrowed.__private_capture_as_serde();
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "serde"))]
+    fn capture_serde_newtype() {
+        use crate::std::string::ToString;
+
+        use serde_lib::Serializer;
+
+        struct Id<T>(T);
+
+        impl<T: Serialize> Serialize for Id<T> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_newtype_struct("Id", &self.0)
+            }
+        }
+
+        // Newtypes are captured as the values they wrap
+        let id = Id(42u64);
+        assert_eq!(Some(42), id.__private_capture_as_serde().to_u64());
+        assert_eq!("42", id.__private_capture_as_serde().to_string());
+
+        let id = Id(Id(-1i64));
+        assert_eq!(Some(-1), id.__private_capture_as_serde().to_i64());
+
+        // Newtypes inside other values aren't unwrapped
+        let ids = (Id(1), Id(2));
+        assert_eq!(
+            "(Id(1), Id(2))",
+            ids.__private_capture_as_serde().to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "serde", feature = "serde_json", feature = "toml"))]
+    fn capture_serde_dynamic() {
+        // Dynamic values are streamed directly instead of through `Serialize`
+        let json: serde_json::Value = serde_json::from_str("{\"a\":[1,-1,1.5]}").unwrap();
+        assert!(json
+            .__private_capture_as_serde()
+            .downcast_ref::<JsonValue>()
+            .is_some());
+
+        let toml: toml::Value = toml::from_str("a = [1, 2]").unwrap();
+        assert!(toml
+            .__private_capture_as_serde()
+            .downcast_ref::<TomlValue>()
+            .is_some());
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "serde", feature = "serde_json"))]
+    fn capture_json_string() {
+        use crate::std::collections::BTreeMap;
+
+        let json = JsonString::new(&(1, "a", [true]));
+        assert_eq!(
+            Some("[1,\"a\",[true]]"),
+            json.__private_capture_as_display().to_borrowed_str()
+        );
+
+        // JSON objects only support string keys
+        let mut map = BTreeMap::new();
+        map.insert((1, 2), 3);
+
+        let json = JsonString::new(&map);
+        assert!(json
+            .__private_capture_as_display()
+            .downcast_ref::<CaptureErrorValue>()
+            .is_some());
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "serde", feature = "serde_json"))]
+    fn capture_diff() {
+        use crate::std::collections::BTreeMap;
+
+        let old = serde_json::json!({ "name": "a", "age": 1, "email": "a@example.com" });
+        let new = serde_json::json!({ "name": "a", "age": 2, "phone": "555" });
+
+        let diff = Diff::new(&old, &new);
+        assert_eq!(
+            &serde_json::json!({
+                "added": { "phone": "555" },
+                "removed": { "email": "a@example.com" },
+                "changed": { "age": { "old": 1, "new": 2 } },
+            }),
+            diff.get().unwrap()
+        );
+        assert!(diff
+            .__private_capture_as_serde()
+            .downcast_ref::<JsonValue>()
+            .is_some());
+
+        // Values that aren't maps are compared as a whole
+        assert_eq!(
+            &serde_json::json!({
+                "added": {},
+                "removed": {},
+                "changed": { "value": { "old": 1, "new": 2 } },
+            }),
+            Diff::new(&1, &2).get().unwrap()
+        );
+
+        // JSON objects only support string keys
+        let mut map = BTreeMap::new();
+        map.insert((1, 2), 3);
+
+        assert!(Diff::new(&map, &map)
+            .__private_capture_as_serde()
+            .downcast_ref::<CaptureErrorValue>()
+            .is_some());
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", any(unix, windows)))]
+    fn capture_exit_status() {
+        #[cfg(unix)]
+        use crate::std::os::unix::process::ExitStatusExt;
+        #[cfg(windows)]
+        use crate::std::os::windows::process::ExitStatusExt;
+
+        let success = ExitStatus::from_raw(0);
+        let value = success.__private_capture_as_exit_status();
+        let status = value.downcast_ref::<ExitStatusValue>().unwrap();
+        assert!(status.success());
+        assert_eq!(Some(0), status.code());
+        assert_eq!("0", status.to_string());
+
+        // On Unix, the exit code is in the high bits of the raw wait status
+        let failure = ExitStatus::from_raw(if cfg!(unix) { 1 << 8 } else { 1 });
+        let value = failure.__private_capture_as_exit_status();
+        let status = value.downcast_ref::<ExitStatusValue>().unwrap();
+        assert!(!status.success());
+        assert_eq!(Some(1), status.code());
+        assert_eq!(None, status.signal());
+        assert_eq!("1", status.to_string());
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", unix))]
+    fn capture_exit_status_signal() {
+        use crate::std::os::unix::process::ExitStatusExt;
+
+        // A raw wait status of `9` means the process was terminated by `SIGKILL`
+        let killed = ExitStatus::from_raw(9);
+        let value = killed.__private_capture_as_exit_status();
+        let status = value.downcast_ref::<ExitStatusValue>().unwrap();
+        assert!(!status.success());
+        assert_eq!(None, status.code());
+        assert_eq!(Some(9), status.signal());
+        assert_eq!("signal 9", status.to_string());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn capture_uptime() {
+        use crate::{std::time::Duration, uptime::process_start};
+
+        let start = process_start();
+
+        let later = start + Duration::from_millis(1500);
+        let value = later.__private_capture_as_uptime();
+        let uptime = value.downcast_ref::<Uptime>().unwrap();
+        assert_eq!(Duration::from_millis(1500), uptime.to_duration());
+        assert_eq!(Some(1.5), value.to_f64());
+        assert_eq!("PT1.5S", uptime.to_string());
+
+        let now = Instant::now();
+        assert!(now.__private_capture_as_uptime().to_f64().unwrap() >= 0.0);
+
+        // Instants before the process started have an uptime of zero
+        if let Some(earlier) = start.checked_sub(Duration::from_secs(1)) {
+            let value = earlier.__private_capture_as_uptime();
+            assert_eq!(Some(0.0), value.to_f64());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn capture_io_error() {
+        use crate::std::io;
+
+        let err = io::Error::new(io::ErrorKind::NotFound, "config.toml is missing");
+        let value = err.__private_capture_as_io_error();
+        let captured = value.downcast_ref::<IoErrorValue>().unwrap();
+        assert_eq!(io::ErrorKind::NotFound, captured.kind());
+        assert_eq!(None, captured.code());
+        assert_eq!("config.toml is missing", captured.to_string());
+        assert_eq!(
+            "{\"kind\": \"NotFound\", \"msg\": \"config.toml is missing\"}",
+            value.to_string()
+        );
+
+        let err = io::Error::from_raw_os_error(2);
+        let value = err.__private_capture_as_io_error();
+        let captured = value.downcast_ref::<IoErrorValue>().unwrap();
+        assert_eq!(io::ErrorKind::NotFound, captured.kind());
+        assert_eq!(Some(2), captured.code());
     }
 
     #[test]
@@ -295,10 +1369,84 @@ mod tests {
 
         // Capture an arbitrary `Error`
         let err = io::Error::from(io::ErrorKind::Other);
-        assert!(err.__private_capture_as_error().to_borrowed_error().is_some());
+        assert!(err
+            .__private_capture_as_error()
+            .to_borrowed_error()
+            .is_some());
 
         // Capture a `&dyn Error`
         let err: &dyn Error = &err;
-        assert!(err.__private_capture_as_error().to_borrowed_error().is_some());
+        assert!(err
+            .__private_capture_as_error()
+            .to_borrowed_error()
+            .is_some());
+    }
+
+    #[test]
+    fn capture_numbers() {
+        fn capture_generic<T: fmt::Display + 'static>(n: T) -> Option<i128> {
+            n.__private_capture_as_default().to_i128()
+        }
+
+        // Numbers keep their signedness, but not their width
+        assert_eq!(Some(u8::MAX as u64), u8::MAX.__private_capture_as_default().to_u64());
+        assert_eq!(Some(u16::MAX as u64), u16::MAX.__private_capture_as_default().to_u64());
+        assert_eq!(Some(u32::MAX as u64), u32::MAX.__private_capture_as_default().to_u64());
+        assert_eq!(Some(u64::MAX), u64::MAX.__private_capture_as_default().to_u64());
+        assert_eq!(Some(u128::MAX), u128::MAX.__private_capture_as_default().to_u128());
+        assert_eq!(Some(usize::MAX as u64), usize::MAX.__private_capture_as_default().to_u64());
+
+        assert_eq!(Some(i8::MIN as i64), i8::MIN.__private_capture_as_default().to_i64());
+        assert_eq!(Some(i16::MIN as i64), i16::MIN.__private_capture_as_default().to_i64());
+        assert_eq!(Some(i32::MIN as i64), i32::MIN.__private_capture_as_default().to_i64());
+        assert_eq!(Some(i64::MIN), i64::MIN.__private_capture_as_default().to_i64());
+        assert_eq!(Some(i128::MIN), i128::MIN.__private_capture_as_default().to_i128());
+        assert_eq!(Some(isize::MIN as i64), isize::MIN.__private_capture_as_default().to_i64());
+
+        assert_eq!(Some(1.5), 1.5f32.__private_capture_as_default().to_f64());
+        assert_eq!(Some(1.5), 1.5f64.__private_capture_as_default().to_f64());
+
+        // Widened floats render with the digits of an `f64`
+        assert_eq!("0.10000000149011612", 0.1f32.__private_capture_as_default().to_string());
+        assert_eq!("0.1", 0.1f32.__private_capture_as_string().to_string());
+
+        // Negative numbers keep their sign
+        assert_eq!(None, (-1i8).__private_capture_as_default().to_u64());
+
+        // Numbers in generic code are typed too
+        assert_eq!(Some(-1), capture_generic(-1i16));
+        assert_eq!(Some(i128::MAX), capture_generic(i128::MAX));
+        assert_eq!(None, capture_generic("-1"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "serde"))]
+    fn capture_type_name() {
+        use crate::{std::io, value::ValueExt};
+
+        #[derive(Debug)]
+        struct SomeType;
+
+        assert_eq!("u64", 42u64.__private_capture_as_default().type_name());
+        assert_eq!("i64", (-1i64).__private_capture_as_default().type_name());
+        assert_eq!("f64", 1.5f64.__private_capture_as_default().type_name());
+        assert_eq!("bool", true.__private_capture_as_default().type_name());
+        assert_eq!("str", "a".__private_capture_as_default().type_name());
+        assert_eq!("debug", SomeType.__private_capture_as_debug().type_name());
+        assert_eq!(
+            "structured",
+            (1, "a").__private_capture_as_serde().type_name()
+        );
+        assert_eq!(
+            "error",
+            io::Error::from(io::ErrorKind::Other)
+                .__private_capture_as_error()
+                .type_name()
+        );
+        assert_eq!(
+            "capture_error",
+            ValueBag::capture_sval1(&CaptureErrorValue::new("failed")).type_name()
+        );
+        assert_eq!("none", ValueBag::from(()).type_name());
     }
 }
@@ -0,0 +1,88 @@
+/*!
+Capturing the differences between two values.
+*/
+
+use crate::std::string::String;
+
+use serde_json::{Map, Value};
+
+use serde_lib::Serialize;
+
+use crate::value::CaptureError;
+
+/**
+The differences between the serialized maps of two values.
+
+The diff is a map with three keys:
+
+- `added`: the keys of the new value that aren't in the old one, with their new values.
+- `removed`: the keys of the old value that aren't in the new one, with their old values.
+- `changed`: the keys of both values whose values are different, with a map of their `old`
+and `new` values.
+
+Only the top-level keys are compared, so a nested map that's changed is reported with its
+whole old and new values. If either value isn't serialized as a map then they're compared
+as a whole, as if they were maps with a single `value` key. If either value can't be
+serialized then the diff is captured as a `CaptureError`.
+*/
+pub struct Diff(Result<Value, CaptureError>);
+
+impl Diff {
+    pub fn new(old: &(impl Serialize + ?Sized), new: &(impl Serialize + ?Sized)) -> Self {
+        Diff(diff(old, new).map_err(|_| CaptureError::new("the values could not be serialized")))
+    }
+
+    pub fn get(&self) -> Result<&Value, &CaptureError> {
+        self.0.as_ref()
+    }
+}
+
+fn diff(
+    old: &(impl Serialize + ?Sized),
+    new: &(impl Serialize + ?Sized),
+) -> Result<Value, serde_json::Error> {
+    let (old, new) = match (serde_json::to_value(old)?, serde_json::to_value(new)?) {
+        (Value::Object(old), Value::Object(new)) => (old, new),
+        (old, new) => (single(old), single(new)),
+    };
+
+    let mut added = Map::new();
+    let mut removed = Map::new();
+    let mut changed = Map::new();
+
+    for (k, new_value) in &new {
+        match old.get(k) {
+            None => {
+                added.insert(k.clone(), new_value.clone());
+            }
+            Some(old_value) if old_value != new_value => {
+                let mut change = Map::new();
+                change.insert("old".into(), old_value.clone());
+                change.insert("new".into(), new_value.clone());
+
+                changed.insert(k.clone(), Value::Object(change));
+            }
+            Some(_) => (),
+        }
+    }
+
+    for (k, old_value) in old {
+        if !new.contains_key(&k) {
+            removed.insert(k, old_value);
+        }
+    }
+
+    let mut diff = Map::new();
+    diff.insert("added".into(), Value::Object(added));
+    diff.insert("removed".into(), Value::Object(removed));
+    diff.insert("changed".into(), Value::Object(changed));
+
+    Ok(Value::Object(diff))
+}
+
+fn single(value: Value) -> Map<String, Value> {
+    let mut map = Map::new();
+    map.insert("value".into(), value);
+
+    map
+}
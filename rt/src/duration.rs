@@ -0,0 +1,81 @@
+/*!
+Formatting durations as ISO 8601 periods.
+*/
+
+use crate::std::{fmt, time::Duration};
+
+/**
+A duration that's formatted as an ISO 8601 period, like `PT1H30M`.
+
+Durations are formatted using hours, minutes, and seconds, so a duration
+of two days is `PT48H`. Any sub-second precision is written as a fraction
+of seconds, like `PT1.5S`. A zero duration is `PT0S`.
+*/
+#[repr(transparent)]
+pub struct IsoDuration(Duration);
+
+impl IsoDuration {
+    pub fn new(value: &Duration) -> &Self {
+        // SAFETY: `IsoDuration` is `repr(transparent)` over `Duration`
+        unsafe { &*(value as *const Duration as *const Self) }
+    }
+}
+
+impl fmt::Display for IsoDuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let secs = self.0.as_secs();
+        let nanos = self.0.subsec_nanos();
+
+        let hours = secs / 3600;
+        let minutes = (secs % 3600) / 60;
+        let seconds = secs % 60;
+
+        f.write_str("PT")?;
+
+        if hours > 0 {
+            write!(f, "{}H", hours)?;
+        }
+
+        if minutes > 0 {
+            write!(f, "{}M", minutes)?;
+        }
+
+        if nanos > 0 {
+            // Write the fraction without any trailing zeros
+            let mut fraction = nanos;
+            let mut width = 9;
+            while fraction % 10 == 0 {
+                fraction /= 10;
+                width -= 1;
+            }
+
+            write!(f, "{}.{:0width$}S", seconds, fraction, width = width)?;
+        } else if seconds > 0 || (hours == 0 && minutes == 0) {
+            write!(f, "{}S", seconds)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::std::{string::ToString, vec};
+
+    #[test]
+    fn format() {
+        let cases = vec![
+            (Duration::from_secs(90 * 60), "PT1H30M"),
+            (Duration::from_secs(0), "PT0S"),
+            (Duration::from_secs(48 * 3600 + 5), "PT48H5S"),
+            (Duration::from_millis(1500), "PT1.5S"),
+            (Duration::from_nanos(60_000_000_001), "PT1M0.000000001S"),
+            (Duration::from_millis(250), "PT0.25S"),
+        ];
+
+        for (duration, expected) in cases {
+            assert_eq!(expected, IsoDuration::new(&duration).to_string());
+        }
+    }
+}
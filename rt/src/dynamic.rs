@@ -0,0 +1,115 @@
+/*!
+Capturing dynamic values from other serialization frameworks.
+
+Values like `serde_json::Value` are already a structured representation,
+so they're streamed directly instead of through their `Serialize` implementation.
+*/
+
+use sval::value::{self, Value};
+
+/**
+A `serde_json::Value` that's streamed directly as a structured value.
+*/
+#[cfg(feature = "serde_json")]
+#[repr(transparent)]
+pub struct JsonValue(serde_json::Value);
+
+#[cfg(feature = "serde_json")]
+impl JsonValue {
+    pub fn new(value: &serde_json::Value) -> &Self {
+        // SAFETY: `JsonValue` is `repr(transparent)` over `serde_json::Value`
+        unsafe { &*(value as *const serde_json::Value as *const Self) }
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl Value for JsonValue {
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        use serde_json::Value as Json;
+
+        match self.0 {
+            Json::Null => stream.none(),
+            Json::Bool(v) => stream.bool(v),
+            Json::Number(ref v) => {
+                if let Some(v) = v.as_u64() {
+                    stream.u64(v)
+                } else if let Some(v) = v.as_i64() {
+                    stream.i64(v)
+                } else {
+                    stream.f64(v.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            Json::String(ref v) => stream.str(v),
+            Json::Array(ref v) => {
+                stream.seq_begin(Some(v.len()))?;
+
+                for elem in v {
+                    stream.seq_elem(JsonValue::new(elem))?;
+                }
+
+                stream.seq_end()
+            }
+            Json::Object(ref v) => {
+                stream.map_begin(Some(v.len()))?;
+
+                for (k, v) in v {
+                    stream.map_key(k)?;
+                    stream.map_value(JsonValue::new(v))?;
+                }
+
+                stream.map_end()
+            }
+        }
+    }
+}
+
+/**
+A `toml::Value` that's streamed directly as a structured value.
+
+Datetimes are streamed as strings.
+*/
+#[cfg(feature = "toml")]
+#[repr(transparent)]
+pub struct TomlValue(toml::Value);
+
+#[cfg(feature = "toml")]
+impl TomlValue {
+    pub fn new(value: &toml::Value) -> &Self {
+        // SAFETY: `TomlValue` is `repr(transparent)` over `toml::Value`
+        unsafe { &*(value as *const toml::Value as *const Self) }
+    }
+}
+
+#[cfg(feature = "toml")]
+impl Value for TomlValue {
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        use toml::Value as Toml;
+
+        match self.0 {
+            Toml::Boolean(v) => stream.bool(v),
+            Toml::Integer(v) => stream.i64(v),
+            Toml::Float(v) => stream.f64(v),
+            Toml::String(ref v) => stream.str(v),
+            Toml::Datetime(ref v) => stream.display(v),
+            Toml::Array(ref v) => {
+                stream.seq_begin(Some(v.len()))?;
+
+                for elem in v {
+                    stream.seq_elem(TomlValue::new(elem))?;
+                }
+
+                stream.seq_end()
+            }
+            Toml::Table(ref v) => {
+                stream.map_begin(Some(v.len()))?;
+
+                for (k, v) in v {
+                    stream.map_key(k)?;
+                    stream.map_value(TomlValue::new(v))?;
+                }
+
+                stream.map_end()
+            }
+        }
+    }
+}
@@ -9,10 +9,8 @@ This module generates calls to `emit`.
 #[cfg(feature = "tracing")]
 macro_rules! __private_forward {
     ($($input:tt)*) => {{
-        extern crate emit;
-
-        emit::rt::__private_forward_emit!($($input)*);
-        emit::rt::__private_forward_tracing!($($input)*);
+        ::emit::rt::__private_forward_emit!($($input)*);
+        ::emit::rt::__private_forward_tracing!($($input)*);
     }};
 }
 
@@ -21,9 +19,7 @@ macro_rules! __private_forward {
 #[cfg(not(feature = "tracing"))]
 macro_rules! __private_forward {
     ($($input:tt)*) => {{
-        extern crate emit;
-
-        emit::rt::__private_forward_emit!($($input)*);
+        ::emit::rt::__private_forward_emit!($($input)*);
     }};
 }
 
@@ -31,24 +27,24 @@ macro_rules! __private_forward {
 #[doc(hidden)]
 macro_rules! __private_forward_emit {
     ({
+        level: $level:expr,
         target: None,
         key_value_cfgs: [$(#$cfg:tt),*],
         keys: [$($key:expr),*],
         values: [$($value:expr),*],
         record: $record:expr,
     }) => {{
-        extern crate emit;
-        emit::__private::emit($record)
+        ::emit::__private::emit($record)
     }};
     ({
+        level: $level:expr,
         target: Some($target:expr),
         key_value_cfgs: [$(#$cfg:tt),*],
         keys: [$($key:expr),*],
         values: [$($value:expr),*],
         record: $record:expr,
     }) => {{
-        extern crate emit;
-        emit::__private::emit_to($target, $record)
+        ::emit::__private::emit_to(&$target, $record)
     }};
 }
 
@@ -58,15 +54,14 @@ pub mod tracing {
     #[doc(hidden)]
     macro_rules! __private_forward_tracing {
         ({
+            level: $level:expr,
             target: $target:expr,
             key_value_cfgs: [$(#$cfg:tt),*],
             keys: [$($key:expr),*],
             values: [$($value:expr),*],
             record: $record:expr,
         }) => {{
-            extern crate emit;
-
-            use emit::rt::__private::{
+            use ::emit::rt::__private::{
                 ValueBag,
                 tracing::{
                     Callsite,
@@ -77,14 +72,14 @@ pub mod tracing {
                 }
             };
 
-            let level = Level::INFO;
+            const LEVEL: Level = ::emit::rt::__private::tracing::to_tracing_level($level);
 
-            if level <= LevelFilter::current() {
+            if LEVEL <= LevelFilter::current() {
                 static CALLSITE: Callsite = Callsite::new(&META);
                 static META: Metadata<'static> = Metadata::new(
                     concat!("event ", file!(), ":", line!()),
                     module_path!(),
-                    Level::INFO,
+                    LEVEL,
                     Some(file!()),
                     Some(line!()),
                     Some(module_path!()),
@@ -102,7 +97,7 @@ pub mod tracing {
                         (&fields.field("msg").unwrap(), Some(&field::display($record.render_msg()) as &dyn Value)),
                         $(
                             #$cfg
-                            (&fields.field($key).unwrap(), Some(&field::debug($value) as &dyn Value))
+                            (&fields.field($key).unwrap(), ::emit::rt::__private::tracing::debug($value).as_ref().map(|value| value as &dyn Value))
                         ),*
                     ]));
                 }
@@ -110,16 +105,43 @@ pub mod tracing {
         }};
     }
 
-    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::{
+        fmt,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
 
     pub use tracing_core as core;
 
     use tracing_core::{
-        callsite, dispatcher, Callsite as TracingCallsite, Interest, Metadata, Once,
+        callsite, dispatcher,
+        field::{self, DebugValue},
+        Callsite as TracingCallsite, Interest, Level as TracingLevel, Metadata, Once,
     };
 
+    use crate::level::Level;
+
     pub type Callsite = MacroCallsite;
 
+    /**
+    Get a value for a field, if it was captured.
+
+    `#[optional]` fields that are `None` are left empty.
+    */
+    pub fn debug<T: fmt::Debug>(value: Option<T>) -> Option<DebugValue<T>> {
+        value.map(field::debug)
+    }
+
+    pub const fn to_tracing_level(level: Level) -> TracingLevel {
+        // Custom levels use the nearest standard level at or below their severity
+        match level.severity() {
+            0..=19 => TracingLevel::TRACE,
+            20..=29 => TracingLevel::DEBUG,
+            30..=39 => TracingLevel::INFO,
+            40..=49 => TracingLevel::WARN,
+            _ => TracingLevel::ERROR,
+        }
+    }
+
     // Inlined from: https://github.com/tokio-rs/tracing/blob/1b5bfa0b996e377bca7cafc70f54f22cfda2b25a/tracing/src/lib.rs#L894-L996
     #[derive(Debug)]
     pub struct MacroCallsite {
@@ -176,7 +198,7 @@ pub mod tracing {
 
         #[inline(always)]
         fn metadata(&self) -> &Metadata<'static> {
-            &self.meta
+            self.meta
         }
     }
 }
@@ -0,0 +1,268 @@
+/*!
+Emitting `serde` values as whole events.
+
+A struct like `RequestCompleted { id: 1, status: 200 }` is emitted as a record with
+the message `RequestCompleted` and the key-values `id` and `status`. The keys are the
+names of the struct's fields as they're serialized, so `#[serde(rename)]` is respected.
+Field values are captured as structured values, so nested structs aren't flattened.
+
+Keys have to be known statically, so only structs, struct variants of enums, and newtypes
+around them can be flattened. Any other value, including structs with `#[serde(flatten)]`
+fields, is emitted as a single `value` key-value without a message.
+*/
+
+use crate::std::{fmt, vec::Vec};
+
+use serde_lib::ser::{self, Impossible, Serialize, SerializeStruct, SerializeStructVariant};
+
+use crate::{
+    dynamic::JsonValue,
+    value::{CaptureError, ValueBag},
+};
+
+/**
+The key used for values that can't be flattened into key-values.
+*/
+pub const SERDE_EVENT_VALUE_KEY: &str = "value";
+
+/**
+A `serde` value flattened into a message and key-values.
+*/
+pub struct SerdeEvent {
+    name: &'static str,
+    kvs: Vec<(&'static str, Result<serde_json::Value, CaptureError>)>,
+}
+
+impl SerdeEvent {
+    pub fn new(event: &(impl Serialize + ?Sized)) -> Self {
+        let mut flattened = SerdeEvent {
+            name: "",
+            kvs: Vec::new(),
+        };
+
+        if event.serialize(Flatten(&mut flattened)).is_err() {
+            flattened.name = "";
+            flattened.kvs = vec![(SERDE_EVENT_VALUE_KEY, to_value(event))];
+        }
+
+        // Key-values are kept in the order they were serialized in if they aren't sorted
+        if !cfg!(feature = "no_sort") {
+            flattened.kvs.sort_by_key(|(k, _)| *k);
+        }
+
+        flattened
+    }
+
+    /**
+    The name of the struct or variant that was flattened.
+
+    This is used as the message of the event.
+    */
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn key_values(&self) -> Vec<(&'static str, ValueBag)> {
+        self.kvs
+            .iter()
+            .map(|(k, v)| {
+                let v = match v {
+                    Ok(v) => ValueBag::capture_sval1(JsonValue::new(v)),
+                    Err(err) => ValueBag::capture_sval1(err),
+                };
+
+                (*k, v)
+            })
+            .collect()
+    }
+}
+
+fn to_value(value: &(impl Serialize + ?Sized)) -> Result<serde_json::Value, CaptureError> {
+    serde_json::to_value(value).map_err(|_| CaptureError::new("the value could not be serialized"))
+}
+
+/**
+The error returned when a value can't be flattened into key-values.
+*/
+#[derive(Debug)]
+struct Unsupported;
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("the value can't be flattened into key-values")
+    }
+}
+
+impl crate::std::error::Error for Unsupported {}
+
+impl ser::Error for Unsupported {
+    fn custom<T: fmt::Display>(_: T) -> Self {
+        Unsupported
+    }
+}
+
+struct Flatten<'a>(&'a mut SerdeEvent);
+
+impl<'a> Flatten<'a> {
+    fn field(&mut self, key: &'static str, value: &(impl Serialize + ?Sized)) {
+        self.0.kvs.push((key, to_value(value)));
+    }
+}
+
+macro_rules! unsupported {
+    ($($method:ident($($ty:ty),*),)*) => {
+        $(
+            fn $method(self, $(_: $ty),*) -> Result<Self::Ok, Self::Error> {
+                Err(Unsupported)
+            }
+        )*
+    };
+}
+
+impl<'a> ser::Serializer for Flatten<'a> {
+    type Ok = ();
+    type Error = Unsupported;
+
+    type SerializeSeq = Impossible<(), Unsupported>;
+    type SerializeTuple = Impossible<(), Unsupported>;
+    type SerializeTupleStruct = Impossible<(), Unsupported>;
+    type SerializeTupleVariant = Impossible<(), Unsupported>;
+    type SerializeMap = Impossible<(), Unsupported>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    unsupported!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_none(),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    );
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _: &T) -> Result<(), Unsupported> {
+        Err(Unsupported)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Unsupported> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), Unsupported> {
+        Err(Unsupported)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Unsupported> {
+        Err(Unsupported)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Unsupported> {
+        Err(Unsupported)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Unsupported> {
+        Err(Unsupported)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Unsupported> {
+        Err(Unsupported)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Unsupported> {
+        Err(Unsupported)
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, Unsupported> {
+        self.0.name = name;
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Unsupported> {
+        self.0.name = variant;
+        Ok(self)
+    }
+
+    fn collect_str<T: fmt::Display + ?Sized>(self, _: &T) -> Result<(), Unsupported> {
+        Err(Unsupported)
+    }
+}
+
+impl<'a> SerializeStruct for Flatten<'a> {
+    type Ok = ();
+    type Error = Unsupported;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Unsupported> {
+        self.field(key, value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), Unsupported> {
+        Ok(())
+    }
+}
+
+impl<'a> SerializeStructVariant for Flatten<'a> {
+    type Ok = ();
+    type Error = Unsupported;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Unsupported> {
+        self.field(key, value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), Unsupported> {
+        Ok(())
+    }
+}
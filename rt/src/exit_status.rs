@@ -0,0 +1,81 @@
+/*!
+Capturing the exit statuses of processes.
+*/
+
+use crate::std::{fmt, process::ExitStatus};
+
+use sval::value::{self, Value};
+
+/**
+The exit status of a process, with its exit code and whether it was successful.
+
+When streamed as a structured value it's a map with a `success` key and a `code` key.
+On Unix, a process that was terminated by a signal has no code, so it also has a `signal` key.
+When rendered in a message it's just the code, or the signal if there's no code.
+*/
+#[repr(transparent)]
+pub struct ExitStatusValue(ExitStatus);
+
+impl ExitStatusValue {
+    pub fn new(value: &ExitStatus) -> &Self {
+        // SAFETY: `ExitStatusValue` is `repr(transparent)` over `ExitStatus`
+        unsafe { &*(value as *const ExitStatus as *const Self) }
+    }
+
+    pub fn success(&self) -> bool {
+        self.0.success()
+    }
+
+    pub fn code(&self) -> Option<i32> {
+        self.0.code()
+    }
+
+    /**
+    The signal that terminated the process.
+
+    This is always `None` on platforms other than Unix.
+    */
+    pub fn signal(&self) -> Option<i32> {
+        #[cfg(unix)]
+        {
+            use crate::std::os::unix::process::ExitStatusExt;
+
+            self.0.signal()
+        }
+        #[cfg(not(unix))]
+        {
+            None
+        }
+    }
+}
+
+impl fmt::Display for ExitStatusValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.code(), self.signal()) {
+            (Some(code), _) => fmt::Display::fmt(&code, f),
+            (None, Some(signal)) => write!(f, "signal {}", signal),
+            (None, None) => fmt::Display::fmt(&self.0, f),
+        }
+    }
+}
+
+impl Value for ExitStatusValue {
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        let signal = self.signal();
+
+        stream.map_begin(Some(if signal.is_some() { 3 } else { 2 }))?;
+
+        stream.map_key("success")?;
+        stream.map_value(self.success())?;
+
+        stream.map_key("code")?;
+        stream.map_value(self.code())?;
+
+        if let Some(signal) = signal {
+            stream.map_key("signal")?;
+            stream.map_value(signal)?;
+        }
+
+        stream.map_end()
+    }
+}
@@ -0,0 +1,100 @@
+/*!
+Capturing bit-flags as the names of their active flags.
+*/
+
+use sval::value::{self, Value};
+
+/**
+A bit-flags type, like one generated by the `bitflags` crate.
+
+Values are captured as a sequence of the names of their active flags instead of their raw bits.
+A flag is active when all of its bits are set. Flags without any bits are never active.
+*/
+pub trait Flags {
+    /**
+    The name and bits of each flag.
+    */
+    const FLAGS: &'static [(&'static str, u64)];
+
+    /**
+    The bits that are set.
+    */
+    fn bits(&self) -> u64;
+}
+
+/**
+A bit-flags value that's streamed as the names of its active flags.
+*/
+#[repr(transparent)]
+pub struct FlagNames<T>(T);
+
+impl<T: Flags> FlagNames<T> {
+    pub fn new(value: &T) -> &Self {
+        // SAFETY: `FlagNames<T>` is `repr(transparent)` over `T`
+        unsafe { &*(value as *const T as *const Self) }
+    }
+
+    pub fn active(&self) -> impl Iterator<Item = &'static str> {
+        let bits = self.0.bits();
+
+        T::FLAGS
+            .iter()
+            .filter(move |(_, flag)| *flag != 0 && bits & flag == *flag)
+            .map(|(name, _)| *name)
+    }
+}
+
+impl<T: Flags> Value for FlagNames<T> {
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        stream.seq_begin(Some(self.active().count()))?;
+
+        for name in self.active() {
+            stream.seq_elem(name)?;
+        }
+
+        stream.seq_end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::std::vec::Vec;
+
+    struct Permissions(u64);
+
+    impl Flags for Permissions {
+        const FLAGS: &'static [(&'static str, u64)] = &[
+            ("NONE", 0),
+            ("READ", 0b001),
+            ("WRITE", 0b010),
+            ("EXECUTE", 0b100),
+            ("READ_WRITE", 0b011),
+        ];
+
+        fn bits(&self) -> u64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn active() {
+        for (bits, expected) in [
+            (0b000, vec![]),
+            (0b001, vec!["READ"]),
+            (0b101, vec!["READ", "EXECUTE"]),
+            (0b011, vec!["READ", "WRITE", "READ_WRITE"]),
+            (0b1000, vec![]),
+        ] {
+            assert_eq!(
+                expected,
+                FlagNames::new(&Permissions(bits))
+                    .active()
+                    .collect::<Vec<_>>(),
+                "{:b}",
+                bits
+            );
+        }
+    }
+}
@@ -0,0 +1,79 @@
+/*!
+Rendering captured bytes as a hex dump.
+*/
+
+use crate::{limit::TRUNCATED, std::fmt};
+
+/**
+The number of bytes rendered on each line of a hex dump.
+*/
+const LINE_LEN: usize = 16;
+
+/**
+Bytes that are rendered as a hex dump of at most `LEN` bytes.
+
+Each line has the offset of its first byte, the bytes themselves in hex, and the bytes
+as ASCII, with `.` for any that aren't printable, in the same layout as `hexdump -C`:
+
+```text
+00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a        |Hello, world!.|
+```
+
+If there are more than `LEN` bytes then the dump ends with a `"<truncated>"` line.
+*/
+#[repr(transparent)]
+pub struct HexDump<T: ?Sized, const LEN: usize>(T);
+
+impl<T: ?Sized, const LEN: usize> HexDump<T, LEN> {
+    pub fn new(value: &T) -> &Self {
+        // SAFETY: `HexDump<T>` is `repr(transparent)` over `T`
+        unsafe { &*(value as *const T as *const Self) }
+    }
+}
+
+impl<T: AsRef<[u8]> + ?Sized, const LEN: usize> fmt::Display for HexDump<T, LEN> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bytes = self.0.as_ref();
+
+        for (i, line) in bytes[..bytes.len().min(LEN)].chunks(LINE_LEN).enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+
+            write!(f, "{:08x} ", i * LINE_LEN)?;
+
+            for j in 0..LINE_LEN {
+                if j % 8 == 0 {
+                    f.write_str(" ")?;
+                }
+
+                match line.get(j) {
+                    Some(b) => write!(f, "{:02x} ", b)?,
+                    None => f.write_str("   ")?,
+                }
+            }
+
+            f.write_str(" |")?;
+
+            for b in line {
+                if b.is_ascii_graphic() || *b == b' ' {
+                    write!(f, "{}", *b as char)?;
+                } else {
+                    f.write_str(".")?;
+                }
+            }
+
+            f.write_str("|")?;
+        }
+
+        if bytes.len() > LEN {
+            if LEN > 0 {
+                f.write_str("\n")?;
+            }
+
+            f.write_str(TRUNCATED)?;
+        }
+
+        Ok(())
+    }
+}
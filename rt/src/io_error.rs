@@ -0,0 +1,66 @@
+/*!
+Capturing I/O errors.
+*/
+
+use crate::std::{
+    fmt, io,
+    string::{String, ToString},
+};
+
+use sval::value::{self, Value};
+
+/**
+An I/O error, with its kind, OS error code, and message.
+
+When streamed as a structured value it's a map with a `kind` key, like `"NotFound"`, and a
+`msg` key. Errors that came from the OS also have a `code` key with the raw OS error code.
+When rendered in a message it's just the error's own message.
+*/
+#[repr(transparent)]
+pub struct IoErrorValue(io::Error);
+
+impl IoErrorValue {
+    pub fn new(value: &io::Error) -> &Self {
+        // SAFETY: `IoErrorValue` is `repr(transparent)` over `io::Error`
+        unsafe { &*(value as *const io::Error as *const Self) }
+    }
+
+    pub fn kind(&self) -> io::ErrorKind {
+        self.0.kind()
+    }
+
+    pub fn code(&self) -> Option<i32> {
+        self.0.raw_os_error()
+    }
+
+    fn kind_name(&self) -> String {
+        format!("{:?}", self.kind())
+    }
+}
+
+impl fmt::Display for IoErrorValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl Value for IoErrorValue {
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        let code = self.code();
+
+        stream.map_begin(Some(if code.is_some() { 3 } else { 2 }))?;
+
+        stream.map_key("kind")?;
+        stream.map_value(self.kind_name())?;
+
+        if let Some(code) = code {
+            stream.map_key("code")?;
+            stream.map_value(code)?;
+        }
+
+        stream.map_key("msg")?;
+        stream.map_value(self.0.to_string())?;
+
+        stream.map_end()
+    }
+}
@@ -0,0 +1,93 @@
+/*!
+Capturing a bounded prefix of an iterator.
+*/
+
+use crate::{limit::TRUNCATED, std::vec::Vec, value::ValueBag};
+
+use sval::value::{self, Value};
+
+/**
+The first few elements of an iterator, collected so they can be captured as a sequence.
+
+If the iterator had more elements than were taken, the sequence ends with a
+`"<truncated>"` element, the same as sequences limited by `#[as_sval(max_entries = N)]`.
+*/
+pub struct IterPrefix<T, F> {
+    items: Vec<T>,
+    truncated: bool,
+    capture_item: F,
+}
+
+impl<T, F> IterPrefix<T, F>
+where
+    F: Fn(&T) -> ValueBag,
+{
+    /**
+    Take up to `take` elements from `iter`.
+
+    At most one more element than `take` is pulled from the iterator to find out
+    whether it was truncated, so the rest of it is never consumed.
+    */
+    pub fn new(iter: impl IntoIterator<Item = T>, take: usize, capture_item: F) -> Self {
+        let mut iter = iter.into_iter();
+
+        let items = iter.by_ref().take(take).collect::<Vec<_>>();
+        let truncated = items.len() == take && iter.next().is_some();
+
+        IterPrefix {
+            items,
+            truncated,
+            capture_item,
+        }
+    }
+
+    pub fn capture(&self) -> ValueBag {
+        ValueBag::from_sval1(self)
+    }
+}
+
+impl<T, F> Value for IterPrefix<T, F>
+where
+    F: Fn(&T) -> ValueBag,
+{
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        stream.seq_begin(Some(self.items.len() + self.truncated as usize))?;
+
+        for item in &self.items {
+            stream.seq_elem((self.capture_item)(item))?;
+        }
+
+        if self.truncated {
+            stream.seq_elem(TRUNCATED)?;
+        }
+
+        stream.seq_end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{capture::__PrivateCapture, std::string::ToString};
+
+    #[test]
+    fn take_prefix() {
+        let mut pulled = 0;
+
+        let prefix = IterPrefix::new((0..100u64).inspect(|_| pulled += 1), 3, |item: &u64| {
+            item.__private_capture_as_default()
+        });
+
+        assert_eq!("[0, 1, 2, \"<truncated>\"]", prefix.capture().to_string());
+        drop(prefix);
+
+        assert_eq!(4, pulled);
+    }
+
+    #[test]
+    fn take_all() {
+        let prefix = IterPrefix::new(0..3u64, 3, |item: &u64| item.__private_capture_as_default());
+
+        assert_eq!("[0, 1, 2]", prefix.capture().to_string());
+    }
+}
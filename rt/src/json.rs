@@ -0,0 +1,30 @@
+/*!
+Capturing values as pre-serialized JSON strings.
+*/
+
+use crate::std::string::String;
+
+use serde_lib::Serialize;
+
+use crate::value::CaptureError;
+
+/**
+A value serialized as compact JSON.
+
+The JSON is captured as a string instead of a structured value, for backends that store
+records as text. If the value can't be serialized then it's captured as a `CaptureError`.
+*/
+pub struct JsonString(Result<String, CaptureError>);
+
+impl JsonString {
+    pub fn new(value: &(impl Serialize + ?Sized)) -> Self {
+        JsonString(
+            serde_json::to_string(value)
+                .map_err(|_| CaptureError::new("the value could not be serialized as JSON")),
+        )
+    }
+
+    pub fn get(&self) -> Result<&str, &CaptureError> {
+        self.0.as_ref().map(|json| json.as_str())
+    }
+}
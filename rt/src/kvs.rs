@@ -2,18 +2,98 @@ use crate::std::{fmt, ops::Index};
 
 use super::value::ValueBag;
 
+#[cfg(any(feature = "std", test))]
+use crate::std::{collections::BTreeMap, vec::Vec};
+
+#[cfg(all(any(feature = "std", test), feature = "no_sort"))]
+use crate::std::collections::BTreeSet;
+
+/**
+The key-values captured on a record.
+
+Keys live as long as the values, instead of being `'static`, so key-values built at runtime can be
+merged into the ones captured by the macros. See `merge_key_values`.
+*/
 #[derive(Clone, Copy)]
 pub struct KeyValues<'a> {
-    pub sorted_key_values: &'a [(&'static str, ValueBag<'a>)],
+    pub sorted_key_values: &'a [(&'a str, ValueBag<'a>)],
 }
 
 impl<'a> KeyValues<'a> {
+    /**
+    Get the value for the given key.
+
+    The key-values are sorted, so this is a binary search. If the `no_sort` feature
+    is enabled then key-values are kept in the order they were declared in instead,
+    and this is a linear scan.
+    */
+    #[cfg(not(feature = "no_sort"))]
     pub fn get(&self, key: impl AsRef<str>) -> Option<&ValueBag<'a>> {
         self.sorted_key_values
             .binary_search_by_key(&key.as_ref(), |(k, _)| k)
             .ok()
             .map(|index| &self.sorted_key_values[index].1)
     }
+
+    #[cfg(feature = "no_sort")]
+    pub fn get(&self, key: impl AsRef<str>) -> Option<&ValueBag<'a>> {
+        let key = key.as_ref();
+
+        self.sorted_key_values
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v)
+    }
+
+    /**
+    Whether there's a value for the given key.
+    */
+    pub fn contains_key(&self, key: impl AsRef<str>) -> bool {
+        self.get(key).is_some()
+    }
+
+    /**
+    Get the key-values with keys that start with the given prefix, like `http.`.
+
+    The key-values are sorted, so this only scans the matching range. If the `no_sort`
+    feature is enabled then this scans all key-values instead.
+    */
+    #[cfg(not(feature = "no_sort"))]
+    pub fn fields_matching<'b>(
+        &'b self,
+        prefix: &'b str,
+    ) -> impl Iterator<Item = (&'a str, &'b ValueBag<'a>)> + 'b {
+        let start = self.sorted_key_values.partition_point(|(k, _)| *k < prefix);
+
+        self.sorted_key_values[start..]
+            .iter()
+            .take_while(move |(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (*k, v))
+    }
+
+    #[cfg(feature = "no_sort")]
+    pub fn fields_matching<'b>(
+        &'b self,
+        prefix: &'b str,
+    ) -> impl Iterator<Item = (&'a str, &'b ValueBag<'a>)> + 'b {
+        self.sorted_key_values
+            .iter()
+            .filter(move |(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (*k, v))
+    }
+
+    /**
+    Get the key-values as a map.
+
+    The map is always sorted by key, even if the `no_sort` feature is enabled.
+    */
+    #[cfg(any(feature = "std", test))]
+    pub fn as_map(&self) -> BTreeMap<&'a str, &ValueBag<'a>> {
+        self.sorted_key_values
+            .iter()
+            .map(|(k, v)| (*k, v))
+            .collect()
+    }
 }
 
 impl<'a, 'b> Index<&'b str> for KeyValues<'a> {
@@ -37,3 +117,222 @@ impl<'a> fmt::Debug for KeyValues<'a> {
         fmt::Debug::fmt(&self.sorted_key_values, f)
     }
 }
+
+/**
+Get the key-value for an `#[optional]` field.
+
+If the value is `None` then a placeholder is returned instead, which is removed by `retain_present`.
+*/
+pub fn optional_key_value<'a>(
+    key: &'static str,
+    kv: &Option<(&'static str, ValueBag<'a>)>,
+) -> (&'static str, ValueBag<'a>) {
+    match kv {
+        Some((_, value)) => (key, value.clone()),
+        None => (key, ValueBag::from(())),
+    }
+}
+
+/**
+Move the key-values that are present to the front, keeping their order, and return how many there are.
+*/
+pub fn retain_present<T>(kvs: &mut [T], present: &[bool]) -> usize {
+    let mut len = 0;
+
+    for (i, present) in present.iter().enumerate() {
+        if *present {
+            kvs.swap(len, i);
+            len += 1;
+        }
+    }
+
+    len
+}
+
+/**
+Merge key-values built at runtime into the sorted key-values captured on a record.
+
+If a key appears in both then the value captured on the record is kept. If a key appears
+more than once in the runtime key-values then the first value is kept. The merged key-values
+are sorted by key, unless the `no_sort` feature is enabled, in which case the runtime
+key-values follow the captured ones in the order they were given in.
+*/
+#[cfg(any(feature = "std", test))]
+pub fn merge_key_values<'a>(
+    sorted: &[(&'a str, ValueBag<'a>)],
+    runtime: &[(&'a str, ValueBag<'a>)],
+) -> Vec<(&'a str, ValueBag<'a>)> {
+    let mut merged = Vec::with_capacity(sorted.len() + runtime.len());
+
+    #[cfg(not(feature = "no_sort"))]
+    {
+        merged.extend(sorted.iter().chain(runtime).map(|(k, v)| (*k, v.clone())));
+
+        // The sort is stable, so the first value for each key is the one that's kept
+        merged.sort_by_key(|(k, _)| *k);
+        merged.dedup_by(|(k, _), (prev, _)| k == prev);
+    }
+
+    #[cfg(feature = "no_sort")]
+    {
+        let mut seen = BTreeSet::new();
+
+        for (k, v) in sorted.iter().chain(runtime) {
+            if seen.insert(*k) {
+                merged.push((*k, v.clone()));
+            }
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::std::{string::ToString, vec};
+
+    #[test]
+    fn as_map() {
+        let kvs = KeyValues {
+            sorted_key_values: &[
+                ("a", ValueBag::from(1)),
+                ("b", ValueBag::from("text")),
+                ("c", ValueBag::from(true)),
+            ],
+        };
+
+        let map = kvs.as_map();
+
+        assert_eq!(
+            vec![
+                ("a", "1".to_string()),
+                ("b", "text".to_string()),
+                ("c", "true".to_string())
+            ],
+            map.iter()
+                .map(|(k, v)| (*k, v.to_string()))
+                .collect::<vec::Vec<_>>()
+        );
+        assert_eq!(Some(1), map["a"].to_u64());
+    }
+
+    #[test]
+    fn retain_present_keeps_order() {
+        let mut kvs = [
+            optional_key_value("a", &None),
+            ("b", ValueBag::from(1)),
+            optional_key_value("c", &Some(("c", ValueBag::from(2)))),
+            optional_key_value("d", &None),
+            ("e", ValueBag::from(3)),
+        ];
+
+        let len = retain_present(&mut kvs, &[false, true, true, false, true]);
+
+        assert_eq!(
+            vec![
+                ("b", "1".to_string()),
+                ("c", "2".to_string()),
+                ("e", "3".to_string())
+            ],
+            kvs[..len]
+                .iter()
+                .map(|(k, v)| (*k, v.to_string()))
+                .collect::<vec::Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn contains_key() {
+        let kvs = KeyValues {
+            sorted_key_values: &[("a", ValueBag::from(1)), ("err", ValueBag::from("failed"))],
+        };
+
+        assert!(kvs.contains_key("a"));
+        assert!(kvs.contains_key("err"));
+        assert!(!kvs.contains_key("b"));
+        assert!(!kvs.contains_key(""));
+    }
+
+    #[test]
+    fn fields_matching() {
+        let kvs = KeyValues {
+            sorted_key_values: &[
+                ("http", ValueBag::from(0)),
+                ("http.method", ValueBag::from("GET")),
+                ("http.status", ValueBag::from(200)),
+                ("httpd", ValueBag::from(1)),
+                ("id", ValueBag::from(2)),
+            ],
+        };
+
+        assert_eq!(
+            vec![
+                ("http.method", "GET".to_string()),
+                ("http.status", "200".to_string())
+            ],
+            kvs.fields_matching("http.")
+                .map(|(k, v)| (k, v.to_string()))
+                .collect::<vec::Vec<_>>()
+        );
+
+        assert_eq!(0, kvs.fields_matching("db.").count());
+        assert_eq!(5, kvs.fields_matching("").count());
+    }
+
+    #[test]
+    #[cfg(feature = "no_sort")]
+    fn declaration_order() {
+        let kvs = KeyValues {
+            sorted_key_values: &[
+                ("id", ValueBag::from(2)),
+                ("http.status", ValueBag::from(200)),
+                ("a", ValueBag::from(1)),
+                ("http.method", ValueBag::from("GET")),
+            ],
+        };
+
+        assert_eq!(Some(2), kvs.get("id").and_then(|v| v.to_u64()));
+        assert_eq!(Some(1), kvs.get("a").and_then(|v| v.to_u64()));
+        assert!(kvs.get("b").is_none());
+
+        assert_eq!(
+            vec![
+                ("http.status", "200".to_string()),
+                ("http.method", "GET".to_string())
+            ],
+            kvs.fields_matching("http.")
+                .map(|(k, v)| (k, v.to_string()))
+                .collect::<vec::Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn merge_runtime() {
+        let sorted = [("b", ValueBag::from(1)), ("d", ValueBag::from(2))];
+
+        let key = "c".to_string();
+        let runtime = [
+            (&*key, ValueBag::from(3)),
+            ("a", ValueBag::from(4)),
+            ("b", ValueBag::from(5)),
+            ("a", ValueBag::from(6)),
+        ];
+
+        let merged = merge_key_values(&sorted, &runtime);
+
+        let expected = if cfg!(feature = "no_sort") {
+            vec![("b", 1), ("d", 2), ("c", 3), ("a", 4)]
+        } else {
+            vec![("a", 4), ("b", 1), ("c", 3), ("d", 2)]
+        };
+
+        assert_eq!(
+            expected,
+            merged
+                .iter()
+                .map(|(k, v)| (*k, v.to_u64().unwrap()))
+                .collect::<vec::Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use crate::std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+#[cfg(feature = "serde")]
+use serde_lib::ser::{Serialize, Serializer};
+
+use sval::value::{self, Value};
+
+/**
+The severity of an emitted record.
+
+Levels are ordered by their numeric `severity`, so custom levels can sit
+between the standard ones. Two levels are equal if they have the same
+name and severity.
+*/
+#[derive(Clone, Copy, Debug)]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    /**
+    A level defined outside of `emit`, like `Notice` or `Critical`.
+
+    Custom levels are usually defined with the `define_levels!` macro.
+    */
+    Custom {
+        name: &'static str,
+        severity: u8,
+    },
+}
+
+impl Level {
+    pub const fn custom(name: &'static str, severity: u8) -> Self {
+        Level::Custom { name, severity }
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Level::Trace => "trace",
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+            Level::Custom { name, .. } => *name,
+        }
+    }
+
+    /**
+    The numeric severity of the level.
+
+    The standard levels go from `10` for `Trace` up to `50` for `Error`, in steps of `10`.
+    */
+    pub const fn severity(&self) -> u8 {
+        match self {
+            Level::Trace => 10,
+            Level::Debug => 20,
+            Level::Info => 30,
+            Level::Warn => 40,
+            Level::Error => 50,
+            Level::Custom { severity, .. } => *severity,
+        }
+    }
+}
+
+impl PartialEq for Level {
+    fn eq(&self, other: &Self) -> bool {
+        self.severity() == other.severity() && self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Level {}
+
+impl PartialOrd for Level {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Level {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.severity(), self.as_str()).cmp(&(other.severity(), other.as_str()))
+    }
+}
+
+impl Hash for Level {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.severity(), self.as_str()).hash(state)
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Value for Level {
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        stream.str(self.as_str())
+    }
+}
+
+/**
+Parse one of the standard levels, like from an environment variable or config file.
+
+Parsing is case-insensitive, and also accepts the aliases `warning`, `err`, and `information`.
+Custom levels can't be parsed.
+*/
+impl FromStr for Level {
+    type Err = ParseLevelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const LEVELS: [(&str, Level); 8] = [
+            ("trace", Level::Trace),
+            ("debug", Level::Debug),
+            ("info", Level::Info),
+            ("information", Level::Info),
+            ("warn", Level::Warn),
+            ("warning", Level::Warn),
+            ("err", Level::Error),
+            ("error", Level::Error),
+        ];
+
+        LEVELS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(|(_, level)| *level)
+            .ok_or(ParseLevelError(()))
+    }
+}
+
+/**
+The error returned when a string isn't a standard level.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLevelError(());
+
+impl fmt::Display for ParseLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("expected a level like `trace`, `debug`, `info`, `warn`, or `error`")
+    }
+}
+
+#[cfg(feature = "std")]
+impl crate::std::error::Error for ParseLevelError {}
+
+#[cfg(feature = "serde")]
+impl Serialize for Level {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        s.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_order() {
+        let notice = Level::custom("notice", 35);
+        let critical = Level::custom("critical", 60);
+
+        assert!(Level::Info < notice);
+        assert!(notice < Level::Warn);
+        assert!(Level::Error < critical);
+
+        assert_eq!(Level::Info, Level::custom("info", 30));
+        assert_ne!(notice, Level::custom("notable", 35));
+    }
+
+    #[test]
+    fn parse_aliases() {
+        for (input, expected) in [
+            ("trace", Level::Trace),
+            ("DEBUG", Level::Debug),
+            ("info", Level::Info),
+            ("Information", Level::Info),
+            ("warn", Level::Warn),
+            ("WARNING", Level::Warn),
+            ("err", Level::Error),
+            ("Error", Level::Error),
+        ] {
+            assert_eq!(Ok(expected), input.parse::<Level>(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn parse_unknown() {
+        assert_eq!(Err(ParseLevelError(())), "critical".parse::<Level>());
+        assert_eq!(Err(ParseLevelError(())), "".parse::<Level>());
+    }
+}
@@ -6,6 +6,7 @@ This crate is not intended to be consumed directly.
 
 #![feature(min_specialization)] // required to accept `T: Sized + 'static || dyn Trait || str`
 #![feature(extern_types)] // could be replaced by empty enums
+#![cfg_attr(feature = "std", feature(once_cell))] // required for the process start of uptimes
 #![no_std]
 
 #[cfg(any(feature = "std", test))]
@@ -18,12 +19,57 @@ extern crate std;
 #[allow(unused_imports)]
 extern crate core as std;
 
+#[cfg(feature = "std")]
+mod ansi;
+mod array;
+mod bounded;
 mod capture;
+#[cfg(all(feature = "std", feature = "serde", feature = "serde_json"))]
+mod diff;
+mod duration;
+#[cfg(feature = "serde")]
+mod dynamic;
 mod emit;
+#[cfg(all(feature = "std", feature = "serde", feature = "serde_json"))]
+mod event;
+#[cfg(feature = "std")]
+mod exit_status;
+mod flags;
+mod hexdump;
+#[cfg(feature = "std")]
+mod io_error;
+#[cfg(feature = "std")]
+mod iter;
+#[cfg(all(feature = "std", feature = "serde", feature = "serde_json"))]
+mod json;
 mod kvs;
+mod level;
+mod limit;
+mod location;
+#[cfg(feature = "std")]
+mod lock;
+#[cfg(feature = "serde")]
+mod newtype;
+#[cfg(feature = "std")]
+mod path;
+#[cfg(feature = "std")]
+mod precision;
 mod record;
+mod records;
+mod redact;
+#[cfg(feature = "serde")]
+mod serialize_with;
+mod status;
 mod template;
+#[cfg(feature = "std")]
+mod timestamp;
+#[cfg(feature = "std")]
+mod uptime;
 mod value;
+#[cfg(feature = "std")]
+mod weak;
+#[cfg(feature = "serde")]
+mod with_display;
 
 /**
 This module is the entrypoint for the macros.
@@ -32,5 +78,20 @@ The code generated by the `ct` crate targets this module here in `rt`.
 */
 #[doc(hidden)]
 pub mod __private {
-    pub use crate::{capture::__PrivateCapture, emit::*, kvs::*, record::*, template::*, value::*};
+    pub use crate::{
+        array::*, bounded::*, capture::{__PrivateCapture, __PrivateCaptureAtomic, __PrivateCaptureInferred, __PrivateReborrow, __PrivateReborrowMut}, duration::*, emit::*, flags::*, hexdump::*, kvs::*, level::*, limit::*, location::*,
+        record::*, records::*, redact::*, status::*, template::*, value::*,
+    };
+
+    #[cfg(feature = "std")]
+    pub use crate::{
+        ansi::*, exit_status::*, io_error::*, iter::*, lock::*, precision::*, timestamp::*,
+        uptime::*, weak::*,
+    };
+
+    #[cfg(feature = "serde")]
+    pub use crate::{serialize_with::*, with_display::*};
+
+    #[cfg(all(feature = "std", feature = "serde", feature = "serde_json"))]
+    pub use crate::{diff::*, event::*, json::*};
 }
@@ -0,0 +1,218 @@
+/*!
+Limiting the size of captured structured values.
+*/
+
+use sval::{
+    stream::{self, Stream},
+    value::{self, Value},
+};
+
+/**
+The marker streamed in place of structured values beyond the depth limit,
+and as the key of the entry counting any entries beyond the size limit.
+*/
+pub const TRUNCATED: &str = "<truncated>";
+
+/**
+A value that stops streaming nested maps and sequences beyond `DEPTH`,
+and entries in any single map or sequence beyond `ENTRIES`.
+
+Nested values beyond the depth limit are replaced by `"<truncated>"`.
+Maps with entries beyond the size limit end with a `"<truncated>"` key
+containing the number of entries skipped, and sequences end with a
+`"<truncated>"` element.
+*/
+#[repr(transparent)]
+pub struct Limited<T: ?Sized, const DEPTH: usize, const ENTRIES: usize>(T);
+
+impl<T: ?Sized, const DEPTH: usize, const ENTRIES: usize> Limited<T, DEPTH, ENTRIES> {
+    pub fn new(value: &T) -> &Self {
+        // SAFETY: `Limited<T>` is `repr(transparent)` over `T`
+        unsafe { &*(value as *const T as *const Self) }
+    }
+}
+
+impl<T: Value + ?Sized, const DEPTH: usize, const ENTRIES: usize> Value
+    for Limited<T, DEPTH, ENTRIES>
+{
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        sval::stream(
+            LimitStream::<_, DEPTH> {
+                stream,
+                entries: ENTRIES,
+                open: 0,
+                counts: [0; DEPTH],
+                skip: 0,
+                discard: false,
+            },
+            &self.0,
+        )?;
+
+        Ok(())
+    }
+}
+
+struct LimitStream<S, const DEPTH: usize> {
+    stream: S,
+    entries: usize,
+    // The number of maps and sequences currently forwarded
+    open: usize,
+    // The number of entries seen in each forwarded map or sequence
+    counts: [usize; DEPTH],
+    // The depth of a map or sequence that's being discarded
+    skip: usize,
+    // Whether the current entry is beyond the size limit
+    discard: bool,
+}
+
+impl<S: Stream, const DEPTH: usize> LimitStream<S, DEPTH> {
+    fn is_discarding(&self) -> bool {
+        self.skip > 0 || self.discard
+    }
+
+    fn primitive(&mut self, f: impl FnOnce(&mut S) -> stream::Result) -> stream::Result {
+        if self.is_discarding() {
+            Ok(())
+        } else {
+            f(&mut self.stream)
+        }
+    }
+
+    fn begin(&mut self, f: impl FnOnce(&mut S) -> stream::Result) -> stream::Result {
+        if self.is_discarding() {
+            self.skip += 1;
+            Ok(())
+        } else if self.open >= DEPTH {
+            self.skip = 1;
+            self.stream.str(TRUNCATED)
+        } else {
+            f(&mut self.stream)?;
+
+            self.counts[self.open] = 0;
+            self.open += 1;
+
+            Ok(())
+        }
+    }
+
+    fn entry(&mut self, f: impl FnOnce(&mut S) -> stream::Result) -> stream::Result {
+        if self.skip > 0 {
+            return Ok(());
+        }
+
+        let count = &mut self.counts[self.open - 1];
+        *count += 1;
+
+        self.discard = *count > self.entries;
+        self.primitive(f)
+    }
+
+    fn end(
+        &mut self,
+        truncated: impl FnOnce(&mut S, usize) -> stream::Result,
+        f: impl FnOnce(&mut S) -> stream::Result,
+    ) -> stream::Result {
+        if self.skip > 0 {
+            self.skip -= 1;
+            return Ok(());
+        }
+
+        self.discard = false;
+        self.open -= 1;
+
+        let count = self.counts[self.open];
+        if count > self.entries {
+            truncated(&mut self.stream, count - self.entries)?;
+        }
+
+        f(&mut self.stream)
+    }
+}
+
+impl<S: Stream, const DEPTH: usize> Stream for LimitStream<S, DEPTH> {
+    fn fmt(&mut self, v: stream::Arguments) -> stream::Result {
+        self.primitive(|stream| stream.fmt(v))
+    }
+
+    fn error(&mut self, v: stream::Source) -> stream::Result {
+        self.primitive(|stream| stream.error(v))
+    }
+
+    fn i64(&mut self, v: i64) -> stream::Result {
+        self.primitive(|stream| stream.i64(v))
+    }
+
+    fn u64(&mut self, v: u64) -> stream::Result {
+        self.primitive(|stream| stream.u64(v))
+    }
+
+    fn i128(&mut self, v: i128) -> stream::Result {
+        self.primitive(|stream| stream.i128(v))
+    }
+
+    fn u128(&mut self, v: u128) -> stream::Result {
+        self.primitive(|stream| stream.u128(v))
+    }
+
+    fn f64(&mut self, v: f64) -> stream::Result {
+        self.primitive(|stream| stream.f64(v))
+    }
+
+    fn bool(&mut self, v: bool) -> stream::Result {
+        self.primitive(|stream| stream.bool(v))
+    }
+
+    fn char(&mut self, v: char) -> stream::Result {
+        self.primitive(|stream| stream.char(v))
+    }
+
+    fn str(&mut self, v: &str) -> stream::Result {
+        self.primitive(|stream| stream.str(v))
+    }
+
+    fn none(&mut self) -> stream::Result {
+        self.primitive(|stream| stream.none())
+    }
+
+    fn map_begin(&mut self, len: Option<usize>) -> stream::Result {
+        self.begin(|stream| stream.map_begin(len))
+    }
+
+    fn map_key(&mut self) -> stream::Result {
+        self.entry(|stream| stream.map_key())
+    }
+
+    fn map_value(&mut self) -> stream::Result {
+        self.primitive(|stream| stream.map_value())
+    }
+
+    fn map_end(&mut self) -> stream::Result {
+        self.end(
+            |stream, skipped| {
+                stream.map_key()?;
+                stream.str(TRUNCATED)?;
+                stream.map_value()?;
+                stream.u64(skipped as u64)
+            },
+            |stream| stream.map_end(),
+        )
+    }
+
+    fn seq_begin(&mut self, len: Option<usize>) -> stream::Result {
+        self.begin(|stream| stream.seq_begin(len))
+    }
+
+    fn seq_elem(&mut self) -> stream::Result {
+        self.entry(|stream| stream.seq_elem())
+    }
+
+    fn seq_end(&mut self) -> stream::Result {
+        self.end(
+            |stream, _| {
+                stream.seq_elem()?;
+                stream.str(TRUNCATED)
+            },
+            |stream| stream.seq_end(),
+        )
+    }
+}
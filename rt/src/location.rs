@@ -0,0 +1,13 @@
+/*!
+The location in source code a record was emitted from.
+*/
+
+/**
+The file, line, and module a record was emitted from.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SourceLocation<'a> {
+    pub file: &'a str,
+    pub line: u32,
+    pub module_path: &'a str,
+}
@@ -0,0 +1,75 @@
+/*!
+Capturing values behind locks.
+*/
+
+use crate::std::{
+    fmt,
+    sync::{Arc, Mutex, RwLock, TryLockError},
+};
+
+/**
+The marker formatted in place of a value behind a poisoned lock.
+*/
+pub const POISONED: &str = "<poisoned>";
+
+/**
+The marker formatted in place of a value behind a lock that's already held.
+*/
+pub const LOCKED: &str = "<locked>";
+
+/**
+A lock that can format the value it guards.
+*/
+pub trait LockDebug {
+    fn fmt_locked(&self, f: &mut fmt::Formatter) -> fmt::Result;
+}
+
+impl<T: fmt::Debug + ?Sized> LockDebug for Mutex<T> {
+    fn fmt_locked(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.try_lock() {
+            Ok(value) => fmt::Debug::fmt(&*value, f),
+            Err(TryLockError::Poisoned(_)) => f.write_str(POISONED),
+            Err(TryLockError::WouldBlock) => f.write_str(LOCKED),
+        }
+    }
+}
+
+impl<T: fmt::Debug + ?Sized> LockDebug for RwLock<T> {
+    fn fmt_locked(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.try_read() {
+            Ok(value) => fmt::Debug::fmt(&*value, f),
+            Err(TryLockError::Poisoned(_)) => f.write_str(POISONED),
+            Err(TryLockError::WouldBlock) => f.write_str(LOCKED),
+        }
+    }
+}
+
+impl<T: LockDebug + ?Sized> LockDebug for Arc<T> {
+    fn fmt_locked(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt_locked(f)
+    }
+}
+
+/**
+A lock that formats the value it guards using its `Debug` implementation.
+
+The lock is only held while the value is being formatted. If the lock is
+poisoned then `"<poisoned>"` is formatted instead. If the lock is already held,
+such as by the code emitting the record, then `"<locked>"` is formatted instead
+of waiting for it.
+*/
+#[repr(transparent)]
+pub struct Locked<T: ?Sized>(T);
+
+impl<T: ?Sized> Locked<T> {
+    pub fn new(value: &T) -> &Self {
+        // SAFETY: `Locked<T>` is `repr(transparent)` over `T`
+        unsafe { &*(value as *const T as *const Self) }
+    }
+}
+
+impl<T: LockDebug + ?Sized> fmt::Debug for Locked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt_locked(f)
+    }
+}
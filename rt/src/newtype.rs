@@ -0,0 +1,132 @@
+/*!
+Capturing `serde` newtype structs as the values they wrap.
+
+In the `serde` data model a newtype struct like `struct Id(u64)` is a wrapper around its
+inner value, and formats like JSON serialize it as that value. Captured values are unwrapped
+the same way, so `Id(42)` is formatted as `42` and can be cast to a `u64` instead of being
+formatted like `Debug`. Only the captured value itself is unwrapped, not any newtype structs
+inside it.
+*/
+
+use crate::std::fmt;
+
+use serde_lib::ser::{Serialize, Serializer};
+
+/**
+A `serde` value with any newtype structs around it unwrapped.
+*/
+#[repr(transparent)]
+pub struct Unwrapped<T: ?Sized>(T);
+
+impl<T: ?Sized> Unwrapped<T> {
+    pub fn new(value: &T) -> &Self {
+        // SAFETY: `Unwrapped<T>` is `repr(transparent)` over `T`
+        unsafe { &*(value as *const T as *const Self) }
+    }
+}
+
+impl<T: Serialize + ?Sized> Serialize for Unwrapped<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(UnwrapNewtype(serializer))
+    }
+}
+
+/**
+A serializer that skips over newtype structs and forwards everything else.
+*/
+struct UnwrapNewtype<S>(S);
+
+macro_rules! forward {
+    ($($method:ident($($arg:ident: $ty:ty),*) -> $ok:ty,)*) => {
+        $(
+            fn $method(self, $($arg: $ty),*) -> Result<$ok, S::Error> {
+                self.0.$method($($arg),*)
+            }
+        )*
+    };
+}
+
+impl<S: Serializer> Serializer for UnwrapNewtype<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    type SerializeSeq = S::SerializeSeq;
+    type SerializeTuple = S::SerializeTuple;
+    type SerializeTupleStruct = S::SerializeTupleStruct;
+    type SerializeTupleVariant = S::SerializeTupleVariant;
+    type SerializeMap = S::SerializeMap;
+    type SerializeStruct = S::SerializeStruct;
+    type SerializeStructVariant = S::SerializeStructVariant;
+
+    forward!(
+        serialize_bool(v: bool) -> S::Ok,
+        serialize_i8(v: i8) -> S::Ok,
+        serialize_i16(v: i16) -> S::Ok,
+        serialize_i32(v: i32) -> S::Ok,
+        serialize_i64(v: i64) -> S::Ok,
+        serialize_i128(v: i128) -> S::Ok,
+        serialize_u8(v: u8) -> S::Ok,
+        serialize_u16(v: u16) -> S::Ok,
+        serialize_u32(v: u32) -> S::Ok,
+        serialize_u64(v: u64) -> S::Ok,
+        serialize_u128(v: u128) -> S::Ok,
+        serialize_f32(v: f32) -> S::Ok,
+        serialize_f64(v: f64) -> S::Ok,
+        serialize_char(v: char) -> S::Ok,
+        serialize_str(v: &str) -> S::Ok,
+        serialize_bytes(v: &[u8]) -> S::Ok,
+        serialize_none() -> S::Ok,
+        serialize_unit() -> S::Ok,
+        serialize_unit_struct(name: &'static str) -> S::Ok,
+        serialize_unit_variant(name: &'static str, index: u32, variant: &'static str) -> S::Ok,
+        serialize_seq(len: Option<usize>) -> S::SerializeSeq,
+        serialize_tuple(len: usize) -> S::SerializeTuple,
+        serialize_tuple_struct(name: &'static str, len: usize) -> S::SerializeTupleStruct,
+        serialize_tuple_variant(
+            name: &'static str,
+            index: u32,
+            variant: &'static str,
+            len: usize
+        ) -> S::SerializeTupleVariant,
+        serialize_map(len: Option<usize>) -> S::SerializeMap,
+        serialize_struct(name: &'static str, len: usize) -> S::SerializeStruct,
+        serialize_struct_variant(
+            name: &'static str,
+            index: u32,
+            variant: &'static str,
+            len: usize
+        ) -> S::SerializeStructVariant,
+    );
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<S::Ok, S::Error> {
+        self.0.serialize_some(value)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        // Newtypes around newtypes are unwrapped too
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.0
+            .serialize_newtype_variant(name, index, variant, value)
+    }
+
+    fn collect_str<T: fmt::Display + ?Sized>(self, value: &T) -> Result<S::Ok, S::Error> {
+        self.0.collect_str(value)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
@@ -0,0 +1,30 @@
+/*!
+Formatting file paths as strings.
+*/
+
+use crate::std::{fmt, path::Path};
+
+/**
+A path that's formatted as a string, replacing any invalid UTF-8 with `U+FFFD`.
+*/
+#[repr(transparent)]
+pub struct LossyPath<P>(P);
+
+impl<P> LossyPath<P>
+where
+    P: AsRef<Path>,
+{
+    pub fn new(value: &P) -> &Self {
+        // SAFETY: `LossyPath<P>` is `repr(transparent)` over `P`
+        unsafe { &*(value as *const P as *const Self) }
+    }
+}
+
+impl<P> fmt::Display for LossyPath<P>
+where
+    P: AsRef<Path>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0.as_ref().display(), f)
+    }
+}
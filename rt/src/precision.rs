@@ -0,0 +1,34 @@
+/*!
+Capturing floats rounded to a number of decimal places.
+*/
+
+/**
+A float that can be rounded to a number of decimal places.
+*/
+pub trait Round {
+    /**
+    Round the value to the given number of decimal places.
+
+    Values that are too large to be rounded, and non-finite values like `NaN`, are returned as-is.
+    */
+    fn round_to(&self, digits: u32) -> f64;
+}
+
+impl Round for f64 {
+    fn round_to(&self, digits: u32) -> f64 {
+        let scale = 10f64.powi(digits as i32);
+        let rounded = (self * scale).round() / scale;
+
+        if rounded.is_finite() {
+            rounded
+        } else {
+            *self
+        }
+    }
+}
+
+impl Round for f32 {
+    fn round_to(&self, digits: u32) -> f64 {
+        (*self as f64).round_to(digits)
+    }
+}
@@ -1,42 +1,174 @@
-use crate::{kvs::KeyValues, std::fmt, template::Template};
+use crate::{
+    kvs::KeyValues,
+    level::Level,
+    location::SourceLocation,
+    status::Status,
+    std::fmt,
+    template::{Context, Part, Template},
+    value::{CaptureError, ValueBag},
+};
 
 use sval::value::{self, Value};
 
+#[cfg(feature = "std")]
+use crate::{exit_status::ExitStatusValue, io_error::IoErrorValue, uptime::Uptime};
+
+#[cfg(feature = "serde")]
+use crate::with_display::WithDisplay;
+
 #[cfg(feature = "serde")]
 use serde_lib::ser::{Serialize, SerializeMap, Serializer};
 
 pub struct Record<'a> {
+    pub level: Level,
+    pub target: &'a str,
+    pub location: SourceLocation<'a>,
     pub kvs: KeyValues<'a>,
     pub template: Template<'a>,
 }
 
+/**
+Options for rendering the message of a record.
+*/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RenderOptions<'a> {
+    missing_hole: Option<&'a str>,
+}
+
+impl<'a> RenderOptions<'a> {
+    pub const fn new() -> Self {
+        RenderOptions { missing_hole: None }
+    }
+
+    /**
+    Render holes that don't have a value as the given placeholder, like `""` or `"<missing>"`.
+
+    By default, holes that don't have a value are rendered as their label in backticks, like `` `key` ``.
+    */
+    pub const fn missing_hole(self, placeholder: &'a str) -> Self {
+        RenderOptions {
+            missing_hole: Some(placeholder),
+        }
+    }
+}
+
 impl<'a> Record<'a> {
-    pub fn render_msg<'b>(&'b self) -> impl fmt::Display + 'b {
-        self.template.render(fv_template::rt::Context::new().fill(
-            move |write: &mut fmt::Formatter, label| {
-                self.kvs
-                    .get(label)
-                    .map(|value| fmt::Display::fmt(&value, write))
-            },
-        ))
+    pub fn render_msg(&self) -> impl fmt::Display + '_ {
+        self.render_msg_with(RenderOptions::new())
+    }
+
+    pub fn render_msg_with<'b>(&'b self, options: RenderOptions<'b>) -> impl fmt::Display + 'b {
+        self.template.render(
+            Context::new()
+                .fill(move |write: &mut fmt::Formatter, label| {
+                    self.kvs.get(label).map(|value| render_value(value, write))
+                })
+                .missing(move |write: &mut fmt::Formatter, label| match options.missing_hole {
+                    Some(placeholder) => write.write_str(placeholder),
+                    None => write!(write, "`{}`", label),
+                }),
+        )
     }
 
-    pub fn render_template<'b>(&'b self) -> impl fmt::Display + 'b {
+    /**
+    Whether the template has anything to render as a message.
+
+    Empty text fragments don't count, so a record emitted with an empty template,
+    like `""`, doesn't have a message.
+    */
+    pub fn has_message(&self) -> bool {
+        self.template.parts().iter().any(|part| match part {
+            Part::Text(text) => !text.is_empty(),
+            _ => true,
+        })
+    }
+
+    pub fn render_template(&self) -> impl fmt::Display + '_ {
         self.template.render(Default::default())
     }
 }
 
+fn render_value(value: &ValueBag, f: &mut fmt::Formatter) -> fmt::Result {
+    if let Some(err) = value.downcast_ref::<CaptureError>() {
+        return write!(f, "<error: {}>", err);
+    }
+
+    // A status is rendered as just its code
+    if let Some(status) = value.downcast_ref::<Status>() {
+        return fmt::Display::fmt(status, f);
+    }
+
+    // An exit status is rendered as just its code or signal
+    #[cfg(feature = "std")]
+    if let Some(status) = value.downcast_ref::<ExitStatusValue>() {
+        return fmt::Display::fmt(status, f);
+    }
+
+    // An uptime is rendered as an ISO 8601 period
+    #[cfg(feature = "std")]
+    if let Some(uptime) = value.downcast_ref::<Uptime>() {
+        return fmt::Display::fmt(uptime, f);
+    }
+
+    // An I/O error is rendered as just its message
+    #[cfg(feature = "std")]
+    if let Some(err) = value.downcast_ref::<IoErrorValue>() {
+        return fmt::Display::fmt(err, f);
+    }
+
+    // A value captured with `#[as_serde(with_display)]` is rendered as its `Display` string
+    // instead of its structure
+    #[cfg(feature = "serde")]
+    if let Some(value) = value.downcast_ref::<WithDisplay<'static>>() {
+        return fmt::Display::fmt(value, f).or_else(|_| f.write_str("<error>"));
+    }
+
+    // A value that fails to format is rendered as a placeholder
+    // instead of failing the rest of the message
+    fmt::Display::fmt(value, f).or_else(|_| f.write_str("<error>"))
+}
+
 impl<'a> fmt::Display for Record<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.render_msg().fmt(f)
     }
 }
 
+/**
+The keys a record is streamed or serialized with before its key-values.
+*/
+const RESERVED_KEYS: [&str; 3] = ["lvl", "msg", "target"];
+
+impl<'a> Record<'a> {
+    /**
+    The key-values that are streamed or serialized along with the record.
+
+    Key-values with the same key as the level, message, or target are skipped,
+    so a record never has more than one value for a key.
+    */
+    fn structured_kvs(&self) -> impl Iterator<Item = &(&'a str, ValueBag<'a>)> {
+        self.kvs
+            .sorted_key_values
+            .iter()
+            .filter(|(k, _)| !RESERVED_KEYS.contains(k))
+    }
+}
+
+// A record is streamed as a map of its level, rendered message, and target, then its key-values
 impl<'a> Value for Record<'a> {
     fn stream(&self, stream: &mut value::Stream) -> value::Result {
-        stream.map_begin(Some(self.kvs.sorted_key_values.len() + 2))?;
+        stream.map_begin(Some(self.structured_kvs().count() + RESERVED_KEYS.len()))?;
+
+        stream.map_key("lvl")?;
+        stream.map_value(self.level)?;
+
+        stream.map_key("msg")?;
+        stream.map_value(format_args!("{}", self.render_msg()))?;
 
-        for (k, v) in self.kvs.sorted_key_values {
+        stream.map_key("target")?;
+        stream.map_value(self.target)?;
+
+        for (k, v) in self.structured_kvs() {
             stream.map_key(k)?;
             stream.map_value(v)?;
         }
@@ -51,12 +183,162 @@ impl<'a> Serialize for Record<'a> {
     where
         S: Serializer,
     {
-        let mut map = s.serialize_map(Some(self.kvs.sorted_key_values.len() + 2))?;
+        let mut map = s.serialize_map(Some(self.structured_kvs().count() + RESERVED_KEYS.len()))?;
+
+        map.serialize_entry("lvl", &self.level)?;
+        map.serialize_entry("msg", &format_args!("{}", self.render_msg()))?;
+        map.serialize_entry("target", self.target)?;
 
-        for (k, v) in self.kvs.sorted_key_values {
+        for (k, v) in self.structured_kvs() {
             map.serialize_entry(k, v)?;
         }
 
         map.end()
     }
 }
+
+/**
+Build a record for tests, with a `test` target and source location.
+*/
+#[cfg(test)]
+pub(crate) fn test_record<'a>(
+    level: Level,
+    kvs: &'a [(&'a str, ValueBag<'a>)],
+    parts: &'a [Part<'a>],
+) -> Record<'a> {
+    Record {
+        level,
+        target: "test",
+        location: SourceLocation {
+            file: "test.rs",
+            line: 1,
+            module_path: "test",
+        },
+        kvs: KeyValues {
+            sorted_key_values: kvs,
+        },
+        template: crate::template::template(parts),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        capture::__PrivateCapture,
+        std::string::ToString,
+        template::Part,
+    };
+
+    #[test]
+    fn render_capture_error() {
+        struct Fallible;
+
+        impl fmt::Display for Fallible {
+            fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+
+        let err = CaptureError::new("failed to read");
+
+        let kvs = [
+            ("a", err.__private_capture_as_default()),
+            ("b", Fallible.__private_capture_as_default()),
+            ("c", 42.__private_capture_as_default()),
+        ];
+        let record = test_record(
+            Level::Info,
+            &kvs,
+            &[
+                Part::Text("a: "),
+                Part::Hole("a"),
+                Part::Text(", b: "),
+                Part::Hole("b"),
+                Part::Text(", c: "),
+                Part::Hole("c"),
+            ],
+        );
+
+        assert_eq!(
+            "a: <error: failed to read>, b: <error>, c: 42",
+            record.render_msg().to_string()
+        );
+    }
+
+    #[test]
+    fn render_missing_hole() {
+        let kvs = [("a", 42.__private_capture_as_default())];
+        let record = test_record(
+            Level::Info,
+            &kvs,
+            &[
+                Part::Text("a: "),
+                Part::Hole("a"),
+                Part::Text(", b: "),
+                Part::Hole("b"),
+            ],
+        );
+
+        assert_eq!("a: 42, b: `b`", record.render_msg().to_string());
+        assert_eq!(
+            "a: 42, b: `b`",
+            record.render_msg_with(RenderOptions::new()).to_string()
+        );
+        assert_eq!(
+            "a: 42, b: <missing>",
+            record
+                .render_msg_with(RenderOptions::new().missing_hole("<missing>"))
+                .to_string()
+        );
+        assert_eq!(
+            "a: 42, b: ",
+            record
+                .render_msg_with(RenderOptions::new().missing_hole(""))
+                .to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "serde"))]
+    fn render_with_display() {
+        use serde_lib::Serializer;
+
+        struct Point(i32, i32);
+
+        impl Serialize for Point {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                (self.0, self.1).serialize(serializer)
+            }
+        }
+
+        impl fmt::Display for Point {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}x{}", self.0, self.1)
+            }
+        }
+
+        let point = Point(1, 2);
+        let point = WithDisplay::new(&point);
+
+        let kvs = [("point", point.capture())];
+        let record = test_record(Level::Info, &kvs, &[Part::Text("at "), Part::Hole("point")]);
+
+        // The structure is captured, but the hole is rendered with `Display`
+        assert_eq!("(1, 2)", kvs[0].1.to_string());
+        assert_eq!("at 1x2", record.render_msg().to_string());
+    }
+
+    #[test]
+    fn has_message() {
+        fn record<'a>(parts: &'a [Part<'a>]) -> Record<'a> {
+            test_record(Level::Info, &[], parts)
+        }
+
+        assert!(!record(&[]).has_message());
+        assert!(!record(&[Part::Text("")]).has_message());
+
+        assert!(record(&[Part::Text("a: "), Part::Hole("a")]).has_message());
+        assert!(record(&[Part::Hole("a")]).has_message());
+    }
+}
@@ -0,0 +1,98 @@
+/*!
+Capturing a sequence of sub-records.
+*/
+
+use crate::{record::Record, value::ValueBag};
+
+use sval::value::{self, Value};
+
+/**
+A sequence of records, one for each element of a slice.
+
+Each record is streamed as a map with its rendered message in a `msg` key,
+followed by its key-values.
+*/
+pub struct Records<'a, T, F> {
+    items: &'a [T],
+    to_record: F,
+}
+
+impl<'a, T, F> Records<'a, T, F>
+where
+    F: Fn(&T, &mut dyn FnMut(&Record)),
+{
+    pub fn new(items: &'a [T], to_record: F) -> Self {
+        Records { items, to_record }
+    }
+
+    pub fn capture(&self) -> ValueBag {
+        ValueBag::from_sval1(self)
+    }
+}
+
+impl<'a, T, F> Value for Records<'a, T, F>
+where
+    F: Fn(&T, &mut dyn FnMut(&Record)),
+{
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        stream.seq_begin(Some(self.items.len()))?;
+
+        for item in self.items {
+            let mut result = Ok(());
+            (self.to_record)(item, &mut |record| {
+                result = stream.seq_elem(SubRecord(record));
+            });
+            result?;
+        }
+
+        stream.seq_end()
+    }
+}
+
+struct SubRecord<'a, 'b>(&'b Record<'a>);
+
+impl<'a, 'b> Value for SubRecord<'a, 'b> {
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        stream.map_begin(Some(self.0.kvs.sorted_key_values.len() + 1))?;
+
+        stream.map_key("msg")?;
+        stream.map_value_begin()?.display(self.0.render_msg())?;
+
+        for (k, v) in self.0.kvs.sorted_key_values {
+            stream.map_key(k)?;
+            stream.map_value(v)?;
+        }
+
+        stream.map_end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{capture::__PrivateCapture, level::Level, std::string::ToString, template::Part};
+
+    #[test]
+    fn stream_records() {
+        struct Item {
+            id: u64,
+        }
+
+        let items = [Item { id: 1 }, Item { id: 2 }];
+
+        let records = Records::new(&items, |item, f: &mut dyn FnMut(&Record)| {
+            let kvs = [("id", item.id.__private_capture_as_default())];
+
+            f(&crate::record::test_record(
+                Level::Info,
+                &kvs,
+                &[Part::Text("item "), Part::Hole("id")],
+            ))
+        });
+
+        assert_eq!(
+            "[{\"msg\": item 1, \"id\": 1}, {\"msg\": item 2, \"id\": 2}]",
+            records.capture().to_string()
+        );
+    }
+}
@@ -0,0 +1,352 @@
+/*!
+Fingerprinting values with SHA-256 instead of capturing their contents.
+*/
+
+use crate::std::fmt;
+
+#[cfg(feature = "std")]
+use crate::std::{lazy::SyncOnceCell, vec::Vec};
+
+/**
+The number of bytes kept from the hash of a redacted value.
+
+Fingerprints are written as hex, so they're twice this many characters long.
+*/
+pub const REDACTED_HASH_BYTES: usize = 16;
+
+#[cfg(feature = "std")]
+static REDACTION_KEY: SyncOnceCell<Vec<u8>> = SyncOnceCell::new();
+
+/**
+Set a secret key that fingerprints of redacted values are computed with.
+
+The key can only be set once, before any values are redacted. Any later calls are ignored.
+*/
+#[cfg(feature = "std")]
+pub fn set_redaction_key(key: &[u8]) {
+    let _ = REDACTION_KEY.set(key.to_vec());
+}
+
+#[cfg(feature = "std")]
+fn redaction_key() -> Option<&'static [u8]> {
+    REDACTION_KEY.get().map(|key| &**key)
+}
+
+#[cfg(not(feature = "std"))]
+fn redaction_key() -> Option<&'static [u8]> {
+    None
+}
+
+/**
+Compute a fingerprint of a value's `Debug` output instead of capturing its contents.
+
+The fingerprint is a truncated SHA-256 hash, so equal inputs always produce the same output,
+but the original value can't be recovered from it. If a key has been set with
+`set_redaction_key` then the fingerprint is an HMAC-SHA-256 using that key instead.
+
+Without a key, values from a small set of possible inputs, like phone numbers, can be found
+by hashing every input and comparing the results.
+*/
+pub fn redact(value: &(impl fmt::Debug + ?Sized)) -> Fingerprint {
+    redact_with_key(redaction_key(), value)
+}
+
+fn redact_with_key(key: Option<&[u8]>, value: &(impl fmt::Debug + ?Sized)) -> Fingerprint {
+    let hash = match key {
+        Some(key) => Hmac::new(key).digest(value),
+        None => {
+            let mut hash = Sha256::new();
+            let _ = fmt::write(&mut hash, format_args!("{:?}", value));
+
+            hash.finish()
+        }
+    };
+
+    let mut fingerprint = [0; REDACTED_HASH_BYTES];
+    fingerprint.copy_from_slice(&hash[..REDACTED_HASH_BYTES]);
+
+    Fingerprint(fingerprint)
+}
+
+/**
+The fingerprint of a redacted value.
+
+It's captured as a string of hex digits.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Fingerprint([u8; REDACTED_HASH_BYTES]);
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for b in &self.0 {
+            write!(f, "{:02x}", b)?;
+        }
+
+        Ok(())
+    }
+}
+
+const BLOCK_LEN: usize = 64;
+
+/**
+An HMAC using SHA-256, as described in RFC 2104.
+*/
+struct Hmac {
+    key: [u8; BLOCK_LEN],
+}
+
+impl Hmac {
+    fn new(key: &[u8]) -> Self {
+        let mut block = [0; BLOCK_LEN];
+
+        // Keys longer than a block are hashed first
+        if key.len() > BLOCK_LEN {
+            let mut hash = Sha256::new();
+            hash.update(key);
+
+            block[..32].copy_from_slice(&hash.finish());
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+
+        Hmac { key: block }
+    }
+
+    fn digest(&self, value: &(impl fmt::Debug + ?Sized)) -> [u8; 32] {
+        let mut inner = Sha256::new();
+        inner.update(&self.pad(0x36));
+        let _ = fmt::write(&mut inner, format_args!("{:?}", value));
+
+        let mut outer = Sha256::new();
+        outer.update(&self.pad(0x5c));
+        outer.update(&inner.finish());
+
+        outer.finish()
+    }
+
+    fn pad(&self, pad: u8) -> [u8; BLOCK_LEN] {
+        let mut block = self.key;
+        for b in &mut block {
+            *b ^= pad;
+        }
+
+        block
+    }
+}
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const H: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/**
+A SHA-256 hash, as described in FIPS 180-4.
+
+Input is written in a streaming fashion, so a value's `Debug` output never needs to be buffered.
+*/
+struct Sha256 {
+    state: [u32; 8],
+    block: [u8; BLOCK_LEN],
+    block_len: usize,
+    len: u64,
+}
+
+impl Sha256 {
+    fn new() -> Self {
+        Sha256 {
+            state: H,
+            block: [0; BLOCK_LEN],
+            block_len: 0,
+            len: 0,
+        }
+    }
+
+    fn update(&mut self, mut input: &[u8]) {
+        self.len += input.len() as u64;
+
+        while !input.is_empty() {
+            let take = (BLOCK_LEN - self.block_len).min(input.len());
+
+            self.block[self.block_len..self.block_len + take].copy_from_slice(&input[..take]);
+            self.block_len += take;
+            input = &input[take..];
+
+            if self.block_len == BLOCK_LEN {
+                self.compress();
+            }
+        }
+    }
+
+    fn finish(mut self) -> [u8; 32] {
+        let len = self.len * 8;
+
+        // The input is padded with a single set bit, then zeroes, then its length in bits
+        self.block[self.block_len] = 0x80;
+        self.block_len += 1;
+
+        if self.block_len > BLOCK_LEN - 8 {
+            self.block[self.block_len..].fill(0);
+            self.compress();
+        }
+
+        self.block[self.block_len..BLOCK_LEN - 8].fill(0);
+        self.block[BLOCK_LEN - 8..].copy_from_slice(&len.to_be_bytes());
+        self.compress();
+
+        let mut hash = [0; 32];
+        for (chunk, word) in hash.chunks_mut(4).zip(&self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+
+        hash
+    }
+
+    fn compress(&mut self) {
+        let mut w = [0u32; 64];
+        for (i, chunk) in self.block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+
+        for (k, w) in K.iter().zip(&w) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(*k)
+                .wrapping_add(*w);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (state, word) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *state = state.wrapping_add(word);
+        }
+
+        self.block_len = 0;
+    }
+}
+
+impl fmt::Write for Sha256 {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.update(s.as_bytes());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::std::{string::String, string::ToString, vec::Vec};
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn sha256(input: &[u8]) -> String {
+        let mut hash = Sha256::new();
+        hash.update(input);
+
+        hex(&hash.finish())
+    }
+
+    #[test]
+    fn sha256_vectors() {
+        assert_eq!(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            sha256(b"")
+        );
+        assert_eq!(
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            sha256(b"abc")
+        );
+        assert_eq!(
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")
+        );
+        assert_eq!(
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0",
+            sha256(&vec![b'a'; 1_000_000])
+        );
+    }
+
+    #[test]
+    fn sha256_streamed() {
+        let input = (0..200u8).collect::<Vec<_>>();
+
+        let mut hash = Sha256::new();
+        for chunk in input.chunks(7) {
+            hash.update(chunk);
+        }
+
+        assert_eq!(sha256(&input), hex(&hash.finish()));
+    }
+
+    #[test]
+    fn hmac_vectors() {
+        struct Raw<'a>(&'a str);
+
+        impl<'a> fmt::Debug for Raw<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(self.0)
+            }
+        }
+
+        // From RFC 4231
+        assert_eq!(
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            hex(&Hmac::new(b"Jefe").digest(&Raw("what do ya want for nothing?")))
+        );
+        assert_eq!(
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            hex(&Hmac::new(&[0xaa; 131]).digest(&Raw(
+                "Test Using Larger Than Block-Size Key - Hash Key First"
+            )))
+        );
+    }
+
+    #[test]
+    fn redact_keyed() {
+        let unkeyed = redact_with_key(None, "123-45-6789");
+        let keyed = redact_with_key(Some(b"secret"), "123-45-6789");
+
+        assert_eq!(REDACTED_HASH_BYTES * 2, unkeyed.to_string().len());
+        assert_ne!(unkeyed, keyed);
+        assert_eq!(keyed, redact_with_key(Some(b"secret"), "123-45-6789"));
+        assert_ne!(keyed, redact_with_key(Some(b"other"), "123-45-6789"));
+    }
+}
@@ -0,0 +1,85 @@
+/*!
+Capturing `serde` values with a custom serialization function.
+*/
+
+use serde_lib::ser::{Serialize, Serializer};
+
+use crate::value::ValueBag;
+
+/**
+The result of serializing a value into a type-erased serializer.
+*/
+pub type ErasedResult =
+    Result<<&'static mut dyn erased_serde::Serializer as Serializer>::Ok, erased_serde::Error>;
+
+/**
+A value that's serialized by a function instead of its own `Serialize` implementation.
+
+The function has the same signature as one for serde's `#[serde(serialize_with)]`,
+like `fn(&T, S) -> Result<S::Ok, S::Error>` for any `S: Serializer`. It's called with a
+type-erased serializer, so the value can be captured without knowing the format it'll be
+serialized into.
+*/
+pub struct SerializeWith<'a, T: ?Sized, F> {
+    value: &'a T,
+    serialize: F,
+}
+
+impl<'a, T: ?Sized, F> SerializeWith<'a, T, F>
+where
+    F: Fn(&T, &mut dyn erased_serde::Serializer) -> ErasedResult,
+{
+    pub fn new(value: &'a T, serialize: F) -> Self {
+        SerializeWith { value, serialize }
+    }
+
+    pub fn capture(&self) -> ValueBag {
+        ValueBag::from_serde1(self)
+    }
+}
+
+impl<'a, T: ?Sized, F> Serialize for SerializeWith<'a, T, F>
+where
+    F: Fn(&T, &mut dyn erased_serde::Serializer) -> ErasedResult,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        erased_serde::serialize(&Erased(self), serializer)
+    }
+}
+
+/**
+Calls the serialization function with the erased serializer it's given.
+
+This doesn't implement `Serialize` itself, so it can implement `erased_serde::Serialize` directly.
+*/
+struct Erased<'a, 'b, T: ?Sized, F>(&'b SerializeWith<'a, T, F>);
+
+impl<'a, 'b, T: ?Sized, F> erased_serde::Serialize for Erased<'a, 'b, T, F>
+where
+    F: Fn(&T, &mut dyn erased_serde::Serializer) -> ErasedResult,
+{
+    fn erased_serialize(&self, serializer: &mut dyn erased_serde::Serializer) -> ErasedResult {
+        (self.0.serialize)(self.0.value, serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::std::string::ToString;
+
+    fn serialize_doubled<S: Serializer>(value: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(*value * 2)
+    }
+
+    #[test]
+    fn serialize_with_fn() {
+        let value = 21u32;
+        let with = SerializeWith::new(&value, |value, serializer| {
+            serialize_doubled(value, serializer)
+        });
+
+        assert_eq!("42", with.capture().to_string());
+    }
+}
@@ -0,0 +1,81 @@
+/*!
+Capturing HTTP-style status codes.
+*/
+
+use crate::std::fmt;
+
+use sval::value::{self, Value};
+
+/**
+A status code, like `200` or `503`, along with its class, like `2xx` or `5xx`.
+
+When streamed as a structured value it's a map with the code in a `code` key and
+its class in a `class` key, so records can be grouped by class. When rendered in a
+message it's just the code. Codes outside of `100..=599` have an `unknown` class.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Status {
+    code: i64,
+}
+
+impl Status {
+    pub fn new(code: impl Into<i64>) -> Self {
+        Status { code: code.into() }
+    }
+
+    pub fn code(&self) -> i64 {
+        self.code
+    }
+
+    pub fn class(&self) -> &'static str {
+        match self.code {
+            100..=199 => "1xx",
+            200..=299 => "2xx",
+            300..=399 => "3xx",
+            400..=499 => "4xx",
+            500..=599 => "5xx",
+            _ => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.code, f)
+    }
+}
+
+impl Value for Status {
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        stream.map_begin(Some(2))?;
+
+        stream.map_key("code")?;
+        stream.map_value(self.code)?;
+
+        stream.map_key("class")?;
+        stream.map_value(self.class())?;
+
+        stream.map_end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn class() {
+        for (code, expected) in [
+            (100, "1xx"),
+            (200, "2xx"),
+            (302, "3xx"),
+            (404, "4xx"),
+            (503, "5xx"),
+            (99, "unknown"),
+            (600, "unknown"),
+            (-1, "unknown"),
+        ] {
+            assert_eq!(expected, Status::new(code).class(), "{}", code);
+        }
+    }
+}
@@ -1 +1,713 @@
-pub use fv_template::rt::{template, Part, Template};
+/*!
+Runtime string templates.
+
+These types match the shape of the code generated by `fv_template`.
+*/
+
+use crate::std::fmt::{self, Write};
+
+/**
+A runtime field-value template.
+*/
+#[derive(Clone, Copy)]
+pub struct Template<'a> {
+    parts: &'a [Part<'a>],
+    source: Option<&'a str>,
+}
+
+impl<'a> Template<'a> {
+    /**
+    The parts that make up this template.
+    */
+    pub fn parts(&self) -> &'a [Part<'a>] {
+        self.parts
+    }
+
+    /**
+    The keys of the holes in this template, in the order they appear in.
+
+    A key that appears in more than one hole is returned once for each of them.
+    */
+    pub fn hole_names(&self) -> impl Iterator<Item = &'a str> {
+        self.parts.iter().filter_map(|part| match *part {
+            Part::Hole(label) | Part::FormattedHole(label, _) | Part::UnrenderedHole(label) => {
+                Some(label)
+            }
+            Part::Text(_) | Part::Escape(_) => None,
+        })
+    }
+
+    /**
+    Check that the holes in this template match a set of keys.
+
+    Every hole needs a matching key, and every key needs a matching hole. If any don't match up
+    then the returned error can be used to find the holes and keys that are missing their pair.
+    */
+    pub fn validate_against<'b>(&self, keys: &'b [&'b str]) -> Result<(), ValidationError<'a, 'b>> {
+        let err = ValidationError {
+            template: *self,
+            keys,
+        };
+
+        if err.unmatched_holes().next().is_none() && err.unmatched_keys().next().is_none() {
+            Ok(())
+        } else {
+            Err(err)
+        }
+    }
+
+    /**
+    Set the literal this template was parsed from.
+
+    The literal is written back out as-is by `source`.
+    */
+    pub fn with_source(self, source: &'a str) -> Self {
+        Template {
+            parts: self.parts,
+            source: Some(source),
+        }
+    }
+
+    /**
+    Write the template back out in the same syntax it was parsed from.
+
+    If the template has the literal it was parsed from then that's written exactly as it was,
+    including any attributes and expressions in its holes. Otherwise, the template is rebuilt
+    from its parts, so escaped braces are written back out as `{{` and `}}`, and holes are
+    written as just their label, like `{a}`.
+    */
+    pub fn source(&self) -> impl fmt::Display + '_ {
+        struct ImplDisplay<'tpl, 'brw>(&'brw Template<'tpl>);
+
+        impl<'tpl, 'brw> fmt::Display for ImplDisplay<'tpl, 'brw> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                if let Some(source) = self.0.source {
+                    return f.write_str(source);
+                }
+
+                for part in self.0.parts {
+                    match part {
+                        Part::Text(text) => f.write_str(text)?,
+                        Part::Escape(c) => {
+                            f.write_char(*c)?;
+                            f.write_char(*c)?;
+                        }
+                        Part::Hole(label) | Part::UnrenderedHole(label) => {
+                            f.write_char('{')?;
+                            f.write_str(label)?;
+                            f.write_char('}')?;
+                        }
+                        Part::FormattedHole(label, spec) => {
+                            f.write_char('{')?;
+                            f.write_str(label)?;
+                            f.write_char(':')?;
+                            fmt::Display::fmt(spec, f)?;
+                            f.write_char('}')?;
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        ImplDisplay(self)
+    }
+
+    /**
+    Render the template using the given context.
+
+    The context helps the template find replacement values and determines how to render them if they're missing.
+    An empty context can be used to render out the template with just its holes.
+    */
+    pub fn render<'brw>(
+        &'brw self,
+        ctx: Context<
+            impl (Fn(&mut fmt::Formatter, &str) -> Option<fmt::Result>) + 'brw,
+            impl (Fn(&mut fmt::Formatter, &str) -> fmt::Result) + 'brw,
+        >,
+    ) -> impl fmt::Display + 'brw {
+        struct ImplDisplay<'tpl, 'brw, TFill, TMissing> {
+            template: &'brw Template<'tpl>,
+            ctx: Context<TFill, TMissing>,
+        }
+
+        impl<'tpl, 'brw, TFill, TMissing> fmt::Display for ImplDisplay<'tpl, 'brw, TFill, TMissing>
+        where
+            TFill: Fn(&mut fmt::Formatter, &str) -> Option<fmt::Result>,
+            TMissing: Fn(&mut fmt::Formatter, &str) -> fmt::Result,
+        {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                for part in self.template.parts {
+                    match part {
+                        Part::Text(text) => f.write_str(text)?,
+                        Part::Escape(c) => f.write_char(*c)?,
+                        Part::Hole(label) => self.hole(f, label)?,
+                        Part::FormattedHole(label, spec) => spec.pad(f, |f| self.hole(f, label))?,
+                        Part::UnrenderedHole(label) => (self.ctx.missing)(f, label)?,
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'tpl, 'brw, TFill, TMissing> ImplDisplay<'tpl, 'brw, TFill, TMissing>
+        where
+            TFill: Fn(&mut fmt::Formatter, &str) -> Option<fmt::Result>,
+            TMissing: Fn(&mut fmt::Formatter, &str) -> fmt::Result,
+        {
+            fn hole(&self, f: &mut fmt::Formatter, label: &str) -> fmt::Result {
+                if let Some(r) = (self.ctx.fill)(f, label) {
+                    r
+                } else {
+                    (self.ctx.missing)(f, label)
+                }
+            }
+        }
+
+        ImplDisplay {
+            template: self,
+            ctx,
+        }
+    }
+}
+
+/**
+The error returned when the holes in a template don't match a set of keys.
+*/
+#[derive(Clone, Copy)]
+pub struct ValidationError<'a, 'b> {
+    template: Template<'a>,
+    keys: &'b [&'b str],
+}
+
+impl<'a, 'b> ValidationError<'a, 'b> {
+    /**
+    The keys of holes in the template that don't have a matching key.
+
+    A key that appears in more than one hole is returned once for each of them.
+    */
+    pub fn unmatched_holes(&self) -> impl Iterator<Item = &'a str> + 'b
+    where
+        'a: 'b,
+    {
+        let keys = self.keys;
+
+        self.template
+            .hole_names()
+            .filter(move |hole| !keys.contains(hole))
+    }
+
+    /**
+    The keys that don't have a matching hole in the template.
+    */
+    pub fn unmatched_keys(&self) -> impl Iterator<Item = &'b str> + 'b
+    where
+        'a: 'b,
+    {
+        let template = self.template;
+
+        self.keys
+            .iter()
+            .copied()
+            .filter(move |key| !template.hole_names().any(|hole| hole == *key))
+    }
+}
+
+impl<'a, 'b> fmt::Debug for ValidationError<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        struct List<F>(F);
+
+        impl<F: Fn() -> I, I: Iterator<Item = T>, T: fmt::Debug> fmt::Debug for List<F> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.debug_list().entries((self.0)()).finish()
+            }
+        }
+
+        f.debug_struct("ValidationError")
+            .field("unmatched_holes", &List(|| self.unmatched_holes()))
+            .field("unmatched_keys", &List(|| self.unmatched_keys()))
+            .finish()
+    }
+}
+
+impl<'a, 'b> fmt::Display for ValidationError<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("the template doesn't match its keys")?;
+
+        let mut first = true;
+        for hole in self.unmatched_holes() {
+            f.write_str(if first {
+                "; holes without a key: "
+            } else {
+                ", "
+            })?;
+            write!(f, "`{}`", hole)?;
+            first = false;
+        }
+
+        let mut first = true;
+        for key in self.unmatched_keys() {
+            f.write_str(if first {
+                "; keys without a hole: "
+            } else {
+                ", "
+            })?;
+            write!(f, "`{}`", key)?;
+            first = false;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, 'b> crate::std::error::Error for ValidationError<'a, 'b> {}
+
+/**
+A context used to render a template.
+*/
+pub struct Context<TFill, TMissing> {
+    fill: TFill,
+    missing: TMissing,
+}
+
+impl
+    Context<
+        fn(&mut fmt::Formatter, &str) -> Option<fmt::Result>,
+        fn(&mut fmt::Formatter, &str) -> fmt::Result,
+    >
+{
+    /**
+    Create a new rendering context with default handling for holes.
+    */
+    pub fn new() -> Self {
+        Context {
+            fill: |_, _| None,
+            missing: |f, label| f.write_fmt(format_args!("`{}`", label)),
+        }
+    }
+}
+
+impl<TFill, TMissing> Context<TFill, TMissing>
+where
+    TFill: Fn(&mut fmt::Formatter, &str) -> Option<fmt::Result>,
+    TMissing: Fn(&mut fmt::Formatter, &str) -> fmt::Result,
+{
+    /**
+    Provide a function to fill the holes in the template with.
+    */
+    pub fn fill<T>(self, fill: T) -> Context<T, TMissing>
+    where
+        T: Fn(&mut fmt::Formatter, &str) -> Option<fmt::Result>,
+    {
+        Context {
+            fill,
+            missing: self.missing,
+        }
+    }
+
+    /**
+    Provide a function to handle unfilled holes.
+    */
+    pub fn missing<T>(self, missing: T) -> Context<TFill, T>
+    where
+        T: Fn(&mut fmt::Formatter, &str) -> fmt::Result,
+    {
+        Context {
+            fill: self.fill,
+            missing,
+        }
+    }
+}
+
+impl Default
+    for Context<
+        fn(&mut fmt::Formatter, &str) -> Option<fmt::Result>,
+        fn(&mut fmt::Formatter, &str) -> fmt::Result,
+    >
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+A fragment of a template.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Part<'a> {
+    /**
+    A plain text fragment.
+    */
+    Text(&'a str),
+    /**
+    An escaped brace, which is written as `{{` or `}}` in the template.
+    */
+    Escape(char),
+    /**
+    A hole in the template with a corresponding label to fill.
+    */
+    Hole(&'a str),
+    /**
+    A hole in the template that pads its value, like `{a:*^8}`.
+    */
+    FormattedHole(&'a str, HoleSpec),
+    /**
+    A hole in the template that's always rendered as missing, even if its label has a value.
+
+    These are the holes of fields captured with `#[no_render]`.
+    */
+    UnrenderedHole(&'a str),
+}
+
+/**
+How a value is aligned within the width of a formatted hole.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    /**
+    Pad after the value, written as `<`.
+    */
+    Left,
+    /**
+    Pad evenly on either side of the value, written as `^`.
+
+    If the padding can't be split evenly then the extra fill character goes after the value.
+    */
+    Center,
+    /**
+    Pad before the value, written as `>`.
+    */
+    Right,
+}
+
+/**
+The fill, alignment, and width of a formatted hole.
+
+These follow the same rules as `format!`, so `{a:*^8}` centers the value
+in 8 characters, padding it with `*`. Values that are already wider than
+the width are written as-is.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HoleSpec {
+    pub fill: char,
+    pub align: Align,
+    pub width: usize,
+}
+
+impl HoleSpec {
+    /**
+    Write a value with padding.
+
+    The value is written twice: once to count its characters and once to the formatter.
+    */
+    fn pad(
+        &self,
+        f: &mut fmt::Formatter,
+        write: impl Fn(&mut fmt::Formatter) -> fmt::Result,
+    ) -> fmt::Result {
+        struct Count(usize);
+
+        impl Write for Count {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0 += s.chars().count();
+                Ok(())
+            }
+        }
+
+        struct ImplDisplay<F>(F);
+
+        impl<F> fmt::Display for ImplDisplay<F>
+        where
+            F: Fn(&mut fmt::Formatter) -> fmt::Result,
+        {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                (self.0)(f)
+            }
+        }
+
+        let mut count = Count(0);
+        write!(count, "{}", ImplDisplay(&write))?;
+
+        let padding = self.width.saturating_sub(count.0);
+        let (before, after) = match self.align {
+            Align::Left => (0, padding),
+            Align::Center => (padding / 2, padding - padding / 2),
+            Align::Right => (padding, 0),
+        };
+
+        for _ in 0..before {
+            f.write_char(self.fill)?;
+        }
+
+        write(f)?;
+
+        for _ in 0..after {
+            f.write_char(self.fill)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for HoleSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_char(self.fill)?;
+        f.write_char(match self.align {
+            Align::Left => '<',
+            Align::Center => '^',
+            Align::Right => '>',
+        })?;
+
+        if self.width > 0 {
+            write!(f, "{}", self.width)?;
+        }
+
+        Ok(())
+    }
+}
+
+/**
+Construct a `Template` from a set of `Part`s.
+*/
+pub fn template<'a>(parts: &'a [Part<'a>]) -> Template<'a> {
+    Template {
+        parts,
+        source: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::std::{string::ToString, vec};
+
+    #[test]
+    fn render() {
+        let cases = vec![
+            (
+                &[Part::Text("Hello "), Part::Hole("world"), Part::Text("!")],
+                Context::new().fill(
+                    (|write, label| Some(write.write_str(label)))
+                        as fn(&mut fmt::Formatter, &str) -> Option<fmt::Result>,
+                ),
+                "Hello world!",
+            ),
+            (
+                &[Part::Text("Hello "), Part::Hole("world"), Part::Text("!")],
+                Context::new(),
+                "Hello `world`!",
+            ),
+            (
+                &[Part::Text("Hello "), Part::Hole("world"), Part::Text("!")],
+                Context::new().missing(
+                    (|write, label| write.write_fmt(format_args!("{{{}}}", label)))
+                        as fn(&mut fmt::Formatter, &str) -> fmt::Result,
+                ),
+                "Hello {world}!",
+            ),
+        ];
+
+        for (parts, ctx, expected) in cases {
+            let template = template(parts);
+
+            let actual = template.render(ctx).to_string();
+
+            assert_eq!(expected, actual);
+        }
+
+        let template = template(&[
+            Part::Text("Hello "),
+            Part::Escape('{'),
+            Part::Text("world"),
+            Part::Escape('}'),
+            Part::Text(" and "),
+            Part::Hole("a"),
+        ]);
+
+        assert_eq!("Hello {world} and `a`", template.render(Context::new()).to_string());
+    }
+
+    #[test]
+    fn render_unrendered_hole() {
+        let template = template(&[
+            Part::Text("Hello "),
+            Part::UnrenderedHole("world"),
+            Part::Text(" and "),
+            Part::Hole("world"),
+        ]);
+
+        // Unrendered holes are missing even if their label has a value
+        assert_eq!(
+            "Hello `world` and world",
+            template
+                .render(Context::new().fill(|write, label| Some(write.write_str(label))))
+                .to_string()
+        );
+
+        assert_eq!("Hello {world} and {world}", template.source().to_string());
+        assert_eq!(vec!["world", "world"], template.hole_names().collect::<vec::Vec<_>>());
+    }
+
+    #[test]
+    fn hole_names() {
+        let template = template(&[
+            Part::Text("Hello "),
+            Part::Hole("user"),
+            Part::Escape('{'),
+            Part::Text(" from "),
+            Part::FormattedHole(
+                "host",
+                HoleSpec {
+                    fill: ' ',
+                    align: Align::Left,
+                    width: 8,
+                },
+            ),
+            Part::Text(" as "),
+            Part::Hole("user"),
+        ]);
+
+        assert_eq!(
+            vec!["user", "host", "user"],
+            template.hole_names().collect::<vec::Vec<_>>()
+        );
+        assert_eq!(0, super::template(&[Part::Text("Hello")]).hole_names().count());
+    }
+
+    #[test]
+    fn validate_against() {
+        let template = template(&[
+            Part::Text("Hello "),
+            Part::Hole("user"),
+            Part::Text(" from "),
+            Part::Hole("host"),
+            Part::Text(" as "),
+            Part::UnrenderedHole("role"),
+        ]);
+
+        assert!(template.validate_against(&["host", "role", "user"]).is_ok());
+
+        let err = template
+            .validate_against(&["user", "role", "port", "addr"])
+            .unwrap_err();
+
+        assert_eq!(vec!["host"], err.unmatched_holes().collect::<vec::Vec<_>>());
+        assert_eq!(
+            vec!["port", "addr"],
+            err.unmatched_keys().collect::<vec::Vec<_>>()
+        );
+
+        assert_eq!(
+            "the template doesn't match its keys; holes without a key: `host`; keys without a hole: `port`, `addr`",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn render_formatted_hole() {
+        let fill = (|write: &mut fmt::Formatter, label: &str| match label {
+            "a" => Some(write.write_str("abc")),
+            _ => None,
+        }) as fn(&mut fmt::Formatter, &str) -> Option<fmt::Result>;
+
+        let cases = vec![
+            (
+                HoleSpec {
+                    fill: ' ',
+                    align: Align::Left,
+                    width: 6,
+                },
+                "[abc   ]",
+            ),
+            (
+                HoleSpec {
+                    fill: '*',
+                    align: Align::Right,
+                    width: 6,
+                },
+                "[***abc]",
+            ),
+            (
+                HoleSpec {
+                    fill: '*',
+                    align: Align::Center,
+                    width: 8,
+                },
+                "[**abc***]",
+            ),
+            (
+                HoleSpec {
+                    fill: '-',
+                    align: Align::Center,
+                    width: 2,
+                },
+                "[abc]",
+            ),
+        ];
+
+        for (spec, expected) in cases {
+            let parts = [
+                Part::Text("["),
+                Part::FormattedHole("a", spec),
+                Part::Text("]"),
+            ];
+
+            let actual = template(&parts)
+                .render(Context::new().fill(fill))
+                .to_string();
+
+            assert_eq!(expected, actual);
+        }
+
+        // Missing values are padded too
+        let parts = [Part::FormattedHole(
+            "b",
+            HoleSpec {
+                fill: '.',
+                align: Align::Right,
+                width: 5,
+            },
+        )];
+        assert_eq!("..`b`", template(&parts).render(Context::new()).to_string());
+    }
+
+    #[test]
+    fn source_round_trip() {
+        let template = template(&[
+            Part::Text("Text "),
+            Part::Escape('{'),
+            Part::Text("a"),
+            Part::Escape('}'),
+            Part::Text(" and "),
+            Part::Hole("b"),
+            Part::Text(" and "),
+            Part::FormattedHole(
+                "c",
+                HoleSpec {
+                    fill: '*',
+                    align: Align::Center,
+                    width: 8,
+                },
+            ),
+        ]);
+
+        assert_eq!(
+            "Text {{a}} and {b} and {c:*^8}",
+            template.source().to_string()
+        );
+    }
+
+    #[test]
+    fn source_literal() {
+        let template = template(&[Part::Text("Text "), Part::Hole("a")])
+            .with_source("Text {#[emit::as_debug] a: 1 + 1}");
+
+        assert_eq!(
+            "Text {#[emit::as_debug] a: 1 + 1}",
+            template.source().to_string()
+        );
+    }
+}
@@ -0,0 +1,117 @@
+/*!
+Formatting timestamps as RFC 3339 date-times.
+*/
+
+use crate::std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/**
+The key that a timestamp supplied with `#[ts]` is captured with.
+*/
+pub const TIMESTAMP_KEY: &str = "ts";
+
+/**
+A point in time that's formatted as an RFC 3339 date-time in UTC, like `2021-10-01T08:30:00Z`.
+
+Any sub-second precision is written as a fraction of seconds, like `2021-10-01T08:30:00.5Z`.
+Times before the Unix epoch are formatted as the epoch.
+*/
+#[repr(transparent)]
+pub struct Timestamp(SystemTime);
+
+impl Timestamp {
+    pub fn new(value: &SystemTime) -> &Self {
+        // SAFETY: `Timestamp` is `repr(transparent)` over `SystemTime`
+        unsafe { &*(value as *const SystemTime as *const Self) }
+    }
+
+    pub fn to_system_time(&self) -> SystemTime {
+        self.0
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let since_epoch = self.0.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+        let secs = since_epoch.as_secs();
+        let nanos = since_epoch.subsec_nanos();
+
+        let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+        let secs_of_day = secs % 86_400;
+
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            secs_of_day / 3600,
+            (secs_of_day % 3600) / 60,
+            secs_of_day % 60
+        )?;
+
+        if nanos > 0 {
+            // Write the fraction without any trailing zeroes
+            let mut fraction = nanos;
+            let mut width = 9;
+            while fraction % 10 == 0 {
+                fraction /= 10;
+                width -= 1;
+            }
+
+            write!(f, ".{:0width$}", fraction, width = width)?;
+        }
+
+        f.write_str("Z")
+    }
+}
+
+/**
+Convert a number of days since the Unix epoch into a year, month, and day.
+
+This is Howard Hinnant's `civil_from_days` algorithm.
+*/
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::std::{string::ToString, time::Duration};
+
+    #[test]
+    fn format() {
+        for (secs, nanos, expected) in [
+            (0, 0, "1970-01-01T00:00:00Z"),
+            (951_782_400, 0, "2000-02-29T00:00:00Z"),
+            (1_633_077_000, 0, "2021-10-01T08:30:00Z"),
+            (1_633_077_000, 500_000_000, "2021-10-01T08:30:00.5Z"),
+            (1_633_077_000, 1_000, "2021-10-01T08:30:00.000001Z"),
+        ] {
+            let ts = UNIX_EPOCH + Duration::new(secs, nanos);
+
+            assert_eq!(expected, Timestamp::new(&ts).to_string());
+        }
+
+        let before_epoch = UNIX_EPOCH - Duration::from_secs(1);
+        assert_eq!(
+            "1970-01-01T00:00:00Z",
+            Timestamp::new(&before_epoch).to_string()
+        );
+    }
+}
@@ -0,0 +1,55 @@
+/*!
+Capturing instants as the time elapsed since the process started.
+*/
+
+use crate::{
+    duration::IsoDuration,
+    std::{fmt, lazy::SyncOnceCell, time::Duration, time::Instant},
+};
+
+use sval::value::{self, Value};
+
+static PROCESS_START: SyncOnceCell<Instant> = SyncOnceCell::new();
+
+/**
+The instant that uptimes are measured from.
+
+There's no portable way to find when the process actually started, so this is the first time
+it's called. Setting an emitter calls it, so for most programs it's close to the start of `main`.
+*/
+pub fn process_start() -> Instant {
+    *PROCESS_START.get_or_init(Instant::now)
+}
+
+/**
+An instant that's captured as the time elapsed between the start of the process and it.
+
+When streamed as a structured value it's a number of seconds, like `1.5`.
+When rendered in a message it's an ISO 8601 period, like `PT1.5S`.
+Instants before the start of the process have an uptime of zero.
+*/
+#[repr(transparent)]
+pub struct Uptime(Instant);
+
+impl Uptime {
+    pub fn new(value: &Instant) -> &Self {
+        // SAFETY: `Uptime` is `repr(transparent)` over `Instant`
+        unsafe { &*(value as *const Instant as *const Self) }
+    }
+
+    pub fn to_duration(&self) -> Duration {
+        self.0.saturating_duration_since(process_start())
+    }
+}
+
+impl fmt::Display for Uptime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(IsoDuration::new(&self.to_duration()), f)
+    }
+}
+
+impl Value for Uptime {
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        stream.f64(self.to_duration().as_secs_f64())
+    }
+}
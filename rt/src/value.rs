@@ -1 +1,238 @@
-pub use value_bag::ValueBag;
+use crate::std::fmt;
+
+use sval::{
+    stream::{self, Stream},
+    value::{self, Value},
+};
+
+pub use value_bag::{visit::Visit, Error as VisitError, ValueBag};
+
+/**
+The key used when streaming a `CaptureError` as a structured value.
+*/
+pub const CAPTURE_ERROR_KEY: &str = "_capture_error";
+
+/**
+A placeholder for a value that failed to be captured.
+
+When captured, holes in a template backed by this value will render as
+`<error: {msg}>` instead of failing the whole message. When streamed as a
+structured value it's a map with the message in a `_capture_error` key.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CaptureError {
+    msg: &'static str,
+}
+
+impl CaptureError {
+    pub const fn new(msg: &'static str) -> Self {
+        CaptureError { msg }
+    }
+
+    pub fn msg(&self) -> &'static str {
+        self.msg
+    }
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.msg)
+    }
+}
+
+impl Value for CaptureError {
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        stream.map_begin(Some(1))?;
+
+        stream.map_key(CAPTURE_ERROR_KEY)?;
+        stream.map_value(self.msg)?;
+
+        stream.map_end()
+    }
+}
+
+/**
+Get the capture error from a value, if it failed to be captured.
+*/
+pub fn capture_error<'a>(value: &'a ValueBag) -> Option<&'a CaptureError> {
+    value.downcast_ref::<CaptureError>()
+}
+
+/**
+Diagnostics for captured values.
+*/
+pub trait ValueExt {
+    /**
+    Get a coarse tag for the kind of value that was captured.
+
+    Primitives are tagged by their type, like `"str"`, `"u64"`, or `"bool"`. Other values are
+    tagged by how they were captured:
+
+    - `"error"` for values captured as an error.
+    - `"debug"` for values captured using their `Debug` or `Display` implementations.
+    Once captured these can't be told apart.
+    - `"structured"` for values captured using `sval` or `serde` that stream as a map or sequence.
+    Structured values that stream as a single primitive are tagged by that primitive.
+    - `"capture_error"` for values that failed to be captured.
+    - `"none"` for empty values.
+    */
+    fn type_name(&self) -> &'static str;
+}
+
+impl<'v> ValueExt for ValueBag<'v> {
+    fn type_name(&self) -> &'static str {
+        if capture_error(self).is_some() {
+            return "capture_error";
+        }
+
+        struct TypeName(Option<&'static str>);
+
+        impl TypeName {
+            fn set(&mut self, type_name: &'static str) -> stream::Result {
+                // Only the first value streamed determines the type name
+                self.0.get_or_insert(type_name);
+                Ok(())
+            }
+        }
+
+        impl<'v> value_bag::visit::Visit<'v> for TypeName {
+            fn visit_any(&mut self, _: ValueBag) -> Result<(), value_bag::Error> {
+                Ok(())
+            }
+
+            fn visit_u64(&mut self, _: u64) -> Result<(), value_bag::Error> {
+                self.0 = Some("u64");
+                Ok(())
+            }
+
+            fn visit_i64(&mut self, _: i64) -> Result<(), value_bag::Error> {
+                self.0 = Some("i64");
+                Ok(())
+            }
+
+            fn visit_u128(&mut self, _: u128) -> Result<(), value_bag::Error> {
+                self.0 = Some("u128");
+                Ok(())
+            }
+
+            fn visit_i128(&mut self, _: i128) -> Result<(), value_bag::Error> {
+                self.0 = Some("i128");
+                Ok(())
+            }
+
+            fn visit_f64(&mut self, _: f64) -> Result<(), value_bag::Error> {
+                self.0 = Some("f64");
+                Ok(())
+            }
+
+            fn visit_bool(&mut self, _: bool) -> Result<(), value_bag::Error> {
+                self.0 = Some("bool");
+                Ok(())
+            }
+
+            fn visit_char(&mut self, _: char) -> Result<(), value_bag::Error> {
+                self.0 = Some("char");
+                Ok(())
+            }
+
+            fn visit_str(&mut self, _: &str) -> Result<(), value_bag::Error> {
+                self.0 = Some("str");
+                Ok(())
+            }
+
+            #[cfg(feature = "std")]
+            fn visit_error(
+                &mut self,
+                _: &(dyn crate::std::error::Error + 'static),
+            ) -> Result<(), value_bag::Error> {
+                self.0 = Some("error");
+                Ok(())
+            }
+        }
+
+        impl Stream for TypeName {
+            fn fmt(&mut self, _: stream::Arguments) -> stream::Result {
+                self.set("debug")
+            }
+
+            fn error(&mut self, _: stream::Source) -> stream::Result {
+                self.set("error")
+            }
+
+            fn i64(&mut self, _: i64) -> stream::Result {
+                self.set("i64")
+            }
+
+            fn u64(&mut self, _: u64) -> stream::Result {
+                self.set("u64")
+            }
+
+            fn i128(&mut self, _: i128) -> stream::Result {
+                self.set("i128")
+            }
+
+            fn u128(&mut self, _: u128) -> stream::Result {
+                self.set("u128")
+            }
+
+            fn f64(&mut self, _: f64) -> stream::Result {
+                self.set("f64")
+            }
+
+            fn bool(&mut self, _: bool) -> stream::Result {
+                self.set("bool")
+            }
+
+            fn char(&mut self, _: char) -> stream::Result {
+                self.set("char")
+            }
+
+            fn str(&mut self, _: &str) -> stream::Result {
+                self.set("str")
+            }
+
+            fn none(&mut self) -> stream::Result {
+                self.set("none")
+            }
+
+            fn map_begin(&mut self, _: Option<usize>) -> stream::Result {
+                self.set("structured")
+            }
+
+            fn map_key(&mut self) -> stream::Result {
+                Ok(())
+            }
+
+            fn map_value(&mut self) -> stream::Result {
+                Ok(())
+            }
+
+            fn map_end(&mut self) -> stream::Result {
+                Ok(())
+            }
+
+            fn seq_begin(&mut self, _: Option<usize>) -> stream::Result {
+                self.set("structured")
+            }
+
+            fn seq_elem(&mut self) -> stream::Result {
+                Ok(())
+            }
+
+            fn seq_end(&mut self) -> stream::Result {
+                Ok(())
+            }
+        }
+
+        // Primitives are visited directly, including primitives captured using `serde` or `sval`
+        let mut type_name = TypeName(None);
+        let _ = self.visit(&mut type_name);
+
+        // Anything else is streamed to see what shape it has
+        if type_name.0.is_none() {
+            let _ = sval::stream(&mut type_name, self);
+        }
+
+        type_name.0.unwrap_or("none")
+    }
+}
@@ -0,0 +1,73 @@
+/*!
+Capturing values behind weak references.
+*/
+
+use crate::std::{
+    fmt,
+    rc::{self, Rc},
+    sync::{self, Arc},
+};
+
+/**
+The marker formatted in place of a value behind a weak reference that's been dropped.
+*/
+pub const DROPPED: &str = "<dropped>";
+
+/**
+A shared reference that can format the value it points to.
+*/
+pub trait WeakDebug {
+    fn fmt_upgraded(&self, f: &mut fmt::Formatter) -> fmt::Result;
+}
+
+impl<T: fmt::Debug + ?Sized> WeakDebug for sync::Weak<T> {
+    fn fmt_upgraded(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.upgrade() {
+            Some(value) => fmt::Debug::fmt(&*value, f),
+            None => f.write_str(DROPPED),
+        }
+    }
+}
+
+impl<T: fmt::Debug + ?Sized> WeakDebug for rc::Weak<T> {
+    fn fmt_upgraded(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.upgrade() {
+            Some(value) => fmt::Debug::fmt(&*value, f),
+            None => f.write_str(DROPPED),
+        }
+    }
+}
+
+impl<T: fmt::Debug + ?Sized> WeakDebug for Arc<T> {
+    fn fmt_upgraded(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: fmt::Debug + ?Sized> WeakDebug for Rc<T> {
+    fn fmt_upgraded(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/**
+A shared reference that formats the value it points to using its `Debug` implementation.
+
+A weak reference is only upgraded while the value is being formatted. If the value
+has been dropped then `"<dropped>"` is formatted instead.
+*/
+#[repr(transparent)]
+pub struct Upgraded<T: ?Sized>(T);
+
+impl<T: ?Sized> Upgraded<T> {
+    pub fn new(value: &T) -> &Self {
+        // SAFETY: `Upgraded<T>` is `repr(transparent)` over `T`
+        unsafe { &*(value as *const T as *const Self) }
+    }
+}
+
+impl<T: WeakDebug + ?Sized> fmt::Debug for Upgraded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt_upgraded(f)
+    }
+}
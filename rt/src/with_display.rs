@@ -0,0 +1,59 @@
+/*!
+Capturing `serde` values that are rendered with their `Display` implementation.
+*/
+
+use crate::std::fmt;
+
+use serde_lib::ser::{Serialize, Serializer};
+
+use crate::{newtype::Unwrapped, value::ValueBag};
+
+/**
+A `serde` value that fills holes in a template with its `Display` string.
+
+It's captured as its `serde` structure, the same as a value captured with `#[as_serde]`, so
+backends that index structured values see the value itself. Only the rendered message uses
+its `Display` implementation, which `record::render_value` finds by downcasting.
+*/
+pub struct WithDisplay<'a> {
+    value: &'a (dyn SerializeDisplay + 'a),
+}
+
+trait SerializeDisplay: erased_serde::Serialize + fmt::Display {}
+
+impl<T: Serialize + fmt::Display + ?Sized> SerializeDisplay for T {}
+
+erased_serde::serialize_trait_object!(SerializeDisplay);
+
+impl<'a> WithDisplay<'a> {
+    pub fn new<T: Serialize + fmt::Display>(value: &'a T) -> Self {
+        WithDisplay { value }
+    }
+
+    pub fn capture(&self) -> ValueBag {
+        ValueBag::capture_serde1(self.erase_lifetime())
+    }
+
+    /**
+    Erase the lifetime of the value so it can be captured with a type id and downcast.
+
+    The returned reference can't outlive `self`, and the value it points to is only ever
+    borrowed through methods on it, so the `'static` lifetime is never observable.
+    */
+    fn erase_lifetime(&self) -> &WithDisplay<'static> {
+        // SAFETY: `WithDisplay<'a>` and `WithDisplay<'static>` only differ in their lifetime
+        unsafe { &*(self as *const Self as *const () as *const WithDisplay<'static>) }
+    }
+}
+
+impl<'a> fmt::Display for WithDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.value, f)
+    }
+}
+
+impl<'a> Serialize for WithDisplay<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Unwrapped::new(self.value).serialize(serializer)
+    }
+}
@@ -0,0 +1,460 @@
+/*!
+An emitter that delivers records on a background thread.
+*/
+
+use std::{
+    collections::VecDeque,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    thread,
+};
+
+use crate::{Emitter, OwnedRecord, Record};
+
+/**
+What to do with a record when the queue of an `AsyncEmitter` is full.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /**
+    Block the caller until there's room in the queue.
+    */
+    Block,
+    /**
+    Drop the record being emitted.
+    */
+    DropNew,
+    /**
+    Drop the oldest record in the queue to make room for the one being emitted.
+    */
+    DropOldest,
+}
+
+/**
+Options for an `AsyncEmitter`.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsyncEmitterOptions {
+    /**
+    The number of records that can be queued before the `overflow` policy applies.
+    */
+    pub capacity: usize,
+    /**
+    What to do with records emitted while the queue is full.
+    */
+    pub overflow: Overflow,
+}
+
+impl Default for AsyncEmitterOptions {
+    fn default() -> Self {
+        AsyncEmitterOptions {
+            capacity: 1024,
+            overflow: Overflow::Block,
+        }
+    }
+}
+
+/**
+An emitter that queues records and delivers them on a background thread.
+
+Records are converted into `OwnedRecord`s when they're emitted and passed to a function
+on the background thread, so slow destinations like files or network connections don't
+block the caller. When the queue is full, the `Overflow` policy decides whether to block
+the caller or drop a record. The number of records dropped is counted by `dropped`.
+
+If the delivery function panics, the record it was delivering is counted as dropped and
+the background thread carries on with the next one. If the background thread stops for
+any other reason then records emitted afterwards are dropped instead of being queued,
+and flushing returns straight away.
+
+Flushing blocks until all records queued so far have been delivered. When the emitter
+is dropped, any queued records are delivered before the background thread exits.
+*/
+pub struct AsyncEmitter {
+    shared: Arc<Shared>,
+    worker: Option<thread::JoinHandle<()>>,
+}
+
+struct Shared {
+    options: AsyncEmitterOptions,
+    state: Mutex<State>,
+    // Notified when records are queued or the emitter is closed
+    not_empty: Condvar,
+    // Notified when records are taken off the queue or delivered
+    not_full: Condvar,
+    dropped: AtomicU64,
+}
+
+struct State {
+    records: VecDeque<OwnedRecord>,
+    // Whether the background thread is currently delivering a record
+    delivering: bool,
+    closed: bool,
+    // Whether the background thread has stopped, so nothing will take records off the queue
+    exited: bool,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl AsyncEmitter {
+    /**
+    Deliver records to `deliver` on a background thread.
+    */
+    pub fn new(
+        options: AsyncEmitterOptions,
+        deliver: impl Fn(&OwnedRecord) + Send + 'static,
+    ) -> Self {
+        assert!(
+            options.capacity > 0,
+            "the capacity must be greater than zero"
+        );
+
+        let shared = Arc::new(Shared {
+            options,
+            state: Mutex::new(State {
+                records: VecDeque::with_capacity(options.capacity),
+                delivering: false,
+                closed: false,
+                exited: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            dropped: AtomicU64::new(0),
+        });
+
+        let worker = thread::spawn({
+            let shared = shared.clone();
+
+            move || {
+                // Wake anything waiting on the background thread if it stops, even by panicking
+                let _exit = Exit(&shared);
+
+                loop {
+                    let record = {
+                        let mut state = shared.lock();
+
+                        state.delivering = false;
+                        shared.not_full.notify_all();
+
+                        loop {
+                            if let Some(record) = state.records.pop_front() {
+                                state.delivering = true;
+                                break record;
+                            }
+
+                            if state.closed {
+                                return;
+                            }
+
+                            state = shared
+                                .not_empty
+                                .wait(state)
+                                .unwrap_or_else(|err| err.into_inner());
+                        }
+                    };
+
+                    if panic::catch_unwind(AssertUnwindSafe(|| deliver(&record))).is_err() {
+                        shared.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+        });
+
+        AsyncEmitter {
+            shared,
+            worker: Some(worker),
+        }
+    }
+
+    /**
+    The number of records that have been dropped instead of delivered.
+
+    This counts records dropped because the queue was full, records whose delivery panicked,
+    and records emitted after the background thread exited.
+    */
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Emitter for AsyncEmitter {
+    fn emit(&self, record: &Record) {
+        // Copy the record before taking the lock
+        let record = record.to_owned();
+
+        let mut state = self.shared.lock();
+
+        if state.exited {
+            self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        if state.records.len() >= self.shared.options.capacity {
+            match self.shared.options.overflow {
+                Overflow::Block => {
+                    while state.records.len() >= self.shared.options.capacity {
+                        if state.exited {
+                            self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                            return;
+                        }
+
+                        state = self
+                            .shared
+                            .not_full
+                            .wait(state)
+                            .unwrap_or_else(|err| err.into_inner());
+                    }
+                }
+                Overflow::DropNew => {
+                    self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                Overflow::DropOldest => {
+                    state.records.pop_front();
+                    self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        state.records.push_back(record);
+        self.shared.not_empty.notify_one();
+    }
+
+    fn flush(&self) {
+        let mut state = self.shared.lock();
+
+        while (!state.records.is_empty() || state.delivering) && !state.exited {
+            state = self
+                .shared
+                .not_full
+                .wait(state)
+                .unwrap_or_else(|err| err.into_inner());
+        }
+    }
+}
+
+/**
+A guard on the background thread that marks it as stopped when it's dropped.
+*/
+struct Exit<'a>(&'a Shared);
+
+impl<'a> Drop for Exit<'a> {
+    fn drop(&mut self) {
+        let mut state = self.0.lock();
+
+        state.delivering = false;
+        state.exited = true;
+
+        self.0.not_full.notify_all();
+        self.0.not_empty.notify_all();
+    }
+}
+
+impl Drop for AsyncEmitter {
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.not_empty.notify_one();
+
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc;
+
+    use crate::rt::__private::{Level, Part, ValueBag};
+
+    fn emit(emitter: &impl Emitter, id: u64) {
+        let kvs = [("id", ValueBag::from(id))];
+        let record = crate::test_record(
+            Level::Info,
+            &kvs,
+            &[Part::Text("request "), Part::Hole("id")],
+        );
+
+        emitter.emit(&Record(&record));
+    }
+
+    /**
+    Create an emitter whose background thread is stuck delivering the first record until it's released.
+    */
+    fn saturated(overflow: Overflow) -> (AsyncEmitter, mpsc::Sender<()>, Arc<Mutex<Vec<String>>>) {
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let release_rx = Mutex::new(release_rx);
+
+        let emitter = AsyncEmitter::new(
+            AsyncEmitterOptions {
+                capacity: 2,
+                overflow,
+            },
+            {
+                let delivered = delivered.clone();
+
+                move |record: &OwnedRecord| {
+                    let _ = started_tx.send(());
+                    let _ = release_rx.lock().unwrap().recv();
+
+                    delivered.lock().unwrap().push(record.msg().to_owned());
+                }
+            },
+        );
+
+        emit(&emitter, 0);
+        started_rx.recv().unwrap();
+
+        (emitter, release_tx, delivered)
+    }
+
+    fn release(release: mpsc::Sender<()>) {
+        // Dropping the sender lets every delivery through
+        drop(release);
+    }
+
+    #[test]
+    fn overflow_drop_new() {
+        let (emitter, release_tx, delivered) = saturated(Overflow::DropNew);
+
+        for id in 1..5 {
+            emit(&emitter, id);
+        }
+
+        assert_eq!(2, emitter.dropped());
+
+        release(release_tx);
+        emitter.flush();
+
+        assert_eq!(
+            vec!["request 0", "request 1", "request 2"],
+            *delivered.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn overflow_drop_oldest() {
+        let (emitter, release_tx, delivered) = saturated(Overflow::DropOldest);
+
+        for id in 1..5 {
+            emit(&emitter, id);
+        }
+
+        assert_eq!(2, emitter.dropped());
+
+        release(release_tx);
+        emitter.flush();
+
+        assert_eq!(
+            vec!["request 0", "request 3", "request 4"],
+            *delivered.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn overflow_block() {
+        let (emitter, release_tx, delivered) = saturated(Overflow::Block);
+        let emitter = Arc::new(emitter);
+
+        for id in 1..3 {
+            emit(&*emitter, id);
+        }
+
+        // The queue is full, so this blocks until the background thread is released
+        let blocked = thread::spawn({
+            let emitter = emitter.clone();
+
+            move || emit(&*emitter, 3)
+        });
+
+        release(release_tx);
+        blocked.join().unwrap();
+        emitter.flush();
+
+        assert_eq!(0, emitter.dropped());
+        assert_eq!(
+            vec!["request 0", "request 1", "request 2", "request 3"],
+            *delivered.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn deliver_panics() {
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+
+        let emitter = AsyncEmitter::new(Default::default(), {
+            let delivered = delivered.clone();
+
+            move |record: &OwnedRecord| {
+                assert_ne!("request 0", record.msg(), "failed to deliver");
+
+                delivered.lock().unwrap().push(record.msg().to_owned())
+            }
+        });
+
+        emit(&emitter, 0);
+        emitter.flush();
+
+        // The background thread keeps delivering records after a panic
+        emit(&emitter, 1);
+        emitter.flush();
+
+        assert_eq!(1, emitter.dropped());
+        assert_eq!(vec!["request 1"], *delivered.lock().unwrap());
+    }
+
+    #[test]
+    fn worker_exited() {
+        let emitter = AsyncEmitter::new(
+            AsyncEmitterOptions {
+                capacity: 1,
+                overflow: Overflow::Block,
+            },
+            |_: &OwnedRecord| {},
+        );
+
+        // Simulate the background thread stopping without closing the emitter
+        emitter.shared.lock().closed = true;
+        emitter.shared.not_empty.notify_one();
+        while !emitter.shared.lock().exited {
+            thread::yield_now();
+        }
+
+        // Neither of these wait on a background thread that's gone
+        emit(&emitter, 0);
+        emit(&emitter, 1);
+        emitter.flush();
+
+        assert_eq!(2, emitter.dropped());
+    }
+
+    #[test]
+    fn drop_delivers_queued() {
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+
+        let emitter = AsyncEmitter::new(Default::default(), {
+            let delivered = delivered.clone();
+
+            move |record: &OwnedRecord| delivered.lock().unwrap().push(record.msg().to_owned())
+        });
+
+        for id in 0..3 {
+            emit(&emitter, id);
+        }
+
+        drop(emitter);
+
+        assert_eq!(3, delivered.lock().unwrap().len());
+    }
+}
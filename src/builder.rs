@@ -0,0 +1,135 @@
+/*!
+Configuring the global emitter, filter, and fields in one place.
+*/
+
+use std::{fmt, sync::Arc};
+
+use crate::{Emitter, Filter, Logger};
+
+/**
+A builder for the global pipeline that records are emitted through.
+
+This wraps `set_emitter` and `set_filter`, and attaches a fixed set of fields to every record
+sent to the emitter, so the whole pipeline can be configured in `main`:
+
+```
+let _guard = emit::Builder::new()
+    .emitter(|record: &emit::Record| println!("{}", record.msg()))
+    .filter(|record: &emit::Record| record.level() >= emit::Level::Info)
+    .with_field("service", "api")
+    .install();
+
+emit::info!("Started on port {port}", port: 8080);
+```
+
+The filter is checked before fields are attached, so it only sees the key-values captured on
+the record and in the context. A key-value captured on the record itself takes precedence over
+a field with the same key. Like the setters it wraps, installing a pipeline doesn't replace an
+emitter or filter that's already been set.
+*/
+#[derive(Default)]
+pub struct Builder {
+    emitter: Option<Box<dyn Emitter + Send + Sync>>,
+    filter: Option<Box<dyn Filter + Send + Sync>>,
+    fields: Vec<(&'static str, Arc<dyn fmt::Display + Send + Sync>)>,
+    thread_fields: bool,
+}
+
+impl Builder {
+    /**
+    Create a builder with no emitter, filter, or fields.
+    */
+    pub fn new() -> Self {
+        Builder::default()
+    }
+
+    /**
+    Set the emitter that records are sent to.
+
+    Without an emitter, installing the pipeline only sets its filter, and any fields are unused.
+    */
+    pub fn emitter(mut self, emitter: impl Emitter + Send + Sync + 'static) -> Self {
+        self.emitter = Some(Box::new(emitter));
+        self
+    }
+
+    /**
+    Set the filter that decides which records are sent to the emitter.
+    */
+    pub fn filter(mut self, filter: impl Filter + Send + Sync + 'static) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /**
+    Add a field to every record sent to the emitter.
+    */
+    pub fn with_field(
+        mut self,
+        key: &'static str,
+        value: impl fmt::Display + Send + Sync + 'static,
+    ) -> Self {
+        self.fields.retain(|(k, _)| *k != key);
+        self.fields.push((key, Arc::new(value)));
+        self
+    }
+
+    /**
+    Add the thread each record is emitted on to every record sent to the emitter.
+
+    Records get a `thread_id` key-value with the id of the thread that emitted them, like
+    `ThreadId(2)`, and a `thread_name` key-value with its name if it has one.
+    See `Logger::with_thread_fields`.
+    */
+    pub fn with_thread_fields(mut self) -> Self {
+        self.thread_fields = true;
+        self
+    }
+
+    /**
+    Set the global emitter and filter.
+
+    The returned guard flushes the emitter when it's dropped, so records emitted
+    before the end of `main` are delivered.
+    */
+    pub fn install(self) -> Installed {
+        if let Some(filter) = self.filter {
+            crate::set_boxed_filter(filter);
+        }
+
+        if let Some(emitter) = self.emitter {
+            if self.fields.is_empty() && !self.thread_fields {
+                crate::set_boxed_emitter(emitter);
+            } else {
+                let mut logger = self
+                    .fields
+                    .into_iter()
+                    .fold(Logger::from_boxed(emitter), |logger, (k, v)| {
+                        logger.with_field(k, v)
+                    });
+
+                if self.thread_fields {
+                    logger = logger.with_thread_fields();
+                }
+
+                crate::set_emitter(logger);
+            }
+        }
+
+        Installed { _private: () }
+    }
+}
+
+/**
+A guard returned by `Builder::install` that flushes the global emitter when it's dropped.
+*/
+#[must_use = "the emitter is flushed when the guard is dropped"]
+pub struct Installed {
+    _private: (),
+}
+
+impl Drop for Installed {
+    fn drop(&mut self) {
+        crate::flush();
+    }
+}
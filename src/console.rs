@@ -0,0 +1,336 @@
+/*!
+An emitter that writes records to the console.
+*/
+
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+    sync::Mutex,
+};
+
+use crate::{timestamp::with_timestamp_format, Emitter, Level, Record, TimestampFormat};
+
+/**
+An emitter that writes each record's level and message on its own line.
+
+By default, every record is written to `stdout`. Use `with_stderr_level` to
+route more severe records to `stderr` instead.
+
+Timestamps aren't written unless a format for them is set with `with_timestamp_format`.
+Key-values aren't written unless a style for them is set with `with_kv_style`.
+*/
+pub struct ConsoleEmitter<O = io::Stdout, E = io::Stderr> {
+    stdout: Mutex<O>,
+    stderr: Mutex<E>,
+    stderr_level: Option<Level>,
+    timestamp_format: Option<TimestampFormat>,
+    kv_style: Option<KvStyle>,
+}
+
+/**
+How the key-values of a record are written by a `ConsoleEmitter`.
+
+The default style writes key-values like `a=1 b="text"`.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KvStyle {
+    /**
+    The text written between a key and its value.
+    */
+    pub separator: &'static str,
+    /**
+    The text written between each key-value.
+    */
+    pub delimiter: &'static str,
+    /**
+    Whether string values are written in quotes, with any quotes in them escaped.
+    */
+    pub quote_strings: bool,
+}
+
+impl Default for KvStyle {
+    fn default() -> Self {
+        KvStyle {
+            separator: "=",
+            delimiter: " ",
+            quote_strings: true,
+        }
+    }
+}
+
+impl ConsoleEmitter {
+    /**
+    Create an emitter that writes to `stdout` and `stderr`.
+    */
+    pub fn new() -> Self {
+        ConsoleEmitter::with_writers(io::stdout(), io::stderr())
+    }
+}
+
+impl Default for ConsoleEmitter {
+    fn default() -> Self {
+        ConsoleEmitter::new()
+    }
+}
+
+impl<O, E> ConsoleEmitter<O, E>
+where
+    O: Write,
+    E: Write,
+{
+    /**
+    Create an emitter that writes to the given writers in place of `stdout` and `stderr`.
+    */
+    pub fn with_writers(stdout: O, stderr: E) -> Self {
+        ConsoleEmitter {
+            stdout: Mutex::new(stdout),
+            stderr: Mutex::new(stderr),
+            stderr_level: None,
+            timestamp_format: None,
+            kv_style: None,
+        }
+    }
+
+    /**
+    Write records at or above the given level to `stderr`, and all others to `stdout`.
+    */
+    pub fn with_stderr_level(mut self, level: Level) -> Self {
+        self.stderr_level = Some(level);
+        self
+    }
+
+    /**
+    Write the timestamp supplied for a record with `#[ts]` before its level, in the given format.
+
+    The format is also used for any `{ts}` hole in the message.
+    */
+    pub fn with_timestamp_format(mut self, timestamp_format: TimestampFormat) -> Self {
+        self.timestamp_format = Some(timestamp_format);
+        self
+    }
+
+    /**
+    Write the key-values of each record after its message, in the given style.
+
+    If a timestamp format is set then the timestamp isn't written again as a key-value.
+    */
+    pub fn with_kv_style(mut self, kv_style: KvStyle) -> Self {
+        self.kv_style = Some(kv_style);
+        self
+    }
+
+    /**
+    Write the key-values of a record to the end of its line, if there's a style for them.
+    */
+    fn write_kvs(&self, line: &mut String, record: &Record) {
+        let kv_style = match self.kv_style {
+            Some(ref kv_style) => kv_style,
+            None => return,
+        };
+
+        let kvs = record.kvs().sorted_key_values.iter().filter(|(k, _)| {
+            self.timestamp_format.is_none() || *k != crate::rt::__private::TIMESTAMP_KEY
+        });
+
+        for (i, (k, v)) in kvs.enumerate() {
+            line.push_str(if i == 0 { " " } else { kv_style.delimiter });
+
+            let _ = match v.to_borrowed_str() {
+                Some(v) if kv_style.quote_strings => {
+                    write!(line, "{}{}{:?}", k, kv_style.separator, v)
+                }
+                _ => write!(line, "{}{}{}", k, kv_style.separator, v),
+            };
+        }
+    }
+
+    /**
+    Get the inner writers back out of the emitter.
+    */
+    pub fn into_inner(self) -> (O, E) {
+        (
+            self.stdout
+                .into_inner()
+                .unwrap_or_else(|err| err.into_inner()),
+            self.stderr
+                .into_inner()
+                .unwrap_or_else(|err| err.into_inner()),
+        )
+    }
+}
+
+impl<O, E> Emitter for ConsoleEmitter<O, E>
+where
+    O: Write,
+    E: Write,
+{
+    fn emit(&self, record: &Record) {
+        let mut line = match self.timestamp_format {
+            Some(ref timestamp_format) => {
+                let mut line = String::new();
+
+                with_timestamp_format(record, timestamp_format, |record| {
+                    line = match record.kvs().get(crate::rt::__private::TIMESTAMP_KEY) {
+                        Some(ts) => format!("{} {}: {}", ts, record.level(), record.msg()),
+                        None => format!("{}: {}", record.level(), record.msg()),
+                    };
+
+                    self.write_kvs(&mut line, record);
+                });
+
+                line
+            }
+            None => {
+                let mut line = format!("{}: {}", record.level(), record.msg());
+
+                self.write_kvs(&mut line, record);
+
+                line
+            }
+        };
+
+        line.push('\n');
+
+        match self.stderr_level {
+            Some(stderr_level) if record.level() >= stderr_level => {
+                let mut stderr = self.stderr.lock().unwrap_or_else(|err| err.into_inner());
+                drop(stderr.write_all(line.as_bytes()));
+            }
+            _ => {
+                let mut stdout = self.stdout.lock().unwrap_or_else(|err| err.into_inner());
+                drop(stdout.write_all(line.as_bytes()));
+            }
+        }
+    }
+
+    fn flush(&self) {
+        drop(
+            self.stdout
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .flush(),
+        );
+        drop(
+            self.stderr
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .flush(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::rt::__private::{__PrivateCapture, Part};
+
+    #[test]
+    fn stderr_level() {
+        let emitter =
+            ConsoleEmitter::with_writers(Vec::new(), Vec::new()).with_stderr_level(Level::Warn);
+
+        for level in [Level::Info, Level::Error] {
+            let record = crate::test_record(level, &[], &[Part::Text("something happened")]);
+
+            emitter.emit(&Record(&record));
+        }
+
+        let (stdout, stderr) = emitter.into_inner();
+
+        assert_eq!(
+            "info: something happened\n",
+            String::from_utf8(stdout).unwrap()
+        );
+        assert_eq!(
+            "error: something happened\n",
+            String::from_utf8(stderr).unwrap()
+        );
+    }
+
+    #[test]
+    fn stdout_by_default() {
+        let emitter = ConsoleEmitter::with_writers(Vec::new(), Vec::new());
+
+        let record = crate::test_record(Level::Error, &[], &[Part::Text("something happened")]);
+
+        emitter.emit(&Record(&record));
+
+        let (stdout, stderr) = emitter.into_inner();
+
+        assert_eq!(
+            "error: something happened\n",
+            String::from_utf8(stdout).unwrap()
+        );
+        assert!(stderr.is_empty());
+    }
+
+    #[test]
+    fn timestamp_format() {
+        let ts = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_633_077_000);
+
+        for (timestamp_format, expected) in [
+            (None, "info: something happened\n"),
+            (
+                Some(TimestampFormat::Rfc3339),
+                "2021-10-01T08:30:00Z info: something happened\n",
+            ),
+            (
+                Some(TimestampFormat::EpochMillis),
+                "1633077000000 info: something happened\n",
+            ),
+        ] {
+            let mut emitter = ConsoleEmitter::with_writers(Vec::new(), Vec::new());
+
+            if let Some(timestamp_format) = timestamp_format {
+                emitter = emitter.with_timestamp_format(timestamp_format);
+            }
+
+            let kvs = [("ts", ts.__private_capture_as_timestamp())];
+            let record = crate::test_record(Level::Info, &kvs, &[Part::Text("something happened")]);
+
+            emitter.emit(&Record(&record));
+
+            let (stdout, _) = emitter.into_inner();
+
+            assert_eq!(expected, String::from_utf8(stdout).unwrap());
+        }
+    }
+
+    #[test]
+    fn kv_style() {
+        for (kv_style, expected) in [
+            (None, "info: something happened\n"),
+            (
+                Some(KvStyle::default()),
+                "info: something happened id=42 user=\"a \\\"b\\\"\"\n",
+            ),
+            (
+                Some(KvStyle {
+                    separator: ": ",
+                    delimiter: ", ",
+                    quote_strings: false,
+                }),
+                "info: something happened id: 42, user: a \"b\"\n",
+            ),
+        ] {
+            let mut emitter = ConsoleEmitter::with_writers(Vec::new(), Vec::new());
+
+            if let Some(kv_style) = kv_style {
+                emitter = emitter.with_kv_style(kv_style);
+            }
+
+            let kvs = [
+                ("id", 42.__private_capture_as_default()),
+                ("user", "a \"b\"".__private_capture_as_default()),
+            ];
+            let record = crate::test_record(Level::Info, &kvs, &[Part::Text("something happened")]);
+
+            emitter.emit(&Record(&record));
+
+            let (stdout, _) = emitter.into_inner();
+
+            assert_eq!(expected, String::from_utf8(stdout).unwrap());
+        }
+    }
+}
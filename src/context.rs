@@ -0,0 +1,316 @@
+/*!
+Per-thread key-values that are added to every record emitted on that thread.
+
+Context is managed explicitly: once a key-value is inserted it's added to every
+record emitted on the current thread until it's removed.
+
+```
+emit::context::insert("request_id", 42);
+
+// Records emitted here have a `request_id` key-value
+emit::info!("handling request");
+
+emit::context::remove("request_id");
+```
+
+Key-values can also be scoped to a block with the `with_fields!` macro:
+
+```
+let request_id = 42;
+
+emit::with_fields!({ request_id }, {
+    // Records emitted here have a `request_id` key-value
+    emit::info!("handling request");
+});
+```
+
+If a record captures a key-value with the same key as the context, the value
+captured on the record takes precedence.
+
+# Correlation ids
+
+A correlation id ties together the records emitted while handling a single request. It's an
+ordinary context key-value under the well-known `correlation_id` key, so emitters can find it
+without any per-call annotation:
+
+```
+emit::context::set_correlation_id("3f2b9c");
+
+// Records emitted here have a `correlation_id` key-value
+emit::info!("handling request");
+
+emit::context::clear_correlation_id();
+```
+
+`scoped_correlation_id` sets it until the returned guard is dropped instead. Like any other
+context key-value, a `correlation_id` captured on a record takes precedence over the one in
+the context.
+*/
+
+use std::{cell::RefCell, fmt, mem, rc::Rc};
+
+use crate::rt::__private::{KeyValues, ValueBag};
+
+thread_local! {
+    static CONTEXT: RefCell<Vec<(&'static str, Rc<dyn ContextValue>)>> = RefCell::new(Vec::new());
+}
+
+trait ContextValue {
+    fn to_value(&self) -> ValueBag<'_>;
+}
+
+impl<T: fmt::Display + 'static> ContextValue for T {
+    fn to_value(&self) -> ValueBag<'_> {
+        ValueBag::capture_display(self)
+    }
+}
+
+/**
+Insert a key-value into the context for the current thread.
+
+If the key is already in the context then its value is replaced.
+*/
+pub fn insert(key: &'static str, value: impl fmt::Display + 'static) {
+    replace(key, Some(Rc::new(value)));
+}
+
+/**
+Remove a key-value from the context for the current thread.
+*/
+pub fn remove(key: &'static str) {
+    replace(key, None);
+}
+
+/**
+Insert a key-value into the context for the current thread until the returned guard is dropped.
+
+When the guard is dropped, the key is restored to the value it had before,
+or removed if it wasn't in the context. The `with_fields!` macro uses this
+to scope key-values to a block.
+*/
+pub fn scoped(key: &'static str, value: impl fmt::Display + 'static) -> Scope {
+    let previous = replace(key, Some(Rc::new(value)));
+
+    Scope { key, previous }
+}
+
+/**
+A guard returned by `scoped` that restores the context when it's dropped.
+*/
+#[must_use = "the key-value is removed from the context when the guard is dropped"]
+pub struct Scope {
+    key: &'static str,
+    previous: Option<Rc<dyn ContextValue>>,
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        replace(self.key, self.previous.take());
+    }
+}
+
+/**
+The key correlation ids are added to records with.
+*/
+pub const CORRELATION_ID: &str = "correlation_id";
+
+/**
+Set the correlation id for the current thread.
+
+If a correlation id is already set then it's replaced.
+*/
+pub fn set_correlation_id(id: impl fmt::Display + 'static) {
+    insert(CORRELATION_ID, id);
+}
+
+/**
+Clear the correlation id for the current thread.
+*/
+pub fn clear_correlation_id() {
+    remove(CORRELATION_ID);
+}
+
+/**
+Set the correlation id for the current thread until the returned guard is dropped.
+
+When the guard is dropped, the correlation id that was set before is restored.
+*/
+pub fn scoped_correlation_id(id: impl fmt::Display + 'static) -> Scope {
+    scoped(CORRELATION_ID, id)
+}
+
+/**
+Set or remove the value for a key, returning the value it had before.
+*/
+fn replace(key: &'static str, value: Option<Rc<dyn ContextValue>>) -> Option<Rc<dyn ContextValue>> {
+    CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+
+        match (context.binary_search_by_key(&key, |(k, _)| k), value) {
+            (Ok(index), Some(value)) => Some(mem::replace(&mut context[index].1, value)),
+            (Ok(index), None) => Some(context.remove(index).1),
+            (Err(index), Some(value)) => {
+                context.insert(index, (key, value));
+                None
+            }
+            (Err(_), None) => None,
+        }
+    })
+}
+
+/**
+Merge the context for the current thread into a record.
+*/
+pub(crate) fn with_context(
+    record: &crate::rt::__private::Record,
+    f: impl FnOnce(&crate::rt::__private::Record),
+) {
+    // Clone the context out so emitters can modify it while the record is emitted
+    // If the context can't be read, like in a panic hook for a panic that happened while it
+    // was being modified, then the record is emitted without it instead of panicking again
+    let context = CONTEXT
+        .try_with(|context| context.try_borrow().map(|context| context.clone()))
+        .ok()
+        .and_then(Result::ok)
+        .unwrap_or_default();
+
+    if context.is_empty() {
+        return f(record);
+    }
+
+    let mut sorted_key_values: Vec<_> = record.kvs.sorted_key_values.to_vec();
+
+    for (k, v) in &context {
+        // Key-values captured on the record take precedence
+        if !record.kvs.contains_key(k) {
+            sorted_key_values.push((*k, v.to_value()));
+        }
+    }
+
+    // Key-values are kept in the order they were added in if they aren't sorted
+    if !cfg!(feature = "no_sort") {
+        sorted_key_values.sort_by_key(|(k, _)| *k);
+    }
+
+    f(&crate::rt::__private::Record {
+        level: record.level,
+        target: record.target,
+        location: record.location,
+        kvs: KeyValues {
+            sorted_key_values: &sorted_key_values,
+        },
+        template: record.template,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::rt::__private::{Level, Part};
+
+    fn emit(kvs: &[(&'static str, ValueBag)]) -> Vec<(String, String)> {
+        let record = crate::test_record(Level::Info, kvs, &[Part::Text("test")]);
+
+        let mut emitted = Vec::new();
+        with_context(&record, |record| {
+            emitted = record
+                .kvs
+                .sorted_key_values
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+        });
+
+        emitted
+    }
+
+    #[test]
+    fn insert_remove() {
+        insert("request_id", 42);
+        insert("a", "context");
+
+        assert_eq!(
+            vec![
+                ("a".to_owned(), "event".to_owned()),
+                ("b".to_owned(), "1".to_owned()),
+                ("request_id".to_owned(), "42".to_owned())
+            ],
+            emit(&[("a", ValueBag::from("event")), ("b", ValueBag::from(1))])
+        );
+
+        remove("request_id");
+        remove("a");
+
+        assert_eq!(
+            vec![("a".to_owned(), "event".to_owned()), ("b".to_owned(), "1".to_owned())],
+            emit(&[("a", ValueBag::from("event")), ("b", ValueBag::from(1))])
+        );
+    }
+
+    #[test]
+    fn scoped_restores() {
+        insert("d", 1);
+
+        {
+            let _outer = scoped("d", 2);
+            let _new = scoped("e", 3);
+
+            {
+                let _inner = scoped("d", 4);
+                assert_eq!(
+                    vec![("d".to_owned(), "4".to_owned()), ("e".to_owned(), "3".to_owned())],
+                    emit(&[])
+                );
+            }
+
+            assert_eq!(
+                vec![("d".to_owned(), "2".to_owned()), ("e".to_owned(), "3".to_owned())],
+                emit(&[])
+            );
+        }
+
+        assert_eq!(vec![("d".to_owned(), "1".to_owned())], emit(&[]));
+
+        remove("d");
+    }
+
+    #[test]
+    fn insert_replaces() {
+        insert("c", 1);
+        insert("c", 2);
+
+        assert_eq!(vec![("c".to_owned(), "2".to_owned())], emit(&[]));
+
+        remove("c");
+    }
+
+    #[test]
+    fn correlation_id() {
+        set_correlation_id("abc");
+
+        assert_eq!(
+            vec![("correlation_id".to_owned(), "abc".to_owned())],
+            emit(&[])
+        );
+
+        {
+            let _scope = scoped_correlation_id("def");
+
+            assert_eq!(
+                vec![("correlation_id".to_owned(), "def".to_owned())],
+                emit(&[])
+            );
+        }
+
+        // The correlation id captured on the record takes precedence
+        assert_eq!(
+            vec![("correlation_id".to_owned(), "record".to_owned())],
+            emit(&[("correlation_id", ValueBag::from("record"))])
+        );
+
+        clear_correlation_id();
+
+        assert_eq!(Vec::<(String, String)>::new(), emit(&[]));
+    }
+}
@@ -0,0 +1,159 @@
+/*!
+An emitter that counts records by their template.
+*/
+
+use std::{collections::HashMap, sync::Mutex};
+
+use crate::{Emitter, Record};
+
+/**
+An emitter that counts how many records have been emitted for each template.
+
+Records are counted by their template, so `"request {id}"` is counted once
+for each record, regardless of the value of `id`. Templates are keyed by their
+source, as returned by `Record::template_source`, so a hole like `{id}` isn't
+confused with escaped text like `{{id}}`. Counted records can also be
+forwarded to another emitter.
+*/
+pub struct CountingEmitter<E = fn(&Record)> {
+    emitter: Option<E>,
+    counts: Mutex<HashMap<String, u64>>,
+}
+
+impl CountingEmitter {
+    /**
+    Count records without forwarding them anywhere.
+    */
+    pub fn new() -> Self {
+        CountingEmitter {
+            emitter: None,
+            counts: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Default for CountingEmitter {
+    fn default() -> Self {
+        CountingEmitter::new()
+    }
+}
+
+impl<E> CountingEmitter<E> {
+    /**
+    Count records and then forward them to `emitter`.
+    */
+    pub fn forwarding(emitter: E) -> Self {
+        CountingEmitter {
+            emitter: Some(emitter),
+            counts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /**
+    Get a snapshot of the number of records emitted for each template.
+
+    Templates are keyed by their source, like `"request {id}"`.
+    */
+    pub fn counts(&self) -> HashMap<String, u64> {
+        self.counts
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+}
+
+impl<E> Emitter for CountingEmitter<E>
+where
+    E: Emitter,
+{
+    fn emit(&self, record: &Record) {
+        {
+            let mut counts = self.counts.lock().unwrap_or_else(|err| err.into_inner());
+
+            *counts
+                .entry(record.template_source().to_string())
+                .or_insert(0) += 1;
+        }
+
+        if let Some(ref emitter) = self.emitter {
+            emitter.emit(record);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(ref emitter) = self.emitter {
+            emitter.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{cell::Cell, rc::Rc};
+
+    use crate::rt::__private::{Level, Part, ValueBag};
+
+    fn emit(emitter: &impl Emitter, template_parts: &[Part], id: u64) {
+        let kvs = [("id", ValueBag::from(id))];
+        let record = crate::test_record(Level::Info, &kvs, template_parts);
+
+        emitter.emit(&Record(&record));
+    }
+
+    #[test]
+    fn count_templates() {
+        let emitter = CountingEmitter::new();
+
+        for id in 0..5 {
+            emit(&emitter, &[Part::Text("request "), Part::Hole("id")], id);
+        }
+        emit(&emitter, &[Part::Text("done")], 0);
+
+        let counts = emitter.counts();
+
+        assert_eq!(2, counts.len());
+        assert_eq!(Some(&5), counts.get("request {id}"));
+        assert_eq!(Some(&1), counts.get("done"));
+    }
+
+    #[test]
+    fn count_escapes_separately() {
+        let emitter = CountingEmitter::new();
+
+        emit(&emitter, &[Part::Text("request "), Part::Hole("id")], 1);
+        emit(
+            &emitter,
+            &[
+                Part::Text("request "),
+                Part::Escape('{'),
+                Part::Text("id"),
+                Part::Escape('}'),
+            ],
+            1,
+        );
+
+        let counts = emitter.counts();
+
+        assert_eq!(Some(&1), counts.get("request {id}"));
+        assert_eq!(Some(&1), counts.get("request {{id}}"));
+    }
+
+    #[test]
+    fn count_forwarding() {
+        let forwarded = Rc::new(Cell::new(0));
+
+        let emitter = CountingEmitter::forwarding({
+            let forwarded = forwarded.clone();
+            move |_: &Record| forwarded.set(forwarded.get() + 1)
+        });
+
+        for id in 0..3 {
+            emit(&emitter, &[Part::Text("request "), Part::Hole("id")], id);
+        }
+
+        assert_eq!(3, forwarded.get());
+        assert_eq!(Some(&3), emitter.counts().get("request {id}"));
+    }
+}
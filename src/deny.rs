@@ -0,0 +1,139 @@
+/*!
+Removing denied keys from records before they're emitted.
+*/
+
+use crate::rt::__private::{KeyValues, ValueBag};
+
+/**
+The value that replaces a denied key-value when it's redacted.
+*/
+const REDACTED: &str = "<redacted>";
+
+/**
+What to do with key-values whose keys are in the denylist.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Denied {
+    /**
+    Remove the key-value from the record.
+
+    Any hole for it in the template is rendered as if it was missing.
+    */
+    Remove,
+    /**
+    Keep the key, but replace its value with `"<redacted>"`.
+    */
+    Redact,
+}
+
+pub(crate) struct Denylist {
+    pub(crate) keys: Vec<String>,
+    pub(crate) denied: Denied,
+}
+
+/**
+Remove or redact any denied key-values in a record, and pass the result to `f`.
+
+Records that don't have any denied keys are passed through as-is.
+*/
+pub(crate) fn deny(
+    denylist: &Denylist,
+    record: &crate::rt::__private::Record,
+    f: impl FnOnce(&crate::rt::__private::Record),
+) {
+    let is_denied = |k: &str| denylist.keys.iter().any(|denied| denied == k);
+
+    if !record
+        .kvs
+        .sorted_key_values
+        .iter()
+        .any(|(k, _)| is_denied(k))
+    {
+        return f(record);
+    }
+
+    let kvs: Vec<_> = record
+        .kvs
+        .sorted_key_values
+        .iter()
+        .filter_map(|(k, v)| match denylist.denied {
+            _ if !is_denied(k) => Some((*k, v.clone())),
+            Denied::Remove => None,
+            Denied::Redact => Some((*k, ValueBag::from(REDACTED))),
+        })
+        .collect();
+
+    f(&crate::rt::__private::Record {
+        level: record.level,
+        target: record.target,
+        location: record.location,
+        kvs: KeyValues {
+            sorted_key_values: &kvs,
+        },
+        template: record.template,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        rt::__private::{Level, Part},
+        Record,
+    };
+
+    #[test]
+    fn deny_kvs() {
+        let kvs = [
+            ("password", ValueBag::from("hunter2")),
+            ("user", ValueBag::from("a")),
+        ];
+        let record = crate::test_record(
+            Level::Info,
+            &kvs,
+            &[
+                Part::Text("user: "),
+                Part::Hole("user"),
+                Part::Text(", password: "),
+                Part::Hole("password"),
+            ],
+        );
+
+        for (denied, expected_kvs, expected_msg) in [
+            (
+                Denied::Remove,
+                vec![("user", "a")],
+                "user: a, password: `password`",
+            ),
+            (
+                Denied::Redact,
+                vec![("password", REDACTED), ("user", "a")],
+                "user: a, password: <redacted>",
+            ),
+        ] {
+            let denylist = Denylist {
+                keys: vec!["password".to_owned(), "authorization".to_owned()],
+                denied,
+            };
+
+            let mut called = false;
+            deny(&denylist, &record, |record| {
+                called = true;
+
+                assert_eq!(
+                    expected_kvs,
+                    record
+                        .kvs
+                        .sorted_key_values
+                        .iter()
+                        .map(|(k, v)| (*k, v.to_borrowed_str().unwrap()))
+                        .collect::<Vec<_>>()
+                );
+                assert_eq!(expected_msg, Record(record).msg().to_string());
+            });
+
+            assert!(called);
+        }
+    }
+}
@@ -1,9 +1,135 @@
-use crate::{Emitter, Record};
+use std::{cell::Cell, thread};
+
+use crate::{context, deny, enrich, Emitter, Record, Target};
+
+thread_local! {
+    static EMITTING: Cell<bool> = Cell::new(false);
+}
+
+/**
+Call `f` to emit a record, unless doing so would re-enter an emitter that's panicking.
+
+A panic hook that emits records runs on the thread that panicked. If the panic came from an
+emitter partway through emitting a record then that emitter might still hold locks, or panic
+again, which would abort the process. Records emitted while that's the case are discarded.
+Emitters that emit records of their own while they're not panicking aren't affected.
+*/
+fn guard_reentrance(f: impl FnOnce()) {
+    struct Reset(bool);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            let emitting = self.0;
+            let _ = EMITTING.try_with(|cell| cell.set(emitting));
+        }
+    }
+
+    let emitting = match EMITTING.try_with(|cell| cell.replace(true)) {
+        Ok(emitting) => emitting,
+        // The thread is being torn down
+        Err(_) => return,
+    };
+
+    if emitting && thread::panicking() {
+        return;
+    }
+
+    let _reset = Reset(emitting);
+    f()
+}
+
+/**
+Add the context to a record, then run the enricher over it, if there is one.
+
+If the record is going to the default emitter then it's checked by the global filter before
+it's enriched, so records that are going to be discarded aren't copied. Denied key-values are
+removed last, so they can't be added back by the context or enricher. Key-values that loggers add
+are checked again by the logger.
+*/
+fn prepare(record: &crate::rt::__private::Record, filtered: bool, f: impl FnOnce(&Record)) {
+    context::with_context(record, |record| match crate::ENRICHER.get() {
+        Some(_) if filtered && !crate::matches_filter(&Record(record)) => (),
+        Some(enricher) => enrich::enrich(&**enricher, record, |record| deny_keys(record, f)),
+        None => deny_keys(record, f),
+    })
+}
+
+/**
+Remove or redact any key-values in the global denylist, if there is one.
+*/
+pub(crate) fn deny_keys(record: &crate::rt::__private::Record, f: impl FnOnce(&Record)) {
+    match crate::DENYLIST.get() {
+        Some(denylist) => deny::deny(denylist, record, |record| f(&Record(record))),
+        None => f(&Record(record)),
+    }
+}
 
 pub fn emit(record: &crate::rt::__private::Record) {
-    crate::emit(&Record(record))
+    guard_reentrance(|| prepare(record, true, crate::emit))
+}
+
+pub fn emit_to(target: &(impl Emitter + ?Sized), record: &crate::rt::__private::Record) {
+    guard_reentrance(|| prepare(record, false, |record| target.emit(record)))
 }
 
-pub fn emit_to(target: Emitter, record: &crate::rt::__private::Record) {
-    target(&Record(record))
+pub fn emit_blocking(record: &crate::rt::__private::Record) {
+    guard_reentrance(|| {
+        prepare(record, true, crate::emit);
+        crate::flush();
+    })
+}
+
+pub fn emit_to_blocking(target: &(impl Emitter + ?Sized), record: &crate::rt::__private::Record) {
+    guard_reentrance(|| {
+        prepare(record, false, |record| target.emit(record));
+        target.flush();
+    })
+}
+
+pub fn fn_target<F: Fn(&Record)>(target: F) -> F {
+    target
+}
+
+/**
+A `target` that's either the target of the record itself or an emitter to send it to.
+
+Which one it is gets resolved by method lookup, so `Target`s are found before `Emitter`s.
+*/
+pub struct TargetArg<'a, T: ?Sized>(pub &'a T);
+
+/**
+The emitter to send a record to, or the default emitter if there isn't one.
+*/
+pub struct TargetEmitter<'a>(Option<&'a dyn Emitter>);
+
+pub trait ResolveTarget<'a> {
+    fn resolve(&self, module_path: &'static str) -> (&'a str, TargetEmitter<'a>);
+}
+
+impl<'a, T: Target + ?Sized> ResolveTarget<'a> for TargetArg<'a, T> {
+    fn resolve(&self, _: &'static str) -> (&'a str, TargetEmitter<'a>) {
+        (self.0.as_str(), TargetEmitter(None))
+    }
+}
+
+impl<'a, 'b, T: Emitter> ResolveTarget<'a> for &'b TargetArg<'a, T> {
+    fn resolve(&self, module_path: &'static str) -> (&'a str, TargetEmitter<'a>) {
+        (module_path, TargetEmitter(Some(self.0)))
+    }
+}
+
+impl<'a> Emitter for TargetEmitter<'a> {
+    fn emit(&self, record: &Record) {
+        match self.0 {
+            Some(emitter) => emitter.emit(record),
+            None => crate::emit(record),
+        }
+    }
+
+    fn flush(&self) {
+        match self.0 {
+            Some(emitter) => emitter.flush(),
+            None => crate::flush(),
+        }
+    }
 }
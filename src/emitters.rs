@@ -0,0 +1,156 @@
+/*!
+Trivial emitters for tests and quick setups.
+
+Like the other emitters, these are re-exported from the crate root instead of this module being public.
+*/
+
+use std::fmt;
+
+use crate::{Emitter, Record};
+
+/**
+An emitter that discards every record.
+
+This is what happens to records when no emitter has been set, so setting it explicitly
+turns off the fallback emitter installed by `fallback_to_stderr`.
+*/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullEmitter;
+
+impl NullEmitter {
+    /**
+    Discard every record.
+    */
+    pub fn new() -> Self {
+        NullEmitter
+    }
+}
+
+impl Emitter for NullEmitter {
+    fn emit(&self, _: &Record) {}
+}
+
+/**
+An emitter that calls a closure for each record.
+
+Any `Fn(&Record)` is already an emitter, this type just gives it a name so it can be stored in
+fields or passed to APIs that expect a type implementing `Emitter` without naming the closure.
+A separate closure can be given to run when the emitter is flushed with `with_flush`.
+*/
+pub struct FnEmitter<F, L = fn()> {
+    emit: F,
+    flush: Option<L>,
+}
+
+impl<F> FnEmitter<F>
+where
+    F: Fn(&Record),
+{
+    /**
+    Call `emit` for each record.
+    */
+    pub fn new(emit: F) -> Self {
+        FnEmitter { emit, flush: None }
+    }
+}
+
+impl<F, L> FnEmitter<F, L> {
+    /**
+    Call `flush` when the emitter is flushed.
+    */
+    pub fn with_flush<T>(self, flush: T) -> FnEmitter<F, T>
+    where
+        T: Fn(),
+    {
+        FnEmitter {
+            emit: self.emit,
+            flush: Some(flush),
+        }
+    }
+}
+
+impl<F, L> fmt::Debug for FnEmitter<F, L> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FnEmitter").finish_non_exhaustive()
+    }
+}
+
+impl<F, L> Emitter for FnEmitter<F, L>
+where
+    F: Fn(&Record),
+    L: Fn(),
+{
+    fn emit(&self, record: &Record) {
+        (self.emit)(record)
+    }
+
+    fn flush(&self) {
+        if let Some(flush) = &self.flush {
+            flush()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+
+    use crate::rt::__private::{Level, Part, ValueBag};
+
+    fn emit(emitter: &impl Emitter) {
+        let record = crate::test_record(Level::Info, &[], &[Part::Text("test")]);
+
+        emitter.emit(&Record(&record));
+        emitter.flush();
+    }
+
+    #[test]
+    fn fn_emitter() {
+        let emitted = Cell::new(0);
+        let flushed = Cell::new(0);
+
+        emit(&FnEmitter::new(|record: &Record| {
+            assert_eq!("test", record.msg().to_string());
+            emitted.set(emitted.get() + 1);
+        }));
+
+        assert_eq!(1, emitted.get());
+
+        emit(
+            &FnEmitter::new(|_: &Record| emitted.set(emitted.get() + 1))
+                .with_flush(|| flushed.set(flushed.get() + 1)),
+        );
+
+        assert_eq!(2, emitted.get());
+        assert_eq!(1, flushed.get());
+    }
+
+    #[test]
+    fn null_emitter() {
+        struct Formatted<'a>(&'a Cell<usize>);
+
+        impl<'a> fmt::Display for Formatted<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.set(self.0.get() + 1);
+                f.write_str("formatted")
+            }
+        }
+
+        let formatted = Cell::new(0);
+        let value = Formatted(&formatted);
+
+        let kvs = [("value", ValueBag::from_dyn_display(&value))];
+        let record = crate::test_record(Level::Info, &kvs, &[Part::Hole("value")]);
+
+        NullEmitter::new().emit(&Record(&record));
+        NullEmitter::new().flush();
+
+        // The record is discarded without its message or values ever being formatted
+        assert_eq!(0, formatted.get());
+
+        assert_eq!("formatted", Record(&record).msg().to_string());
+        assert_eq!(1, formatted.get());
+    }
+}
@@ -0,0 +1,142 @@
+/*!
+Transforming records with the global enricher before they're emitted.
+*/
+
+use crate::{
+    rt::__private::{KeyValues, ValueBag},
+    OwnedRecord, OwnedValue, Record,
+};
+
+/**
+A value after it's been through the enricher.
+*/
+enum Enriched<'a, 'b> {
+    /**
+    The enricher didn't change the value, so the record's original value is used.
+    */
+    Original(&'a ValueBag<'a>),
+    /**
+    The enricher added or replaced the value.
+    */
+    Changed(&'b OwnedValue),
+}
+
+/**
+Find the index of a key in a record's key-values.
+*/
+fn position(kvs: &KeyValues, key: &str) -> Option<usize> {
+    if cfg!(feature = "no_sort") {
+        kvs.sorted_key_values.iter().position(|(k, _)| *k == key)
+    } else {
+        kvs.sorted_key_values
+            .binary_search_by_key(&key, |(k, _)| k)
+            .ok()
+    }
+}
+
+/**
+Run the enricher over an owned copy of a record, and pass the result to `f`.
+
+Only the key-values of the record can be changed. Any the enricher doesn't change keep their
+original values, and any it adds or replaces are emitted as the `OwnedValue` it set.
+*/
+pub(crate) fn enrich(
+    enricher: &(dyn Fn(&mut OwnedRecord) + Send + Sync),
+    record: &crate::rt::__private::Record,
+    f: impl FnOnce(&crate::rt::__private::Record),
+) {
+    let original = Record(record).to_owned();
+    let mut enriched = original.clone_owned();
+
+    enricher(&mut enriched);
+
+    let originals: Vec<_> = original.kvs().map(|(_, v)| v).collect();
+
+    // The owned copy keeps key-values in the same order as the record,
+    // so originals can be looked up by their index in the record
+    let values: Vec<_> = enriched
+        .kvs()
+        .map(|(k, v)| match position(&record.kvs, k) {
+            Some(index) if originals[index] == v => (
+                k,
+                Enriched::Original(&record.kvs.sorted_key_values[index].1),
+            ),
+            _ => (k, Enriched::Changed(v)),
+        })
+        .collect();
+
+    let mut kvs: Vec<_> = values
+        .iter()
+        .map(|(k, value)| match value {
+            Enriched::Original(value) => (*k, (*value).clone()),
+            Enriched::Changed(value) => (*k, value.to_value()),
+        })
+        .collect();
+
+    if !cfg!(feature = "no_sort") {
+        kvs.sort_by_key(|(k, _)| *k);
+    }
+
+    f(&crate::rt::__private::Record {
+        level: record.level,
+        target: record.target,
+        location: record.location,
+        kvs: KeyValues {
+            sorted_key_values: &kvs,
+        },
+        template: record.template,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::rt::__private::{Level, Part};
+
+    #[test]
+    fn enrich_kvs() {
+        let kvs = [
+            ("a", ValueBag::from(1)),
+            ("b", ValueBag::from("b")),
+            ("d", ValueBag::from("d")),
+        ];
+        let record = crate::test_record(
+            Level::Info,
+            &kvs,
+            &[
+                Part::Text("a: "),
+                Part::Hole("a"),
+                Part::Text(", c: "),
+                Part::Hole("c"),
+            ],
+        );
+
+        let mut called = false;
+        enrich(
+            &|record: &mut OwnedRecord| {
+                record.insert_kv("c", 3);
+                record.insert_kv("e", "e");
+                record.remove_kv("b");
+            },
+            &record,
+            |record| {
+                called = true;
+
+                // Unchanged values keep their original types
+                assert_eq!(Some(1), record.kvs["a"].to_i64());
+                assert_eq!(Some("d"), record.kvs["d"].to_borrowed_str());
+                assert!(record.kvs.get("b").is_none());
+                assert_eq!(Some(3), record.kvs["c"].to_i64());
+
+                // Strings that are added aren't quoted
+                assert_eq!(Some("e"), record.kvs["e"].to_borrowed_str());
+                assert_eq!("e", record.kvs["e"].to_string());
+
+                assert_eq!("a: 1, c: 3", Record(record).msg().to_string());
+            },
+        );
+
+        assert!(called);
+    }
+}
@@ -0,0 +1,462 @@
+/*!
+An emitter that maps records into owned log records for an exporter.
+*/
+
+use std::{
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use crate::{
+    rt::__private::{ValueBag, TIMESTAMP_KEY},
+    Emitter, Level, Record,
+};
+
+/**
+The key-value mapped to the trace id of a log record.
+*/
+pub const TRACE_ID_KEY: &str = "trace_id";
+
+/**
+The key-value mapped to the span id of a log record.
+*/
+pub const SPAN_ID_KEY: &str = "span_id";
+
+/**
+An emitter that maps each record to an `ExportRecord` and hands it to an exporter.
+
+Records are mapped as follows:
+
+- The level is mapped to a severity number, and its name is used as the severity text.
+  The standard levels are `TRACE`, `DEBUG`, `INFO`, `WARN`, and `ERROR`, and levels above
+  `Error` are `FATAL`. Custom levels between two standard levels get one of the numbers in
+  between them, so a level with a severity of `35` is `INFO3`.
+- The rendered message is the body.
+- The timestamp is the one supplied with `#[ts]`, if there is one. The observed timestamp is
+  always the time the record was emitted.
+- A `trace_id` key-value is the trace id, if it's a `u128` or 32 hex digits, and a `span_id`
+  key-value is the span id, if it's a `u64` or 16 hex digits. That means records emitted within
+  a `span!` get the span's id.
+- All other key-values are attributes. Booleans, integers that fit in an `i64`, and floats keep
+  their type, and anything else is written as a string using its `Display` implementation.
+
+The fields of an `ExportRecord` follow the shape of the OpenTelemetry log data model, but
+this crate doesn't depend on or integrate with OpenTelemetry. Implement the `Exporter` trait
+to send records somewhere, like a log collector. `InMemoryExporter` keeps records in memory,
+which is useful for testing.
+*/
+pub struct ExportEmitter<E> {
+    exporter: E,
+}
+
+impl<E> ExportEmitter<E>
+where
+    E: Exporter,
+{
+    /**
+    Create an emitter that sends log records to the given exporter.
+    */
+    pub fn new(exporter: E) -> Self {
+        ExportEmitter { exporter }
+    }
+
+    /**
+    Get the inner exporter back out of the emitter.
+    */
+    pub fn into_inner(self) -> E {
+        self.exporter
+    }
+}
+
+impl<E> Emitter for ExportEmitter<E>
+where
+    E: Exporter,
+{
+    fn emit(&self, record: &Record) {
+        self.exporter
+            .export(ExportRecord::from_record(record, SystemTime::now()));
+    }
+
+    fn flush(&self) {
+        self.exporter.flush();
+    }
+}
+
+/**
+A destination for the log records produced by an `ExportEmitter`.
+*/
+pub trait Exporter {
+    /**
+    Export a log record.
+    */
+    fn export(&self, record: ExportRecord);
+
+    /**
+    Export any log records that are buffered.
+    */
+    fn flush(&self) {}
+}
+
+impl<'a, E> Exporter for &'a E
+where
+    E: Exporter + ?Sized,
+{
+    fn export(&self, record: ExportRecord) {
+        (**self).export(record)
+    }
+
+    fn flush(&self) {
+        (**self).flush()
+    }
+}
+
+impl<E> Exporter for Arc<E>
+where
+    E: Exporter + ?Sized,
+{
+    fn export(&self, record: ExportRecord) {
+        (**self).export(record)
+    }
+
+    fn flush(&self) {
+        (**self).flush()
+    }
+}
+
+/**
+A log record mapped from an emitted record.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportRecord {
+    /**
+    The time the event occurred, if it's known.
+    */
+    pub timestamp: Option<SystemTime>,
+    /**
+    The time the event was emitted.
+    */
+    pub observed_timestamp: SystemTime,
+    /**
+    The severity of the event, between `1` for `TRACE` and `24` for `FATAL4`.
+    */
+    pub severity_number: u8,
+    /**
+    The name of the level of the event.
+    */
+    pub severity_text: String,
+    /**
+    The target of the event.
+    */
+    pub target: String,
+    /**
+    The rendered message of the event.
+    */
+    pub body: String,
+    /**
+    The key-values of the event that aren't mapped to other fields.
+    */
+    pub attributes: Vec<(String, ExportValue)>,
+    /**
+    The id of the trace the event is part of, if there is one.
+    */
+    pub trace_id: Option<[u8; 16]>,
+    /**
+    The id of the span the event is part of, if there is one.
+    */
+    pub span_id: Option<[u8; 8]>,
+}
+
+impl ExportRecord {
+    fn from_record(record: &Record, now: SystemTime) -> Self {
+        let kvs = record.kvs();
+
+        let trace_id = kvs.get(TRACE_ID_KEY).and_then(trace_id);
+        let span_id = kvs.get(SPAN_ID_KEY).and_then(span_id);
+
+        // Ids that can't be mapped are kept as attributes instead
+        let attributes = kvs
+            .sorted_key_values
+            .iter()
+            .filter(|(k, _)| match *k {
+                TIMESTAMP_KEY => false,
+                TRACE_ID_KEY => trace_id.is_none(),
+                SPAN_ID_KEY => span_id.is_none(),
+                _ => true,
+            })
+            .map(|(k, v)| ((*k).to_owned(), ExportValue::from_value(v)))
+            .collect();
+
+        ExportRecord {
+            timestamp: record.timestamp(),
+            observed_timestamp: now,
+            severity_number: severity_number(record.level()),
+            severity_text: record.level().as_str().to_uppercase(),
+            target: record.target().to_owned(),
+            body: record.msg().to_string(),
+            attributes,
+            trace_id,
+            span_id,
+        }
+    }
+}
+
+/**
+The value of an attribute on a log record.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportValue {
+    /**
+    A boolean.
+    */
+    Bool(bool),
+    /**
+    A signed integer.
+    */
+    Int(i64),
+    /**
+    A floating point number.
+    */
+    Double(f64),
+    /**
+    Any other value, formatted as a string.
+    */
+    String(String),
+}
+
+impl ExportValue {
+    fn from_value(value: &ValueBag) -> Self {
+        if let Some(value) = value.to_bool() {
+            ExportValue::Bool(value)
+        } else if let Some(value) = value.to_i64() {
+            ExportValue::Int(value)
+        } else if let Some(value) = value.to_f64() {
+            ExportValue::Double(value)
+        } else {
+            ExportValue::String(value.to_string())
+        }
+    }
+}
+
+/**
+An exporter that keeps the log records it's given in memory.
+
+Clones of the exporter share the same records, so a clone can be given to an `ExportEmitter`
+and the records read back from the original.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryExporter {
+    records: Arc<Mutex<Vec<ExportRecord>>>,
+}
+
+impl InMemoryExporter {
+    /**
+    Create an exporter with no records.
+    */
+    pub fn new() -> Self {
+        InMemoryExporter::default()
+    }
+
+    /**
+    Get a copy of the records that have been exported so far.
+    */
+    pub fn records(&self) -> Vec<ExportRecord> {
+        self.records
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /**
+    Remove all the records that have been exported so far.
+    */
+    pub fn clear(&self) {
+        self.records
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+    }
+}
+
+impl Exporter for InMemoryExporter {
+    fn export(&self, record: ExportRecord) {
+        self.records
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(record);
+    }
+}
+
+/**
+Map the level of a record to a severity number.
+
+Each standard level has a range of four numbers, starting at `1` for `Trace`.
+Custom levels are mapped into the range of the standard level below them.
+*/
+fn severity_number(level: Level) -> u8 {
+    match level.severity() {
+        s if s < 10 => 1,
+        s if s > 50 => 21,
+        s => 1 + (s / 10 - 1) * 4 + (s % 10) * 4 / 10,
+    }
+}
+
+fn trace_id(value: &ValueBag) -> Option<[u8; 16]> {
+    let id = match value.to_u128() {
+        Some(id) => id,
+        None => u128::from_str_radix(&hex_digits(value, 32)?, 16).ok()?,
+    };
+
+    // An id of zero isn't a valid trace id
+    if id == 0 {
+        None
+    } else {
+        Some(id.to_be_bytes())
+    }
+}
+
+fn span_id(value: &ValueBag) -> Option<[u8; 8]> {
+    let id = match value.to_u64() {
+        Some(id) => id,
+        None => u64::from_str_radix(&hex_digits(value, 16)?, 16).ok()?,
+    };
+
+    // An id of zero isn't a valid span id
+    if id == 0 {
+        None
+    } else {
+        Some(id.to_be_bytes())
+    }
+}
+
+/**
+Get a value as a string of exactly `len` hex digits.
+*/
+fn hex_digits(value: &ValueBag, len: usize) -> Option<String> {
+    let digits = value.to_string();
+
+    if digits.len() == len && digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Some(digits)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::rt::__private::{Part, Timestamp};
+
+    fn with_record(level: Level, kvs: &[(&str, ValueBag)], f: impl FnOnce(&Record)) {
+        let record = crate::rt::__private::Record {
+            target: "my_app",
+            ..crate::test_record(level, kvs, &[Part::Text("request "), Part::Hole("id")])
+        };
+
+        f(&Record(&record))
+    }
+
+    #[test]
+    fn export() {
+        let exporter = InMemoryExporter::new();
+        let emitter = ExportEmitter::new(exporter.clone());
+
+        let ts = UNIX_EPOCH + Duration::from_secs(1_633_077_000);
+        with_record(
+            Level::Warn,
+            &[
+                ("id", ValueBag::from(1)),
+                ("ok", ValueBag::from(true)),
+                ("ratio", ValueBag::from(0.5)),
+                ("user", ValueBag::from("alice")),
+                (
+                    TIMESTAMP_KEY,
+                    ValueBag::capture_display(Timestamp::new(&ts)),
+                ),
+            ],
+            |record| emitter.emit(record),
+        );
+
+        let records = exporter.records();
+        assert_eq!(1, records.len());
+
+        let record = &records[0];
+        assert_eq!(Some(ts), record.timestamp);
+        assert_eq!(13, record.severity_number);
+        assert_eq!("WARN", record.severity_text);
+        assert_eq!("my_app", record.target);
+        assert_eq!("request 1", record.body);
+        assert_eq!(None, record.trace_id);
+        assert_eq!(None, record.span_id);
+
+        let mut attributes = record.attributes.clone();
+        attributes.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            vec![
+                ("id".to_owned(), ExportValue::Int(1)),
+                ("ok".to_owned(), ExportValue::Bool(true)),
+                ("ratio".to_owned(), ExportValue::Double(0.5)),
+                ("user".to_owned(), ExportValue::String("alice".to_owned())),
+            ],
+            attributes
+        );
+    }
+
+    #[test]
+    fn export_ids() {
+        let exporter = InMemoryExporter::new();
+        let emitter = ExportEmitter::new(&exporter);
+
+        with_record(
+            Level::Info,
+            &[
+                ("span_id", ValueBag::from(42u64)),
+                (
+                    "trace_id",
+                    ValueBag::from("4bf92f3577b34da6a3ce929d0e0e4736"),
+                ),
+            ],
+            |record| emitter.emit(record),
+        );
+
+        // Ids that aren't valid are kept as attributes
+        with_record(
+            Level::Info,
+            &[
+                ("span_id", ValueBag::from(0u64)),
+                ("trace_id", ValueBag::from("not a trace")),
+            ],
+            |record| emitter.emit(record),
+        );
+
+        let records = exporter.records();
+
+        assert_eq!(
+            Some([
+                0x4b, 0xf9, 0x2f, 0x35, 0x77, 0xb3, 0x4d, 0xa6, 0xa3, 0xce, 0x92, 0x9d, 0x0e, 0x0e,
+                0x47, 0x36
+            ]),
+            records[0].trace_id
+        );
+        assert_eq!(Some([0, 0, 0, 0, 0, 0, 0, 42]), records[0].span_id);
+        assert!(records[0].attributes.is_empty());
+
+        assert_eq!(None, records[1].trace_id);
+        assert_eq!(None, records[1].span_id);
+        assert_eq!(2, records[1].attributes.len());
+    }
+
+    #[test]
+    fn severities() {
+        assert_eq!(1, severity_number(Level::Trace));
+        assert_eq!(5, severity_number(Level::Debug));
+        assert_eq!(9, severity_number(Level::Info));
+        assert_eq!(11, severity_number(Level::custom("notice", 35)));
+        assert_eq!(13, severity_number(Level::Warn));
+        assert_eq!(17, severity_number(Level::Error));
+        assert_eq!(21, severity_number(Level::custom("critical", 60)));
+    }
+}
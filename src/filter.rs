@@ -0,0 +1,238 @@
+/*!
+Composable filters for deciding which records to emit.
+
+Filters are built from primitives like `level_at_least` and `target_starts_with`,
+and combined using `and`, `or`, and `not`:
+
+```
+use emit::{filter::{self, Filter}, Level};
+
+emit::set_filter(
+    filter::level_at_least(Level::Info)
+        .and(filter::target_starts_with("db").not())
+        .or(filter::level_at_least(Level::Error)),
+);
+```
+*/
+
+use crate::{Level, Record};
+
+/**
+A type that decides whether a record should be emitted.
+
+Any `Fn(&Record) -> bool` closure is a filter.
+*/
+pub trait Filter {
+    /**
+    Whether the record should be emitted.
+    */
+    fn matches(&self, record: &Record) -> bool;
+
+    /**
+    Match records that match both this filter and `other`.
+    */
+    fn and<F: Filter>(self, other: F) -> And<Self, F>
+    where
+        Self: Sized,
+    {
+        And(self, other)
+    }
+
+    /**
+    Match records that match either this filter or `other`.
+    */
+    fn or<F: Filter>(self, other: F) -> Or<Self, F>
+    where
+        Self: Sized,
+    {
+        Or(self, other)
+    }
+
+    /**
+    Match records that don't match this filter.
+    */
+    fn not(self) -> Not<Self>
+    where
+        Self: Sized,
+    {
+        Not(self)
+    }
+}
+
+impl<F> Filter for F
+where
+    F: Fn(&Record) -> bool,
+{
+    fn matches(&self, record: &Record) -> bool {
+        self(record)
+    }
+}
+
+/**
+Match records at or above the given level.
+*/
+pub fn level_at_least(level: Level) -> LevelAtLeast {
+    LevelAtLeast(level)
+}
+
+/**
+Match records with a level at or above the given numeric severity.
+
+This is useful for filtering custom levels, which don't have a standard level to compare against.
+*/
+pub fn severity_at_least(severity: u8) -> SeverityAtLeast {
+    SeverityAtLeast(severity)
+}
+
+/**
+Match records with a target that starts with the given prefix.
+*/
+pub fn target_starts_with(prefix: impl Into<String>) -> TargetStartsWith {
+    TargetStartsWith(prefix.into())
+}
+
+/**
+A filter created by `level_at_least`.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct LevelAtLeast(Level);
+
+impl Filter for LevelAtLeast {
+    fn matches(&self, record: &Record) -> bool {
+        record.level() >= self.0
+    }
+}
+
+/**
+A filter created by `severity_at_least`.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct SeverityAtLeast(u8);
+
+impl Filter for SeverityAtLeast {
+    fn matches(&self, record: &Record) -> bool {
+        record.level().severity() >= self.0
+    }
+}
+
+/**
+A filter created by `target_starts_with`.
+*/
+#[derive(Debug, Clone)]
+pub struct TargetStartsWith(String);
+
+impl Filter for TargetStartsWith {
+    fn matches(&self, record: &Record) -> bool {
+        record.target().starts_with(&self.0)
+    }
+}
+
+/**
+A filter created by `Filter::and`.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct And<A, B>(A, B);
+
+impl<A: Filter, B: Filter> Filter for And<A, B> {
+    fn matches(&self, record: &Record) -> bool {
+        self.0.matches(record) && self.1.matches(record)
+    }
+}
+
+/**
+A filter created by `Filter::or`.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct Or<A, B>(A, B);
+
+impl<A: Filter, B: Filter> Filter for Or<A, B> {
+    fn matches(&self, record: &Record) -> bool {
+        self.0.matches(record) || self.1.matches(record)
+    }
+}
+
+/**
+A filter created by `Filter::not`.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct Not<A>(A);
+
+impl<A: Filter> Filter for Not<A> {
+    fn matches(&self, record: &Record) -> bool {
+        !self.0.matches(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(filter: impl Filter, level: Level, target: &str) -> bool {
+        let record = crate::rt::__private::Record {
+            target,
+            ..crate::test_record(level, &[], &[])
+        };
+
+        filter.matches(&Record(&record))
+    }
+
+    #[test]
+    fn level_at_least_matches() {
+        assert!(matches(level_at_least(Level::Info), Level::Info, "app"));
+        assert!(matches(level_at_least(Level::Info), Level::Error, "app"));
+        assert!(!matches(level_at_least(Level::Info), Level::Debug, "app"));
+    }
+
+    #[test]
+    fn severity_at_least_matches() {
+        let notice = Level::custom("notice", 35);
+
+        assert!(matches(severity_at_least(35), notice, "app"));
+        assert!(matches(severity_at_least(35), Level::Warn, "app"));
+        assert!(!matches(severity_at_least(35), Level::Info, "app"));
+
+        assert!(matches(level_at_least(Level::Info), notice, "app"));
+        assert!(!matches(level_at_least(Level::Warn), notice, "app"));
+    }
+
+    #[test]
+    fn target_starts_with_matches() {
+        assert!(matches(target_starts_with("db"), Level::Info, "db"));
+        assert!(matches(target_starts_with("db"), Level::Info, "db::pool"));
+        assert!(!matches(target_starts_with("db"), Level::Info, "app::db"));
+    }
+
+    #[test]
+    fn and_or_not_matches() {
+        let info = || level_at_least(Level::Info);
+        let db = || target_starts_with("db");
+
+        assert!(matches(info().and(db()), Level::Info, "db"));
+        assert!(!matches(info().and(db()), Level::Debug, "db"));
+        assert!(!matches(info().and(db()), Level::Info, "app"));
+
+        assert!(matches(info().or(db()), Level::Debug, "db"));
+        assert!(matches(info().or(db()), Level::Info, "app"));
+        assert!(!matches(info().or(db()), Level::Debug, "app"));
+
+        assert!(matches(db().not(), Level::Info, "app"));
+        assert!(!matches(db().not(), Level::Info, "db"));
+    }
+
+    #[test]
+    fn composed_matches() {
+        // Info and above outside of `db`, or errors from anywhere
+        let filter = || {
+            level_at_least(Level::Info)
+                .and(target_starts_with("db").not())
+                .or(level_at_least(Level::Error))
+                .or(|record: &Record| record.target() == "audit")
+        };
+
+        assert!(matches(filter(), Level::Info, "app"));
+        assert!(!matches(filter(), Level::Debug, "app"));
+        assert!(!matches(filter(), Level::Warn, "db::pool"));
+        assert!(matches(filter(), Level::Error, "db::pool"));
+        assert!(matches(filter(), Level::Trace, "audit"));
+    }
+}
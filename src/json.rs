@@ -0,0 +1,458 @@
+/*!
+An emitter that writes records as newline-delimited JSON.
+*/
+
+use std::{fmt, io, mem, sync::Mutex};
+
+use sval::stream::{self, Stream};
+
+use crate::{timestamp::with_timestamp_format, Emitter, Record, TimestampFormat};
+
+/**
+How to represent floating-point values that JSON can't, like `NaN` and `Infinity`.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonFinite {
+    /**
+    Write non-finite values as `null`.
+    */
+    Null,
+    /**
+    Write non-finite values as the strings `"NaN"`, `"Infinity"`, and `"-Infinity"`.
+    */
+    String,
+}
+
+impl Default for NonFinite {
+    fn default() -> Self {
+        NonFinite::Null
+    }
+}
+
+/**
+An emitter that writes each record as a JSON object on its own line.
+
+Each object starts with the record's level as `lvl`, its rendered message as `msg`,
+and its target as `target`, followed by its key-values. Key-values with one of
+those keys are skipped so the object doesn't have duplicate keys:
+
+```text
+{"lvl":"info","msg":"Started on port 8080","target":"app","port":8080}
+```
+
+JSON only supports strings as object keys, so maps captured with other
+primitive keys, like a `BTreeMap<u32, String>`, have their keys written
+as strings. Maps with keys that are themselves structured can't be
+represented.
+
+Records that fail to serialize are discarded, along with any partially written output.
+*/
+pub struct JsonEmitter<W> {
+    writer: Mutex<W>,
+    non_finite: NonFinite,
+    timestamp_format: TimestampFormat,
+}
+
+impl<W> JsonEmitter<W>
+where
+    W: io::Write,
+{
+    /**
+    Create an emitter that writes to the given writer.
+    */
+    pub fn new(writer: W) -> Self {
+        JsonEmitter {
+            writer: Mutex::new(writer),
+            non_finite: NonFinite::default(),
+            timestamp_format: TimestampFormat::default(),
+        }
+    }
+
+    /**
+    Set how floating-point values like `NaN` and `Infinity` are written.
+    */
+    pub fn with_non_finite(mut self, non_finite: NonFinite) -> Self {
+        self.non_finite = non_finite;
+        self
+    }
+
+    /**
+    Set how the timestamp supplied for a record with `#[ts]` is written.
+
+    By default, timestamps are written as RFC 3339 date-times.
+    */
+    pub fn with_timestamp_format(mut self, timestamp_format: TimestampFormat) -> Self {
+        self.timestamp_format = timestamp_format;
+        self
+    }
+
+    /**
+    Get the inner writer back out of the emitter.
+    */
+    pub fn into_inner(self) -> W {
+        self.writer
+            .into_inner()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<W> Emitter for JsonEmitter<W>
+where
+    W: io::Write,
+{
+    fn emit(&self, record: &Record) {
+        with_timestamp_format(record, &self.timestamp_format, |record| {
+            if let Some(mut buf) = to_vec(record, self.non_finite) {
+                buf.push(b'\n');
+
+                let mut writer = self.writer.lock().unwrap_or_else(|err| err.into_inner());
+
+                drop(writer.write_all(&buf));
+            }
+        })
+    }
+
+    fn flush(&self) {
+        let mut writer = self.writer.lock().unwrap_or_else(|err| err.into_inner());
+
+        drop(writer.flush());
+    }
+}
+
+/**
+Get the JSON object the `JsonEmitter` would write for a record, as a `serde_json::Value`.
+*/
+pub(crate) fn to_value(record: &Record) -> serde_json::Value {
+    to_vec(record, NonFinite::default())
+        .and_then(|buf| serde_json::from_slice(&buf).ok())
+        .unwrap_or(serde_json::Value::Null)
+}
+
+pub(crate) fn to_vec(record: &Record, non_finite: NonFinite) -> Option<Vec<u8>> {
+    let mut stream = JsonCompatStream {
+        stream: sval_json::Writer::new(Vec::new()),
+        non_finite,
+        is_key: false,
+    };
+
+    sval::stream(&mut stream, record).ok()?;
+
+    stream.stream.end().ok()
+}
+
+/**
+A stream that replaces values JSON can't represent before they reach the JSON writer.
+
+That includes non-finite floats and map keys that aren't strings.
+*/
+struct JsonCompatStream<S> {
+    stream: S,
+    non_finite: NonFinite,
+    is_key: bool,
+}
+
+impl<S> JsonCompatStream<S>
+where
+    S: Stream,
+{
+    fn key_or(
+        &mut self,
+        key: impl fmt::Display,
+        value: impl FnOnce(&mut S) -> stream::Result,
+    ) -> stream::Result {
+        if mem::replace(&mut self.is_key, false) {
+            self.stream.str(&key.to_string())
+        } else {
+            value(&mut self.stream)
+        }
+    }
+}
+
+impl<S> Stream for JsonCompatStream<S>
+where
+    S: Stream,
+{
+    fn fmt(&mut self, v: stream::Arguments) -> stream::Result {
+        self.stream.fmt(v)
+    }
+
+    fn error(&mut self, v: stream::Source) -> stream::Result {
+        self.stream.error(v)
+    }
+
+    fn i64(&mut self, v: i64) -> stream::Result {
+        self.key_or(v, |stream| stream.i64(v))
+    }
+
+    fn u64(&mut self, v: u64) -> stream::Result {
+        self.key_or(v, |stream| stream.u64(v))
+    }
+
+    fn i128(&mut self, v: i128) -> stream::Result {
+        self.key_or(v, |stream| stream.i128(v))
+    }
+
+    fn u128(&mut self, v: u128) -> stream::Result {
+        self.key_or(v, |stream| stream.u128(v))
+    }
+
+    fn f64(&mut self, v: f64) -> stream::Result {
+        if v.is_finite() {
+            return self.key_or(v, |stream| stream.f64(v));
+        }
+
+        self.is_key = false;
+
+        match self.non_finite {
+            NonFinite::Null => self.stream.none(),
+            NonFinite::String if v.is_nan() => self.stream.str("NaN"),
+            NonFinite::String if v.is_sign_positive() => self.stream.str("Infinity"),
+            NonFinite::String => self.stream.str("-Infinity"),
+        }
+    }
+
+    fn bool(&mut self, v: bool) -> stream::Result {
+        self.key_or(v, |stream| stream.bool(v))
+    }
+
+    fn char(&mut self, v: char) -> stream::Result {
+        self.key_or(v, |stream| stream.char(v))
+    }
+
+    fn str(&mut self, v: &str) -> stream::Result {
+        self.is_key = false;
+        self.stream.str(v)
+    }
+
+    fn none(&mut self) -> stream::Result {
+        self.stream.none()
+    }
+
+    fn map_begin(&mut self, len: Option<usize>) -> stream::Result {
+        self.is_key = false;
+        self.stream.map_begin(len)
+    }
+
+    fn map_key(&mut self) -> stream::Result {
+        self.is_key = true;
+        self.stream.map_key()
+    }
+
+    fn map_value(&mut self) -> stream::Result {
+        // A key streamed as anything but a primitive doesn't clear the flag itself
+        self.is_key = false;
+        self.stream.map_value()
+    }
+
+    fn map_end(&mut self) -> stream::Result {
+        self.stream.map_end()
+    }
+
+    fn seq_begin(&mut self, len: Option<usize>) -> stream::Result {
+        self.is_key = false;
+        self.stream.seq_begin(len)
+    }
+
+    fn seq_elem(&mut self) -> stream::Result {
+        self.stream.seq_elem()
+    }
+
+    fn seq_end(&mut self) -> stream::Result {
+        self.stream.seq_end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use crate::rt::__private::{__PrivateCapture, Level, Part, ValueBag};
+
+    fn emit_floats(non_finite: NonFinite) -> String {
+        let emitter = JsonEmitter::new(Vec::new()).with_non_finite(non_finite);
+
+        let kvs = [
+            ("a", ValueBag::from(f64::NAN)),
+            ("b", ValueBag::from(f64::INFINITY)),
+            ("c", ValueBag::from(f64::NEG_INFINITY)),
+            ("d", ValueBag::from(1.5f64)),
+        ];
+        let record = crate::test_record(Level::Info, &kvs, &[Part::Text("floats")]);
+
+        emitter.emit(&Record(&record));
+
+        String::from_utf8(emitter.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn capture_error() {
+        let emitter = JsonEmitter::new(Vec::new());
+
+        let err = crate::CaptureError::new("failed to read");
+
+        let kvs = [
+            ("a", err.__private_capture_as_default()),
+            ("b", ValueBag::from(1)),
+        ];
+        let record = crate::test_record(Level::Info, &kvs, &[Part::Text("a: "), Part::Hole("a")]);
+
+        let record = Record(&record);
+
+        assert_eq!(
+            vec![("a", "failed to read")],
+            record
+                .capture_errors()
+                .map(|(k, err)| (k, err.msg()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("a: <error: failed to read>", record.msg().to_string());
+
+        emitter.emit(&record);
+
+        assert_eq!(
+            "{\"lvl\":\"info\",\"msg\":\"a: <error: failed to read>\",\"target\":\"test\",\"a\":{\"_capture_error\":\"failed to read\"},\"b\":1}\n",
+            String::from_utf8(emitter.into_inner()).unwrap()
+        );
+    }
+
+    #[test]
+    fn as_json_value() {
+        let kvs = [("a", ValueBag::from(1)), ("b", ValueBag::from(f64::NAN))];
+        let record = crate::test_record(Level::Warn, &kvs, &[Part::Text("a: "), Part::Hole("a")]);
+
+        let value = Record(&record).as_json_value();
+        let object = value.as_object().expect("expected a JSON object");
+
+        assert_eq!(
+            vec!["a", "b", "lvl", "msg", "target"],
+            object.keys().map(|k| k.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(Some("warn"), object["lvl"].as_str());
+        assert_eq!(Some("a: 1"), object["msg"].as_str());
+        assert_eq!(Some("test"), object["target"].as_str());
+        assert_eq!(Some(1), object["a"].as_u64());
+        assert!(object["b"].is_null());
+    }
+
+    #[test]
+    fn non_finite_null() {
+        assert_eq!(
+            "{\"lvl\":\"info\",\"msg\":\"floats\",\"target\":\"test\",\"a\":null,\"b\":null,\"c\":null,\"d\":1.5}\n",
+            emit_floats(NonFinite::Null)
+        );
+    }
+
+    #[test]
+    fn non_finite_string() {
+        assert_eq!(
+            "{\"lvl\":\"info\",\"msg\":\"floats\",\"target\":\"test\",\"a\":\"NaN\",\"b\":\"Infinity\",\"c\":\"-Infinity\",\"d\":1.5}\n",
+            emit_floats(NonFinite::String)
+        );
+    }
+
+    #[test]
+    fn non_string_map_keys() {
+        let emitter = JsonEmitter::new(Vec::new());
+
+        let mut map = BTreeMap::new();
+        map.insert(1u32, "a".to_owned());
+        map.insert(2u32, "b".to_owned());
+
+        let kvs = [("map", map.__private_capture_as_serde())];
+        let record = crate::test_record(Level::Info, &kvs, &[Part::Text("map")]);
+
+        emitter.emit(&Record(&record));
+
+        assert_eq!(
+            "{\"lvl\":\"info\",\"msg\":\"map\",\"target\":\"test\",\"map\":{\"1\":\"a\",\"2\":\"b\"}}\n",
+            String::from_utf8(emitter.into_inner()).unwrap()
+        );
+    }
+
+    #[test]
+    fn reserved_keys() {
+        let emitter = JsonEmitter::new(Vec::new());
+
+        let kvs = [
+            ("a", ValueBag::from(1)),
+            ("lvl", ValueBag::from("user")),
+            ("msg", ValueBag::from("user")),
+        ];
+        let record = crate::test_record(Level::Info, &kvs, &[Part::Text("a: "), Part::Hole("a")]);
+
+        emitter.emit(&Record(&record));
+
+        // Key-values with the same key as the level or message aren't written again
+        assert_eq!(
+            "{\"lvl\":\"info\",\"msg\":\"a: 1\",\"target\":\"test\",\"a\":1}\n",
+            String::from_utf8(emitter.into_inner()).unwrap()
+        );
+    }
+
+    #[test]
+    fn fmt_map_keys() {
+        struct FmtKey;
+
+        impl sval::value::Value for FmtKey {
+            fn stream(&self, stream: &mut sval::value::Stream) -> sval::value::Result {
+                stream.map_begin(Some(1))?;
+                stream.map_key(format_args!("key"))?;
+                stream.map_value(42u64)?;
+                stream.map_end()
+            }
+        }
+
+        let emitter = JsonEmitter::new(Vec::new());
+
+        let kvs = [("s", FmtKey.__private_capture_as_sval())];
+        let record = crate::test_record(Level::Info, &kvs, &[Part::Text("s")]);
+
+        emitter.emit(&Record(&record));
+
+        // The value after a formatted key isn't written as a key
+        assert_eq!(
+            "{\"lvl\":\"info\",\"msg\":\"s\",\"target\":\"test\",\"s\":{\"key\":42}}\n",
+            String::from_utf8(emitter.into_inner()).unwrap()
+        );
+    }
+
+    fn emit_timestamp(timestamp_format: TimestampFormat) -> String {
+        let emitter = JsonEmitter::new(Vec::new()).with_timestamp_format(timestamp_format);
+
+        let ts = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_633_077_000);
+
+        let kvs = [("ts", ts.__private_capture_as_timestamp())];
+        let record = crate::test_record(Level::Info, &kvs, &[Part::Text("ts")]);
+
+        emitter.emit(&Record(&record));
+
+        String::from_utf8(emitter.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn timestamp_rfc3339() {
+        assert_eq!(
+            "{\"lvl\":\"info\",\"msg\":\"ts\",\"target\":\"test\",\"ts\":\"2021-10-01T08:30:00Z\"}\n",
+            emit_timestamp(TimestampFormat::Rfc3339)
+        );
+    }
+
+    #[test]
+    fn timestamp_epoch_millis() {
+        assert_eq!(
+            "{\"lvl\":\"info\",\"msg\":\"ts\",\"target\":\"test\",\"ts\":1633077000000}\n",
+            emit_timestamp(TimestampFormat::EpochMillis)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn timestamp_custom() {
+        assert_eq!(
+            "{\"lvl\":\"info\",\"msg\":\"ts\",\"target\":\"test\",\"ts\":\"01/10/2021 08:30\"}\n",
+            emit_timestamp(TimestampFormat::Custom("%d/%m/%Y %H:%M".into()))
+        );
+    }
+}
@@ -1,65 +1,480 @@
 #![feature(once_cell)]
 
-use std::{error::Error, fmt, lazy::SyncOnceCell};
+#[cfg(feature = "json")]
+extern crate serde_json_lib as serde_json;
 
-use sval::value::{self, Value};
+use std::{error::Error, fmt, lazy::SyncOnceCell, time::SystemTime};
+
+use sval::value;
 
 /**
 Macros for emitting log events.
+
+The macros can be called by path, like `emit::info!`, or imported with `use emit::info`,
+so `#[macro_use]` isn't needed. Code they generate refers to this crate as `::emit`, so it
+resolves the same way wherever the macros are used from, even alongside modules named `emit`.
 */
 pub use emit_ct::{
-    debug, emit, error, info, source, trace, warn, as_debug, as_display, as_serde, as_sval,
+    as_debug, as_diff, as_display, as_exit_status, as_flags, as_hexdump, as_io_error,
+    as_iso_duration, as_iter, as_json_string, as_locked_debug, as_path, as_records, as_serde,
+    as_status, as_string, as_sval, as_timestamp, as_uptime, as_weak_debug, debug, emit,
+    emit_blocking, emit_kvs, error, fmt, info, redact, source, strip_ansi, trace, warn,
 };
 
+/**
+The severity of an emitted record.
+*/
+pub use rt::__private::Level;
+
+/**
+The error returned when parsing a `Level` from a string fails.
+*/
+pub use rt::__private::ParseLevelError;
+
+/**
+The key-values captured on a record.
+
+Keys are borrowed for as long as the record is, rather than being `&'static str`, because
+key-values passed to `emit_kvs!` can have keys that are built at runtime.
+*/
+pub use rt::__private::KeyValues;
+
+/**
+A captured value.
+
+This is the type of the values in `KeyValues`. It's exported so values can be built at runtime
+to emit alongside a record's own key-values with `emit_kvs!`.
+*/
+pub use rt::__private::ValueBag as Value;
+
+/**
+The location in source code a record was emitted from.
+*/
+pub use rt::__private::SourceLocation;
+
+/**
+A placeholder for a value that failed to be captured.
+*/
+pub use rt::__private::CaptureError;
+
+/**
+Options for rendering the message of a record with `Record::render_with`.
+*/
+pub use rt::__private::RenderOptions;
+
+/**
+A bit-flags type that can be captured with `#[as_flags]` as the names of its active flags.
+*/
+pub use rt::__private::Flags;
+
+/**
+Diagnostics for captured values, like `type_name` for getting a coarse tag for what kind of value was captured.
+*/
+pub use rt::__private::ValueExt;
+
 /**
 A type that receives and emits event records.
+
+Any `Fn(&Record)` closure is an emitter.
+
+Records borrow their captured values, and are only valid for the duration of the call to `emit`.
+The borrows never outlive the statement that emitted the record, so emitting inside an `async`
+function doesn't hold them across an `.await`. Emitters that buffer records or send them
+to another task should convert them into an `OwnedRecord` using `Record::to_owned` first.
+*/
+pub trait Emitter {
+    /**
+    Emit a record.
+    */
+    fn emit(&self, record: &Record);
+
+    /**
+    Block until any records emitted so far have been delivered.
+
+    Emitters that buffer records, or send them to another thread or async task, should wait
+    for them to be delivered before returning. Emitters that deliver records as they're emitted
+    only need to flush any writers they use. Blocking an async task stops it from making progress,
+    so async emitters should deliver records on a separate thread or runtime from the one that
+    calls `flush`, otherwise flushing from an async task on that runtime won't ever return.
+
+    Records emitted with `emit_blocking!` are flushed before the macro returns.
+    */
+    fn flush(&self) {}
+}
+
+impl<F> Emitter for F
+where
+    F: Fn(&Record),
+{
+    fn emit(&self, record: &Record) {
+        self(record)
+    }
+}
+
+/**
+A type that can be used as the target of a record.
+
+A `target` that isn't a string literal is usually the emitter to send the record to.
+If it's a `Target` instead then it becomes the target of the record itself, so structured
+targets like enums can be used in place of strings:
+
+```
+enum Route {
+    Billing,
+}
+
+impl emit::Target for Route {
+    fn as_str(&self) -> &str {
+        match self {
+            Route::Billing => "app::billing",
+        }
+    }
+}
+
+emit::info!(target: Route::Billing, "Charged an account");
+```
+
+If a type implements both `Target` and `Emitter` then it's used as a `Target`.
 */
-pub type Emitter = fn(&Record);
+pub trait Target {
+    /**
+    The target as a string.
+    */
+    fn as_str(&self) -> &str;
+}
+
+impl Target for str {
+    fn as_str(&self) -> &str {
+        self
+    }
+}
+
+impl Target for String {
+    fn as_str(&self) -> &str {
+        self
+    }
+}
+
+impl<'a, T: Target + ?Sized> Target for &'a T {
+    fn as_str(&self) -> &str {
+        (**self).as_str()
+    }
+}
 
 /**
 The global implicit emitter.
 */
-static EMITTER: SyncOnceCell<Emitter> = SyncOnceCell::new();
+static EMITTER: SyncOnceCell<Box<dyn Emitter + Send + Sync>> = SyncOnceCell::new();
+
+/**
+The emitter to use when no global emitter has been set.
+*/
+static FALLBACK: SyncOnceCell<ConsoleEmitter> = SyncOnceCell::new();
+
+/**
+The global filter applied before records are emitted.
+*/
+static FILTER: SyncOnceCell<Box<dyn Filter + Send + Sync>> = SyncOnceCell::new();
+
+/**
+The global enricher applied to records before they're filtered and emitted.
+*/
+static ENRICHER: SyncOnceCell<Box<dyn Fn(&mut OwnedRecord) + Send + Sync>> = SyncOnceCell::new();
+
+/**
+The global keys that are removed or redacted from records before they're emitted.
+*/
+static DENYLIST: SyncOnceCell<deny::Denylist> = SyncOnceCell::new();
 
 fn emit(record: &Record) {
+    if !matches_filter(record) {
+        return;
+    }
+
+    if let Some(emitter) = EMITTER.get() {
+        emitter.emit(record)
+    } else if let Some(fallback) = FALLBACK.get() {
+        fallback.emit(record)
+    }
+}
+
+/**
+Whether a record passes the global filter, if there is one.
+*/
+fn matches_filter(record: &Record) -> bool {
+    FILTER.get().map_or(true, |filter| filter.matches(record))
+}
+
+/**
+Flush the default emitter.
+
+This blocks until records emitted so far have been delivered by the global emitter, or the
+fallback if one hasn't been set. The guard returned by `Builder::install` calls this when it's
+dropped; call it directly in shutdown paths that don't hold onto a guard, like before calling
+`std::process::exit`. See `Emitter::flush` for details.
+*/
+pub fn flush() {
     if let Some(emitter) = EMITTER.get() {
-        emitter(record)
+        emitter.flush()
+    } else if let Some(fallback) = FALLBACK.get() {
+        fallback.flush()
     }
 }
 
 /**
 Set the default target to emit to.
 */
-pub fn target(emitter: Emitter) {
+pub fn target(emitter: impl Fn(&Record) + Send + Sync + 'static) {
+    set_emitter(emitter);
+}
+
+/**
+Set the default emitter.
+
+Unlike `target`, this method accepts any `Emitter`, not just closures.
+*/
+pub fn set_emitter(emitter: impl Emitter + Send + Sync + 'static) {
+    set_boxed_emitter(Box::new(emitter));
+}
+
+fn set_boxed_emitter(emitter: Box<dyn Emitter + Send + Sync>) {
+    // Uptimes captured with `#[as_uptime]` are measured from about when the emitter is set
+    rt::__private::process_start();
+
     drop(EMITTER.set(emitter));
 }
 
+/**
+Set the filter that decides which records are emitted to the default emitter.
+
+See the `filter` module for composable filters.
+*/
+pub fn set_filter(filter: impl Filter + Send + Sync + 'static) {
+    set_boxed_filter(Box::new(filter));
+}
+
+fn set_boxed_filter(filter: Box<dyn Filter + Send + Sync>) {
+    drop(FILTER.set(filter));
+}
+
+/**
+Set a function that can change the key-values of every record before it's emitted.
+
+The enricher gets an owned copy of each record, after the key-values in the context have been
+added to it. It's called for records emitted to the default emitter, and to emitters given as
+a `target`, but not for records forwarded to `tracing`. Records emitted to the default emitter
+are checked by the filter before they're copied, so records it discards aren't enriched, and
+checked again after the enricher has changed them.
+
+```
+emit::set_enricher(|record: &mut emit::OwnedRecord| {
+    record.insert_kv("pid", std::process::id());
+});
+
+// Records emitted here have a `pid` key-value
+emit::info!("started");
+```
+
+Key-values the enricher adds or replaces are emitted as the `OwnedValue` they're stored as in
+the owned record, so numbers and strings keep their type. Copying records to enrich them isn't free,
+so key-values that don't change for every record are better added to the emitter,
+like with `Builder::with_field`.
+*/
+pub fn set_enricher(enricher: impl Fn(&mut OwnedRecord) + Send + Sync + 'static) {
+    drop(ENRICHER.set(Box::new(enricher)));
+}
+
+/**
+Remove or redact key-values with the given keys from every record before it's emitted.
+
+The denylist is applied after the context and enricher, and again after the fields of a `Logger`
+or `Builder` are added, so a denied key can't be added back by them, whichever call site emitted
+the record. Like the enricher, it's applied to records
+emitted to the default emitter and to emitters given as a `target`, but not to records
+forwarded to `tracing`.
+
+```
+emit::set_key_denylist(&["password", "authorization"], emit::Denied::Redact);
+
+let password = "hunter2";
+
+// The password is captured as `"<redacted>"`
+emit::info!("logging in", password);
+```
+*/
+pub fn set_key_denylist(keys: &[&str], denied: Denied) {
+    drop(DENYLIST.set(deny::Denylist {
+        keys: keys.iter().map(|key| (*key).to_owned()).collect(),
+        denied,
+    }));
+}
+
+/**
+Set a secret key that fingerprints of redacted values are computed with.
+
+Values captured with `#[emit::redact]` are fingerprinted with a plain SHA-256 hash by default,
+which can be reversed for values with few possible inputs by hashing each of them. With a key,
+fingerprints are computed with HMAC-SHA-256 instead, so they can't be reproduced without it.
+The key can only be set once, and should be set before any records are emitted.
+
+```
+// Load the key from somewhere outside of the application's logs
+let key = std::env::var("REDACTION_KEY").unwrap_or_default();
+
+emit::set_redaction_key(key);
+```
+*/
+pub fn set_redaction_key(key: impl AsRef<[u8]>) {
+    rt::__private::set_redaction_key(key.as_ref());
+}
+
+/**
+Write records to `stderr` until an emitter is set.
+
+By default, records emitted before an emitter is set are discarded.
+Calling this function makes them visible instead. Once an emitter is set
+with `target` or `set_emitter`, records are only sent to it.
+*/
+pub fn fallback_to_stderr() {
+    drop(FALLBACK.set(ConsoleEmitter::new().with_stderr_level(Level::Trace)));
+}
+
 /**
 An emitted record.
 */
 pub struct Record<'a>(&'a rt::__private::Record<'a>);
 
-impl<'a> Value for Record<'a> {
+impl<'a> value::Value for Record<'a> {
     fn stream(&self, stream: &mut value::Stream) -> value::Result {
         self.0.stream(stream)
     }
 }
 
 impl<'a> Record<'a> {
+    /**
+    The level associated with this record.
+    */
+    pub fn level(&self) -> Level {
+        self.0.level
+    }
+
+    /**
+    The target associated with this record.
+
+    Unless a string target is given explicitly, this is the path of the module
+    the record was emitted from.
+    */
+    pub fn target(&self) -> &str {
+        self.0.target
+    }
+
+    /**
+    The file, line, and module this record was emitted from.
+    */
+    pub fn source_location(&self) -> SourceLocation<'a> {
+        self.0.location
+    }
+
+    /**
+    The key-values associated with this record.
+    */
+    pub fn kvs(&self) -> KeyValues<'a> {
+        self.0.kvs
+    }
+
     /**
     The formatted message associated with this record.
     */
-    pub fn msg<'b>(&'b self) -> impl fmt::Display + 'b {
+    pub fn msg(&self) -> impl fmt::Display + '_ {
         self.0.render_msg()
     }
 
+    /**
+    Whether this record has a message to render.
+
+    Records emitted with an empty template, like `emit::info!("", a, b)`, don't have a
+    message, so emitters can leave it out instead of writing an empty one.
+    */
+    pub fn has_message(&self) -> bool {
+        self.0.has_message()
+    }
+
+    /**
+    The formatted message associated with this record, rendered using the given options.
+
+    Emitters that render messages can keep their own `RenderOptions` to customize how
+    they're rendered, like the placeholder written for holes that don't have a value.
+    */
+    pub fn render_with<'b>(&'b self, options: RenderOptions<'b>) -> impl fmt::Display + 'b {
+        self.0.render_msg_with(options)
+    }
+
+    /**
+    The JSON object the `JsonEmitter` would write for this record.
+
+    This is handy for returning records from HTTP handlers without going through a writer.
+    Records that can't be represented as JSON, like ones with maps keyed by structured
+    values, are returned as `null`.
+    */
+    #[cfg(feature = "json")]
+    pub fn as_json_value(&self) -> serde_json::Value {
+        json::to_value(self)
+    }
+
     /**
     The original template associated with this record.
     */
-    pub fn template<'b>(&'b self) -> impl fmt::Display + 'b {
+    pub fn template(&self) -> impl fmt::Display + '_ {
         self.0.render_template()
     }
 
+    /**
+    The timestamp supplied for this record with `#[ts]`, if there is one.
+
+    Records don't capture the time they're emitted at by themselves, so emitters
+    that need a timestamp should fall back to `SystemTime::now` if this is `None`.
+    */
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        self.0
+            .kvs
+            .get(rt::__private::TIMESTAMP_KEY)
+            .and_then(|ts| ts.downcast_ref::<rt::__private::Timestamp>())
+            .map(|ts| ts.to_system_time())
+    }
+
+    /**
+    The key-values associated with this record that failed to be captured.
+
+    Keys are borrowed from the record, like the keys of `KeyValues`, since they may have
+    been built at runtime.
+    */
+    pub fn capture_errors(&self) -> impl Iterator<Item = (&'a str, &CaptureError)> + '_ {
+        self.0
+            .kvs
+            .sorted_key_values
+            .iter()
+            .filter_map(|(k, v)| rt::__private::capture_error(v).map(|err| (*k, err)))
+    }
+
+    /**
+    The keys of the holes in the template associated with this record, in the order they appear in.
+
+    This can be used to check that every hole has a key-value to fill it.
+    */
+    pub fn hole_names(&self) -> impl Iterator<Item = &'a str> {
+        self.0.template.hole_names()
+    }
+
+    /**
+    The original template associated with this record, written in the same syntax it was parsed from.
+
+    Templates from the macros are written exactly as they appear in the source, including any
+    attributes, expressions, and format specs in their holes, but not comments.
+    */
+    pub fn template_source(&self) -> impl fmt::Display + '_ {
+        self.0.template.source()
+    }
+
     /**
     The source error associated with this record.
     */
@@ -71,6 +486,124 @@ impl<'a> Record<'a> {
     }
 }
 
+/**
+Add key-values to every record emitted within a block on the current thread.
+
+Key-values are given as field-values, like `{ request_id, user: name }`. A field that's just
+an identifier is cloned, so it can still be used inside the block. Values need to implement
+`Display`. The key-values are removed when the block ends, restoring any values they replaced.
+
+```
+let request_id = 42;
+let name = "user";
+
+emit::with_fields!({ request_id, user: name }, {
+    emit::info!("handling request");
+});
+```
+*/
+#[macro_export]
+macro_rules! with_fields {
+    ({ $($key:ident $(: $value:expr)?),* $(,)? }, $body:block) => {{
+        let __emit_scope = ($($crate::context::scoped(
+            ::std::stringify!($key),
+            $crate::__private_with_fields_value!($key $(: $value)?),
+        ),)*);
+
+        $body
+    }};
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __private_with_fields_value {
+    ($key:ident) => {
+        ::std::clone::Clone::clone(&$key)
+    };
+    ($key:ident : $value:expr) => {
+        $value
+    };
+}
+
+/**
+Group the records emitted within a block on the current thread into a span.
+
+Records emitted within the block get `span_id` and `span_name` key-values. If the span is
+nested in another one, they also get a `parent_span_id` key-value with the id of the outer span.
+See the `span` module for details.
+
+```
+emit::span!("load_user", {
+    emit::info!("loading user");
+
+    emit::span!("query", {
+        emit::info!("running query");
+    });
+});
+```
+*/
+#[macro_export]
+macro_rules! span {
+    ($name:expr, $body:block) => {{
+        let __emit_span = $crate::span::enter($name);
+
+        $body
+    }};
+}
+
+/**
+Define an enum of custom levels with numeric severities.
+
+Each variant is converted into a `Level::Custom` with its name and severity, so custom levels
+can be ordered and filtered alongside the standard ones, which go from `10` for `Trace` up to
+`50` for `Error`. Custom levels can be given to `emit!` instead of a standard level.
+
+```
+emit::define_levels! {
+    pub enum MyLevel {
+        Notice = 35,
+        Critical = 60,
+    }
+}
+
+emit::emit!(MyLevel::Critical, "the disk is full");
+
+assert!(MyLevel::Notice.to_level() > emit::Level::Info);
+```
+*/
+#[macro_export]
+macro_rules! define_levels {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($variant:ident = $severity:expr),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        $vis enum $name {
+            $($variant),*
+        }
+
+        impl $name {
+            /**
+            Convert this custom level into a `Level`.
+            */
+            pub const fn to_level(self) -> $crate::Level {
+                match self {
+                    $($name::$variant => $crate::Level::custom(::std::stringify!($variant), $severity)),*
+                }
+            }
+        }
+
+        impl ::std::convert::From<$name> for $crate::Level {
+            fn from(level: $name) -> Self {
+                level.to_level()
+            }
+        }
+    };
+}
+
 /**
 Private entrypoint for the `ct` crate.
 
@@ -87,7 +620,60 @@ Code generation expects to find items at `emit::rt::__private`, not `emit_rt::__
 #[doc(hidden)]
 pub use emit_rt as rt;
 
+mod async_emitter;
+mod builder;
+mod console;
+pub mod context;
+mod counting;
+mod deny;
 mod emit;
+mod emitters;
+mod enrich;
+pub mod filter;
+mod logfmt;
+mod logger;
+mod owned;
+mod project;
+pub mod rate_limit;
+mod ring_buffer;
+pub mod span;
+mod timed;
+mod timestamp;
+
+pub use self::{
+    async_emitter::{AsyncEmitter, AsyncEmitterOptions, Overflow},
+    builder::{Builder, Installed},
+    console::{ConsoleEmitter, KvStyle}, counting::CountingEmitter, deny::Denied, emitters::{FnEmitter, NullEmitter}, filter::Filter,
+    logfmt::LogfmtEmitter, logger::Logger, owned::{OwnedRecord, OwnedValue}, project::Project, rate_limit::RateLimit,
+    ring_buffer::RingBufferEmitter, timed::{Latency, TimedEmitter}, timestamp::TimestampFormat,
+};
+
+#[cfg(feature = "tracing")]
+mod tracing;
+
+#[cfg(feature = "tracing")]
+pub use self::tracing::TracingEmitter;
+
+#[cfg(feature = "json")]
+pub mod json;
+
+#[cfg(feature = "json")]
+mod socket;
+
+#[cfg(feature = "json")]
+pub use self::{json::JsonEmitter, socket::SocketEmitter};
+
+#[cfg(feature = "syslog")]
+mod syslog;
+
+#[cfg(feature = "syslog")]
+pub use self::syslog::SyslogEmitter;
+
+#[cfg(feature = "export")]
+pub mod export;
+
+#[cfg(feature = "export")]
+pub use self::export::ExportEmitter;
 
 /**
 Private entrypoint for the `emit` crate.
@@ -99,6 +685,32 @@ pub mod __private {
     pub use crate::emit::*;
 }
 
+/**
+Build a record for tests, with a `test` target and source location.
+
+Other fields can be set using struct update syntax.
+*/
+#[cfg(test)]
+pub(crate) fn test_record<'a>(
+    level: Level,
+    kvs: &'a [(&'a str, Value<'a>)],
+    parts: &'a [rt::__private::Part<'a>],
+) -> rt::__private::Record<'a> {
+    rt::__private::Record {
+        level,
+        target: "test",
+        location: SourceLocation {
+            file: "test.rs",
+            line: 1,
+            module_path: "test",
+        },
+        kvs: KeyValues {
+            sorted_key_values: kvs,
+        },
+        template: rt::__private::template(parts),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -0,0 +1,165 @@
+/*!
+An emitter that writes records in the logfmt format.
+*/
+
+use std::{
+    fmt::{self, Write as _},
+    io,
+    sync::Mutex,
+};
+
+use crate::{Emitter, Record};
+
+/**
+The keys written at the start of each line before the record's key-values.
+*/
+const RESERVED_KEYS: [&str; 2] = ["lvl", "msg"];
+
+/**
+An emitter that writes each record as a line of space-separated `key=value` pairs.
+
+Each line starts with the record's level as `lvl`, and its rendered message as `msg`,
+followed by its key-values. Key-values are sorted by key, or kept in the order they were
+captured if the `no_sort` feature is enabled:
+
+```text
+lvl=info msg="hello world" user=alice attempts=3
+```
+
+Values are written using their `Display` implementation. Values that contain spaces, quotes,
+`=`, or control characters, and empty values, are quoted and escaped.
+
+Key-values named `lvl` or `msg` are skipped, so a line never has more than one value for a key.
+*/
+pub struct LogfmtEmitter<W> {
+    writer: Mutex<W>,
+}
+
+impl<W> LogfmtEmitter<W>
+where
+    W: io::Write,
+{
+    /**
+    Create an emitter that writes to the given writer.
+    */
+    pub fn new(writer: W) -> Self {
+        LogfmtEmitter {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /**
+    Get the inner writer back out of the emitter.
+    */
+    pub fn into_inner(self) -> W {
+        self.writer
+            .into_inner()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<W> Emitter for LogfmtEmitter<W>
+where
+    W: io::Write,
+{
+    fn emit(&self, record: &Record) {
+        let mut line = String::new();
+
+        write_pair(&mut line, "lvl", record.level());
+        line.push(' ');
+        write_pair(&mut line, "msg", record.msg());
+
+        for (k, v) in record
+            .kvs()
+            .sorted_key_values
+            .iter()
+            .filter(|(k, _)| !RESERVED_KEYS.contains(k))
+        {
+            line.push(' ');
+            write_pair(&mut line, k, v);
+        }
+
+        line.push('\n');
+
+        let mut writer = self.writer.lock().unwrap_or_else(|err| err.into_inner());
+
+        drop(writer.write_all(line.as_bytes()));
+    }
+
+    fn flush(&self) {
+        let mut writer = self.writer.lock().unwrap_or_else(|err| err.into_inner());
+
+        drop(writer.flush());
+    }
+}
+
+fn write_pair(line: &mut String, key: &str, value: impl fmt::Display) {
+    let value = value.to_string();
+
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == ' ' || c == '=' || c == '"' || c.is_control());
+
+    let _ = if needs_quotes {
+        write!(line, "{}={:?}", key, value)
+    } else {
+        write!(line, "{}={}", key, value)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::rt::__private::{Level, Part, ValueBag};
+
+    #[test]
+    fn emit_logfmt() {
+        let emitter = LogfmtEmitter::new(Vec::new());
+
+        let kvs = [
+            ("attempts", ValueBag::from(3)),
+            ("empty", ValueBag::from("")),
+            ("path", ValueBag::from("a=b")),
+            ("user", ValueBag::from("alice smith")),
+            ("quoted", ValueBag::from("say \"hi\"")),
+            ("word", ValueBag::from("alice")),
+        ];
+        let record = crate::test_record(
+            Level::Info,
+            &kvs,
+            &[Part::Text("hello "), Part::Hole("word")],
+        );
+
+        emitter.emit(&Record(&record));
+
+        assert_eq!(
+            "lvl=info msg=\"hello alice\" attempts=3 empty=\"\" path=\"a=b\" user=\"alice smith\" quoted=\"say \\\"hi\\\"\" word=alice\n",
+            String::from_utf8(emitter.into_inner()).unwrap()
+        );
+    }
+
+    #[test]
+    fn emit_logfmt_reserved_keys() {
+        let emitter = LogfmtEmitter::new(Vec::new());
+
+        let kvs = [
+            ("lvl", ValueBag::from("custom")),
+            ("msg", ValueBag::from("other")),
+            ("word", ValueBag::from("alice")),
+        ];
+        let record = crate::test_record(
+            Level::Info,
+            &kvs,
+            &[Part::Text("hello "), Part::Hole("word")],
+        );
+
+        emitter.emit(&Record(&record));
+
+        assert_eq!(
+            "lvl=info msg=\"hello alice\" word=alice\n",
+            String::from_utf8(emitter.into_inner()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,254 @@
+/*!
+A reusable emitter with a set of default fields.
+*/
+
+use std::{fmt, sync::Arc, thread};
+
+use crate::{
+    rt::__private::{KeyValues, ValueBag},
+    Emitter, Record,
+};
+
+/**
+An emitter that attaches a fixed set of key-values to every record.
+
+Loggers are cheap to clone and can be passed as the first argument of any of
+the emitting macros, or as their `target`:
+
+```
+let logger = emit::Logger::new(|record: &emit::Record| println!("{}", record.msg()))
+    .with_field("service", "api");
+
+emit::info!(logger, "Started on port {port}", port: 8080);
+emit::warn!(target: logger, "Running without TLS");
+```
+
+A key-value captured on the record itself takes precedence over a default
+field with the same key. Default fields are checked by the key denylist like any other key-value.
+
+Loggers can also attach the thread each record was emitted on with `with_thread_fields`.
+*/
+#[derive(Clone)]
+pub struct Logger {
+    emitter: Arc<dyn Emitter + Send + Sync>,
+    fields: Vec<(&'static str, Arc<dyn DefaultField>)>,
+    thread_fields: bool,
+}
+
+impl Logger {
+    /**
+    Create a logger that emits to the given emitter.
+    */
+    pub fn new(emitter: impl Emitter + Send + Sync + 'static) -> Self {
+        Logger {
+            emitter: Arc::new(emitter),
+            fields: Vec::new(),
+            thread_fields: false,
+        }
+    }
+
+    pub(crate) fn from_boxed(emitter: Box<dyn Emitter + Send + Sync>) -> Self {
+        Logger {
+            emitter: Arc::from(emitter),
+            fields: Vec::new(),
+            thread_fields: false,
+        }
+    }
+
+    /**
+    Add a default field to every record emitted through this logger.
+    */
+    pub fn with_field(
+        mut self,
+        key: &'static str,
+        value: impl fmt::Display + Send + Sync + 'static,
+    ) -> Self {
+        self.fields.retain(|(k, _)| *k != key);
+        self.fields.push((key, Arc::new(value)));
+        self
+    }
+
+    /**
+    Add the thread each record is emitted on to every record emitted through this logger.
+
+    Records get a `thread_id` key-value with the `Debug` representation of the thread's id,
+    like `ThreadId(2)`, and a `thread_name` key-value with its name if it has one. These are read
+    from `std::thread::current` when the logger emits the record. That's the thread that called
+    the emitting macro as long as the logger isn't behind an `AsyncEmitter`, which would emit
+    records on its background thread instead.
+    */
+    pub fn with_thread_fields(mut self) -> Self {
+        self.thread_fields = true;
+        self
+    }
+}
+
+impl Emitter for Logger {
+    fn emit(&self, record: &Record) {
+        if self.fields.is_empty() && !self.thread_fields {
+            return self.emitter.emit(record);
+        }
+
+        // The current thread and its id are borrowed by their key-values
+        let thread = self.thread_fields.then(thread::current);
+        let thread_id = thread.as_ref().map(|thread| thread.id());
+
+        let mut sorted_key_values = record.0.kvs.sorted_key_values.to_vec();
+
+        for (k, v) in &self.fields {
+            if record.0.kvs.get(k).is_none() {
+                sorted_key_values.push((k, v.to_value()));
+            }
+        }
+
+        if let (Some(thread), Some(thread_id)) = (&thread, &thread_id) {
+            if record.0.kvs.get("thread_id").is_none() {
+                sorted_key_values.push(("thread_id", ValueBag::capture_debug(thread_id)));
+            }
+
+            if let Some(name) = thread.name() {
+                if record.0.kvs.get("thread_name").is_none() {
+                    sorted_key_values.push(("thread_name", ValueBag::from(name)));
+                }
+            }
+        }
+
+        // Key-values are kept in the order they were added in if they aren't sorted
+        if !cfg!(feature = "no_sort") {
+            sorted_key_values.sort_by_key(|(k, _)| *k);
+        }
+
+        let record = crate::rt::__private::Record {
+            level: record.0.level,
+            target: record.0.target,
+            location: record.0.location,
+            kvs: KeyValues {
+                sorted_key_values: &sorted_key_values,
+            },
+            template: record.0.template,
+        };
+
+        // Fields are added after the denylist was applied to the record, so it's applied again
+        crate::emit::deny_keys(&record, |record| self.emitter.emit(record))
+    }
+
+    fn flush(&self) {
+        self.emitter.flush()
+    }
+}
+
+impl<'a> Emitter for &'a Logger {
+    fn emit(&self, record: &Record) {
+        (**self).emit(record)
+    }
+
+    fn flush(&self) {
+        (**self).flush()
+    }
+}
+
+trait DefaultField: Send + Sync {
+    fn to_value(&self) -> ValueBag;
+}
+
+impl<T> DefaultField for T
+where
+    T: fmt::Display + Send + Sync + 'static,
+{
+    fn to_value(&self) -> ValueBag {
+        ValueBag::capture_display(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    use crate::{
+        rt::__private::{Level, Part},
+        OwnedValue,
+    };
+
+    #[test]
+    fn emit_with_fields() {
+        let emitted = Arc::new(Mutex::new(Vec::new()));
+
+        let logger = Logger::new({
+            let emitted = emitted.clone();
+            move |record: &Record| {
+                emitted.lock().unwrap().push((
+                    record.msg().to_string(),
+                    sval_json::to_string(record).unwrap(),
+                ))
+            }
+        })
+        .with_field("service", "api")
+        .with_field("a", 1);
+
+        let kvs = [("a", ValueBag::from(42)), ("z", ValueBag::from(true))];
+        let record = crate::test_record(
+            Level::Info,
+            &kvs,
+            &[Part::Text("From "), Part::Hole("service")],
+        );
+
+        logger.emit(&Record(&record));
+
+        let emitted = emitted.lock().unwrap();
+
+        let expected = if cfg!(feature = "no_sort") {
+            "{\"lvl\":\"info\",\"msg\":\"From api\",\"target\":\"test\",\"a\":42,\"z\":true,\"service\":\"api\"}"
+        } else {
+            "{\"lvl\":\"info\",\"msg\":\"From api\",\"target\":\"test\",\"a\":42,\"service\":\"api\",\"z\":true}"
+        };
+
+        assert_eq!(vec![("From api".to_owned(), expected.to_owned())], *emitted);
+    }
+
+    #[test]
+    fn emit_with_thread_fields() {
+        let emitted = Arc::new(Mutex::new(Vec::new()));
+
+        let logger = Logger::new({
+            let emitted = emitted.clone();
+            move |record: &Record| emitted.lock().unwrap().push(record.to_owned())
+        })
+        .with_thread_fields();
+
+        fn emit(logger: &Logger) -> OwnedValue {
+            let record = crate::test_record(Level::Info, &[], &[Part::Text("test")]);
+
+            logger.emit(&Record(&record));
+            OwnedValue::from_debug(thread::current().id())
+        }
+
+        let named = thread::Builder::new()
+            .name("worker".into())
+            .spawn({
+                let logger = logger.clone();
+                move || emit(&logger)
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        // Unnamed threads only get an id
+        let unnamed = thread::spawn(move || emit(&logger)).join().unwrap();
+
+        let emitted = emitted.lock().unwrap();
+
+        assert_eq!(
+            vec![
+                ("thread_id", &named),
+                ("thread_name", &OwnedValue::from("worker"))
+            ],
+            emitted[0].kvs().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![("thread_id", &unnamed)],
+            emitted[1].kvs().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,539 @@
+/*!
+An owned copy of an emitted record.
+*/
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use crate::{
+    rt::__private::{ValueBag, Visit, VisitError},
+    Level, Record,
+};
+
+/**
+A record that owns its data, so it can be kept after it's been emitted.
+
+Owned records are useful for collecting records in tests. Two owned records
+are equal if they have the same level, target, template, and key-values.
+The timestamp is ignored by `PartialEq` and `Hash`; use `eq_with_timestamp`
+to compare it too.
+
+Values that are numbers, booleans, characters, or strings keep their type, so `42` and `"42"`
+are different values. Any other value is stored as its `Debug` representation.
+
+The data of an owned record is shared between its clones, so cloning one with `clone_owned`,
+like when it's sent to several emitters, doesn't copy or format anything again. Changing the
+key-values of a clone copies them first, so other clones aren't affected.
+*/
+#[derive(Clone, Debug)]
+pub struct OwnedRecord {
+    timestamp: SystemTime,
+    level: Level,
+    target: Arc<str>,
+    template: Arc<str>,
+    msg: Arc<str>,
+    kvs: Arc<Vec<(String, OwnedValue)>>,
+}
+
+impl<'a> Record<'a> {
+    /**
+    Copy this record into an owned record.
+
+    The owned record is timestamped with the timestamp supplied for the record with `#[ts]`,
+    or the current time if there isn't one.
+    */
+    pub fn to_owned(&self) -> OwnedRecord {
+        OwnedRecord {
+            timestamp: self.timestamp().unwrap_or_else(SystemTime::now),
+            level: self.level(),
+            target: self.target().into(),
+            template: self.template().to_string().into(),
+            msg: self.msg().to_string().into(),
+            kvs: Arc::new(
+                self.kvs()
+                    .sorted_key_values
+                    .iter()
+                    .map(|(k, v)| ((*k).to_owned(), OwnedValue::capture(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl OwnedRecord {
+    /**
+    Get another owned copy of this record.
+
+    Unlike `Record::to_owned`, this doesn't format the message or capture the key-values again.
+    The copy shares its data with this record until either of them is changed.
+    */
+    pub fn clone_owned(&self) -> OwnedRecord {
+        self.clone()
+    }
+
+    /**
+    Replace the timestamp of this record.
+    */
+    pub fn with_timestamp(mut self, timestamp: SystemTime) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /**
+    The timestamp supplied for this record, or the time it was copied at.
+    */
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    /**
+    The level associated with this record.
+    */
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /**
+    The target associated with this record.
+    */
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /**
+    The original template associated with this record.
+    */
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /**
+    The formatted message associated with this record.
+    */
+    pub fn msg(&self) -> &str {
+        &self.msg
+    }
+
+    /**
+    The key-values associated with this record, sorted by key.
+    */
+    pub fn kvs(&self) -> impl Iterator<Item = (&str, &OwnedValue)> {
+        self.kvs.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /**
+    Set the value of a key-value.
+
+    If the record already has a value for the key then it's replaced. Values that aren't
+    numbers, booleans, characters, or strings can be set with `OwnedValue::from_debug`.
+    */
+    pub fn insert_kv(&mut self, key: impl Into<String>, value: impl Into<OwnedValue>) {
+        let key = key.into();
+        let value = value.into();
+        let kvs = Arc::make_mut(&mut self.kvs);
+
+        match kvs.iter().position(|(k, _)| *k == key) {
+            Some(index) => kvs[index].1 = value,
+            // Key-values are kept in the order they were declared in with `no_sort`
+            None if cfg!(feature = "no_sort") => kvs.push((key, value)),
+            None => {
+                let index = kvs.partition_point(|(k, _)| *k < key);
+                kvs.insert(index, (key, value));
+            }
+        }
+    }
+
+    /**
+    Remove a key-value, returning its value if it was present.
+    */
+    pub fn remove_kv(&mut self, key: &str) -> Option<OwnedValue> {
+        let index = self.kvs.iter().position(|(k, _)| k == key)?;
+
+        Some(Arc::make_mut(&mut self.kvs).remove(index).1)
+    }
+
+    /**
+    Compare two records, including their timestamps.
+    */
+    pub fn eq_with_timestamp(&self, other: &Self) -> bool {
+        self == other && self.timestamp == other.timestamp
+    }
+}
+
+impl PartialEq for OwnedRecord {
+    fn eq(&self, other: &Self) -> bool {
+        self.level == other.level
+            && self.target == other.target
+            && self.template == other.template
+            && self.kvs == other.kvs
+    }
+}
+
+impl Eq for OwnedRecord {}
+
+impl Hash for OwnedRecord {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.level.hash(state);
+        self.target.hash(state);
+        self.template.hash(state);
+        self.kvs.hash(state);
+    }
+}
+
+/**
+A value of an owned record.
+
+Numbers, booleans, characters, and strings keep their type, so they're emitted the same way
+as the value they were copied from. Any other value is stored as its `Debug` representation.
+*/
+#[derive(Clone)]
+pub enum OwnedValue {
+    Bool(bool),
+    Char(char),
+    I64(i64),
+    U64(u64),
+    I128(i128),
+    U128(u128),
+    F64(f64),
+    Str(String),
+    /**
+    The `Debug` representation of a value that isn't a primitive.
+    */
+    Debug(String),
+}
+
+impl OwnedValue {
+    /**
+    Store a value as its `Debug` representation.
+    */
+    pub fn from_debug(value: impl fmt::Debug) -> Self {
+        OwnedValue::Debug(format!("{:?}", value))
+    }
+
+    /**
+    Get a value that can be emitted as part of a record.
+    */
+    pub fn to_value(&self) -> ValueBag {
+        match self {
+            OwnedValue::Bool(v) => ValueBag::from(*v),
+            OwnedValue::Char(v) => ValueBag::from(*v),
+            OwnedValue::I64(v) => ValueBag::from(*v),
+            OwnedValue::U64(v) => ValueBag::from(*v),
+            OwnedValue::I128(v) => ValueBag::from(*v),
+            OwnedValue::U128(v) => ValueBag::from(*v),
+            OwnedValue::F64(v) => ValueBag::from(*v),
+            OwnedValue::Str(v) => ValueBag::from(&**v),
+            // The `Debug` implementation writes the stored representation as-is
+            OwnedValue::Debug(_) => ValueBag::from_dyn_debug(self),
+        }
+    }
+
+    fn capture(value: &ValueBag) -> Self {
+        struct Capture(Option<OwnedValue>);
+
+        impl<'v> Visit<'v> for Capture {
+            fn visit_any(&mut self, value: ValueBag) -> Result<(), VisitError> {
+                self.0 = Some(OwnedValue::from_debug(value));
+                Ok(())
+            }
+
+            fn visit_u64(&mut self, value: u64) -> Result<(), VisitError> {
+                self.0 = Some(OwnedValue::U64(value));
+                Ok(())
+            }
+
+            fn visit_i64(&mut self, value: i64) -> Result<(), VisitError> {
+                self.0 = Some(OwnedValue::I64(value));
+                Ok(())
+            }
+
+            fn visit_u128(&mut self, value: u128) -> Result<(), VisitError> {
+                self.0 = Some(OwnedValue::U128(value));
+                Ok(())
+            }
+
+            fn visit_i128(&mut self, value: i128) -> Result<(), VisitError> {
+                self.0 = Some(OwnedValue::I128(value));
+                Ok(())
+            }
+
+            fn visit_f64(&mut self, value: f64) -> Result<(), VisitError> {
+                self.0 = Some(OwnedValue::F64(value));
+                Ok(())
+            }
+
+            fn visit_bool(&mut self, value: bool) -> Result<(), VisitError> {
+                self.0 = Some(OwnedValue::Bool(value));
+                Ok(())
+            }
+
+            fn visit_char(&mut self, value: char) -> Result<(), VisitError> {
+                self.0 = Some(OwnedValue::Char(value));
+                Ok(())
+            }
+
+            fn visit_str(&mut self, value: &str) -> Result<(), VisitError> {
+                self.0 = Some(OwnedValue::Str(value.to_owned()));
+                Ok(())
+            }
+        }
+
+        let mut capture = Capture(None);
+        let _ = value.visit(&mut capture);
+
+        capture.0.unwrap_or_else(|| OwnedValue::from_debug(value))
+    }
+}
+
+impl fmt::Debug for OwnedValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OwnedValue::Debug(v) => f.write_str(v),
+            v => fmt::Debug::fmt(&v.to_value(), f),
+        }
+    }
+}
+
+impl fmt::Display for OwnedValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OwnedValue::Str(v) | OwnedValue::Debug(v) => f.write_str(v),
+            v => fmt::Display::fmt(&v.to_value(), f),
+        }
+    }
+}
+
+impl PartialEq for OwnedValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (OwnedValue::Bool(a), OwnedValue::Bool(b)) => a == b,
+            (OwnedValue::Char(a), OwnedValue::Char(b)) => a == b,
+            (OwnedValue::I64(a), OwnedValue::I64(b)) => a == b,
+            (OwnedValue::U64(a), OwnedValue::U64(b)) => a == b,
+            (OwnedValue::I128(a), OwnedValue::I128(b)) => a == b,
+            (OwnedValue::U128(a), OwnedValue::U128(b)) => a == b,
+            // Floats are compared by their bits so values are always equal to themselves
+            (OwnedValue::F64(a), OwnedValue::F64(b)) => a.to_bits() == b.to_bits(),
+            (OwnedValue::Str(a), OwnedValue::Str(b)) => a == b,
+            (OwnedValue::Debug(a), OwnedValue::Debug(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for OwnedValue {}
+
+impl Hash for OwnedValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+
+        match self {
+            OwnedValue::Bool(v) => v.hash(state),
+            OwnedValue::Char(v) => v.hash(state),
+            OwnedValue::I64(v) => v.hash(state),
+            OwnedValue::U64(v) => v.hash(state),
+            OwnedValue::I128(v) => v.hash(state),
+            OwnedValue::U128(v) => v.hash(state),
+            OwnedValue::F64(v) => v.to_bits().hash(state),
+            OwnedValue::Str(v) | OwnedValue::Debug(v) => v.hash(state),
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($($ty:ty => $variant:ident,)*) => {
+        $(
+            impl From<$ty> for OwnedValue {
+                fn from(value: $ty) -> Self {
+                    OwnedValue::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from! {
+    bool => Bool,
+    char => Char,
+    i8 => I64,
+    i16 => I64,
+    i32 => I64,
+    i64 => I64,
+    u8 => U64,
+    u16 => U64,
+    u32 => U64,
+    u64 => U64,
+    i128 => I128,
+    u128 => U128,
+    f32 => F64,
+    f64 => F64,
+    String => Str,
+}
+
+impl<'a> From<&'a str> for OwnedValue {
+    fn from(value: &'a str) -> Self {
+        OwnedValue::Str(value.to_owned())
+    }
+}
+
+impl From<usize> for OwnedValue {
+    fn from(value: usize) -> Self {
+        OwnedValue::U64(value as u64)
+    }
+}
+
+impl From<isize> for OwnedValue {
+    fn from(value: isize) -> Self {
+        OwnedValue::I64(value as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{collections::HashSet, time::Duration};
+
+    use crate::rt::__private::{Part, ValueBag};
+
+    #[test]
+    fn eq_ignores_timestamp() {
+        let kvs = [("a", ValueBag::from(42)), ("b", ValueBag::from("42"))];
+        let record = crate::test_record(
+            Level::Info,
+            &kvs,
+            &[
+                Part::Text("a "),
+                Part::Hole("a"),
+                Part::Text(" b "),
+                Part::Hole("b"),
+            ],
+        );
+
+        let a = Record(&record)
+            .to_owned()
+            .with_timestamp(SystemTime::UNIX_EPOCH);
+        let b = Record(&record)
+            .to_owned()
+            .with_timestamp(SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+
+        assert_eq!(a, b);
+        assert!(!a.eq_with_timestamp(&b));
+        assert!(a.eq_with_timestamp(&a.clone()));
+
+        let set: HashSet<_> = vec![a.clone(), b].into_iter().collect();
+        assert_eq!(1, set.len());
+
+        assert_eq!("a 42 b 42", a.msg());
+        assert_eq!(
+            vec![("a", &OwnedValue::I64(42)), ("b", &OwnedValue::from("42"))],
+            a.kvs().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn insert_remove_kvs() {
+        let kvs = [("b", ValueBag::from(2))];
+        let record = crate::test_record(Level::Info, &kvs, &[Part::Text("test")]);
+
+        let mut owned = Record(&record).to_owned();
+
+        owned.insert_kv("a", "a");
+        owned.insert_kv("c", 3);
+        owned.insert_kv("b", 20);
+
+        let kvs = owned
+            .kvs()
+            .map(|(k, v)| (k, v.to_string()))
+            .collect::<Vec<_>>();
+
+        // Strings are stored as themselves instead of their `Debug` representation
+        if cfg!(feature = "no_sort") {
+            assert_eq!(
+                vec![
+                    ("b", "20".to_owned()),
+                    ("a", "a".to_owned()),
+                    ("c", "3".to_owned())
+                ],
+                kvs
+            );
+        } else {
+            assert_eq!(
+                vec![
+                    ("a", "a".to_owned()),
+                    ("b", "20".to_owned()),
+                    ("c", "3".to_owned())
+                ],
+                kvs
+            );
+        }
+
+        assert_eq!(Some(OwnedValue::I64(20)), owned.remove_kv("b"));
+        assert_eq!(None, owned.remove_kv("b"));
+    }
+
+    #[test]
+    fn owned_values() {
+        let kvs = [
+            ("a", ValueBag::from(-1)),
+            ("b", ValueBag::from(1.5)),
+            ("c", ValueBag::from(true)),
+            ("d", ValueBag::from("text")),
+            ("e", ValueBag::from_dyn_debug(&Some(1))),
+        ];
+        let record = crate::test_record(Level::Info, &kvs, &[Part::Text("test")]);
+
+        let owned = Record(&record).to_owned();
+        let values = owned.kvs().map(|(_, v)| v).collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                &OwnedValue::I64(-1),
+                &OwnedValue::F64(1.5),
+                &OwnedValue::Bool(true),
+                &OwnedValue::Str("text".to_owned()),
+                &OwnedValue::Debug("Some(1)".to_owned()),
+            ],
+            values
+        );
+
+        // Values keep their type when they're emitted again
+        assert_eq!(Some(-1), values[0].to_value().to_i64());
+        assert_eq!(Some("text"), values[3].to_value().to_borrowed_str());
+        assert_eq!("text", values[3].to_string());
+        assert_eq!("\"text\"", format!("{:?}", values[3]));
+        assert_eq!("Some(1)", values[4].to_value().to_string());
+    }
+
+    #[test]
+    fn clone_shares_data() {
+        let kvs = [("a", ValueBag::from(1))];
+        let record = crate::test_record(Level::Info, &kvs, &[Part::Text("a "), Part::Hole("a")]);
+
+        let a = Record(&record).to_owned();
+        let mut b = a.clone_owned();
+
+        assert!(a.eq_with_timestamp(&b));
+
+        // Clones point to the same data instead of formatting it again
+        assert_eq!(a.msg().as_ptr(), b.msg().as_ptr());
+        assert_eq!(a.template().as_ptr(), b.template().as_ptr());
+        assert!(std::ptr::eq(
+            a.kvs().next().unwrap().1,
+            b.kvs().next().unwrap().1
+        ));
+
+        // Changing a clone doesn't change the original
+        b.insert_kv("b", 2);
+
+        assert_eq!(
+            vec![("a", &OwnedValue::I64(1))],
+            a.kvs().collect::<Vec<_>>()
+        );
+        assert_eq!(2, b.kvs().count());
+    }
+}
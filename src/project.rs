@@ -0,0 +1,150 @@
+/*!
+An emitter that only forwards chosen key-values.
+*/
+
+use crate::{
+    rt::__private::{template, KeyValues, Part, TIMESTAMP_KEY},
+    Emitter, Record,
+};
+
+/**
+An emitter that drops any key-values that aren't in a fixed set of keys.
+
+This is useful for sinks where the size of each record matters. The level,
+target, and message of records are always forwarded, along with their
+timestamp if they have one. Holes in the template for dropped key-values are
+rendered into the message before it's forwarded, so the message is unchanged.
+
+```
+let emitter = emit::Project::new(
+    |record: &emit::Record| println!("{}", record.msg()),
+    ["user", "status"],
+);
+
+emit::info!(target: emitter, "{user} got {status} for {path}", user: "ferris", status: 200, path: "/");
+```
+*/
+pub struct Project<E> {
+    emitter: E,
+    keys: Vec<String>,
+}
+
+impl<E> Project<E> {
+    /**
+    Forward records to `emitter` with just the key-values in `keys`.
+    */
+    pub fn new(emitter: E, keys: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Project {
+            emitter,
+            keys: keys.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl<E> Emitter for Project<E>
+where
+    E: Emitter,
+{
+    fn emit(&self, record: &Record) {
+        let sorted_key_values: Vec<_> = record
+            .0
+            .kvs
+            .sorted_key_values
+            .iter()
+            .filter(|(k, _)| *k == TIMESTAMP_KEY || self.keys.iter().any(|key| key == k))
+            .cloned()
+            .collect();
+
+        let kvs = KeyValues {
+            sorted_key_values: &sorted_key_values,
+        };
+
+        // If any holes would be left empty then the message is rendered up-front
+        let msg;
+        let parts;
+        let template = if record
+            .0
+            .template
+            .parts()
+            .iter()
+            .any(|part| {
+                matches!(part, Part::Hole(label) | Part::FormattedHole(label, _) if !kvs.contains_key(label))
+            })
+        {
+            msg = record.msg().to_string();
+            parts = [Part::Text(&msg)];
+
+            template(&parts)
+        } else {
+            record.0.template
+        };
+
+        let record = crate::rt::__private::Record {
+            level: record.0.level,
+            target: record.0.target,
+            location: record.0.location,
+            kvs,
+            template,
+        };
+
+        self.emitter.emit(&Record(&record))
+    }
+
+    fn flush(&self) {
+        self.emitter.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    use crate::rt::__private::{Level, ValueBag};
+
+    #[test]
+    fn project_fields() {
+        let emitted = Arc::new(Mutex::new(Vec::new()));
+
+        let project = Project::new(
+            {
+                let emitted = emitted.clone();
+                move |record: &Record| {
+                    emitted.lock().unwrap().push((
+                        record.msg().to_string(),
+                        sval_json::to_string(record).unwrap(),
+                    ))
+                }
+            },
+            ["b", "d"],
+        );
+
+        let kvs = [
+            ("a", ValueBag::from(1)),
+            ("b", ValueBag::from(2)),
+            ("c", ValueBag::from(3)),
+            ("d", ValueBag::from(4)),
+        ];
+        let record = crate::test_record(
+            Level::Info,
+            &kvs,
+            &[
+                Part::Text("a is "),
+                Part::Hole("a"),
+                Part::Text(" and b is "),
+                Part::Hole("b"),
+            ],
+        );
+
+        project.emit(&Record(&record));
+
+        assert_eq!(
+            vec![(
+                "a is 1 and b is 2".to_owned(),
+                "{\"lvl\":\"info\",\"msg\":\"a is 1 and b is 2\",\"target\":\"test\",\"b\":2,\"d\":4}".to_owned()
+            )],
+            *emitted.lock().unwrap()
+        );
+    }
+}
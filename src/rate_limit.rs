@@ -0,0 +1,459 @@
+/*!
+An emitter that suppresses repeated records.
+*/
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    rt::__private::{template, KeyValues, Level, Part, SourceLocation, ValueBag},
+    Emitter, Record,
+};
+
+/**
+A source of the current time.
+*/
+pub trait Clock {
+    /**
+    Get the current time.
+    */
+    fn now(&self) -> Instant;
+}
+
+/**
+A clock that reads the system time.
+*/
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/**
+An emitter that only forwards a limited number of similar records in a
+window of time.
+
+Records are considered similar when they share the same template, or the
+same value for a chosen key. Once more than the limit have been emitted in
+a window, any others are suppressed. The next similar record after the
+window ends is preceded by a summary of how many records were suppressed,
+with the suppressed count in a `suppressed` key.
+
+The summary is only emitted when a similar record arrives after the window
+has ended, not when the window itself ends. If no similar record arrives,
+the summary is delayed until its key is no longer tracked.
+
+Only a fixed number of recently seen keys are tracked. When a new key would
+go over that number, the least recently seen key is dropped, and a summary
+of any records it suppressed is emitted with the level, target, and source
+location of the first record in its window.
+*/
+pub struct RateLimit<E, C = SystemClock> {
+    emitter: E,
+    clock: C,
+    limit: usize,
+    window: Duration,
+    key: Option<&'static str>,
+    capacity: usize,
+    windows: Mutex<Windows>,
+}
+
+/**
+The windows of recently seen keys.
+
+Keys are ordered by a counter that's bumped each time one is seen, so the
+least recently seen key is always the first in `order`.
+*/
+#[derive(Default)]
+struct Windows {
+    by_key: HashMap<String, Window>,
+    order: BTreeMap<u64, String>,
+    next: u64,
+}
+
+struct Window {
+    last_seen: u64,
+    start: Instant,
+    emitted: usize,
+    suppressed: usize,
+    origin: Origin,
+}
+
+/**
+An owned copy of the metadata of the first record in a window.
+*/
+struct Origin {
+    level: Level,
+    target: String,
+    file: String,
+    line: u32,
+    module_path: String,
+}
+
+/**
+A count of records that were suppressed for a key.
+*/
+struct Summary {
+    key: String,
+    suppressed: usize,
+    origin: Origin,
+}
+
+impl Windows {
+    /**
+    Get the window for a key, making it the most recently seen.
+
+    If the key is new and there are already `capacity` keys, the least
+    recently seen one is removed and returned.
+    */
+    fn get_or_insert(
+        &mut self,
+        key: &str,
+        capacity: usize,
+        insert: impl FnOnce() -> Window,
+    ) -> (&mut Window, Option<(String, Window)>) {
+        let seen = self.next;
+        self.next += 1;
+
+        let mut evicted = None;
+
+        if let Some(window) = self.by_key.get_mut(key) {
+            let key = self
+                .order
+                .remove(&window.last_seen)
+                .expect("missing order for a tracked key");
+            self.order.insert(seen, key);
+        } else {
+            if self.by_key.len() >= capacity.max(1) {
+                let oldest = *self
+                    .order
+                    .keys()
+                    .next()
+                    .expect("missing order for tracked keys");
+                let oldest = self.order.remove(&oldest).expect("missing oldest key");
+                let window = self
+                    .by_key
+                    .remove(&oldest)
+                    .expect("missing window for a tracked key");
+
+                evicted = Some((oldest, window));
+            }
+
+            self.order.insert(seen, key.to_owned());
+            self.by_key.insert(key.to_owned(), insert());
+        }
+
+        let window = self
+            .by_key
+            .get_mut(key)
+            .expect("missing window for a tracked key");
+        window.last_seen = seen;
+
+        (window, evicted)
+    }
+}
+
+impl<E> RateLimit<E> {
+    /**
+    Forward at most `limit` similar records to `emitter` in each `window`.
+    */
+    pub fn new(emitter: E, limit: usize, window: Duration) -> Self {
+        RateLimit {
+            emitter,
+            clock: SystemClock,
+            limit,
+            window,
+            key: None,
+            capacity: 128,
+            windows: Mutex::new(Windows::default()),
+        }
+    }
+}
+
+impl<E, C> RateLimit<E, C> {
+    /**
+    Use the value of the given key to group similar records instead of their template.
+
+    Records without the key are grouped together.
+    */
+    pub fn with_key(mut self, key: &'static str) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /**
+    Set the number of recently seen keys to track.
+    */
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /**
+    Use a different source of time for windows.
+    */
+    pub fn with_clock<T>(self, clock: T) -> RateLimit<E, T> {
+        RateLimit {
+            emitter: self.emitter,
+            clock,
+            limit: self.limit,
+            window: self.window,
+            key: self.key,
+            capacity: self.capacity,
+            windows: self.windows,
+        }
+    }
+}
+
+impl<E, C> Emitter for RateLimit<E, C>
+where
+    E: Emitter,
+    C: Clock,
+{
+    fn emit(&self, record: &Record) {
+        let key = match self.key {
+            Some(key) => record
+                .kvs()
+                .get(key)
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            None => record.template().to_string(),
+        };
+
+        let now = self.clock.now();
+
+        let (emit, summaries) = {
+            let mut windows = self.windows.lock().unwrap_or_else(|err| err.into_inner());
+
+            let (window, evicted) = windows.get_or_insert(&key, self.capacity, || Window {
+                last_seen: 0,
+                start: now,
+                emitted: 0,
+                suppressed: 0,
+                origin: Origin::new(record),
+            });
+
+            let mut summaries = Vec::new();
+
+            if now.saturating_duration_since(window.start) >= self.window {
+                if window.suppressed > 0 {
+                    summaries.push(Summary {
+                        key: key.clone(),
+                        suppressed: window.suppressed,
+                        origin: Origin::new(record),
+                    });
+                }
+
+                window.start = now;
+                window.emitted = 0;
+                window.suppressed = 0;
+                window.origin = Origin::new(record);
+            }
+
+            let emit = if window.emitted < self.limit {
+                window.emitted += 1;
+                true
+            } else {
+                window.suppressed += 1;
+                false
+            };
+
+            if let Some((key, window)) = evicted {
+                if window.suppressed > 0 {
+                    summaries.push(Summary {
+                        key,
+                        suppressed: window.suppressed,
+                        origin: window.origin,
+                    });
+                }
+            }
+
+            (emit, summaries)
+        };
+
+        for summary in summaries {
+            summary.emit(&self.emitter);
+        }
+
+        if emit {
+            self.emitter.emit(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.emitter.flush()
+    }
+}
+
+impl Origin {
+    fn new(record: &Record) -> Self {
+        let location = record.source_location();
+
+        Origin {
+            level: record.level(),
+            target: record.target().to_owned(),
+            file: location.file.to_owned(),
+            line: location.line,
+            module_path: location.module_path.to_owned(),
+        }
+    }
+}
+
+impl Summary {
+    fn emit<E: Emitter>(&self, emitter: &E) {
+        let summary = crate::rt::__private::Record {
+            level: self.origin.level,
+            target: &self.origin.target,
+            location: SourceLocation {
+                file: &self.origin.file,
+                line: self.origin.line,
+                module_path: &self.origin.module_path,
+            },
+            kvs: KeyValues {
+                sorted_key_values: &[
+                    ("key", ValueBag::from(&*self.key)),
+                    ("suppressed", ValueBag::from(self.suppressed)),
+                ],
+            },
+            template: template(&[
+                Part::Text("suppressed "),
+                Part::Hole("suppressed"),
+                Part::Text(" similar records"),
+            ]),
+        };
+
+        emitter.emit(&Record(&summary));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{cell::Cell, sync::Arc};
+
+    use crate::rt::__private::Level;
+
+    struct FakeClock {
+        start: Instant,
+        elapsed: Arc<Mutex<Duration>>,
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            self.start + *self.elapsed.lock().unwrap()
+        }
+    }
+
+    #[test]
+    fn suppress_repeated_templates() {
+        let emitted = Arc::new(Mutex::new(Vec::new()));
+        let elapsed = Arc::new(Mutex::new(Duration::from_secs(0)));
+
+        let emitter = RateLimit::new(
+            {
+                let emitted = emitted.clone();
+                move |record: &Record| emitted.lock().unwrap().push(record.msg().to_string())
+            },
+            2,
+            Duration::from_secs(10),
+        )
+        .with_clock(FakeClock {
+            start: Instant::now(),
+            elapsed: elapsed.clone(),
+        });
+
+        let n = Cell::new(0);
+        let emit = |text: &'static str| {
+            n.set(n.get() + 1);
+
+            let parts = [Part::Text(text), Part::Hole("n")];
+            let kvs = [("n", ValueBag::from(n.get()))];
+            let record = crate::test_record(Level::Error, &kvs, &parts);
+
+            emitter.emit(&Record(&record));
+        };
+
+        for _ in 0..5 {
+            emit("failed ");
+        }
+        emit("other ");
+
+        *elapsed.lock().unwrap() = Duration::from_secs(11);
+        emit("failed ");
+
+        assert_eq!(
+            vec![
+                "failed 1",
+                "failed 2",
+                "other 6",
+                "suppressed 3 similar records",
+                "failed 7",
+            ],
+            *emitted.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn summarize_evicted_keys() {
+        let emitted = Arc::new(Mutex::new(Vec::new()));
+
+        let emitter = RateLimit::new(
+            {
+                let emitted = emitted.clone();
+                move |record: &Record| {
+                    emitted
+                        .lock()
+                        .unwrap()
+                        .push((record.target().to_owned(), record.msg().to_string()))
+                }
+            },
+            1,
+            Duration::from_secs(10),
+        )
+        .with_capacity(2);
+
+        let emit = |target: &'static str, text: &'static str| {
+            let parts = [Part::Text(text)];
+            let record = crate::rt::__private::Record {
+                target,
+                ..crate::test_record(Level::Error, &[], &parts)
+            };
+
+            emitter.emit(&Record(&record));
+        };
+
+        emit("a", "failed a");
+        emit("a", "failed a");
+        emit("a", "failed a");
+        emit("b", "failed b");
+
+        // Seeing `a` again keeps it tracked, so `b` is dropped next
+        emit("a", "failed a");
+        emit("c", "failed c");
+        emit("d", "failed d");
+
+        let emitted = emitted.lock().unwrap();
+        let emitted = emitted
+            .iter()
+            .map(|(target, msg)| (&**target, &**msg))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                ("a", "failed a"),
+                ("b", "failed b"),
+                ("c", "failed c"),
+                ("a", "suppressed 3 similar records"),
+                ("d", "failed d"),
+            ],
+            emitted
+        );
+    }
+}
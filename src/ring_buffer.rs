@@ -0,0 +1,119 @@
+/*!
+An emitter that keeps the most recent records in memory.
+*/
+
+use std::{collections::VecDeque, sync::Mutex};
+
+use crate::{Emitter, OwnedRecord, Record};
+
+/**
+An emitter that keeps the last `capacity` records it's emitted in a circular buffer.
+
+Once the buffer is full, each new record replaces the oldest one. The buffer can be
+inspected at runtime with `snapshot`, like in a `/debug/logs` endpoint.
+*/
+pub struct RingBufferEmitter {
+    capacity: usize,
+    records: Mutex<VecDeque<OwnedRecord>>,
+}
+
+impl RingBufferEmitter {
+    /**
+    Keep the last `capacity` records that are emitted.
+    */
+    pub fn new(capacity: usize) -> Self {
+        RingBufferEmitter {
+            capacity,
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /**
+    Get a copy of the records in the buffer, from oldest to newest.
+    */
+    pub fn snapshot(&self) -> Vec<OwnedRecord> {
+        self.records
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter()
+            .map(OwnedRecord::clone_owned)
+            .collect()
+    }
+}
+
+impl Emitter for RingBufferEmitter {
+    fn emit(&self, record: &Record) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        // Copy the record before taking the lock
+        let record = record.to_owned();
+
+        let mut records = self.records.lock().unwrap_or_else(|err| err.into_inner());
+
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+
+        records.push_back(record);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::rt::__private::{Level, Part, ValueBag};
+
+    fn emit(emitter: &impl Emitter, id: u64) {
+        let kvs = [("id", ValueBag::from(id))];
+        let record = crate::test_record(
+            Level::Info,
+            &kvs,
+            &[Part::Text("request "), Part::Hole("id")],
+        );
+
+        emitter.emit(&Record(&record));
+    }
+
+    #[test]
+    fn keeps_most_recent() {
+        let emitter = RingBufferEmitter::new(3);
+
+        for id in 0..5 {
+            emit(&emitter, id);
+        }
+
+        assert_eq!(
+            vec!["request 2", "request 3", "request 4"],
+            emitter
+                .snapshot()
+                .iter()
+                .map(|record| record.msg().to_owned())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn snapshot_shares_data() {
+        let emitter = RingBufferEmitter::new(1);
+
+        emit(&emitter, 0);
+
+        // Snapshots don't format the buffered records again
+        let a = emitter.snapshot();
+        let b = emitter.snapshot();
+
+        assert_eq!(a[0].msg().as_ptr(), b[0].msg().as_ptr());
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let emitter = RingBufferEmitter::new(0);
+
+        emit(&emitter, 0);
+
+        assert!(emitter.snapshot().is_empty());
+    }
+}
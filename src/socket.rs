@@ -0,0 +1,337 @@
+/*!
+An emitter that writes records as newline-delimited JSON to a socket.
+*/
+
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+    net::{SocketAddr, TcpStream},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+#[cfg(unix)]
+use std::{os::unix::net::UnixStream, path::PathBuf};
+
+use crate::{
+    json::{self, NonFinite},
+    timestamp::with_timestamp_format,
+    Emitter, Record, TimestampFormat,
+};
+
+/**
+How long to wait when connecting or writing to the socket before giving up.
+*/
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/**
+An emitter that writes each record as a JSON object on its own line to a TCP or Unix socket.
+
+Records are written in the same format as the `JsonEmitter`, for shipping them to a local collector.
+
+The socket is connected when the first record is emitted. If connecting or writing fails then
+the connection is dropped, and a new one is attempted when a record is emitted after the
+reconnect interval has passed, which is 1 second by default. Connecting and writing each
+time out after 1 second, so an unresponsive collector doesn't block the caller for long.
+
+Lines that can't be written are buffered in memory until they can be, up to a limit of 64KiB
+by default. When the buffer is full, the oldest lines are discarded to make room for newer ones.
+A line that fails partway through being written is written again in full on the next connection.
+*/
+pub struct SocketEmitter {
+    addr: Addr,
+    non_finite: NonFinite,
+    timestamp_format: TimestampFormat,
+    reconnect_interval: Duration,
+    max_buffered: usize,
+    state: Mutex<State>,
+}
+
+enum Addr {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl Addr {
+    fn connect(&self) -> io::Result<Box<dyn Write + Send>> {
+        match self {
+            Addr::Tcp(addr) => {
+                let stream = TcpStream::connect_timeout(addr, TIMEOUT)?;
+                stream.set_write_timeout(Some(TIMEOUT))?;
+
+                Ok(Box::new(stream))
+            }
+            #[cfg(unix)]
+            Addr::Unix(path) => {
+                let stream = UnixStream::connect(path)?;
+                stream.set_write_timeout(Some(TIMEOUT))?;
+
+                Ok(Box::new(stream))
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    stream: Option<Box<dyn Write + Send>>,
+    last_connect: Option<Instant>,
+    buffered: VecDeque<Vec<u8>>,
+    buffered_len: usize,
+}
+
+impl SocketEmitter {
+    /**
+    Create an emitter that writes to a TCP socket at the given address.
+    */
+    pub fn tcp(addr: SocketAddr) -> Self {
+        SocketEmitter::new(Addr::Tcp(addr))
+    }
+
+    /**
+    Create an emitter that writes to a Unix domain socket at the given path.
+    */
+    #[cfg(unix)]
+    pub fn unix(path: impl Into<PathBuf>) -> Self {
+        SocketEmitter::new(Addr::Unix(path.into()))
+    }
+
+    fn new(addr: Addr) -> Self {
+        SocketEmitter {
+            addr,
+            non_finite: NonFinite::default(),
+            timestamp_format: TimestampFormat::default(),
+            reconnect_interval: Duration::from_secs(1),
+            max_buffered: 64 * 1024,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /**
+    Set how floating-point values like `NaN` and `Infinity` are written.
+    */
+    pub fn with_non_finite(mut self, non_finite: NonFinite) -> Self {
+        self.non_finite = non_finite;
+        self
+    }
+
+    /**
+    Set how the timestamp supplied for a record with `#[ts]` is written.
+    */
+    pub fn with_timestamp_format(mut self, timestamp_format: TimestampFormat) -> Self {
+        self.timestamp_format = timestamp_format;
+        self
+    }
+
+    /**
+    Set the minimum time between attempts to connect to the socket.
+    */
+    pub fn with_reconnect_interval(mut self, reconnect_interval: Duration) -> Self {
+        self.reconnect_interval = reconnect_interval;
+        self
+    }
+
+    /**
+    Set the maximum number of bytes of lines to keep while the socket can't be written to.
+    */
+    pub fn with_max_buffered(mut self, max_buffered: usize) -> Self {
+        self.max_buffered = max_buffered;
+        self
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /**
+    Write as many buffered lines as possible, connecting to the socket if needed.
+    */
+    fn write_buffered(&self, state: &mut State) {
+        if state.stream.is_none() {
+            let now = Instant::now();
+
+            match state.last_connect {
+                Some(last) if now.duration_since(last) < self.reconnect_interval => return,
+                _ => state.last_connect = Some(now),
+            }
+
+            state.stream = self.addr.connect().ok();
+        }
+
+        while let (Some(stream), Some(line)) = (state.stream.as_mut(), state.buffered.front()) {
+            if stream.write_all(line).is_err() {
+                state.stream = None;
+                return;
+            }
+
+            state.buffered_len -= line.len();
+            state.buffered.pop_front();
+        }
+    }
+}
+
+impl Emitter for SocketEmitter {
+    fn emit(&self, record: &Record) {
+        let mut line = None;
+        with_timestamp_format(record, &self.timestamp_format, |record| {
+            line = json::to_vec(record, self.non_finite);
+        });
+
+        let mut line = match line {
+            Some(line) => line,
+            None => return,
+        };
+        line.push(b'\n');
+
+        let mut state = self.lock();
+
+        state.buffered_len += line.len();
+        state.buffered.push_back(line);
+
+        while state.buffered_len > self.max_buffered {
+            match state.buffered.pop_front() {
+                Some(discarded) => state.buffered_len -= discarded.len(),
+                None => break,
+            }
+        }
+
+        self.write_buffered(&mut state);
+    }
+
+    fn flush(&self) {
+        let mut state = self.lock();
+
+        self.write_buffered(&mut state);
+
+        if let Some(stream) = state.stream.as_mut() {
+            if stream.flush().is_err() {
+                state.stream = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{
+        io::{BufRead, BufReader},
+        net::TcpListener,
+    };
+
+    use crate::rt::__private::{Level, Part, ValueBag};
+
+    fn emit(emitter: &impl Emitter, id: u64) {
+        let kvs = [("id", ValueBag::from(id))];
+        let record = crate::test_record(
+            Level::Info,
+            &kvs,
+            &[Part::Text("request "), Part::Hole("id")],
+        );
+
+        emitter.emit(&Record(&record));
+    }
+
+    fn read_lines(stream: impl io::Read, n: usize) -> Vec<String> {
+        BufReader::new(stream)
+            .lines()
+            .take(n)
+            .map(|line| line.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn tcp_lines() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let emitter = SocketEmitter::tcp(listener.local_addr().unwrap());
+
+        emit(&emitter, 1);
+        emit(&emitter, 2);
+        emitter.flush();
+
+        let (stream, _) = listener.accept().unwrap();
+
+        assert_eq!(
+            vec![
+                "{\"lvl\":\"info\",\"msg\":\"request 1\",\"target\":\"test\",\"id\":1}".to_owned(),
+                "{\"lvl\":\"info\",\"msg\":\"request 2\",\"target\":\"test\",\"id\":2}".to_owned(),
+            ],
+            read_lines(stream, 2)
+        );
+    }
+
+    #[test]
+    fn tcp_reconnect_buffered() {
+        // Find a free port, then close it so connecting fails
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let emitter = SocketEmitter::tcp(addr).with_reconnect_interval(Duration::from_secs(0));
+
+        emit(&emitter, 1);
+
+        // The first line is buffered until the listener is back
+        let listener = TcpListener::bind(addr).unwrap();
+
+        emit(&emitter, 2);
+
+        let (stream, _) = listener.accept().unwrap();
+
+        assert_eq!(
+            vec![
+                "{\"lvl\":\"info\",\"msg\":\"request 1\",\"target\":\"test\",\"id\":1}".to_owned(),
+                "{\"lvl\":\"info\",\"msg\":\"request 2\",\"target\":\"test\",\"id\":2}".to_owned(),
+            ],
+            read_lines(stream, 2)
+        );
+    }
+
+    #[test]
+    fn max_buffered() {
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        // Each line fits in the buffer by itself, but not alongside another
+        let emitter = SocketEmitter::tcp(addr).with_max_buffered(64);
+
+        emit(&emitter, 1);
+        emit(&emitter, 2);
+
+        let state = emitter.lock();
+        assert_eq!(1, state.buffered.len());
+        assert_eq!(
+            b"{\"lvl\":\"info\",\"msg\":\"request 2\",\"target\":\"test\",\"id\":2}\n",
+            &*state.buffered[0]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn unix_lines() {
+        use std::os::unix::net::UnixListener;
+
+        let path = std::env::temp_dir().join(format!("emit-socket-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let listener = UnixListener::bind(&path).unwrap();
+        let emitter = SocketEmitter::unix(&path);
+
+        emit(&emitter, 1);
+
+        let (stream, _) = listener.accept().unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(
+            vec![
+                "{\"lvl\":\"info\",\"msg\":\"request 1\",\"target\":\"test\",\"id\":1}".to_owned()
+            ],
+            read_lines(stream, 1)
+        );
+    }
+}
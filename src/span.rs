@@ -0,0 +1,126 @@
+/*!
+Grouping the records emitted within a block into spans.
+
+A span is entered with the `span!` macro. Every record emitted on the current thread while the
+span is active gets a `span_id` key-value with its id, and a `span_name` key-value with its name.
+Spans can be nested, and records emitted within a nested span also get a `parent_span_id`
+key-value with the id of the span it was entered in, so emitters can reconstruct the tree:
+
+```
+emit::span!("load_user", {
+    // Records emitted here have a `span_id` key-value, but no `parent_span_id`
+    emit::info!("loading user");
+
+    emit::span!("query", {
+        // Records emitted here have a `parent_span_id` key-value with the id of `load_user`
+        emit::info!("running query");
+    });
+});
+```
+
+A span entered when no other span is active is a root span. Records emitted in a root span
+don't have a `parent_span_id` key-value, and records emitted outside of any span have none of
+the span key-values. Span ids are unique within the process.
+*/
+
+use std::{
+    cell::Cell,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::context::{self, Scope};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static CURRENT: Cell<Option<u64>> = Cell::new(None);
+}
+
+/**
+Enter a span on the current thread until the returned guard is dropped.
+
+The `span!` macro uses this to scope a span to a block.
+*/
+pub fn enter(name: &'static str) -> Span {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let parent = CURRENT.with(|current| current.replace(Some(id)));
+
+    Span {
+        id,
+        parent,
+        _scope: (
+            context::scoped("span_id", id),
+            context::scoped("span_name", name),
+            parent.map(|parent| context::scoped("parent_span_id", parent)),
+        ),
+        _not_send: std::marker::PhantomData,
+    }
+}
+
+/**
+The id of the span that's active on the current thread, if there is one.
+*/
+pub fn current_id() -> Option<u64> {
+    CURRENT.with(Cell::get)
+}
+
+/**
+A guard returned by `enter` that exits the span when it's dropped.
+*/
+#[must_use = "the span is exited when the guard is dropped"]
+pub struct Span {
+    id: u64,
+    parent: Option<u64>,
+    // The context scopes are dropped after the guard restores the current span
+    _scope: (Scope, Scope, Option<Scope>),
+    // Spans are tracked per-thread, so guards can't be moved to other threads
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl Span {
+    /**
+    The id of this span.
+    */
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /**
+    The id of the span this one was entered in, or `None` if it's a root span.
+    */
+    pub fn parent_id(&self) -> Option<u64> {
+        self.parent
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.set(self.parent));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_spans() {
+        assert_eq!(None, current_id());
+
+        let parent = enter("parent");
+        assert_eq!(None, parent.parent_id());
+        assert_eq!(Some(parent.id()), current_id());
+
+        {
+            let child = enter("child");
+            assert_eq!(Some(parent.id()), child.parent_id());
+            assert_eq!(Some(child.id()), current_id());
+            assert_ne!(parent.id(), child.id());
+        }
+
+        assert_eq!(Some(parent.id()), current_id());
+
+        drop(parent);
+        assert_eq!(None, current_id());
+    }
+}
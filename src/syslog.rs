@@ -0,0 +1,509 @@
+/*!
+An emitter that writes records as RFC 5424 syslog messages.
+*/
+
+use std::{
+    fmt::{self, Write as _},
+    io::{self, Write as _},
+    net::{SocketAddr, TcpStream, UdpSocket},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(unix)]
+use std::{os::unix::net::UnixDatagram, path::PathBuf};
+
+use crate::{
+    rt::__private::{Timestamp, TIMESTAMP_KEY},
+    Emitter, Level, Record,
+};
+
+/**
+How long to wait when connecting or writing to the socket before giving up.
+*/
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/**
+The value written for a header field that isn't known, like the hostname.
+*/
+const NIL: &str = "-";
+
+/**
+An emitter that writes each record as an RFC 5424 syslog message over UDP, TCP, or a Unix socket.
+
+Each message looks like:
+
+```text
+<14>1 2021-10-01T08:30:00.5Z web-1 my_app 4242 - [emit@32473 id="1"] request 1
+```
+
+The level of the record is mapped to a syslog severity, and its target is used as the app name.
+The timestamp is the one supplied with `#[ts]`, or the time the record was emitted if there isn't
+one. The process id is written as the proc id, and the message id is always `-`.
+
+The key-values of the record are written as parameters of a single SD-ELEMENT, with an SD-ID of
+`emit@32473` by default. Characters that aren't allowed in syslog names are replaced by `_`.
+
+The hostname isn't discovered automatically, so it's `-` unless it's set with `with_hostname`.
+Messages sent over TCP are framed by their length, as described in RFC 6587.
+
+The socket is connected when the first record is emitted. If sending a message fails then it's
+discarded along with the connection, and a new connection is attempted for the next record.
+Records with a key-value that fails to format are discarded instead of being sent incomplete.
+*/
+pub struct SyslogEmitter {
+    addr: Addr,
+    facility: u8,
+    hostname: String,
+    sd_id: String,
+    conn: Mutex<Option<Conn>>,
+}
+
+enum Addr {
+    Udp(SocketAddr),
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+enum Conn {
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+}
+
+impl Addr {
+    fn connect(&self) -> io::Result<Conn> {
+        match self {
+            Addr::Udp(addr) => {
+                let local: SocketAddr = if addr.is_ipv4() {
+                    ([0, 0, 0, 0], 0).into()
+                } else {
+                    ([0u16; 8], 0).into()
+                };
+
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(addr)?;
+                socket.set_write_timeout(Some(TIMEOUT))?;
+
+                Ok(Conn::Udp(socket))
+            }
+            Addr::Tcp(addr) => {
+                let stream = TcpStream::connect_timeout(addr, TIMEOUT)?;
+                stream.set_write_timeout(Some(TIMEOUT))?;
+
+                Ok(Conn::Tcp(stream))
+            }
+            #[cfg(unix)]
+            Addr::Unix(path) => {
+                let socket = UnixDatagram::unbound()?;
+                socket.connect(path)?;
+                socket.set_write_timeout(Some(TIMEOUT))?;
+
+                Ok(Conn::Unix(socket))
+            }
+        }
+    }
+}
+
+impl Conn {
+    fn send(&mut self, msg: &str) -> io::Result<()> {
+        match self {
+            Conn::Udp(socket) => socket.send(msg.as_bytes()).map(drop),
+            Conn::Tcp(stream) => {
+                // Octet counting, from RFC 6587
+                write!(stream, "{} {}", msg.len(), msg)?;
+                stream.flush()
+            }
+            #[cfg(unix)]
+            Conn::Unix(socket) => socket.send(msg.as_bytes()).map(drop),
+        }
+    }
+}
+
+impl SyslogEmitter {
+    /**
+    Create an emitter that sends messages to a syslog server over UDP.
+    */
+    pub fn udp(addr: SocketAddr) -> Self {
+        SyslogEmitter::new(Addr::Udp(addr))
+    }
+
+    /**
+    Create an emitter that sends messages to a syslog server over TCP.
+    */
+    pub fn tcp(addr: SocketAddr) -> Self {
+        SyslogEmitter::new(Addr::Tcp(addr))
+    }
+
+    /**
+    Create an emitter that sends messages to a Unix datagram socket at the given path.
+    */
+    #[cfg(unix)]
+    pub fn unix(path: impl Into<PathBuf>) -> Self {
+        SyslogEmitter::new(Addr::Unix(path.into()))
+    }
+
+    /**
+    Create an emitter that sends messages to the local syslog daemon at `/dev/log`.
+    */
+    #[cfg(unix)]
+    pub fn local() -> Self {
+        SyslogEmitter::unix("/dev/log")
+    }
+
+    fn new(addr: Addr) -> Self {
+        SyslogEmitter {
+            addr,
+            facility: 1,
+            hostname: NIL.into(),
+            sd_id: "emit@32473".into(),
+            conn: Mutex::new(None),
+        }
+    }
+
+    /**
+    Set the syslog facility code that messages are sent with.
+
+    The default is `1`, for user-level messages.
+    Codes `16` through `23` are `local0` through `local7`.
+    */
+    pub fn with_facility(mut self, facility: u8) -> Self {
+        assert!(facility < 24, "syslog facilities must be between 0 and 23");
+
+        self.facility = facility;
+        self
+    }
+
+    /**
+    Set the hostname written in the header of each message.
+    */
+    pub fn with_hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.hostname = hostname.into();
+        self
+    }
+
+    /**
+    Set the SD-ID of the SD-ELEMENT that key-values are written to.
+
+    IDs without an `@` are reserved for the names registered by RFC 5424, so custom
+    IDs should be a name followed by a private enterprise number, like `myapp@32473`.
+    */
+    pub fn with_sd_id(mut self, sd_id: impl Into<String>) -> Self {
+        self.sd_id = sd_id.into();
+        self
+    }
+
+    fn format(&self, record: &Record, now: SystemTime) -> Option<String> {
+        let mut msg = String::new();
+
+        // Writing to a string only fails if a value fails to format, which would leave only
+        // part of the message written, so it's discarded instead of being sent
+        self.write(&mut msg, record, now).ok()?;
+
+        Some(msg)
+    }
+
+    fn write(&self, msg: &mut String, record: &Record, now: SystemTime) -> fmt::Result {
+        // The fraction of a second can't be more precise than microseconds
+        let ts = record.timestamp().unwrap_or(now);
+        let ts = ts.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let ts = UNIX_EPOCH + Duration::from_micros(ts.as_micros() as u64);
+
+        write!(
+            msg,
+            "<{}>1 {} ",
+            self.facility * 8 + severity(record.level()),
+            Timestamp::new(&ts)
+        )?;
+
+        write_name(msg, &self.hostname, 255)?;
+        msg.write_char(' ')?;
+        write_name(msg, record.target(), 48)?;
+
+        write!(msg, " {} {} ", std::process::id(), NIL)?;
+
+        let mut kvs = record
+            .0
+            .kvs
+            .sorted_key_values
+            .iter()
+            .filter(|(k, _)| *k != TIMESTAMP_KEY)
+            .peekable();
+
+        if kvs.peek().is_some() {
+            msg.write_char('[')?;
+            write_sd_name(msg, &self.sd_id)?;
+
+            for (k, v) in kvs {
+                msg.write_char(' ')?;
+                write_sd_name(msg, k)?;
+                msg.write_str("=\"")?;
+                write!(Escape(msg), "{}", v)?;
+                msg.write_char('"')?;
+            }
+
+            msg.write_char(']')?;
+        } else {
+            msg.write_str(NIL)?;
+        }
+
+        write!(msg, " {}", record.msg())
+    }
+}
+
+/**
+Map the level of a record to a syslog severity.
+
+Custom levels are mapped by their numeric severity, so levels between `Info` and `Warn`
+are notices, and levels above `Error` are critical.
+*/
+fn severity(level: Level) -> u8 {
+    match level.severity() {
+        s if s > 50 => 2,
+        s if s == 50 => 3,
+        s if s >= 40 => 4,
+        s if s > 30 => 5,
+        s if s == 30 => 6,
+        _ => 7,
+    }
+}
+
+/**
+Write a header field, replacing characters that aren't printable ASCII.
+*/
+fn write_name(msg: &mut String, name: &str, max_len: usize) -> fmt::Result {
+    if name.is_empty() {
+        return msg.write_str(NIL);
+    }
+
+    for c in name.chars().take(max_len) {
+        msg.write_char(if c.is_ascii_graphic() { c } else { '_' })?;
+    }
+
+    Ok(())
+}
+
+/**
+Write an SD-ID or parameter name, replacing characters that aren't allowed in them.
+*/
+fn write_sd_name(msg: &mut String, name: &str) -> fmt::Result {
+    for c in name.chars().take(32) {
+        msg.write_char(match c {
+            '=' | ']' | '"' => '_',
+            c if c.is_ascii_graphic() => c,
+            _ => '_',
+        })?;
+    }
+
+    Ok(())
+}
+
+/**
+A writer that escapes the characters that can't appear unescaped in a parameter value.
+*/
+struct Escape<'a>(&'a mut String);
+
+impl<'a> fmt::Write for Escape<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if let '"' | '\\' | ']' = c {
+                self.0.push('\\');
+            }
+
+            self.0.push(c);
+        }
+
+        Ok(())
+    }
+}
+
+impl Emitter for SyslogEmitter {
+    fn emit(&self, record: &Record) {
+        let msg = match self.format(record, SystemTime::now()) {
+            Some(msg) => msg,
+            None => return,
+        };
+
+        let mut conn = self.conn.lock().unwrap_or_else(|err| err.into_inner());
+
+        if conn.is_none() {
+            *conn = self.addr.connect().ok();
+        }
+
+        if let Some(ref mut sent) = *conn {
+            if sent.send(&msg).is_err() {
+                *conn = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Read;
+
+    use crate::rt::__private::{Part, ValueBag};
+
+    fn with_record(level: Level, kvs: &[(&str, ValueBag)], f: impl FnOnce(&Record)) {
+        let record = crate::rt::__private::Record {
+            target: "my_app",
+            ..crate::test_record(level, kvs, &[Part::Text("request "), Part::Hole("id")])
+        };
+
+        f(&Record(&record))
+    }
+
+    fn now() -> SystemTime {
+        UNIX_EPOCH + Duration::new(1_633_077_000, 500_000_000)
+    }
+
+    #[test]
+    fn format() {
+        let emitter = SyslogEmitter::udp(([127, 0, 0, 1], 514).into()).with_hostname("web-1");
+
+        with_record(
+            Level::Info,
+            &[
+                ("id", ValueBag::from(1)),
+                ("path", ValueBag::from("/a \"b\" [c]")),
+            ],
+            |record| {
+                assert_eq!(
+                    format!(
+                        "<14>1 2021-10-01T08:30:00.5Z web-1 my_app {} - [emit@32473 id=\"1\" path=\"/a \\\"b\\\" [c\\]\"] request 1",
+                        std::process::id()
+                    ),
+                    emitter.format(record, now()).unwrap()
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn format_no_kvs() {
+        let emitter = SyslogEmitter::udp(([127, 0, 0, 1], 514).into()).with_facility(16);
+
+        with_record(Level::Error, &[], |record| {
+            assert_eq!(
+                format!(
+                    "<131>1 2021-10-01T08:30:00.5Z - my_app {} - - request `id`",
+                    std::process::id()
+                ),
+                emitter.format(record, now()).unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn format_err() {
+        struct Fallible;
+
+        impl fmt::Display for Fallible {
+            fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+
+        let emitter = SyslogEmitter::udp(([127, 0, 0, 1], 514).into());
+
+        // A message that's only partly written isn't sent
+        with_record(
+            Level::Info,
+            &[
+                ("id", ValueBag::from(1)),
+                ("value", ValueBag::from_dyn_display(&Fallible)),
+            ],
+            |record| {
+                assert_eq!(None, emitter.format(record, now()));
+            },
+        );
+    }
+
+    #[test]
+    fn format_timestamp() {
+        let emitter = SyslogEmitter::udp(([127, 0, 0, 1], 514).into());
+
+        // Supplied timestamps are written in the header instead of the SD-ELEMENT
+        let ts = UNIX_EPOCH + Duration::new(1_633_077_000, 123_456_789);
+        with_record(
+            Level::Warn,
+            &[
+                ("id", ValueBag::from(1)),
+                (
+                    TIMESTAMP_KEY,
+                    ValueBag::capture_display(Timestamp::new(&ts)),
+                ),
+            ],
+            |record| {
+                assert!(emitter
+                    .format(record, now())
+                    .unwrap()
+                    .starts_with("<12>1 2021-10-01T08:30:00.123456Z - my_app "));
+            },
+        );
+    }
+
+    #[test]
+    fn severities() {
+        assert_eq!(7, severity(Level::Trace));
+        assert_eq!(7, severity(Level::Debug));
+        assert_eq!(6, severity(Level::Info));
+        assert_eq!(5, severity(Level::custom("notice", 35)));
+        assert_eq!(4, severity(Level::Warn));
+        assert_eq!(3, severity(Level::Error));
+        assert_eq!(2, severity(Level::custom("critical", 60)));
+    }
+
+    #[test]
+    fn udp() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let emitter = SyslogEmitter::udp(socket.local_addr().unwrap());
+
+        with_record(Level::Info, &[("id", ValueBag::from(1))], |record| {
+            emitter.emit(record)
+        });
+
+        let mut buf = [0; 512];
+        let len = socket.recv(&mut buf).unwrap();
+        let msg = std::str::from_utf8(&buf[..len]).unwrap();
+
+        assert!(msg.starts_with("<14>1 "));
+        assert!(msg.ends_with("[emit@32473 id=\"1\"] request 1"));
+    }
+
+    #[test]
+    fn tcp() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let emitter = SyslogEmitter::tcp(listener.local_addr().unwrap());
+
+        with_record(Level::Info, &[("id", ValueBag::from(1))], |record| {
+            emitter.emit(record);
+            emitter.emit(record);
+        });
+        drop(emitter);
+
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let mut received = String::new();
+        stream.read_to_string(&mut received).unwrap();
+
+        // Each message is prefixed by its length
+        fn split_frame(frame: &str) -> (&str, &str) {
+            let (len, rest) = frame.split_at(frame.find(' ').unwrap());
+            rest[1..].split_at(len.parse().unwrap())
+        }
+
+        let (first, rest) = split_frame(&received);
+        let (second, rest) = split_frame(rest);
+
+        assert_eq!("", rest);
+        assert!(first.starts_with("<14>1 "));
+        assert!(first.ends_with("request 1"));
+        assert!(second.starts_with("<14>1 "));
+        assert!(second.ends_with("request 1"));
+    }
+}
@@ -0,0 +1,225 @@
+/*!
+An emitter that measures how long another emitter takes to emit records.
+*/
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    rt::__private::{template, IsoDuration, KeyValues, Level, Part, SourceLocation, ValueBag},
+    Emitter, Record,
+};
+
+/**
+The time taken to emit records, aggregated over every record emitted so far.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Latency {
+    /**
+    The number of records that were emitted.
+    */
+    pub count: u64,
+    /**
+    The shortest time taken to emit a record.
+    */
+    pub min: Duration,
+    /**
+    The longest time taken to emit a record.
+    */
+    pub max: Duration,
+    /**
+    The total time taken to emit every record.
+    */
+    pub total: Duration,
+}
+
+impl Latency {
+    /**
+    The average time taken to emit a record, or `None` if no records have been emitted.
+    */
+    pub fn mean(&self) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+
+        Some(Duration::from_secs_f64(
+            self.total.as_secs_f64() / self.count as f64,
+        ))
+    }
+
+    fn record(&mut self, elapsed: Duration) {
+        if self.count == 0 || elapsed < self.min {
+            self.min = elapsed;
+        }
+
+        if elapsed > self.max {
+            self.max = elapsed;
+        }
+
+        self.count += 1;
+        self.total += elapsed;
+    }
+}
+
+/**
+An emitter that measures how long it takes to emit each record to another emitter.
+
+The time is measured around the inner emitter's `emit`, and aggregated into a `Latency`
+that can be read with `latency`. Flushing isn't measured.
+
+With a threshold set by `with_threshold`, emitting a record that takes longer than the threshold
+also emits a `Warn` record to the inner emitter with the `template` of the slow record and how
+long it took as an `elapsed` ISO 8601 duration. That record isn't measured itself.
+*/
+pub struct TimedEmitter<E> {
+    emitter: E,
+    threshold: Option<Duration>,
+    latency: Mutex<Latency>,
+}
+
+impl<E> TimedEmitter<E> {
+    /**
+    Measure the time taken to emit records to `emitter`.
+    */
+    pub fn new(emitter: E) -> Self {
+        TimedEmitter {
+            emitter,
+            threshold: None,
+            latency: Mutex::new(Latency::default()),
+        }
+    }
+
+    /**
+    Emit a record to the inner emitter whenever a record takes longer than `threshold` to emit.
+    */
+    pub fn with_threshold(mut self, threshold: Duration) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /**
+    Get a snapshot of the time taken to emit records so far.
+    */
+    pub fn latency(&self) -> Latency {
+        *self.latency.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<E> Emitter for TimedEmitter<E>
+where
+    E: Emitter,
+{
+    fn emit(&self, record: &Record) {
+        let start = Instant::now();
+        self.emitter.emit(record);
+        let elapsed = start.elapsed();
+
+        self.latency
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .record(elapsed);
+
+        match self.threshold {
+            Some(threshold) if elapsed > threshold => {
+                let slow_template = record.template().to_string();
+
+                self.emitter.emit(&Record(&crate::rt::__private::Record {
+                    level: Level::Warn,
+                    target: module_path!(),
+                    location: SourceLocation {
+                        file: file!(),
+                        line: line!(),
+                        module_path: module_path!(),
+                    },
+                    kvs: KeyValues {
+                        sorted_key_values: &[
+                            (
+                                "elapsed",
+                                ValueBag::capture_display(IsoDuration::new(&elapsed)),
+                            ),
+                            ("template", ValueBag::from(slow_template.as_str())),
+                        ],
+                    },
+                    template: template(&[
+                        Part::Text("emitting a record took "),
+                        Part::Hole("elapsed"),
+                    ]),
+                }));
+            }
+            _ => (),
+        }
+    }
+
+    fn flush(&self) {
+        self.emitter.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{sync::Arc, thread};
+
+    use crate::{OwnedRecord, OwnedValue};
+
+    fn emit(emitter: &impl Emitter) {
+        let record = crate::test_record(Level::Info, &[], &[Part::Text("slow")]);
+
+        emitter.emit(&Record(&record));
+    }
+
+    #[test]
+    fn measure_latency() {
+        let emitter = TimedEmitter::new(|_: &Record| thread::sleep(Duration::from_millis(20)));
+
+        assert_eq!(None, emitter.latency().mean());
+
+        emit(&emitter);
+        emit(&emitter);
+
+        let latency = emitter.latency();
+
+        assert_eq!(2, latency.count);
+        assert!(latency.min >= Duration::from_millis(20));
+        assert!(latency.max >= latency.min);
+        assert!(latency.total >= Duration::from_millis(40));
+        assert!(latency.mean().unwrap() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn emit_over_threshold() {
+        let emitted = Arc::new(Mutex::new(Vec::<OwnedRecord>::new()));
+
+        let emitter = TimedEmitter::new({
+            let emitted = emitted.clone();
+            move |record: &Record| {
+                if record.level() == Level::Info {
+                    thread::sleep(Duration::from_millis(20));
+                }
+
+                emitted.lock().unwrap().push(record.to_owned())
+            }
+        })
+        .with_threshold(Duration::from_millis(10));
+
+        emit(&emitter);
+
+        let emitted = emitted.lock().unwrap();
+
+        assert_eq!(2, emitted.len());
+        assert_eq!("slow", emitted[0].msg());
+
+        assert_eq!(Level::Warn, emitted[1].level());
+        assert!(emitted[1].msg().starts_with("emitting a record took PT0.0"));
+        assert_eq!(
+            Some(("template", &OwnedValue::from("slow"))),
+            emitted[1].kvs().find(|(k, _)| *k == "template")
+        );
+
+        // The record about the slow one isn't measured
+        assert_eq!(1, emitter.latency().count);
+    }
+}
@@ -0,0 +1,169 @@
+/*!
+Formatting the timestamps of records in emitters.
+*/
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    rt::__private::{KeyValues, ValueBag, TIMESTAMP_KEY},
+    Record,
+};
+
+/**
+How an emitter writes the timestamp supplied for a record with `#[ts]`.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /**
+    Write timestamps as RFC 3339 date-times in UTC, like `2021-10-01T08:30:00Z`.
+    */
+    Rfc3339,
+    /**
+    Write timestamps as the number of milliseconds since the Unix epoch, like `1633077000000`.
+
+    Times before the Unix epoch are written as `0`.
+    */
+    EpochMillis,
+    /**
+    Write timestamps in UTC using a `chrono` format string, like `"%Y-%m-%d %H:%M:%S"`.
+
+    If the format string is invalid then timestamps are written as RFC 3339 date-times instead.
+    */
+    #[cfg(feature = "chrono")]
+    Custom(String),
+}
+
+impl Default for TimestampFormat {
+    fn default() -> Self {
+        TimestampFormat::Rfc3339
+    }
+}
+
+impl TimestampFormat {
+    /**
+    Write a timestamp in this format.
+
+    Epoch millis are a number, and other formats are strings.
+    */
+    fn format(&self, ts: SystemTime, f: impl FnOnce(ValueBag)) {
+        match self {
+            TimestampFormat::Rfc3339 => f(ValueBag::capture_display(
+                crate::rt::__private::Timestamp::new(&ts),
+            )),
+            TimestampFormat::EpochMillis => {
+                let millis = ts
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis();
+
+                f(ValueBag::from(millis as u64))
+            }
+            #[cfg(feature = "chrono")]
+            TimestampFormat::Custom(format) => {
+                use std::fmt::Write;
+
+                let mut formatted = String::new();
+
+                match write!(
+                    formatted,
+                    "{}",
+                    chrono::DateTime::<chrono::Utc>::from(ts).format(format)
+                ) {
+                    Ok(()) => f(ValueBag::from(&*formatted)),
+                    Err(_) => TimestampFormat::Rfc3339.format(ts, f),
+                }
+            }
+        }
+    }
+}
+
+/**
+Replace the timestamp of a record with one written in the given format.
+
+Records without a timestamp, or with the default format, are passed through as-is.
+*/
+pub(crate) fn with_timestamp_format(
+    record: &Record,
+    format: &TimestampFormat,
+    f: impl FnOnce(&Record),
+) {
+    let ts = match record.timestamp() {
+        Some(ts) if *format != TimestampFormat::Rfc3339 => ts,
+        _ => return f(record),
+    };
+
+    format.format(ts, |ts| {
+        let sorted_key_values: Vec<_> = record
+            .0
+            .kvs
+            .sorted_key_values
+            .iter()
+            .map(|(k, v)| match *k {
+                TIMESTAMP_KEY => (*k, ts.clone()),
+                _ => (*k, v.clone()),
+            })
+            .collect();
+
+        f(&Record(&crate::rt::__private::Record {
+            level: record.0.level,
+            target: record.0.target,
+            location: record.0.location,
+            kvs: KeyValues {
+                sorted_key_values: &sorted_key_values,
+            },
+            template: record.0.template,
+        }))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use crate::rt::__private::{Level, Part, Timestamp};
+
+    fn format(format: TimestampFormat) -> String {
+        let ts = UNIX_EPOCH + Duration::new(1_633_077_000, 500_000_000);
+
+        let kvs = [(
+            TIMESTAMP_KEY,
+            ValueBag::capture_display(Timestamp::new(&ts)),
+        )];
+        let record = crate::test_record(Level::Info, &kvs, &[Part::Text("at "), Part::Hole("ts")]);
+
+        let mut formatted = String::new();
+        with_timestamp_format(&Record(&record), &format, |record| {
+            formatted = record.msg().to_string();
+        });
+
+        formatted
+    }
+
+    #[test]
+    fn rfc3339() {
+        assert_eq!(
+            "at 2021-10-01T08:30:00.5Z",
+            format(TimestampFormat::Rfc3339)
+        );
+    }
+
+    #[test]
+    fn epoch_millis() {
+        assert_eq!("at 1633077000500", format(TimestampFormat::EpochMillis));
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn custom() {
+        assert_eq!(
+            "at 2021-10-01 08:30:00",
+            format(TimestampFormat::Custom("%Y-%m-%d %H:%M:%S".into()))
+        );
+        assert_eq!(
+            "at 2021-10-01T08:30:00.5Z",
+            format(TimestampFormat::Custom("%Q".into()))
+        );
+    }
+}
@@ -0,0 +1,459 @@
+/*!
+An emitter that forwards records to `tracing`.
+*/
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
+    lazy::SyncOnceCell,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use crate::{
+    rt::__private::{
+        tracing::{
+            core::{
+                callsite::{self, Identifier},
+                dispatcher,
+                field::{self, Field, FieldSet, Value},
+                metadata::Kind,
+                Callsite, Event, Interest, LevelFilter, Metadata,
+            },
+            to_tracing_level,
+        },
+        Level, ValueBag,
+    },
+    Emitter, Record,
+};
+
+/**
+The maximum number of fields `tracing` supports on a single event.
+
+One of these is always used by the rendered message.
+*/
+const MAX_FIELDS: usize = 32;
+
+/**
+The maximum number of callsites an emitter will register for distinct targets and sets of keys.
+
+Callsites can't be unregistered once they've been created, so this bounds the memory leaked
+by records with keys that are built at runtime.
+*/
+const MAX_CALLSITES: usize = 1024;
+
+/**
+An emitter that converts records into `tracing` events.
+
+Each record is dispatched to the current `tracing` subscriber as an event
+at its mapped level, with the record's target. The rendered message is recorded as a `msg` field and
+each key-value is recorded as a field of its own. Events are dispatched
+in the context of the currently active `tracing` span, if there is one.
+
+`tracing` only supports a fixed number of fields per event, so any
+key-values beyond the first 31 are not forwarded.
+
+`tracing` needs a callsite for each distinct target and set of keys, which is registered
+the first time it's seen and lives for the rest of the program. Once 1024 have been registered,
+records with other targets or keys are dispatched with an `emit` target instead, and their
+original target and key-values are recorded as `target` and `kvs` fields.
+*/
+#[derive(Default)]
+pub struct TracingEmitter {
+    callsites: Mutex<Callsites>,
+}
+
+#[derive(Default)]
+struct Callsites {
+    /**
+    Registered callsites, by the hash of their level, target, and keys.
+
+    Looking callsites up by hash means a record's keys don't need to be copied
+    to find the callsite for them.
+    */
+    by_hash: HashMap<u64, Vec<&'static DynamicCallsite>>,
+    len: usize,
+    /**
+    Callsites for records seen after `MAX_CALLSITES` have been registered, by level.
+    */
+    overflow: HashMap<Level, &'static DynamicCallsite>,
+    /**
+    The targets and keys that have been leaked for callsites.
+
+    Each one is only leaked once, however many callsites it's used by.
+    */
+    names: HashSet<&'static str>,
+}
+
+impl Callsites {
+    fn get_or_register(
+        &mut self,
+        level: Level,
+        target: &str,
+        kvs: &[(&str, ValueBag)],
+    ) -> (&'static DynamicCallsite, bool) {
+        let mut hasher = DefaultHasher::new();
+        level.hash(&mut hasher);
+        target.hash(&mut hasher);
+        for (k, _) in kvs {
+            k.hash(&mut hasher);
+        }
+        let hash = hasher.finish();
+
+        if let Some(callsite) = self.by_hash.get(&hash).and_then(|callsites| {
+            callsites
+                .iter()
+                .find(|callsite| callsite.matches(level, target, kvs))
+        }) {
+            return (callsite, false);
+        }
+
+        if self.len >= MAX_CALLSITES {
+            let names = &mut self.names;
+            let callsite = *self.overflow.entry(level).or_insert_with(|| {
+                DynamicCallsite::register(level, "emit", ["target", "kvs"], names)
+            });
+
+            return (callsite, true);
+        }
+
+        let callsite =
+            DynamicCallsite::register(level, target, kvs.iter().map(|(k, _)| *k), &mut self.names);
+
+        self.by_hash.entry(hash).or_default().push(callsite);
+        self.len += 1;
+
+        (callsite, false)
+    }
+}
+
+impl TracingEmitter {
+    /**
+    Create a new emitter that forwards to `tracing`.
+    */
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Emitter for TracingEmitter {
+    fn emit(&self, record: &Record) {
+        let level = record.level();
+
+        if to_tracing_level(level) > LevelFilter::current() {
+            return;
+        }
+
+        let kvs = record.0.kvs.sorted_key_values;
+        let kvs = &kvs[..kvs.len().min(MAX_FIELDS - 1)];
+
+        let (callsite, overflow) = self
+            .callsites
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get_or_register(level, record.target(), kvs);
+
+        if !callsite.is_enabled() {
+            return;
+        }
+
+        let meta = callsite.metadata();
+        let fields = callsite.fields();
+
+        let msg = field::display(record.msg());
+
+        if overflow {
+            let target = field::display(record.target());
+            let kvs = field::debug(&record.0.kvs);
+
+            Event::dispatch(
+                meta,
+                &meta.fields().value_set(&[
+                    (&fields[0], Some(&msg as &dyn Value)),
+                    (&fields[1], Some(&target as &dyn Value)),
+                    (&fields[2], Some(&kvs as &dyn Value)),
+                ]),
+            );
+
+            return;
+        }
+
+        let mut kv_values: [Option<field::DebugValue<&ValueBag>>; MAX_FIELDS - 1] =
+            Default::default();
+        for (slot, (_, v)) in kv_values.iter_mut().zip(kvs) {
+            *slot = Some(field::debug(v));
+        }
+
+        let mut values: [(&Field, Option<&dyn Value>); MAX_FIELDS] =
+            [(&fields[0], None); MAX_FIELDS];
+
+        values[0].1 = Some(&msg);
+        for (slot, (field, value)) in values[1..].iter_mut().zip(fields[1..].iter().zip(&kv_values)) {
+            *slot = (field, value.as_ref().map(|value| value as &dyn Value));
+        }
+
+        Event::dispatch(meta, &meta.fields().value_set(&values));
+    }
+}
+
+/**
+A callsite for a target and set of keys that's only known at runtime.
+
+These callsites are leaked, so they should only be created once
+per unique target and set of keys.
+*/
+struct DynamicCallsite {
+    level: Level,
+    interest: AtomicUsize,
+    meta: SyncOnceCell<(Metadata<'static>, Vec<Field>)>,
+}
+
+impl DynamicCallsite {
+    fn register<'a>(
+        level: Level,
+        target: &str,
+        keys: impl IntoIterator<Item = &'a str>,
+        names: &mut HashSet<&'static str>,
+    ) -> &'static Self {
+        let mut intern = |name: &str| match names.get(name) {
+            Some(name) => *name,
+            None => {
+                let name: &'static str = Box::leak(name.to_owned().into_boxed_str());
+                names.insert(name);
+
+                name
+            }
+        };
+
+        let target = intern(target);
+
+        let field_names: &'static [&'static str] = Box::leak(
+            Some("msg")
+                .into_iter()
+                .chain(keys.into_iter().map(&mut intern))
+                .collect::<Vec<_>>()
+                .into_boxed_slice(),
+        );
+
+        let callsite: &'static Self = Box::leak(Box::new(DynamicCallsite {
+            level,
+            interest: AtomicUsize::new(0),
+            meta: SyncOnceCell::new(),
+        }));
+
+        // The field set needs the callsite's address, so the metadata is set after it's leaked
+        let meta = Metadata::new(
+            "event",
+            target,
+            to_tracing_level(level),
+            None,
+            None,
+            None,
+            FieldSet::new(field_names, Identifier(callsite)),
+            Kind::EVENT,
+        );
+        let fields = meta.fields().iter().collect();
+        drop(callsite.meta.set((meta, fields)));
+
+        callsite::register(callsite);
+
+        callsite
+    }
+
+    fn matches(&self, level: Level, target: &str, kvs: &[(&str, ValueBag)]) -> bool {
+        let fields = self.fields();
+
+        self.level == level
+            && self.metadata().target() == target
+            && fields.len() == kvs.len() + 1
+            && fields[1..]
+                .iter()
+                .zip(kvs)
+                .all(|(field, (k, _))| field.name() == *k)
+    }
+
+    /**
+    The fields of the callsite's metadata, starting with `msg`.
+    */
+    fn fields(&self) -> &[Field] {
+        &self
+            .meta
+            .get()
+            .expect("callsite metadata is set on registration")
+            .1
+    }
+
+    fn is_enabled(&self) -> bool {
+        match self.interest.load(Ordering::Relaxed) {
+            0 => false,
+            2 => true,
+            _ => dispatcher::get_default(|current| current.enabled(self.metadata())),
+        }
+    }
+}
+
+impl Callsite for DynamicCallsite {
+    fn set_interest(&self, interest: Interest) {
+        let interest = match () {
+            _ if interest.is_never() => 0,
+            _ if interest.is_always() => 2,
+            _ => 1,
+        };
+        self.interest.store(interest, Ordering::SeqCst);
+    }
+
+    fn metadata(&self) -> &Metadata<'static> {
+        &self
+            .meta
+            .get()
+            .expect("callsite metadata is set on registration")
+            .0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{fmt, sync::Arc};
+
+    use crate::rt::__private::{
+        tracing::core::{span, Level as TracingLevel, Subscriber},
+        Part, ValueBag,
+    };
+
+    #[derive(Debug, Default, PartialEq)]
+    struct CapturedEvent {
+        level: Option<TracingLevel>,
+        target: String,
+        contextual: bool,
+        fields: Vec<(String, String)>,
+    }
+
+    #[derive(Clone, Default)]
+    struct CaptureSubscriber(Arc<Mutex<Vec<CapturedEvent>>>);
+
+    impl Subscriber for CaptureSubscriber {
+        fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+            Interest::always()
+        }
+
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record) {}
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event) {
+            struct Visitor<'a>(&'a mut Vec<(String, String)>);
+
+            impl<'a> field::Visit for Visitor<'a> {
+                fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+                    self.0
+                        .push((field.name().to_owned(), format!("{:?}", value)));
+                }
+            }
+
+            let mut captured = CapturedEvent {
+                level: Some(*event.metadata().level()),
+                target: event.metadata().target().to_owned(),
+                contextual: event.is_contextual(),
+                fields: Vec::new(),
+            };
+
+            event.record(&mut Visitor(&mut captured.fields));
+
+            self.0.lock().unwrap().push(captured);
+        }
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn forward_record() {
+        let subscriber = CaptureSubscriber::default();
+        let emitter = TracingEmitter::new();
+
+        let kvs = [("a", ValueBag::from(42)), ("b", ValueBag::from("text"))];
+        let record =
+            crate::test_record(Level::Warn, &kvs, &[Part::Text("value "), Part::Hole("a")]);
+
+        dispatcher::with_default(&dispatcher::Dispatch::new(subscriber.clone()), || {
+            emitter.emit(&Record(&record));
+            emitter.emit(&Record(&record));
+        });
+
+        let events = subscriber.0.lock().unwrap();
+
+        let expected = CapturedEvent {
+            level: Some(TracingLevel::WARN),
+            target: "test".to_owned(),
+            contextual: true,
+            fields: vec![
+                ("msg".to_owned(), "value 42".to_owned()),
+                ("a".to_owned(), "42".to_owned()),
+                ("b".to_owned(), "\"text\"".to_owned()),
+            ],
+        };
+
+        assert_eq!(2, events.len());
+        assert_eq!(expected, events[0]);
+        assert_eq!(expected, events[1]);
+
+        // The same set of keys reuses the same callsite
+        assert_eq!(1, emitter.callsites.lock().unwrap().len);
+    }
+
+    #[test]
+    fn forward_record_overflow() {
+        let subscriber = CaptureSubscriber::default();
+        let emitter = TracingEmitter::new();
+
+        let keys = (0..=MAX_CALLSITES)
+            .map(|i| format!("key{}", i))
+            .collect::<Vec<_>>();
+
+        dispatcher::with_default(&dispatcher::Dispatch::new(subscriber.clone()), || {
+            for key in &keys {
+                let kvs = [(&**key, ValueBag::from(42))];
+                let record = crate::test_record(Level::Info, &kvs, &[Part::Text("text")]);
+
+                emitter.emit(&Record(&record));
+            }
+        });
+
+        let events = subscriber.0.lock().unwrap();
+
+        let expected = CapturedEvent {
+            level: Some(TracingLevel::INFO),
+            target: "emit".to_owned(),
+            contextual: true,
+            fields: vec![
+                ("msg".to_owned(), "text".to_owned()),
+                ("target".to_owned(), "test".to_owned()),
+                (
+                    "kvs".to_owned(),
+                    format!("[(\"key{}\", 42)]", MAX_CALLSITES),
+                ),
+            ],
+        };
+
+        assert_eq!(MAX_CALLSITES + 1, events.len());
+        assert_eq!("key0", events[0].fields[1].0);
+        assert_eq!(expected, events[MAX_CALLSITES]);
+
+        let callsites = emitter.callsites.lock().unwrap();
+        assert_eq!(MAX_CALLSITES, callsites.len);
+        assert_eq!(1, callsites.overflow.len());
+    }
+}
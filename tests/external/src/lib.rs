@@ -0,0 +1,37 @@
+/*!
+Types for the UI tests that need to come from another crate.
+
+Attributes like `#[non_exhaustive]` only apply outside of the crate a type is defined in,
+so these can't be constructed with literals by the tests that use them.
+*/
+
+#[macro_use]
+extern crate serde_derive;
+
+#[derive(Serialize)]
+#[non_exhaustive]
+pub struct Account {
+    pub id: u64,
+}
+
+impl Account {
+    pub fn new(id: u64) -> Self {
+        Account { id }
+    }
+}
+
+#[derive(Serialize)]
+#[non_exhaustive]
+pub enum Plan {
+    Free,
+    Paid { seats: u32 },
+}
+
+#[derive(Serialize)]
+pub struct UserId(pub u64);
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct Email {
+    pub address: String,
+}
@@ -0,0 +1,10 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+#[macro_use]
+extern crate emit;
+
+fn main() {
+    let a = 42;
+
+    info!("Text {a}", #[as_debug] #[emit::as_display] a);
+}
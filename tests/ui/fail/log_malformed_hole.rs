@@ -0,0 +1,6 @@
+#[macro_use]
+extern crate emit;
+
+fn main() {
+    info!("Text {a} and {b: } and more", a: 42);
+}
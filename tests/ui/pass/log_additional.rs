@@ -25,5 +25,7 @@ fn main() {
         err: e,
         #[as_sval]
         f,
+        #[as_string]
+        g: u64::MAX,
     );
 }
@@ -0,0 +1,13 @@
+fn main() {
+    emit::target(|record| {
+        let kvs = record.kvs();
+
+        assert_eq!(Some(42), kvs.get("user_id").and_then(|v| v.to_u64()));
+        assert_eq!(Some(42), kvs.get("user").and_then(|v| v.to_u64()));
+        assert_eq!(Some(42), kvs.get("uid").and_then(|v| v.to_u64()));
+        assert_eq!("logged in 42", record.msg().to_string());
+    });
+
+    let id = 42;
+    emit::info!("logged in {user_id}", #[alias = "user"] #[alias = "uid"] user_id: id);
+}
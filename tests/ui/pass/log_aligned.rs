@@ -0,0 +1,16 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!("[sam       ] [    42] [**ok***]", record.msg().to_string());
+        assert_eq!("[`user`    ] [  `id`] [`status`]", record.template().to_string());
+    });
+
+    let user = "sam";
+    let id = 42;
+
+    emit::info!(
+        "[{user:<10}] [{id:>6}] [{status:*^7}]",
+        #[emit::as_display] status: "ok",
+    );
+}
@@ -0,0 +1,23 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::fmt;
+
+#[derive(Debug)]
+struct Pos(i32);
+
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!("pos Pos(1) 2", record.msg().to_string());
+    });
+
+    let x = Pos(1);
+    emit::info!(#[all_debug] "pos {x} {#[emit::as_display] y: Pos(2)}");
+}
@@ -0,0 +1,42 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::sync::{Arc, Mutex};
+
+fn main() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+
+    emit::target({
+        let records = records.clone();
+        move |record| records.lock().unwrap().push(record.to_owned())
+    });
+
+    let tag1 = "a";
+    let tag2 = 2;
+    let tag3 = true;
+    let tag4 = 4;
+
+    emit::info!("tagged {tags}", #[as_array] tags: [tag1, tag2, tag3], #[key = "id"] other: 1, tag4);
+    emit::info!("tagged {tags}", #[as_array] tags: (tag4,));
+
+    let records = records.lock().unwrap();
+
+    // Key-values are in the order they were captured in with `no_sort`
+    let mut kvs = records[0]
+        .kvs()
+        .map(|(k, v)| (k, v.to_string()))
+        .collect::<Vec<_>>();
+    kvs.sort();
+
+    assert_eq!(
+        vec![
+            ("id", "1".to_owned()),
+            ("tag4", "4".to_owned()),
+            ("tags", "[\"a\", 2, true]".to_owned())
+        ],
+        kvs
+    );
+    assert_eq!("tagged [\"a\", 2, true]", records[0].msg());
+    assert_eq!("tagged [4]", records[1].msg());
+}
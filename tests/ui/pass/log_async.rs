@@ -0,0 +1,52 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::sync::{Arc, Mutex};
+
+async fn yield_now() {}
+
+async fn process(work: &str, size: usize) -> usize {
+    // Borrowed captures only live until the end of the statement,
+    // so they aren't held across the `.await` below
+    emit::info!("processing {work} of {size} bytes");
+
+    yield_now().await;
+
+    emit::info!("processed {work}", remaining: size - work.len());
+
+    size
+}
+
+fn main() {
+    let emitted = Arc::new(Mutex::new(Vec::new()));
+
+    emit::set_emitter({
+        let emitted = emitted.clone();
+
+        // Records are borrowed, so an emitter that holds onto them
+        // needs to convert them into owned records first
+        move |record: &emit::Record| emitted.lock().unwrap().push(record.to_owned())
+    });
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    // Spawning requires the future to be `Send`
+    let size = runtime
+        .block_on(runtime.spawn(async {
+            let work = String::from("upload");
+
+            process(&work, 1024).await
+        }))
+        .unwrap();
+
+    assert_eq!(1024, size);
+
+    let emitted = emitted.lock().unwrap();
+    assert_eq!(
+        vec!["processing upload of 1024 bytes", "processed upload"],
+        emitted.iter().map(|record| record.msg()).collect::<Vec<_>>()
+    );
+}
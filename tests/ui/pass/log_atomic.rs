@@ -0,0 +1,29 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!(
+            concat!(
+                "{\"lvl\":\"info\",\"msg\":\"handled 3 requests\",\"target\":\"",
+                module_path!(),
+                "\",\"requests\":3}"
+            ),
+            sval_json::to_string(record).unwrap()
+        );
+    });
+
+    let requests = Arc::new(AtomicU64::new(0));
+    for _ in 0..3 {
+        requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    emit::info!("handled {requests: *requests} requests");
+
+    // Atomics are captured as the value they hold with `#[as_debug]` too
+    emit::info!("handled {#[emit::as_debug] requests: *requests} requests");
+}
@@ -0,0 +1,91 @@
+use std::{
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use emit::{Emitter, OwnedRecord, Record};
+
+enum Message {
+    Record(OwnedRecord),
+    Flush(Sender<()>),
+}
+
+/**
+An emitter that delivers records on a background thread.
+*/
+struct BackgroundEmitter {
+    sender: Mutex<Sender<Message>>,
+}
+
+impl BackgroundEmitter {
+    fn new(delivered: Arc<Mutex<Vec<String>>>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for message in receiver {
+                match message {
+                    Message::Record(record) => {
+                        // Simulate a slow sink
+                        thread::sleep(Duration::from_millis(50));
+                        delivered.lock().unwrap().push(record.msg().to_owned());
+                    }
+                    Message::Flush(done) => drop(done.send(())),
+                }
+            }
+        });
+
+        BackgroundEmitter {
+            sender: Mutex::new(sender),
+        }
+    }
+}
+
+impl Emitter for BackgroundEmitter {
+    fn emit(&self, record: &Record) {
+        drop(
+            self.sender
+                .lock()
+                .unwrap()
+                .send(Message::Record(record.to_owned())),
+        );
+    }
+
+    fn flush(&self) {
+        let (done, wait) = mpsc::channel();
+
+        if self
+            .sender
+            .lock()
+            .unwrap()
+            .send(Message::Flush(done))
+            .is_ok()
+        {
+            drop(wait.recv());
+        }
+    }
+}
+
+fn main() {
+    let delivered = Arc::new(Mutex::new(Vec::new()));
+
+    emit::set_emitter(BackgroundEmitter::new(delivered.clone()));
+
+    emit::info!("queued");
+    emit::emit_blocking!(emit::Level::Error, "failed with {code}", code: 1);
+
+    // Both records have been delivered by the time the macro returns
+    assert_eq!(vec!["queued", "failed with 1"], *delivered.lock().unwrap());
+
+    // Emitting to an explicit target flushes that target
+    let target = BackgroundEmitter::new(delivered.clone());
+    emit::emit_blocking!(target: target, "sent to {name}", name: "a target");
+
+    assert_eq!(
+        vec!["queued", "failed with 1", "sent to a target"],
+        *delivered.lock().unwrap()
+    );
+}
@@ -0,0 +1,48 @@
+use std::sync::{Arc, Mutex};
+
+fn main() {
+    let emitted = Arc::new(Mutex::new(Vec::new()));
+
+    let guard = emit::Builder::new()
+        .emitter({
+            let emitted = emitted.clone();
+            move |record: &emit::Record| {
+                let kvs = record.kvs();
+
+                emitted.lock().unwrap().push((
+                    record.msg().to_string(),
+                    kvs.get("service").map(|v| v.to_string()),
+                    kvs.get("env").map(|v| v.to_string()),
+                ))
+            }
+        })
+        .filter(|record: &emit::Record| record.level() >= emit::Level::Info)
+        .with_field("service", "api")
+        .with_field("env", "dev")
+        .with_field("env", "prod")
+        .install();
+
+    emit::debug!("filtered out");
+    emit::info!("started");
+
+    // Key-values captured on the record take precedence over fields
+    emit::warn!("overridden", service: "worker");
+
+    drop(guard);
+
+    assert_eq!(
+        vec![
+            (
+                "started".to_owned(),
+                Some("api".to_owned()),
+                Some("prod".to_owned())
+            ),
+            (
+                "overridden".to_owned(),
+                Some("worker".to_owned()),
+                Some("prod".to_owned())
+            ),
+        ],
+        *emitted.lock().unwrap()
+    );
+}
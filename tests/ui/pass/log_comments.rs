@@ -0,0 +1,19 @@
+extern crate emit;
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!("Text 1 and 2", record.msg().to_string());
+        assert_eq!("Text {a} and {b}", record.template_source().to_string());
+        assert_eq!(
+            vec!["a", "b"],
+            record
+                .kvs()
+                .sorted_key_values
+                .iter()
+                .map(|(k, _)| *k)
+                .collect::<Vec<_>>()
+        );
+    });
+
+    emit::info!("Text {a}{! the first hole !} and {! the second hole !}{b}", a: 1, b: 2);
+}
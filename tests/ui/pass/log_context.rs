@@ -0,0 +1,23 @@
+extern crate emit;
+
+fn main() {
+    emit::target(|record| {
+        let request_id = record.kvs().get("request_id").map(|v| v.to_string());
+
+        match record.template().to_string().as_str() {
+            "with context" => assert_eq!(Some("42"), request_id.as_deref()),
+            "without context" => assert_eq!(None, request_id),
+            "overridden" => assert_eq!(Some("1"), request_id.as_deref()),
+            _ => unreachable!(),
+        }
+    });
+
+    emit::context::insert("request_id", 42);
+    emit::info!("with context");
+
+    let request_id = 1;
+    emit::info!("overridden", request_id);
+
+    emit::context::remove("request_id");
+    emit::info!("without context");
+}
@@ -0,0 +1,38 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::sync::{Arc, Mutex};
+
+fn main() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+
+    emit::target({
+        let records = records.clone();
+        move |record| records.lock().unwrap().push(record.to_owned())
+    });
+
+    emit::context::set_correlation_id("3f2b9c");
+
+    emit::info!("handling request");
+    emit::info!("handling request", correlation_id: "override");
+
+    emit::context::clear_correlation_id();
+
+    emit::info!("done");
+
+    let records = records.lock().unwrap();
+
+    assert_eq!(
+        vec![("correlation_id", &emit::OwnedValue::from("3f2b9c"))],
+        records[0].kvs().collect::<Vec<_>>()
+    );
+
+    // The correlation id captured on the record takes precedence
+    assert_eq!(
+        vec![("correlation_id", &emit::OwnedValue::from("override"))],
+        records[1].kvs().collect::<Vec<_>>()
+    );
+
+    assert_eq!(0, records[2].kvs().count());
+}
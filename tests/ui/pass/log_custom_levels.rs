@@ -0,0 +1,52 @@
+use std::sync::{Arc, Mutex};
+
+use emit::{filter, Level};
+
+emit::define_levels! {
+    pub enum MyLevel {
+        Notice = 35,
+        Critical = 60,
+    }
+}
+
+fn main() {
+    let emitted = Arc::new(Mutex::new(Vec::new()));
+
+    emit::set_filter(filter::severity_at_least(35));
+    emit::target({
+        let emitted = emitted.clone();
+        move |record| {
+            emitted.lock().unwrap().push((
+                record.level(),
+                record.level().severity(),
+                record.msg().to_string(),
+            ))
+        }
+    });
+
+    emit::emit!(MyLevel::Critical, "the disk is {percent}% full", percent: 95);
+    emit::emit!(MyLevel::Notice, "the disk is filling up");
+    emit::emit!(Level::Warn, "the disk is slow");
+    emit::emit!(Level::Info, "the disk is fine");
+    emit::debug!("checked the disk");
+
+    assert_eq!(
+        vec![
+            (
+                Level::custom("Critical", 60),
+                60,
+                "the disk is 95% full".to_owned()
+            ),
+            (
+                Level::custom("Notice", 35),
+                35,
+                "the disk is filling up".to_owned()
+            ),
+            (Level::Warn, 40, "the disk is slow".to_owned()),
+        ],
+        *emitted.lock().unwrap()
+    );
+
+    assert!(MyLevel::Critical.to_level() > Level::Error);
+    assert_eq!(Level::from(MyLevel::Notice), MyLevel::Notice.to_level());
+}
@@ -0,0 +1,41 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::{cell::RefCell, fmt, rc::Rc};
+
+// A node in a graph that can point back to itself
+struct Node {
+    id: u32,
+    next: RefCell<Option<Rc<Node>>>,
+}
+
+impl fmt::Debug for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Node")
+            .field("id", &self.id)
+            .field("next", &self.next.borrow())
+            .finish()
+    }
+}
+
+fn main() {
+    emit::target(|record| {
+        let node = record.msg().to_string();
+
+        assert!(node.len() <= "node ".len() + 256 + "<truncated>".len());
+        assert!(node.starts_with("node Node { id: 1, next: Some(Node { id: 1"));
+        assert!(node.ends_with("<truncated>"));
+    });
+
+    let node = Rc::new(Node {
+        id: 1,
+        next: RefCell::new(None),
+    });
+    *node.next.borrow_mut() = Some(node.clone());
+
+    emit::info!("node {#[emit::as_debug(max_len = 256)] node}");
+
+    // Break the cycle so the node is dropped
+    node.next.borrow_mut().take();
+}
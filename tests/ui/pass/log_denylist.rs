@@ -0,0 +1,48 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::sync::{Arc, Mutex};
+
+fn main() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+
+    emit::set_key_denylist(&["password", "authorization"], emit::Denied::Remove);
+
+    // Keys added by the enricher are denied too
+    emit::set_enricher(|record: &mut emit::OwnedRecord| {
+        record.insert_kv("authorization", "Bearer abc");
+    });
+
+    // Fields added by the builder are denied too
+    let guard = emit::Builder::new()
+        .emitter({
+            let records = records.clone();
+            move |record: &emit::Record| records.lock().unwrap().push(record.to_owned())
+        })
+        .with_field("service", "api")
+        .with_field("password", "hunter2")
+        .install();
+
+    let password = "hunter2";
+    emit::info!("logging in {user} with {password}", user: "a", password);
+    emit::info!("logged in");
+
+    drop(guard);
+
+    let records = records.lock().unwrap();
+
+    let kvs = |i: usize| {
+        records[i]
+            .kvs()
+            .map(|(k, v)| (k, v.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        vec![("service", "api".to_owned()), ("user", "a".to_owned())],
+        kvs(0)
+    );
+    assert_eq!("logging in a with `password`", records[0].msg());
+    assert_eq!(vec![("service", "api".to_owned())], kvs(1));
+}
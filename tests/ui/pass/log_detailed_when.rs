@@ -0,0 +1,49 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+use std::fmt;
+
+use sval::value::{self, Value};
+
+struct Payload {
+    id: u64,
+}
+
+impl fmt::Display for Payload {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "payload {}", self.id)
+    }
+}
+
+impl Value for Payload {
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        stream.map_begin(Some(1))?;
+        stream.map_key("id")?;
+        stream.map_value(self.id)?;
+        stream.map_end()
+    }
+}
+
+fn main() {
+    emit::target(|record| {
+        let expected = match record.level() {
+            emit::Level::Debug => concat!(
+                "{\"lvl\":\"debug\",\"msg\":\"received {\\\"id\\\": 1}\",\"target\":\"",
+                module_path!(),
+                "\",\"payload\":{\"id\":1}}"
+            ),
+            emit::Level::Info => concat!(
+                "{\"lvl\":\"info\",\"msg\":\"received payload 1\",\"target\":\"",
+                module_path!(),
+                "\",\"payload\":\"payload 1\"}"
+            ),
+            level => panic!("unexpected level {}", level),
+        };
+
+        assert_eq!(expected, sval_json::to_string(record).unwrap());
+    });
+
+    let payload = Payload { id: 1 };
+
+    emit::debug!("received {#[detailed_when(Level::Debug)] payload}");
+    emit::info!("received {#[detailed_when(Level::Debug)] payload}");
+}
@@ -0,0 +1,49 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+#[macro_use]
+extern crate serde_derive;
+
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Serialize)]
+struct User {
+    name: String,
+    email: String,
+    admin: bool,
+}
+
+fn main() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+
+    emit::target({
+        let records = records.clone();
+        move |record| {
+            records
+                .lock()
+                .unwrap()
+                .push(sval_json::to_string(record).unwrap())
+        }
+    });
+
+    let old = User {
+        name: "a".into(),
+        email: "a@example.com".into(),
+        admin: false,
+    };
+
+    let new = User {
+        email: "b@example.com".into(),
+        admin: true,
+        ..old.clone()
+    };
+
+    emit::info!("updated user", #[emit::as_diff] user: (old, new));
+
+    // Only the keys that changed are reported
+    assert_eq!(
+        concat!("{\"lvl\":\"info\",\"msg\":\"updated user\",\"target\":\"", module_path!(), "\",\"user\":{\"added\":{},\"changed\":{\"admin\":{\"new\":true,\"old\":false},\"email\":{\"new\":\"b@example.com\",\"old\":\"a@example.com\"}},\"removed\":{}}}"),
+        records.lock().unwrap()[0]
+    );
+}
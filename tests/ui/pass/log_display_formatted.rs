@@ -0,0 +1,14 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!("id [        42] name [emit]", record.msg().to_string());
+    });
+
+    let id = 42;
+    emit::info!(
+        "id [{#[emit::as_display(\"{:>10}\")] id}] name [{#[emit::as_display(\"{:.4}\")] name: \"emitted\"}]"
+    );
+}
@@ -0,0 +1,67 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::sync::{Arc, Mutex};
+
+fn main() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+
+    emit::target({
+        let records = records.clone();
+        move |record| records.lock().unwrap().push(record.to_owned())
+    });
+
+    // The enricher runs after the context is added
+    emit::set_enricher(|record: &mut emit::OwnedRecord| {
+        record.insert_kv("pid", 42);
+        record.insert_kv("host", "web-1");
+
+        if record.kvs().any(|(k, _)| k == "request_id") {
+            record.insert_kv("traced", true);
+        }
+    });
+
+    emit::info!("first {id}", id: 1);
+
+    emit::context::insert("request_id", 7);
+    emit::info!("second");
+    emit::context::remove("request_id");
+
+    let records = records.lock().unwrap();
+
+    // Key-values are in the order they were added in with `no_sort`
+    let kvs = |i: usize| {
+        let mut kvs = records[i]
+            .kvs()
+            .map(|(k, v)| (k, v.clone()))
+            .collect::<Vec<_>>();
+        kvs.sort_by_key(|(k, _)| *k);
+        kvs
+    };
+
+    // Values the enricher adds keep their type
+    assert_eq!(
+        vec![
+            ("host", emit::OwnedValue::from("web-1")),
+            ("id", emit::OwnedValue::I64(1)),
+            ("pid", emit::OwnedValue::I64(42))
+        ],
+        kvs(0)
+    );
+    assert_eq!(
+        vec![
+            ("host", emit::OwnedValue::from("web-1")),
+            ("pid", emit::OwnedValue::I64(42)),
+            ("request_id", emit::OwnedValue::I64(7)),
+            ("traced", emit::OwnedValue::Bool(true))
+        ],
+        kvs(1)
+    );
+
+    assert_eq!("first 1", records[0].msg());
+
+    // Strings the enricher adds aren't quoted
+    let host = records[0].kvs().find(|(k, _)| *k == "host").unwrap().1;
+    assert_eq!("web-1", host.to_string());
+}
@@ -0,0 +1,26 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+#[derive(Debug)]
+struct Id(u32);
+
+fn main() {
+    emit::target(|record| match record.kvs().get("b") {
+        Some(_) => {
+            assert_eq!("Text {a} and 1", record.msg().to_string());
+            assert_eq!("Text {a} and `b`", record.template().to_string());
+            assert_eq!("Text {{a}} and {b}", record.template_source().to_string());
+        }
+        // Holes are written exactly as they appear in the template
+        None => assert_eq!(
+            "{{{#[emit::as_debug] id}}} has {len: 1 + 1}",
+            record.template_source().to_string()
+        ),
+    });
+
+    emit::info!("Text {{a}} and {b}", b: 1);
+
+    let id = Id(1);
+    emit::info!("{{{#[emit::as_debug] id}}} has {len: 1 + 1}");
+}
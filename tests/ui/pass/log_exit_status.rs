@@ -0,0 +1,39 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::process::ExitStatus;
+
+#[cfg(unix)]
+use std::os::unix::process::ExitStatusExt;
+#[cfg(windows)]
+use std::os::windows::process::ExitStatusExt;
+
+fn main() {
+    emit::target(|record| {
+        let expected = match record.msg().to_string().as_str() {
+            "exited with 0" => concat!(
+                "{\"lvl\":\"info\",\"msg\":\"exited with 0\",\"target\":\"",
+                module_path!(),
+                "\",\"status\":{\"success\":true,\"code\":0}}"
+            ),
+            "exited with 1" => concat!(
+                "{\"lvl\":\"info\",\"msg\":\"exited with 1\",\"target\":\"",
+                module_path!(),
+                "\",\"status\":{\"success\":false,\"code\":1}}"
+            ),
+            msg => panic!("unexpected message {}", msg),
+        };
+
+        assert_eq!(expected, sval_json::to_string(record).unwrap());
+    });
+
+    // On Unix, the exit code is in the high bits of the raw wait status
+    let statuses = if cfg!(unix) { [0, 1 << 8] } else { [0, 1] };
+
+    for status in statuses {
+        let status = ExitStatus::from_raw(status as _);
+
+        emit::info!("exited with {#[emit::as_exit_status] status}");
+    }
+}
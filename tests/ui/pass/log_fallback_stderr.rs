@@ -0,0 +1,51 @@
+extern crate emit;
+
+use std::{
+    env,
+    process::Command,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+const CHILD: &str = "EMIT_FALLBACK_STDERR_CHILD";
+
+fn main() {
+    // Run the logging in a child process so its stderr can be checked
+    if env::var_os(CHILD).is_some() {
+        return child();
+    }
+
+    let output = Command::new(env::current_exe().unwrap())
+        .env(CHILD, "1")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(stderr.contains("Before the emitter is set 1"), "{}", stderr);
+    assert!(!stderr.contains("After the emitter is set"), "{}", stderr);
+}
+
+fn child() {
+    emit::fallback_to_stderr();
+
+    // Written to stderr
+    emit::info!("Before the emitter is set {a}", a: 1);
+
+    let emitted = Arc::new(AtomicUsize::new(0));
+
+    emit::target({
+        let emitted = emitted.clone();
+        move |_| {
+            emitted.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    emit::info!("After the emitter is set {a}", a: 2);
+
+    assert_eq!(1, emitted.load(Ordering::Relaxed));
+}
@@ -0,0 +1,44 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+#[derive(Clone, Copy)]
+struct Permissions(u8);
+
+impl Permissions {
+    const READ: Self = Permissions(0b001);
+    const WRITE: Self = Permissions(0b010);
+    const EXECUTE: Self = Permissions(0b100);
+}
+
+impl std::ops::BitOr for Permissions {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Permissions(self.0 | other.0)
+    }
+}
+
+impl emit::Flags for Permissions {
+    const FLAGS: &'static [(&'static str, u64)] = &[
+        ("READ", Self::READ.0 as u64),
+        ("WRITE", Self::WRITE.0 as u64),
+        ("EXECUTE", Self::EXECUTE.0 as u64),
+    ];
+
+    fn bits(&self) -> u64 {
+        self.0 as u64
+    }
+}
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!(
+            concat!("{\"lvl\":\"info\",\"msg\":\"opened with [\\\"READ\\\", \\\"EXECUTE\\\"]\",\"target\":\"", module_path!(), "\",\"perms\":[\"READ\",\"EXECUTE\"]}"),
+            sval_json::to_string(record).unwrap()
+        );
+    });
+
+    let perms = Permissions::READ | Permissions::EXECUTE;
+    emit::info!("opened with {#[emit::as_flags] perms}");
+}
@@ -0,0 +1,35 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+fn main() {
+    let delivered = Arc::new(Mutex::new(Vec::new()));
+
+    emit::set_emitter(emit::AsyncEmitter::new(Default::default(), {
+        let delivered = delivered.clone();
+
+        move |record: &emit::OwnedRecord| {
+            // Deliver slowly so records are still queued when they're flushed
+            thread::sleep(Duration::from_millis(10));
+
+            delivered.lock().unwrap().push(record.msg().to_owned())
+        }
+    }));
+
+    for i in 0..5 {
+        emit::info!("record {i}", i);
+    }
+
+    emit::flush();
+
+    assert_eq!(
+        vec!["record 0", "record 1", "record 2", "record 3", "record 4"],
+        *delivered.lock().unwrap()
+    );
+}
@@ -0,0 +1,17 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene, format_args_capture)]
+
+#[derive(Debug)]
+struct Request {
+    method: &'static str,
+}
+
+fn main() {
+    let status = 200;
+    let request = Request { method: "GET" };
+
+    let expected = format!("{} {:?} finished with {status} in {ms}ms {{done}}", request.method, request, ms = 12.5);
+
+    emit::target(move |record| assert_eq!(expected, record.msg().to_string()));
+
+    emit::fmt!("{} {:?} finished with {status} in {ms}ms {{done}}", request.method, request, ms = 12.5);
+}
@@ -0,0 +1,37 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+fn main() {
+    let emitted = Arc::new(AtomicUsize::new(0));
+    let flushed = Arc::new(AtomicUsize::new(0));
+
+    emit::set_emitter(
+        emit::FnEmitter::new({
+            let emitted = emitted.clone();
+            move |record| {
+                assert_eq!("hello world", record.msg().to_string());
+                emitted.fetch_add(1, Ordering::Relaxed);
+            }
+        })
+        .with_flush({
+            let flushed = flushed.clone();
+            move || {
+                flushed.fetch_add(1, Ordering::Relaxed);
+            }
+        }),
+    );
+
+    let world = "world";
+    emit::info!("hello {world}", world);
+
+    emit::flush();
+
+    assert_eq!(1, emitted.load(Ordering::Relaxed));
+    assert_eq!(1, flushed.load(Ordering::Relaxed));
+}
@@ -0,0 +1,16 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+fn main() {
+    let method = "GET";
+
+    emit::info!(
+        target: |record| assert_eq!("Request GET finished with 200", record.msg().to_string()),
+        "Request {method} finished with {status}",
+        #[group = "http"]
+        method,
+        #[group = "http"]
+        status: 200,
+    );
+}
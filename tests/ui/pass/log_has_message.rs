@@ -0,0 +1,17 @@
+use std::sync::{Arc, Mutex};
+
+fn main() {
+    let emitted = Arc::new(Mutex::new(Vec::new()));
+
+    emit::target({
+        let emitted = emitted.clone();
+        move |record| emitted.lock().unwrap().push(record.has_message())
+    });
+
+    let a = 1;
+    emit::info!("", a);
+    emit::info!("");
+    emit::info!("Text {a}", a);
+
+    assert_eq!(vec![false, false, true], *emitted.lock().unwrap());
+}
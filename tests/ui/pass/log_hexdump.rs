@@ -0,0 +1,25 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!(
+            "received 00000000  01 02 41 42                                       |..AB|",
+            record.msg().to_string()
+        );
+        assert_eq!(
+            "00000000  01 02                                             |..|\n<truncated>",
+            record.kvs().get("header").unwrap().to_string()
+        );
+    });
+
+    let buf = vec![1u8, 2, b'A', b'B'];
+    let header = &buf[..];
+
+    emit::info!(
+        "received {#[emit::as_hexdump] buf}",
+        #[emit::as_hexdump(max_len = 2)]
+        header
+    );
+}
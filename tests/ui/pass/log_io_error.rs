@@ -0,0 +1,34 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::io;
+
+fn main() {
+    emit::target(|record| {
+        let expected = match record.msg().to_string().as_str() {
+            "failed to open: config.toml is missing" => {
+                concat!("{\"lvl\":\"error\",\"msg\":\"failed to open: config.toml is missing\",\"target\":\"", module_path!(), "\",\"err\":{\"kind\":\"NotFound\",\"msg\":\"config.toml is missing\"}}")
+                    .to_owned()
+            }
+            msg if msg.starts_with("failed to open: ") => format!(
+                "{{\"lvl\":\"error\",\"msg\":\"{}\",\"target\":\"{}\",\"err\":{{\"kind\":\"PermissionDenied\",\"code\":13,\"msg\":\"{}\"}}}}",
+                msg,
+                module_path!(),
+                &msg["failed to open: ".len()..]
+            ),
+            msg => panic!("unexpected message {}", msg),
+        };
+
+        assert_eq!(expected, sval_json::to_string(record).unwrap());
+    });
+
+    let err = io::Error::new(io::ErrorKind::NotFound, "config.toml is missing");
+    emit::error!("failed to open: {#[emit::as_io_error] err}");
+
+    // `EACCES` on Linux and macOS
+    if cfg!(any(target_os = "linux", target_os = "macos")) {
+        let err = io::Error::from_raw_os_error(13);
+        emit::error!("failed to open: {#[emit::as_io_error] err}");
+    }
+}
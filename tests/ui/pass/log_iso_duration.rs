@@ -0,0 +1,14 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::time::Duration;
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!("took PT1H30M", record.msg().to_string());
+    });
+
+    let elapsed = Duration::from_secs(90 * 60);
+    emit::info!("took {#[emit::as_iso_duration] elapsed}");
+}
@@ -0,0 +1,36 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+use std::cell::Cell;
+
+fn main() {
+    emit::target(|record| {
+        let expected = match record.msg().to_string().as_str() {
+            "numbers" => concat!(
+                "{\"lvl\":\"info\",\"msg\":\"numbers\",\"target\":\"",
+                module_path!(),
+                "\",\"numbers\":[0,1,2,3,4,5,6,7,8,9,\"<truncated>\"]}"
+            ),
+            "names" => concat!(
+                "{\"lvl\":\"info\",\"msg\":\"names\",\"target\":\"",
+                module_path!(),
+                "\",\"names\":[\"a\",\"b\"]}"
+            ),
+            msg => panic!("unexpected message {}", msg),
+        };
+
+        assert_eq!(expected, sval_json::to_string(record).unwrap());
+    });
+
+    let pulled = Cell::new(0);
+    let mut numbers = (0..u64::MAX).inspect(|_| pulled.set(pulled.get() + 1));
+
+    emit::info!("numbers", #[emit::as_iter(take = 10)] numbers: numbers.by_ref());
+
+    // Only one element beyond the limit is pulled to tell whether it was truncated
+    assert_eq!(11, pulled.get());
+    assert_eq!(Some(11), numbers.next());
+
+    // Iterators of references capture the values they point to
+    let names = vec![String::from("a"), String::from("b")];
+    emit::info!("names", #[emit::as_iter(take = 10)] names: names.iter());
+}
@@ -0,0 +1,46 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+extern crate serde_json_lib as serde_json;
+
+#[macro_use]
+extern crate serde_derive;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Work {
+    id: u64,
+    description: String,
+    tags: Vec<String>,
+}
+
+fn main() {
+    emit::target(|record| {
+        let kvs = record.kvs();
+        let json = kvs
+            .get("work")
+            .and_then(|work| work.to_borrowed_str())
+            .expect("expected a string");
+
+        // The JSON is compact
+        assert!(!json.contains(' '));
+
+        let work: Work = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            Work {
+                id: 1,
+                description: "upload".to_owned(),
+                tags: vec!["a".to_owned(), "b".to_owned()],
+            },
+            work
+        );
+    });
+
+    let work = Work {
+        id: 1,
+        description: "upload".to_owned(),
+        tags: vec!["a".to_owned(), "b".to_owned()],
+    };
+
+    emit::info!("scheduling {#[emit::as_json_string] work}");
+}
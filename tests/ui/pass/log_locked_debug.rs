@@ -0,0 +1,14 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::sync::Mutex;
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!("state [1, 2, 3]", record.msg().to_string());
+    });
+
+    let state = Mutex::new(vec![1, 2, 3]);
+    emit::info!("state {#[emit::as_locked_debug] state}");
+}
@@ -0,0 +1,39 @@
+use std::sync::{Arc, Mutex};
+
+fn main() {
+    let emitted = Arc::new(Mutex::new(Vec::new()));
+
+    let logger = emit::Logger::new({
+        let emitted = emitted.clone();
+        move |record: &emit::Record| emitted.lock().unwrap().push(sval_json::to_string(record).unwrap())
+    })
+    .with_field("service", "api");
+
+    emit::info!(target: logger, "Started on port {port}", port: 8080);
+    emit::warn!(target: &logger, "Overriding the service", service: "web");
+
+    // Loggers can also be passed as the first argument
+    emit::info!(logger, "Started on port {port}", port: 8080);
+
+    struct Service {
+        logger: emit::Logger,
+    }
+
+    impl Service {
+        fn stop(&self) {
+            emit::emit!(emit::Level::Error, self.logger, "Stopping");
+        }
+    }
+
+    Service { logger }.stop();
+
+    assert_eq!(
+        vec![
+            concat!("{\"lvl\":\"info\",\"msg\":\"Started on port 8080\",\"target\":\"", module_path!(), "\",\"port\":8080,\"service\":\"api\"}"),
+            concat!("{\"lvl\":\"warn\",\"msg\":\"Overriding the service\",\"target\":\"", module_path!(), "\",\"service\":\"web\"}"),
+            concat!("{\"lvl\":\"info\",\"msg\":\"Started on port 8080\",\"target\":\"", module_path!(), "\",\"port\":8080,\"service\":\"api\"}"),
+            concat!("{\"lvl\":\"error\",\"msg\":\"Stopping\",\"target\":\"", module_path!(), "\",\"service\":\"api\"}"),
+        ],
+        *emitted.lock().unwrap()
+    );
+}
@@ -0,0 +1,59 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+use std::sync::{Arc, Mutex};
+
+use emit::{as_debug, emit, emit_blocking, info, warn, with_fields};
+
+mod api {
+    // Items with the same names as the ones generated code uses don't interfere with it
+    mod rt {}
+    mod emit {}
+
+    use emit_imports::info;
+
+    pub fn handle(id: u64) {
+        info!("handling {id}", id);
+        info!("handled in {elapsed}s", #[precision(2)] elapsed: 1.2345);
+    }
+
+    mod emit_imports {
+        pub use ::emit::info;
+    }
+}
+
+#[derive(Debug)]
+struct Request;
+
+fn main() {
+    let msgs = Arc::new(Mutex::new(Vec::new()));
+
+    ::emit::target({
+        let msgs = msgs.clone();
+        move |record| msgs.lock().unwrap().push(record.msg().to_string())
+    });
+
+    let request = Request;
+
+    info!("received {#[as_debug] request}");
+    warn!("slow {ms}", ms: 150);
+    emit!(::emit::Level::Error, "failed");
+    emit_blocking!("flushed");
+    api::handle(1);
+
+    with_fields!({ request_id: 7 }, {
+        info!("in scope");
+    });
+
+    assert_eq!(
+        vec![
+            "received Request",
+            "slow 150",
+            "failed",
+            "flushed",
+            "handling 1",
+            "handled in 1.23s",
+            "in scope",
+        ],
+        *msgs.lock().unwrap()
+    );
+}
@@ -0,0 +1,47 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::sync::{Arc, Mutex};
+
+fn build(buf: &mut String) {
+    buf.push_str("abc");
+
+    emit::info!("built {buf}", buf);
+    emit::info!(
+        "built",
+        #[emit::as_debug]
+        buf
+    );
+
+    buf.push_str("def");
+}
+
+fn main() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+
+    emit::target({
+        let records = records.clone();
+        move |record| records.lock().unwrap().push(record.to_owned())
+    });
+
+    let mut buf = String::new();
+    build(&mut buf);
+
+    let mut n = 1;
+    let n_mut = &mut n;
+    emit::info!("n: {n_mut}", n_mut);
+    *n_mut += 1;
+
+    assert_eq!("abcdef", buf);
+    assert_eq!(2, n);
+
+    let records = records.lock().unwrap();
+
+    assert_eq!("built abc", records[0].msg());
+    assert_eq!(
+        vec![("buf", &emit::OwnedValue::from_debug("abc"))],
+        records[1].kvs().collect::<Vec<_>>()
+    );
+    assert_eq!("n: 1", records[2].msg());
+}
@@ -0,0 +1,6 @@
+extern crate emit;
+
+fn main() {
+    // Emitting without an emitter is a no-op
+    emit::info!("Nothing to see here {a}", a: 1);
+}
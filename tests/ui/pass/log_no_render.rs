@@ -0,0 +1,30 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+fn main() {
+    emit::target(|record| {
+        // The field is stored, but its hole renders as missing
+        assert_eq!(
+            Some("alice"),
+            record.kvs().get("user").and_then(|v| v.to_borrowed_str())
+        );
+        assert_eq!("`user` logged in from 1.1.1.1", record.msg().to_string());
+        assert_eq!(
+            "<hidden> logged in from 1.1.1.1",
+            record
+                .render_with(emit::RenderOptions::new().missing_hole("<hidden>"))
+                .to_string()
+        );
+    });
+
+    let user = "alice";
+    let ip = "1.1.1.1";
+
+    emit::info!(
+        "{user} logged in from {ip}",
+        #[no_render]
+        user,
+        ip
+    );
+}
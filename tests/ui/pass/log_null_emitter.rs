@@ -0,0 +1,31 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static FORMATTED: AtomicUsize = AtomicUsize::new(0);
+
+struct Formatted;
+
+impl fmt::Display for Formatted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        FORMATTED.fetch_add(1, Ordering::Relaxed);
+        f.write_str("formatted")
+    }
+}
+
+fn main() {
+    emit::fallback_to_stderr();
+    emit::set_emitter(emit::NullEmitter::new());
+
+    // Discarded instead of being written to stderr by the fallback,
+    // which would format the value into the message
+    emit::info!("discarded {value}", value: Formatted);
+    emit::flush();
+
+    assert_eq!(0, FORMATTED.load(Ordering::Relaxed));
+}
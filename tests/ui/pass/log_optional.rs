@@ -0,0 +1,37 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+#[path = "../support/json.rs"]
+mod support;
+
+use support::json;
+
+fn main() {
+    emit::target(|record| {
+        let expected = match record.msg().to_string().as_str() {
+            "user is \"sam\"" => concat!("{\"lvl\":\"info\",\"msg\":\"user is \\\"sam\\\"\",\"target\":\"", module_path!(), "\",\"id\":1,\"user\":\"\\\"sam\\\"\"}"),
+            "user is `user`" => concat!("{\"lvl\":\"info\",\"msg\":\"user is `user`\",\"target\":\"", module_path!(), "\",\"id\":1}"),
+            "owned user" => concat!("{\"lvl\":\"info\",\"msg\":\"owned user\",\"target\":\"", module_path!(), "\",\"user\":\"sam\"}"),
+            "no owned user" => concat!("{\"lvl\":\"info\",\"msg\":\"no owned user\",\"target\":\"", module_path!(), "\"}"),
+            msg => panic!("unexpected message {}", msg),
+        };
+
+        assert_eq!(json(expected), json(&sval_json::to_string(record).unwrap()));
+    });
+
+    let name = String::from("sam");
+
+    // `Option<&T>` captures the inner `&T` without cloning it
+    for user in [Some(&name), None] {
+        emit::info!("user is {#[optional] #[emit::as_debug] user}", id: 1);
+    }
+
+    // `Option<T>` is captured by reference to the inner `T`
+    let user = Some(name);
+    emit::info!("owned user", #[optional] user);
+    let _ = user;
+
+    let user: Option<String> = None;
+    emit::info!("no owned user", #[optional] user);
+}
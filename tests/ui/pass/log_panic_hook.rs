@@ -0,0 +1,47 @@
+use std::{
+    panic,
+    sync::{Arc, Mutex},
+};
+
+fn main() {
+    let emitted = Arc::new(Mutex::new(Vec::new()));
+
+    emit::target({
+        let emitted = emitted.clone();
+        move |record| {
+            let msg = record.msg().to_string();
+
+            // Keep the lock while panicking so re-entering this emitter would deadlock
+            let mut emitted = emitted.lock().unwrap_or_else(|err| err.into_inner());
+
+            if msg == "the emitter panics" {
+                panic!("failed to emit");
+            }
+
+            emitted.push(msg);
+        }
+    });
+
+    panic::set_hook(Box::new(|info| {
+        let payload = info
+            .payload()
+            .downcast_ref::<&str>()
+            .copied()
+            .unwrap_or("unknown");
+
+        emit::emit_blocking!(emit::Level::Error, "panicked: {payload}", payload);
+    }));
+
+    // A panic outside of an emitter is emitted from the hook
+    assert!(panic::catch_unwind(|| panic!("oh no")).is_err());
+
+    // A panic inside the emitter isn't emitted again from the hook
+    assert!(panic::catch_unwind(|| emit::info!("the emitter panics")).is_err());
+
+    emit::info!("still emitting");
+
+    assert_eq!(
+        vec!["panicked: oh no", "still emitting"],
+        *emitted.lock().unwrap_or_else(|err| err.into_inner())
+    );
+}
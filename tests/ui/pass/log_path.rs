@@ -0,0 +1,23 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::path::{Path, PathBuf};
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!(
+            Some("/var/log/app.log"),
+            record.kvs().get("path").and_then(|path| path.to_borrowed_str())
+        );
+        assert_eq!("writing to /var/log/app.log", record.msg().to_string());
+    });
+
+    let path = PathBuf::from("/var/log/app.log");
+
+    emit::info!("writing to {#[emit::as_path] path}");
+
+    let path: &Path = &path;
+
+    emit::info!("writing to {#[emit::as_path] path}");
+}
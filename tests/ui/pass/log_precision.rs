@@ -0,0 +1,21 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!("took 3.14s", record.msg().to_string());
+        assert_eq!(Some(3.14), record.kvs()["secs"].to_f64());
+        assert_eq!(Some(0.3), record.kvs()["ratio"].to_f64());
+    });
+
+    let secs = 3.14159;
+    let ratio: Option<f32> = Some(0.2999);
+
+    emit::info!(
+        "took {#[precision(2)] secs}s",
+        #[optional]
+        #[precision(1)]
+        ratio
+    );
+}
@@ -0,0 +1,29 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+#[path = "../support/json.rs"]
+mod support;
+
+use support::json;
+
+struct Item {
+    id: u64,
+    name: &'static str,
+}
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!(
+            json(concat!("{\"lvl\":\"info\",\"msg\":\"processed a batch\",\"target\":\"", module_path!(), "\",\"items\":[{\"msg\":\"1: a (1)\",\"id\":1,\"len\":1,\"name\":\"a\"},{\"msg\":\"2: bc (2)\",\"id\":2,\"len\":2,\"name\":\"bc\"}]}")),
+            json(&sval_json::to_string(record).unwrap())
+        );
+    });
+
+    let items = vec![Item { id: 1, name: "a" }, Item { id: 2, name: "bc" }];
+
+    emit::info!(
+        "processed a batch",
+        #[emit::as_records("{id}: {name} ({len: item.name.len()})")] items
+    );
+}
@@ -0,0 +1,21 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+fn main() {
+    emit::target(|record| {
+        let msg = record.msg().to_string();
+
+        assert!(!msg.contains("123-45-6789"), "{}", msg);
+
+        let ssn = record.kvs()["ssn"].to_string();
+        assert_eq!(32, ssn.len());
+        assert!(ssn.chars().all(|c| c.is_ascii_hexdigit()), "{}", ssn);
+        assert_eq!(ssn, record.kvs()["other"].to_string());
+    });
+
+    let ssn = "123-45-6789";
+    emit::info!(
+        "Checking {#[emit::redact] ssn} against {#[emit::as_debug(redact)] other: String::from(ssn)}"
+    );
+}
@@ -0,0 +1,50 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+fn main() {
+    let emitted = Arc::new(Mutex::new(Vec::new()));
+
+    emit::target({
+        let emitted = emitted.clone();
+        move |record| {
+            emitted.lock().unwrap().push(
+                record
+                    .kvs()
+                    .as_map()
+                    .into_iter()
+                    .map(|(k, v)| (k.to_owned(), v.to_string()))
+                    .collect::<Vec<_>>(),
+            )
+        }
+    });
+
+    let mut config = BTreeMap::new();
+    config.insert("db.host".to_owned(), "localhost".to_owned());
+    config.insert("db.port".to_owned(), "5432".to_owned());
+    config.insert("env".to_owned(), "dev".to_owned());
+
+    let kvs = config
+        .iter()
+        .map(|(k, v)| (k.as_str(), emit::Value::from(v.as_str())))
+        .collect::<Vec<_>>();
+
+    let env = "test";
+    emit::emit_kvs!("loaded config for {env}", env, kvs);
+    emit::emit_kvs!(emit::Level::Warn, "no config", &[]);
+    emit::emit_kvs!("inline", [("a", emit::Value::from(1))],);
+
+    assert_eq!(
+        vec![
+            vec![
+                ("db.host".to_owned(), "localhost".to_owned()),
+                ("db.port".to_owned(), "5432".to_owned()),
+                ("env".to_owned(), "test".to_owned()),
+            ],
+            vec![],
+            vec![("a".to_owned(), "1".to_owned())],
+        ],
+        *emitted.lock().unwrap()
+    );
+}
@@ -0,0 +1,26 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+extern crate serde_json_lib as serde_json;
+
+fn main() {
+    let json: serde_json::Value = serde_json::from_str(
+        "{\"big\":18446744073709551615,\"float\":1.0,\"neg\":-1,\"nested\":{\"list\":[null,true,\"text\"]}}",
+    )
+    .unwrap();
+
+    let value = serde_json::to_string(&json).unwrap();
+
+    emit::target(move |record| {
+        let expected = format!(
+            "{{\"lvl\":\"info\",\"msg\":{},\"target\":\"{}\",\"json\":{}}}",
+            serde_json::to_string(&record.msg().to_string()).unwrap(),
+            module_path!(),
+            value
+        );
+
+        assert_eq!(expected, sval_json::to_string(record).unwrap());
+    });
+
+    emit::info!("captured {#[emit::as_serde] json}");
+}
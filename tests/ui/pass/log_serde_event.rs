@@ -0,0 +1,74 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+#[macro_use]
+extern crate serde_derive;
+
+#[path = "../support/json.rs"]
+mod support;
+
+use support::json;
+
+#[derive(Serialize)]
+struct RequestCompleted<'a> {
+    id: u64,
+    #[serde(rename = "http.status")]
+    status: u16,
+    path: &'a str,
+}
+
+#[derive(Serialize)]
+enum Job {
+    Failed { id: u64, retries: u8 },
+}
+
+fn main() {
+    emit::target(|record| {
+        let expected = match record.msg().to_string().as_str() {
+            "RequestCompleted" => {
+                concat!(
+                    "{\"lvl\":\"warn\",\"msg\":\"RequestCompleted\",\"target\":\"",
+                    module_path!(),
+                    "\",\"http.status\":503,\"id\":1,\"path\":\"/api\"}"
+                )
+            }
+            "Failed" => concat!(
+                "{\"lvl\":\"info\",\"msg\":\"Failed\",\"target\":\"",
+                module_path!(),
+                "\",\"id\":2,\"retries\":3}"
+            ),
+            "" => concat!(
+                "{\"lvl\":\"info\",\"msg\":\"\",\"target\":\"",
+                module_path!(),
+                "\",\"value\":[1,2]}"
+            ),
+            msg => panic!("unexpected message {}", msg),
+        };
+
+        assert_eq!(json(expected), json(&sval_json::to_string(record).unwrap()));
+    });
+
+    let path = String::from("/api");
+
+    emit::warn!(
+        #[emit::serde]
+        RequestCompleted {
+            id: 1,
+            status: 503,
+            path: &path,
+        }
+    );
+
+    let job = Job::Failed { id: 2, retries: 3 };
+    emit::info!(
+        #[emit::serde]
+        job
+    );
+
+    // Values that aren't structs are emitted as a single `value`
+    emit::info!(
+        #[emit::serde]
+        [1, 2]
+    );
+}
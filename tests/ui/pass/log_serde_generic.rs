@@ -0,0 +1,29 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+#[macro_use]
+extern crate serde_derive;
+
+#[derive(Serialize)]
+struct Work<'a> {
+    description: &'a str,
+}
+
+fn log<T: serde::Serialize>(work: &T) {
+    emit::info!("scheduling work", #[emit::as_serde] work);
+}
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!(
+            concat!("{\"lvl\":\"info\",\"msg\":\"scheduling work\",\"target\":\"", module_path!(), "\",\"work\":{\"description\":\"upload\"}}"),
+            sval_json::to_string(record).unwrap()
+        );
+    });
+
+    let description = String::from("upload");
+    log(&Work {
+        description: &description,
+    });
+}
@@ -0,0 +1,68 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+extern crate emit_test_external as external;
+
+use std::sync::{Arc, Mutex};
+
+#[path = "../support/json.rs"]
+mod support;
+
+use support::json;
+
+fn main() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+
+    emit::target({
+        let records = records.clone();
+        move |record| {
+            records.lock().unwrap().push((
+                record.msg().to_string(),
+                sval_json::to_string(record).unwrap(),
+            ))
+        }
+    });
+
+    let account = external::Account::new(1);
+    let plans = [external::Plan::Free, external::Plan::Paid { seats: 5 }];
+    emit::info!(
+        "opened",
+        #[emit::as_serde]
+        account,
+        #[emit::as_serde]
+        plans
+    );
+
+    let user = external::UserId(42);
+    let email = external::Email {
+        address: String::from("a@example.com"),
+    };
+    emit::info!(
+        "signed in {user} {email}",
+        #[emit::as_serde]
+        user,
+        #[emit::as_serde]
+        email
+    );
+
+    let records = records.lock().unwrap();
+    assert_eq!(
+        json(concat!(
+            "{\"lvl\":\"info\",\"msg\":\"opened\",\"target\":\"",
+            module_path!(),
+            "\",\"account\":{\"id\":1},\"plans\":[\"Free\",{\"Paid\":{\"seats\":5}}]}"
+        )),
+        json(&records[0].1)
+    );
+
+    // Newtypes and transparent types are captured as the values they wrap
+    assert_eq!("signed in 42 \"a@example.com\"", records[1].0);
+    assert_eq!(
+        json(concat!(
+            "{\"lvl\":\"info\",\"msg\":\"signed in 42 \\\"a@example.com\\\"\",\"target\":\"",
+            module_path!(),
+            "\",\"email\":\"a@example.com\",\"user\":42}"
+        )),
+        json(&records[1].1)
+    );
+}
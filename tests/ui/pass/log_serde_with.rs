@@ -0,0 +1,52 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+extern crate serde;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use serde::Serializer;
+
+// `SystemTime` is normally serialized as a struct of seconds and nanoseconds
+fn serialize_unix_millis<S: Serializer>(ts: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+    let since_epoch = ts.duration_since(SystemTime::UNIX_EPOCH).unwrap();
+
+    serializer.collect_str(&format_args!(
+        "{}.{:03}",
+        since_epoch.as_secs(),
+        since_epoch.subsec_millis()
+    ))
+}
+
+fn main() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+
+    emit::target({
+        let records = records.clone();
+        move |record| {
+            records
+                .lock()
+                .unwrap()
+                .push(sval_json::to_string(record).unwrap())
+        }
+    });
+
+    let started = SystemTime::UNIX_EPOCH + Duration::from_millis(1_500);
+    emit::info!(
+        "started",
+        #[emit::as_serde(with = serialize_unix_millis)]
+        started
+    );
+
+    assert_eq!(
+        concat!(
+            "{\"lvl\":\"info\",\"msg\":\"started\",\"target\":\"",
+            module_path!(),
+            "\",\"started\":\"1.500\"}"
+        ),
+        records.lock().unwrap()[0]
+    );
+}
@@ -0,0 +1,46 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+#[macro_use]
+extern crate serde_derive;
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Serialize)]
+struct Version {
+    major: u32,
+    minor: u32,
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "v{}.{}", self.major, self.minor)
+    }
+}
+
+fn main() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+
+    emit::target({
+        let records = records.clone();
+        move |record| {
+            records
+                .lock()
+                .unwrap()
+                .push(sval_json::to_string(record).unwrap())
+        }
+    });
+
+    let version = Version { major: 1, minor: 2 };
+    emit::info!("deployed {version}", #[emit::as_serde(with_display)] version);
+
+    // The structure is captured, but the message is rendered with the text
+    assert_eq!(
+        concat!("{\"lvl\":\"info\",\"msg\":\"deployed v1.2\",\"target\":\"", module_path!(), "\",\"version\":{\"major\":1,\"minor\":2}}"),
+        records.lock().unwrap()[0]
+    );
+}
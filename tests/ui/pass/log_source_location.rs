@@ -0,0 +1,17 @@
+extern crate emit;
+
+fn main() {
+    emit::target(|record| {
+        let location = record.source_location();
+
+        assert_eq!(file!(), location.file);
+        assert_eq!(16, location.line);
+        assert_eq!(module_path!(), location.module_path);
+    });
+
+    log();
+}
+
+fn log() {
+    emit::info!("a record with a location");
+}
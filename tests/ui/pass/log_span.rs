@@ -0,0 +1,62 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::sync::{Arc, Mutex};
+
+fn main() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+
+    emit::target({
+        let records = records.clone();
+        move |record| records.lock().unwrap().push(record.to_owned())
+    });
+
+    emit::info!("outside");
+
+    let (parent, child) = emit::span!("parent", {
+        let parent = emit::span::current_id().unwrap();
+
+        emit::info!("in parent");
+
+        let child = emit::span!("child", {
+            emit::info!("in child");
+
+            emit::span::current_id().unwrap()
+        });
+
+        (parent, child)
+    });
+
+    let records = records.lock().unwrap();
+
+    let kvs = |i: usize| {
+        records[i]
+            .kvs()
+            .map(|(k, v)| (k, v.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    // Records outside of spans don't have any span key-values
+    assert_eq!(0, records[0].kvs().count());
+
+    // Records in a root span don't have a parent
+    assert_eq!(
+        vec![
+            ("span_id", parent.to_string()),
+            ("span_name", "parent".to_owned())
+        ],
+        kvs(1)
+    );
+
+    assert_eq!(
+        vec![
+            ("parent_span_id", parent.to_string()),
+            ("span_id", child.to_string()),
+            ("span_name", "child".to_owned())
+        ],
+        kvs(2)
+    );
+
+    assert_eq!(None, emit::span::current_id());
+}
@@ -0,0 +1,32 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+fn main() {
+    emit::target(|record| {
+        let expected = match record.msg().to_string().as_str() {
+            "responded 200" => concat!(
+                "{\"lvl\":\"info\",\"msg\":\"responded 200\",\"target\":\"",
+                module_path!(),
+                "\",\"code\":{\"code\":200,\"class\":\"2xx\"}}"
+            ),
+            "responded 404" => concat!(
+                "{\"lvl\":\"info\",\"msg\":\"responded 404\",\"target\":\"",
+                module_path!(),
+                "\",\"code\":{\"code\":404,\"class\":\"4xx\"}}"
+            ),
+            "responded 503" => concat!(
+                "{\"lvl\":\"info\",\"msg\":\"responded 503\",\"target\":\"",
+                module_path!(),
+                "\",\"code\":{\"code\":503,\"class\":\"5xx\"}}"
+            ),
+            msg => panic!("unexpected message {}", msg),
+        };
+
+        assert_eq!(expected, sval_json::to_string(record).unwrap());
+    });
+
+    for code in [200, 404, 503] {
+        emit::info!("responded {#[emit::as_status] code}");
+    }
+}
@@ -0,0 +1,33 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+#[path = "../support/json.rs"]
+mod support;
+
+use support::json;
+
+#[derive(Debug)]
+struct Output {
+    stdout: String,
+}
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!(
+            json(concat!("{\"lvl\":\"info\",\"msg\":\"ran with error: failed and Output { stdout: \\\"ok\\\" }\",\"target\":\"", module_path!(), "\",\"output\":\"Output { stdout: \\\"ok\\\" }\",\"status\":\"error: failed\"}")),
+            json(&sval_json::to_string(record).unwrap())
+        );
+        assert_eq!(
+            "ran with error: failed and Output { stdout: \"ok\" }",
+            record.msg().to_string()
+        );
+    });
+
+    let status = "\u{1b}[31merror\u{1b}[0m: failed";
+    let output = Output {
+        stdout: "\u{1b}[32mok\u{1b}[0m".to_owned(),
+    };
+
+    emit::info!("ran with {#[emit::strip_ansi] status} and {#[emit::as_debug(strip_ansi)] output}");
+}
@@ -0,0 +1,47 @@
+use std::sync::{Arc, Mutex};
+
+enum Route {
+    Billing,
+    Search,
+}
+
+impl emit::Target for Route {
+    fn as_str(&self) -> &str {
+        match self {
+            Route::Billing => "app::billing",
+            Route::Search => "app::search",
+        }
+    }
+}
+
+fn route() -> Route {
+    Route::Search
+}
+
+fn main() {
+    let targets = Arc::new(Mutex::new(Vec::new()));
+
+    emit::target({
+        let targets = targets.clone();
+        move |record| targets.lock().unwrap().push(record.target().to_owned())
+    });
+
+    emit::info!(target: Route::Billing, "Charged an account");
+    emit::info!(target: route(), "Searched for {query}", query: "shoes");
+
+    let name = String::from("app::dynamic");
+    emit::info!(target: name, "Handling a request");
+
+    // Targets that are emitters still receive the record themselves
+    let emitter = emit::RingBufferEmitter::new(1);
+    emit::info!(target: emitter, "Handling a request");
+    emit::emit_blocking!(target: route(), "Flushed");
+
+    assert_eq!(1, emitter.snapshot().len());
+
+    let target = targets.lock().unwrap();
+    assert_eq!(
+        vec!["app::billing", "app::search", "app::dynamic", "app::search"],
+        *target
+    );
+}
@@ -0,0 +1,37 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+use std::collections::BTreeMap;
+
+use sval::value::{self, Value};
+
+struct Nested(u64);
+
+impl Value for Nested {
+    fn stream(&self, stream: &mut value::Stream) -> value::Result {
+        if self.0 == 0 {
+            return stream.u64(0);
+        }
+
+        stream.map_begin(Some(1))?;
+        stream.map_key("child")?;
+        stream.map_value(Nested(self.0 - 1))?;
+        stream.map_end()
+    }
+}
+
+fn main() {
+    emit::target(|record| {
+        assert_eq!(
+            concat!("{\"lvl\":\"info\",\"msg\":\"Captured\",\"target\":\"", module_path!(), "\",\"entries\":{\"a\":1,\"b\":2,\"<truncated>\":2},\"map\":{\"child\":{\"child\":\"<truncated>\"}}}"),
+            sval_json::to_string(record).unwrap()
+        )
+    });
+
+    let entries: BTreeMap<_, _> = vec![("a", 1), ("b", 2), ("c", 3), ("d", 4)].into_iter().collect();
+
+    emit::info!(
+        "Captured",
+        #[emit::as_sval(max_depth = 2)] map: Nested(10),
+        #[emit::as_sval(max_entries = 2)] entries,
+    );
+}
@@ -0,0 +1,24 @@
+use std::sync::{Arc, Mutex};
+
+mod api {
+    pub fn handle() {
+        emit::info!("Handling a request");
+    }
+}
+
+fn main() {
+    let targets = Arc::new(Mutex::new(Vec::new()));
+
+    emit::target({
+        let targets = targets.clone();
+        move |record| targets.lock().unwrap().push(record.target().to_owned())
+    });
+
+    api::handle();
+    emit::info!(target: "http", "Handling a request");
+
+    let target = targets.lock().unwrap();
+    assert_eq!(2, target.len());
+    assert!(target[0].ends_with("::api"), "{}", target[0]);
+    assert_eq!("http", target[1]);
+}
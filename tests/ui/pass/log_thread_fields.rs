@@ -0,0 +1,44 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+};
+
+fn main() {
+    let emitted = Arc::new(Mutex::new(Vec::new()));
+
+    let _guard = emit::Builder::new()
+        .emitter({
+            let emitted = emitted.clone();
+            move |record: &emit::Record| {
+                let kvs = record.kvs();
+
+                emitted.lock().unwrap().push((
+                    kvs.get("thread_id").map(|v| v.to_string()),
+                    kvs.get("thread_name").map(|v| v.to_string()),
+                ))
+            }
+        })
+        .with_thread_fields()
+        .install();
+
+    let id = thread::Builder::new()
+        .name("worker".into())
+        .spawn(|| {
+            emit::info!("on a worker");
+
+            format!("{:?}", thread::current().id())
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    emit::info!("on main");
+
+    let emitted = emitted.lock().unwrap();
+
+    assert_eq!(
+        (Some(id.to_string()), Some("worker".to_owned())),
+        emitted[0]
+    );
+    assert_eq!(Some("main".to_owned()), emitted[1].1);
+}
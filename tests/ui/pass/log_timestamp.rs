@@ -0,0 +1,33 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::time::{Duration, UNIX_EPOCH};
+
+fn main() {
+    emit::target(|record| {
+        let at = UNIX_EPOCH + Duration::from_secs(1_633_077_000);
+
+        match record.msg().to_string().as_str() {
+            "replayed at 2021-10-01T08:30:00Z" => {
+                assert_eq!(Some(at), record.timestamp());
+                assert_eq!(at, record.to_owned().timestamp());
+
+                assert_eq!(
+                    concat!("{\"lvl\":\"info\",\"msg\":\"replayed at 2021-10-01T08:30:00Z\",\"target\":\"", module_path!(), "\",\"ts\":\"2021-10-01T08:30:00Z\"}"),
+                    sval_json::to_string(record).unwrap()
+                );
+            }
+            "emitted now" => {
+                assert_eq!(None, record.timestamp());
+                assert!(record.to_owned().timestamp() > at);
+            }
+            msg => panic!("unexpected message {}", msg),
+        }
+    });
+
+    let at = UNIX_EPOCH + Duration::from_secs(1_633_077_000);
+    emit::info!("replayed at {#[ts] at}");
+
+    emit::info!("emitted now");
+}
@@ -0,0 +1,22 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::time::{Duration, Instant};
+
+fn main() {
+    emit::target(|record| {
+        let uptime = record.kvs()["at"].to_f64().unwrap();
+
+        assert!(uptime >= 0.0);
+        assert!(record.msg().to_string().starts_with("ready at PT"));
+    });
+
+    let at = Instant::now() + Duration::from_millis(10);
+    emit::info!("ready at {#[emit::as_uptime] at}");
+
+    // Instants before the process started are captured as zero
+    if let Some(at) = Instant::now().checked_sub(Duration::from_secs(60 * 60 * 24 * 365)) {
+        emit::info!("ready at {#[emit::as_uptime] at}");
+    }
+}
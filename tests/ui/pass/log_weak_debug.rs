@@ -0,0 +1,27 @@
+#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+
+extern crate emit;
+
+use std::sync::Arc;
+
+fn main() {
+    emit::target(|record| {
+        let expected = match record.kvs().get("handle").map(|handle| handle.to_string()) {
+            Some(handle) if handle == "[1, 2, 3]" => "live [1, 2, 3]",
+            _ => "dropped <dropped>",
+        };
+
+        assert_eq!(expected, record.msg().to_string());
+    });
+
+    let state = Arc::new(vec![1, 2, 3]);
+    let handle = Arc::downgrade(&state);
+
+    emit::info!("live {#[emit::as_weak_debug] handle}");
+
+    drop(state);
+    emit::info!("dropped {#[emit::as_weak_debug] handle}");
+
+    let handle = Arc::new(vec![1, 2, 3]);
+    emit::info!("live {#[emit::as_weak_debug] handle}");
+}
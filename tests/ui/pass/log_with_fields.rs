@@ -0,0 +1,33 @@
+extern crate emit;
+
+fn main() {
+    emit::target(|record| {
+        let request_id = record.kvs().get("request_id").map(|v| v.to_string());
+        let user = record.kvs().get("user").map(|v| v.to_string());
+
+        match record.template().to_string().as_str() {
+            "inside" => {
+                assert_eq!(Some("42"), request_id.as_deref());
+                assert_eq!(Some("ferris"), user.as_deref());
+            }
+            "after" => {
+                assert_eq!(None, request_id);
+                assert_eq!(None, user);
+            }
+            template => panic!("unexpected template {}", template),
+        }
+    });
+
+    let request_id = 42;
+    let name = String::from("ferris");
+
+    let result = emit::with_fields!({ request_id, user: name }, {
+        emit::info!("inside");
+
+        request_id + 1
+    });
+
+    assert_eq!(43, result);
+
+    emit::info!("after");
+}
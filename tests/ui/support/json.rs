@@ -0,0 +1,17 @@
+/*!
+Comparing records as JSON in the UI tests.
+
+This module is shared between tests with `#[path]`, so it lives outside of `tests/ui/pass`.
+*/
+
+use serde_json_lib::Value;
+
+/**
+Parse a JSON string for comparing.
+
+Key-values are in the order they were captured in with `no_sort`,
+so JSON objects are compared without depending on the order of their keys.
+*/
+pub fn json(s: &str) -> Value {
+    serde_json_lib::from_str(s).unwrap()
+}